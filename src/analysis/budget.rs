@@ -0,0 +1,245 @@
+use crate::analysis::dominator::DominatorIndex;
+use crate::analysis::suggest;
+use crate::error::SnapshotError;
+use crate::snapshot::SnapshotRaw;
+
+/// Which size a budget limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetMetric {
+    /// Sum of the constructor's self sizes.
+    SelfSize,
+    /// Memory freed if every instance were collected: the retained sizes of instances
+    /// that are not dominated by another instance of the same constructor.
+    Retained,
+}
+
+impl BudgetMetric {
+    pub fn label(self) -> &'static str {
+        match self {
+            BudgetMetric::SelfSize => "self size",
+            BudgetMetric::Retained => "retained size",
+        }
+    }
+}
+
+/// One `--assert` rule: `Name<=10MB`, or `Name:retained<=10MB` for the retained size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeBudget {
+    /// Exact constructor name.
+    pub name: String,
+    pub metric: BudgetMetric,
+    pub max_bytes: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct BudgetViolation {
+    pub budget: SizeBudget,
+    pub actual_bytes: i64,
+}
+
+impl SizeBudget {
+    pub fn parse(spec: &str) -> Result<Self, SnapshotError> {
+        let invalid = |reason: &str| SnapshotError::InvalidData {
+            details: format!("invalid --assert {spec:?}: {reason} (expected e.g. \"Foo<=10MB\")"),
+        };
+        let (target, size) = spec
+            .rsplit_once("<=")
+            .ok_or_else(|| invalid("missing \"<=\""))?;
+        let (name, metric) = match target.rsplit_once(':') {
+            Some((name, "retained")) => (name, BudgetMetric::Retained),
+            Some((name, "self")) => (name, BudgetMetric::SelfSize),
+            _ => (target, BudgetMetric::SelfSize),
+        };
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(invalid("missing constructor name"));
+        }
+        let max_bytes = parse_size(size).map_err(|_| invalid("bad size"))?;
+        Ok(Self {
+            name: name.to_string(),
+            metric,
+            max_bytes,
+        })
+    }
+
+    /// `Some` when `actual_bytes` exceeds the budget.
+    fn check(&self, actual_bytes: i64) -> Option<BudgetViolation> {
+        (actual_bytes > self.max_bytes).then(|| BudgetViolation {
+            budget: self.clone(),
+            actual_bytes,
+        })
+    }
+}
+
+/// Parses a byte count with an optional `B` / `KB` / `MB` / `GB` suffix (case-insensitive,
+/// powers of 1024; `KiB` / `MiB` / `GiB` are accepted as synonyms). Fractions are allowed
+/// with a suffix, e.g. `1.5MB`.
+pub fn parse_size(value: &str) -> Result<i64, SnapshotError> {
+    let invalid = || SnapshotError::InvalidData {
+        details: format!("invalid size: {value:?} (expected e.g. 512, 64KB, 10MB or 1.5GB)"),
+    };
+    let trimmed = value.trim();
+    let split = trimmed
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let multiplier: i64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" | "kib" | "k" => 1024,
+        "mb" | "mib" | "m" => 1024 * 1024,
+        "gb" | "gib" | "g" => 1024 * 1024 * 1024,
+        _ => return Err(invalid()),
+    };
+    if multiplier == 1 {
+        return number.parse::<i64>().map_err(|_| invalid());
+    }
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+    if !number.is_finite() {
+        return Err(invalid());
+    }
+    Ok((number * multiplier as f64).round() as i64)
+}
+
+/// Checks every budget against the whole snapshot (not just the summary's top rows).
+/// `dominators` is required when any budget uses `BudgetMetric::Retained`. A budget whose
+/// constructor has no nodes is a `NotFound` error rather than a pass.
+pub fn check_budgets(
+    snapshot: &SnapshotRaw,
+    budgets: &[SizeBudget],
+    dominators: Option<(&DominatorIndex, &[i64])>,
+) -> Result<Vec<BudgetViolation>, SnapshotError> {
+    let mut violations = Vec::new();
+    for budget in budgets {
+        let indices = snapshot.string_indices_of(&budget.name);
+        let instances: Vec<bool> = snapshot
+            .nodes()
+            .map(|node| {
+                node.name_index()
+                    .is_some_and(|index| indices.contains(&index))
+            })
+            .collect();
+        // A misspelled name would measure 0 bytes and pass, silently disabling the gate.
+        if !instances.contains(&true) {
+            let suggestions = suggest::did_you_mean(&suggest::similar_names(
+                &budget.name,
+                suggest::node_names(snapshot),
+            ));
+            return Err(SnapshotError::NotFound {
+                details: format!(
+                    "budget names a constructor with no nodes: {}{suggestions}",
+                    budget.name
+                ),
+            });
+        }
+        let actual = match budget.metric {
+            BudgetMetric::SelfSize => snapshot
+                .nodes()
+                .filter(|node| instances[node.node_index()])
+                .map(|node| node.self_size().unwrap_or(0))
+                .sum(),
+            BudgetMetric::Retained => {
+                let (index, retained) = dominators.ok_or_else(|| SnapshotError::InvalidData {
+                    details: "retained budgets need the dominator tree".to_string(),
+                })?;
+                outermost_retained(index, retained, &instances)
+            }
+        };
+        violations.extend(budget.check(actual));
+    }
+    Ok(violations)
+}
+
+/// Sum of `retained` over instances with no other instance among their dominators, so
+/// nested instances are not counted twice.
+fn outermost_retained(index: &DominatorIndex, retained: &[i64], instances: &[bool]) -> i64 {
+    // inside[n]: n or one of its dominators is an instance.
+    let mut inside: Vec<Option<bool>> = vec![None; index.idom.len()];
+    let mut total = 0;
+    for node in 0..index.idom.len() {
+        if !instances[node] {
+            continue;
+        }
+        let nested = match index.idom[node] {
+            Some(parent) if parent != node => is_inside(index, instances, &mut inside, parent),
+            Some(_) => false,
+            // Unreachable from the roots: retains nothing.
+            None => continue,
+        };
+        if !nested {
+            total += retained.get(node).copied().unwrap_or(0);
+        }
+    }
+    total
+}
+
+/// Walks the dominator chain from `start` until an instance or a memoized node, then
+/// records the answer for every non-instance node on the way.
+fn is_inside(
+    index: &DominatorIndex,
+    instances: &[bool],
+    inside: &mut [Option<bool>],
+    start: usize,
+) -> bool {
+    let mut chain = Vec::new();
+    let mut current = start;
+    let answer = loop {
+        if let Some(known) = inside[current] {
+            break known;
+        }
+        if instances[current] {
+            break true;
+        }
+        chain.push(current);
+        match index.idom[current] {
+            Some(parent) if parent != current => current = parent,
+            _ => break false,
+        }
+    };
+    for node in chain {
+        inside[node] = Some(answer);
+    }
+    answer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sizes_with_suffixes() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("64KB").unwrap(), 64 * 1024);
+        assert_eq!(parse_size("10mb").unwrap(), 10 * 1024 * 1024);
+        assert_eq!(parse_size("1.5 GiB").unwrap(), 3 * 512 * 1024 * 1024);
+        assert!(parse_size("10XB").is_err());
+        assert!(parse_size("1.5").is_err());
+        assert!(parse_size("").is_err());
+    }
+
+    #[test]
+    fn parses_budget_specs() {
+        let budget = SizeBudget::parse("Foo<=10MB").unwrap();
+        assert_eq!(budget.name, "Foo");
+        assert_eq!(budget.metric, BudgetMetric::SelfSize);
+        assert_eq!(budget.max_bytes, 10 * 1024 * 1024);
+
+        let budget = SizeBudget::parse("system / Context:retained<=1KB").unwrap();
+        assert_eq!(budget.name, "system / Context");
+        assert_eq!(budget.metric, BudgetMetric::Retained);
+
+        assert!(SizeBudget::parse("Foo=10MB").is_err());
+        assert!(SizeBudget::parse("<=10MB").is_err());
+    }
+
+    #[test]
+    fn nested_instances_are_not_counted_twice() {
+        // 0 (root) -> 1 (Foo) -> 2 (Foo) -> 3; 0 -> 4 (Foo)
+        let index = DominatorIndex {
+            roots: vec![0],
+            idom: vec![Some(0), Some(0), Some(1), Some(2), Some(0)],
+        };
+        let retained = [100, 60, 40, 10, 5];
+        let instances = [false, true, true, false, true];
+        assert_eq!(outermost_retained(&index, &retained, &instances), 65);
+    }
+}
@@ -1,14 +1,33 @@
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::analysis::histogram::{DEFAULT_BUCKET_BOUNDS, SizeBucket, SizeHistogram};
+use crate::analysis::name_index::NameIndex;
+use crate::analysis::reachability::{Reachability, reachability_of};
+use crate::analysis::suggest;
 use crate::error::SnapshotError;
-use crate::snapshot::{EdgeView, SnapshotRaw};
+use crate::snapshot::{EdgeView, NodeView, SnapshotRaw, SourceLocation};
 
 #[derive(Debug)]
 pub struct DetailOptions {
     pub id: Option<u64>,
     pub name: Option<String>,
+    /// Select the nodes whose name is this string-table index, separating constructors
+    /// whose names render as the same text.
+    pub name_index: Option<usize>,
     pub skip: usize,
     pub limit: usize,
     pub top_retainers: usize,
     pub top_edges: usize,
+    /// Keep only retainers/outgoing edges whose edge name contains this string.
+    /// Applied before the `top_*` truncation.
+    pub edge_name_filter: Option<String>,
+    /// Also return the node's and its outgoing edges' raw field values (id mode).
+    pub raw: bool,
+    /// Stop reading the node's outgoing edges after this many (id mode), so a giant
+    /// array answers quickly with a partial top list.
+    pub max_edges_per_node: Option<usize>,
 }
 
 #[derive(Debug)]
@@ -20,15 +39,24 @@ pub enum DetailResult {
 #[derive(Debug)]
 pub struct DetailByName {
     pub name: String,
+    /// String-table index the nodes were selected by, when `--name-index` was used.
+    pub name_index: Option<usize>,
     pub total_count: u64,
     pub self_size_sum: i64,
     pub max_self_size: i64,
     pub min_self_size: i64,
     pub avg_self_size: f64,
+    /// Nearest-rank median and 95th percentile of the self sizes.
+    pub median_self_size: i64,
+    pub p95_self_size: i64,
     pub ids: Vec<NodeRef>,
     pub skip: usize,
     pub limit: usize,
     pub total_ids: u64,
+    /// (node type, count, self size sum) for every node type under this name, largest first.
+    pub node_type_breakdown: Vec<(String, u64, i64)>,
+    /// `SnapshotRaw::quick_fingerprint` of the inspected snapshot.
+    pub snapshot_fingerprint: u64,
 }
 
 #[derive(Debug)]
@@ -38,18 +66,53 @@ pub struct DetailById {
     pub name: String,
     pub node_type: Option<String>,
     pub self_size: i64,
+    /// Allocation site from the snapshot's `locations` section, when present.
+    pub location: Option<SourceLocation>,
+    /// Whether the node is held strongly, only through weak edges, or not at all.
+    pub reachability: Reachability,
     pub total_count: u64,
     pub self_size_sum: i64,
     pub max_self_size: i64,
     pub min_self_size: i64,
     pub avg_self_size: f64,
+    /// Nearest-rank median and 95th percentile of the self sizes.
+    pub median_self_size: i64,
+    pub p95_self_size: i64,
     pub ids: Vec<NodeRef>,
     pub skip: usize,
     pub limit: usize,
     pub total_ids: u64,
     pub retainers: Vec<RetainerSummary>,
+    /// Incoming edges that passed `edge_name_filter`, before the `top_retainers` cut.
+    pub total_retainers: u64,
     pub outgoing_edges: Vec<OutgoingEdgeSummary>,
-    pub shallow_size_distribution: Vec<ShallowSizeBucket>,
+    /// Set when `max_edges_per_node` stopped the outgoing edge scan early.
+    pub edges_truncated: Option<EdgesTruncated>,
+    pub shallow_size_distribution: Vec<SizeBucket>,
+    /// Set when `DetailOptions::raw` was requested.
+    pub raw: Option<RawRecords>,
+    /// `SnapshotRaw::quick_fingerprint` of the inspected snapshot.
+    pub snapshot_fingerprint: u64,
+}
+
+/// How much of a node's outgoing edge list the scan read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EdgesTruncated {
+    pub examined: usize,
+    pub total: usize,
+}
+
+/// Undecoded integers from the `nodes` / `edges` arrays, paired with their meta field names.
+#[derive(Debug, Clone)]
+pub struct RawRecords {
+    pub node: Vec<(String, i64)>,
+    pub edges: Vec<RawEdge>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RawEdge {
+    pub edge_index: usize,
+    pub fields: Vec<(String, i64)>,
 }
 
 #[derive(Debug, Clone)]
@@ -84,107 +147,179 @@ pub struct OutgoingEdgeSummary {
     pub to_self_size: i64,
 }
 
-#[derive(Debug, Clone)]
-pub struct ShallowSizeBucket {
-    pub label: String,
-    pub min: i64,
-    pub max: Option<i64>,
-    pub count: u64,
+pub fn detail(
+    snapshot: &SnapshotRaw,
+    options: DetailOptions,
+) -> Result<DetailResult, SnapshotError> {
+    detail_impl(snapshot, None, options)
 }
 
-const DEFAULT_BUCKETS: &[(i64, Option<i64>)] = &[
-    (0, Some(0)),
-    (1, Some(31)),
-    (32, Some(127)),
-    (128, Some(511)),
-    (512, Some(2047)),
-    (2048, Some(8191)),
-    (8192, Some(32767)),
-    (32768, None),
-];
+/// Like `detail`, but finds the nodes of a constructor through a prebuilt `NameIndex`
+/// instead of scanning every node.
+pub fn detail_with_names(
+    snapshot: &SnapshotRaw,
+    names: &NameIndex,
+    options: DetailOptions,
+) -> Result<DetailResult, SnapshotError> {
+    detail_impl(snapshot, Some(names), options)
+}
 
-pub fn detail(
+fn detail_impl(
     snapshot: &SnapshotRaw,
+    names: Option<&NameIndex>,
     options: DetailOptions,
 ) -> Result<DetailResult, SnapshotError> {
-    if options.id.is_some() && options.name.is_some() {
+    let selectors = usize::from(options.id.is_some())
+        + usize::from(options.name.is_some())
+        + usize::from(options.name_index.is_some());
+    if selectors > 1 {
         return Err(SnapshotError::InvalidData {
-            details: "use either --id or --name, not both".to_string(),
+            details: "use only one of --id, --name and --name-index".to_string(),
         });
     }
-    if options.id.is_none() && options.name.is_none() {
+    if selectors == 0 {
         return Err(SnapshotError::InvalidData {
-            details: "either --id or --name must be specified".to_string(),
+            details: "either --id, --name or --name-index must be specified".to_string(),
         });
     }
 
     if let Some(node_id) = options.id {
         let (node_index, name, node_type, self_size) = find_node_by_id(snapshot, node_id)?;
-        let stats = collect_name_stats(snapshot, &name, options.skip, options.limit)?;
-        let retainers = top_retainers(snapshot, node_index, options.top_retainers)?;
-        let outgoing_edges = top_outgoing_edges(snapshot, node_index, options.top_edges)?;
-        let distribution = shallow_size_distribution(snapshot, &name)?;
+        let nodes = named_nodes(snapshot, names, &name);
+        let stats = collect_name_stats(snapshot, &nodes, options.skip, options.limit)?;
+        let edge_name_filter = options.edge_name_filter.as_deref();
+        let (retainers, total_retainers) = top_retainers(
+            snapshot,
+            node_index,
+            options.top_retainers,
+            edge_name_filter,
+        )?;
+        let (outgoing_edges, edges_truncated) = top_outgoing_edges(
+            snapshot,
+            node_index,
+            options.top_edges,
+            edge_name_filter,
+            options.max_edges_per_node,
+        )?;
+        let distribution = shallow_size_distribution(snapshot, &nodes)?;
 
         return Ok(DetailResult::ById(DetailById {
             id: node_id,
             node_index,
+            snapshot_fingerprint: snapshot.quick_fingerprint(),
             name,
             node_type,
             self_size,
+            location: snapshot.location_of(node_index),
+            reachability: reachability_of(snapshot, node_index)?,
             total_count: stats.total_count,
             self_size_sum: stats.self_size_sum,
             max_self_size: stats.max_self_size,
             min_self_size: stats.min_self_size,
             avg_self_size: stats.avg_self_size,
+            median_self_size: stats.median_self_size,
+            p95_self_size: stats.p95_self_size,
             ids: stats.ids,
             skip: stats.skip,
             limit: stats.limit,
             total_ids: stats.total_ids,
             retainers,
+            total_retainers,
             outgoing_edges,
+            edges_truncated,
             shallow_size_distribution: distribution,
+            raw: if options.raw {
+                Some(raw_records(snapshot, node_index)?)
+            } else {
+                None
+            },
         }));
     }
 
+    if let Some(name_index) = options.name_index {
+        let name = snapshot
+            .strings
+            .get(name_index)
+            .ok_or_else(|| SnapshotError::InvalidData {
+                details: format!(
+                    "--name-index {name_index} is out of range (the string table has {} entries)",
+                    snapshot.strings.len()
+                ),
+            })?
+            .clone();
+        let nodes: Vec<usize> = snapshot
+            .nodes()
+            .filter(|node| node.name_index() == Some(name_index))
+            .map(|node| node.node_index())
+            .collect();
+        let stats = collect_name_stats(snapshot, &nodes, options.skip, options.limit)?;
+        if stats.total_count == 0 {
+            return Err(SnapshotError::NotFound {
+                details: format!("no nodes are named by string index {name_index} ({name:?})"),
+            });
+        }
+        return Ok(DetailResult::ByName(by_name(
+            snapshot,
+            name,
+            Some(name_index),
+            stats,
+        )));
+    }
+
     let name = options.name.unwrap_or_default();
-    let stats = collect_name_stats(snapshot, &name, options.skip, options.limit)?;
+    let nodes = named_nodes(snapshot, names, &name);
+    let stats = collect_name_stats(snapshot, &nodes, options.skip, options.limit)?;
     if stats.total_count == 0 {
-        return Err(SnapshotError::InvalidData {
-            details: format!("no nodes match name: {name}"),
+        let known = match names {
+            Some(names) => names.iter().map(|(name, _)| name).collect(),
+            None => suggest::node_names(snapshot),
+        };
+        let suggestions = suggest::did_you_mean(&suggest::similar_names(&name, known));
+        return Err(SnapshotError::NotFound {
+            details: format!("no nodes match name: {name}{suggestions}"),
         });
     }
-    Ok(DetailResult::ByName(DetailByName {
+    Ok(DetailResult::ByName(by_name(snapshot, name, None, stats)))
+}
+
+fn by_name(
+    snapshot: &SnapshotRaw,
+    name: String,
+    name_index: Option<usize>,
+    stats: NameStats,
+) -> DetailByName {
+    DetailByName {
+        snapshot_fingerprint: snapshot.quick_fingerprint(),
         name,
+        name_index,
         total_count: stats.total_count,
         self_size_sum: stats.self_size_sum,
         max_self_size: stats.max_self_size,
         min_self_size: stats.min_self_size,
         avg_self_size: stats.avg_self_size,
+        median_self_size: stats.median_self_size,
+        p95_self_size: stats.p95_self_size,
         ids: stats.ids,
         skip: stats.skip,
         limit: stats.limit,
         total_ids: stats.total_ids,
-    }))
+        node_type_breakdown: stats.node_type_breakdown,
+    }
 }
 
 fn find_node_by_id(
     snapshot: &SnapshotRaw,
     node_id: u64,
 ) -> Result<(usize, String, Option<String>, i64), SnapshotError> {
-    for index in 0..snapshot.node_count() {
-        let node = snapshot
-            .node_view(index)
-            .ok_or_else(|| SnapshotError::InvalidData {
-                details: format!("node index out of range: {index}"),
-            })?;
+    for node in snapshot.nodes() {
         if node.id() == Some(node_id as i64) {
             let name = node.name().unwrap_or("<unknown>").to_string();
             let node_type = node.node_type().map(str::to_string);
             let self_size = node.self_size().unwrap_or(0);
-            return Ok((index, name, node_type, self_size));
+            return Ok((node.node_index(), name, node_type, self_size));
         }
     }
-    Err(SnapshotError::InvalidData {
+    Err(SnapshotError::NotFound {
         details: format!("node id not found: {node_id} (use --name to select a constructor)"),
     })
 }
@@ -195,15 +330,45 @@ struct NameStats {
     max_self_size: i64,
     min_self_size: i64,
     avg_self_size: f64,
+    median_self_size: i64,
+    p95_self_size: i64,
     ids: Vec<NodeRef>,
     skip: usize,
     limit: usize,
     total_ids: u64,
+    node_type_breakdown: Vec<(String, u64, i64)>,
 }
 
-fn collect_name_stats(
+/// Nodes named exactly `target_name`, in node order. Nodes without a resolvable name
+/// count as `""`.
+pub(crate) fn named_nodes<'a>(
     snapshot: &SnapshotRaw,
+    names: Option<&'a NameIndex>,
     target_name: &str,
+) -> Cow<'a, [usize]> {
+    match names {
+        Some(names) if target_name.is_empty() && !names.unnamed().is_empty() => {
+            let mut nodes = [names.nodes(""), names.unnamed()].concat();
+            nodes.sort_unstable();
+            Cow::Owned(nodes)
+        }
+        Some(names) => Cow::Borrowed(names.nodes(target_name)),
+        None => {
+            let matcher = NameMatcher::new(snapshot, target_name);
+            Cow::Owned(
+                snapshot
+                    .nodes()
+                    .filter(|node| matcher.matches(node))
+                    .map(|node| node.node_index())
+                    .collect(),
+            )
+        }
+    }
+}
+
+fn collect_name_stats(
+    snapshot: &SnapshotRaw,
+    nodes: &[usize],
     skip: usize,
     limit: usize,
 ) -> Result<NameStats, SnapshotError> {
@@ -212,29 +377,29 @@ fn collect_name_stats(
     let mut max_self_size: i64 = i64::MIN;
     let mut min_self_size: i64 = i64::MAX;
     let mut ids: Vec<NodeRef> = Vec::new();
+    let mut by_type: HashMap<&str, (u64, i64)> = HashMap::new();
+    // One i64 per node, the same length as `nodes` which is already in memory.
+    let mut sizes: Vec<i64> = Vec::with_capacity(nodes.len());
 
-    for index in 0..snapshot.node_count() {
-        let node = snapshot
-            .node_view(index)
-            .ok_or_else(|| SnapshotError::InvalidData {
-                details: format!("node index out of range: {index}"),
-            })?;
-        let name = node.name().unwrap_or("");
-        if name != target_name {
-            continue;
-        }
+    for node in nodes.iter().filter_map(|index| snapshot.node_view(*index)) {
         total_count += 1;
         let self_size = node.self_size().unwrap_or(0);
         self_size_sum += self_size;
+        sizes.push(self_size);
         if self_size > max_self_size {
             max_self_size = self_size;
         }
         if self_size < min_self_size {
             min_self_size = self_size;
         }
+        let type_entry = by_type
+            .entry(node.node_type().unwrap_or("unknown"))
+            .or_insert((0, 0));
+        type_entry.0 += 1;
+        type_entry.1 += self_size;
         if total_count as usize > skip && ids.len() < limit {
             ids.push(NodeRef {
-                index,
+                index: node.node_index(),
                 id: node.id(),
                 node_type: node.node_type().map(str::to_string),
                 self_size,
@@ -249,126 +414,158 @@ fn collect_name_stats(
             max_self_size: 0,
             min_self_size: 0,
             avg_self_size: 0.0,
+            median_self_size: 0,
+            p95_self_size: 0,
             ids,
             skip,
             limit,
             total_ids: 0,
+            node_type_breakdown: Vec::new(),
         });
     }
 
+    let mut node_type_breakdown: Vec<(String, u64, i64)> = by_type
+        .into_iter()
+        .map(|(node_type, (count, size))| (node_type.to_string(), count, size))
+        .collect();
+    node_type_breakdown.sort_by(|a, b| {
+        b.2.cmp(&a.2)
+            .then_with(|| b.1.cmp(&a.1))
+            .then_with(|| a.0.cmp(&b.0))
+    });
+
     let avg_self_size = self_size_sum as f64 / total_count as f64;
+    let median_self_size = percentile(&mut sizes, 50);
+    let p95_self_size = percentile(&mut sizes, 95);
     Ok(NameStats {
         total_count,
         self_size_sum,
         max_self_size,
         min_self_size,
         avg_self_size,
+        median_self_size,
+        p95_self_size,
         ids,
         skip,
         limit,
         total_ids: total_count,
+        node_type_breakdown,
     })
 }
 
+/// Retainers of `target` with the largest `from_self_size` first (ties by node, then edge
+/// order), plus how many matched in total. Only the best `limit` candidates are kept
+/// during the scan, as plain indices in a bounded heap, and names / types are looked up
+/// for those survivors alone, so a node with tens of thousands of retainers costs
+/// `limit` entries rather than one allocation per retainer.
 fn top_retainers(
     snapshot: &SnapshotRaw,
     target: usize,
     limit: usize,
-) -> Result<Vec<RetainerSummary>, SnapshotError> {
-    let edge_offsets = compute_edge_offsets(snapshot)?;
-    let mut items: Vec<RetainerSummary> = Vec::new();
-
-    for (node_index, start_edge) in edge_offsets.iter().enumerate() {
-        let node = snapshot
-            .node_view(node_index)
-            .ok_or_else(|| SnapshotError::InvalidData {
-                details: format!("node index out of range: {node_index}"),
-            })?;
-        let edge_count = node.edge_count().unwrap_or(0);
-        let edge_count = usize::try_from(edge_count).map_err(|_| SnapshotError::InvalidData {
-            details: format!("edge_count negative at node {node_index}"),
-        })?;
-        for offset in 0..edge_count {
-            let edge_index = start_edge + offset;
-            let edge =
-                snapshot
-                    .edge_view(edge_index)
-                    .ok_or_else(|| SnapshotError::InvalidData {
-                        details: format!("edge index out of range: {edge_index}"),
-                    })?;
-            let to_node = match edge.to_node_index() {
-                Some(value) => value,
-                None => continue,
-            };
-            if to_node != target {
+    edge_name_filter: Option<&str>,
+) -> Result<(Vec<RetainerSummary>, u64), SnapshotError> {
+    // Greatest key = worst candidate, so the heap top is the one to evict.
+    let mut best: BinaryHeap<(Reverse<i64>, usize, usize)> = BinaryHeap::with_capacity(limit);
+    let mut total = 0u64;
+
+    for node in snapshot.nodes() {
+        for edge in node.outgoing_edges()? {
+            if edge.to_node_index() != Some(target) {
+                continue;
+            }
+            if edge_name_filter.is_some()
+                && !edge_name_matches(edge_name(snapshot, edge).as_deref(), edge_name_filter)
+            {
                 continue;
             }
-            let from_self_size = node.self_size().unwrap_or(0);
-            items.push(RetainerSummary {
-                from_index: node_index,
+            total += 1;
+            let key = (
+                Reverse(node.self_size().unwrap_or(0)),
+                node.node_index(),
+                edge.edge_index(),
+            );
+            if best.len() < limit {
+                best.push(key);
+            } else if best.peek().is_some_and(|worst| key < *worst) {
+                best.pop();
+                best.push(key);
+            }
+        }
+    }
+
+    let items = best
+        .into_sorted_vec()
+        .into_iter()
+        .filter_map(|(Reverse(from_self_size), from_index, edge_index)| {
+            let node = snapshot.node_view(from_index)?;
+            let edge = snapshot.edge_view(edge_index)?;
+            Some(RetainerSummary {
+                from_index,
                 from_id: node.id(),
                 from_name: node.name().map(str::to_string),
                 from_node_type: node.node_type().map(str::to_string),
                 from_self_size,
                 edge_index,
                 edge_type: edge.edge_type().map(str::to_string),
-                edge_name: edge_name(snapshot, edge),
-            });
-        }
-    }
-
-    items.sort_by(|a, b| {
-        b.from_self_size
-            .cmp(&a.from_self_size)
-            .then_with(|| a.from_index.cmp(&b.from_index))
-    });
-    if items.len() > limit {
-        items.truncate(limit);
-    }
-    Ok(items)
+                edge_name: edge_name(snapshot, edge).map(Cow::into_owned),
+            })
+        })
+        .collect();
+    Ok((items, total))
 }
 
-fn top_outgoing_edges(
-    snapshot: &SnapshotRaw,
-    node_index: usize,
-    limit: usize,
-) -> Result<Vec<OutgoingEdgeSummary>, SnapshotError> {
-    let edge_offsets = compute_edge_offsets(snapshot)?;
-    let start_edge =
-        edge_offsets
-            .get(node_index)
-            .copied()
-            .ok_or_else(|| SnapshotError::InvalidData {
-                details: format!("node index out of range: {node_index}"),
-            })?;
+fn raw_records(snapshot: &SnapshotRaw, node_index: usize) -> Result<RawRecords, SnapshotError> {
     let node = snapshot
         .node_view(node_index)
         .ok_or_else(|| SnapshotError::InvalidData {
             details: format!("node index out of range: {node_index}"),
         })?;
-    let edge_count = node.edge_count().unwrap_or(0);
-    let edge_count = usize::try_from(edge_count).map_err(|_| SnapshotError::InvalidData {
-        details: format!("edge_count negative at node {node_index}"),
-    })?;
+    let edges = snapshot
+        .edges_of(node_index)?
+        .map(|edge| RawEdge {
+            edge_index: edge.edge_index(),
+            fields: named_fields(&snapshot.meta.edge_fields, edge.raw_fields()),
+        })
+        .collect();
+    Ok(RawRecords {
+        node: named_fields(&snapshot.meta.node_fields, node.raw_fields()),
+        edges,
+    })
+}
+
+fn named_fields(names: &[String], values: &[i64]) -> Vec<(String, i64)> {
+    names.iter().cloned().zip(values.iter().copied()).collect()
+}
 
+/// The `limit` outgoing edges pointing at the largest nodes, read from at most
+/// `max_edges` of the node's edges (in edge order).
+fn top_outgoing_edges(
+    snapshot: &SnapshotRaw,
+    node_index: usize,
+    limit: usize,
+    edge_name_filter: Option<&str>,
+    max_edges: Option<usize>,
+) -> Result<(Vec<OutgoingEdgeSummary>, Option<EdgesTruncated>), SnapshotError> {
+    let edges = snapshot.edges_of(node_index)?;
+    let total = edges.len();
+    let examined = max_edges.map_or(total, |max| max.min(total));
+    let truncated = (examined < total).then_some(EdgesTruncated { examined, total });
     let mut items: Vec<OutgoingEdgeSummary> = Vec::new();
-    for offset in 0..edge_count {
-        let edge_index = start_edge + offset;
-        let edge = snapshot
-            .edge_view(edge_index)
-            .ok_or_else(|| SnapshotError::InvalidData {
-                details: format!("edge index out of range: {edge_index}"),
-            })?;
+    for edge in edges.take(examined) {
         let to_node = match edge.to_node_index() {
             Some(value) => value,
             None => continue,
         };
+        let edge_name = edge_name(snapshot, edge);
+        if !edge_name_matches(edge_name.as_deref(), edge_name_filter) {
+            continue;
+        }
         let to_node_view = snapshot.node_view(to_node);
         let to_self_size = to_node_view.and_then(|n| n.self_size()).unwrap_or(0);
         items.push(OutgoingEdgeSummary {
-            edge_index,
+            edge_index: edge.edge_index(),
             edge_type: edge.edge_type().map(str::to_string),
-            edge_name: edge_name(snapshot, edge),
+            edge_name: edge_name.map(Cow::into_owned),
             to_index: to_node,
             to_id: to_node_view.and_then(|n| n.id()),
             to_name: to_node_view.and_then(|n| n.name()).map(str::to_string),
@@ -385,102 +582,114 @@ fn top_outgoing_edges(
     if items.len() > limit {
         items.truncate(limit);
     }
-    Ok(items)
+    Ok((items, truncated))
 }
 
-fn shallow_size_distribution(
-    snapshot: &SnapshotRaw,
-    target_name: &str,
-) -> Result<Vec<ShallowSizeBucket>, SnapshotError> {
-    let mut buckets: Vec<ShallowSizeBucket> = DEFAULT_BUCKETS
-        .iter()
-        .map(|(min, max)| ShallowSizeBucket {
-            label: bucket_label(*min, *max),
-            min: *min,
-            max: *max,
-            count: 0,
-        })
-        .collect();
-
-    for index in 0..snapshot.node_count() {
-        let node = snapshot
-            .node_view(index)
-            .ok_or_else(|| SnapshotError::InvalidData {
-                details: format!("node index out of range: {index}"),
-            })?;
-        let name = node.name().unwrap_or("");
-        if name != target_name {
-            continue;
-        }
-        let size = node.self_size().unwrap_or(0);
-        for bucket in buckets.iter_mut() {
-            let in_range = match bucket.max {
-                Some(max) => size >= bucket.min && size <= max,
-                None => size >= bucket.min,
-            };
-            if in_range {
-                bucket.count += 1;
-                break;
-            }
-        }
+/// Nearest-rank `percent`-th percentile (the smallest value with at least `percent`% of
+/// the values at or below it). Reorders `values`; returns 0 when empty.
+fn percentile(values: &mut [i64], percent: usize) -> i64 {
+    if values.is_empty() {
+        return 0;
     }
+    let rank = (values.len() * percent).div_ceil(100).max(1);
+    *values.select_nth_unstable(rank - 1).1
+}
 
-    Ok(buckets)
+pub(crate) fn edge_name_matches(edge_name: Option<&str>, filter: Option<&str>) -> bool {
+    match filter {
+        Some(filter) => edge_name.is_some_and(|name| name.contains(filter)),
+        None => true,
+    }
 }
 
-fn bucket_label(min: i64, max: Option<i64>) -> String {
-    match max {
-        Some(max) => format!("{min}-{max}"),
-        None => format!("{min}+"),
+fn shallow_size_distribution(
+    snapshot: &SnapshotRaw,
+    nodes: &[usize],
+) -> Result<Vec<SizeBucket>, SnapshotError> {
+    let mut histogram = SizeHistogram::new(DEFAULT_BUCKET_BOUNDS)?;
+    for node in nodes.iter().filter_map(|index| snapshot.node_view(*index)) {
+        histogram.add(node.self_size().unwrap_or(0));
     }
+
+    Ok(histogram.into_buckets())
 }
 
-fn compute_edge_offsets(snapshot: &SnapshotRaw) -> Result<Vec<usize>, SnapshotError> {
-    let mut offsets = Vec::with_capacity(snapshot.node_count());
-    let mut cursor = 0usize;
+/// Exact constructor-name match on the integer name index instead of the string.
+/// Nodes without a resolvable name count as `""`, as `node.name().unwrap_or("")` did.
+struct NameMatcher {
+    indices: Vec<usize>,
+    string_count: usize,
+    matches_unnamed: bool,
+}
 
-    for node_index in 0..snapshot.node_count() {
-        offsets.push(cursor);
-        let node = snapshot
-            .node_view(node_index)
-            .ok_or_else(|| SnapshotError::InvalidData {
-                details: format!("node index out of range: {node_index}"),
-            })?;
-        let edge_count = node.edge_count().unwrap_or(0);
-        let edge_count = usize::try_from(edge_count).map_err(|_| SnapshotError::InvalidData {
-            details: format!("edge_count negative at node {node_index}"),
-        })?;
-        cursor = cursor.saturating_add(edge_count);
+impl NameMatcher {
+    fn new(snapshot: &SnapshotRaw, target_name: &str) -> Self {
+        Self {
+            indices: snapshot.string_indices_of(target_name),
+            string_count: snapshot.strings.len(),
+            matches_unnamed: target_name.is_empty(),
+        }
     }
 
-    if cursor != snapshot.edge_count() {
-        return Err(SnapshotError::InvalidData {
-            details: format!(
-                "edge_count sum ({}) does not match edges length ({})",
-                cursor,
-                snapshot.edge_count()
-            ),
-        });
+    fn matches(&self, node: &NodeView<'_>) -> bool {
+        match node.name_index() {
+            Some(index) if index < self.string_count => self.indices.contains(&index),
+            _ => self.matches_unnamed,
+        }
     }
-
-    Ok(offsets)
 }
 
-fn edge_name(snapshot: &SnapshotRaw, edge: EdgeView<'_>) -> Option<String> {
+/// Display name of an edge. Named edges borrow their string-table entry, so filtering
+/// and rendering many edges only allocates for element indices and broken references.
+pub(crate) fn edge_name<'a>(snapshot: &'a SnapshotRaw, edge: EdgeView<'_>) -> Option<Cow<'a, str>> {
     let edge_type = edge.edge_type().unwrap_or("unknown");
     let name_or_index = edge.name_or_index().unwrap_or(-1);
 
     if edge_type == "element" {
-        return Some(format!("[{name_or_index}]"));
+        return Some(Cow::Owned(format!("[{name_or_index}]")));
     }
 
     if name_or_index >= 0 {
         let idx = name_or_index as usize;
         if let Some(name) = snapshot.strings.get(idx) {
-            return Some(name.to_string());
+            return Some(Cow::Borrowed(name.as_str()));
         }
-        return Some(format!("<string:{name_or_index}>"));
+        return Some(Cow::Owned(format!("<string:{name_or_index}>")));
     }
 
-    Some(format!("<name:{name_or_index}>"))
+    Some(Cow::Owned(format!("<name:{name_or_index}>")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::read_snapshot;
+
+    #[test]
+    fn edge_name_borrows_string_table_entries() {
+        let json = r#"
+        {
+          "snapshot": {
+            "meta": {
+              "node_fields": ["type","name","id","self_size","edge_count"],
+              "node_types": [["object"],"string","number","number","number"],
+              "edge_fields": ["type","name_or_index","to_node"],
+              "edge_types": [["property","element"],"string_or_number","node"]
+            }
+          },
+          "nodes": [0,0,1,0,3, 0,1,3,8,0],
+          "edges": [0,1,5, 1,7,5, 0,9,5],
+          "strings": ["root","cache"]
+        }
+        "#;
+        let snapshot = read_snapshot(&mut json.as_bytes()).expect("snapshot");
+        let names: Vec<Cow<'_, str>> = snapshot
+            .edges_of(0)
+            .expect("edges")
+            .filter_map(|edge| edge_name(&snapshot, edge))
+            .collect();
+        assert!(matches!(&names[0], Cow::Borrowed("cache")));
+        assert_eq!(names[1], "[7]");
+        assert_eq!(names[2], "<string:9>");
+    }
 }
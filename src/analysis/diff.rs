@@ -1,8 +1,9 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
 use serde::Serialize;
 
-use crate::analysis::summary::{SummaryOptions, SummaryRow, summarize};
+use crate::analysis::summary::{SummaryOptions, SummaryResult, SummaryRow, summarize};
 use crate::error::SnapshotError;
 use crate::snapshot::SnapshotRaw;
 
@@ -10,6 +11,106 @@ use crate::snapshot::SnapshotRaw;
 pub struct DiffOptions {
     pub top: usize,
     pub contains: Option<String>,
+    /// Drop rows whose absolute self size delta is below this, before `top`.
+    pub min_self_size: Option<i64>,
+    /// Drop rows whose absolute count delta is below this, before `top`.
+    pub min_count: Option<u64>,
+    pub sort: DiffSort,
+    /// Keep only constructors found on this side alone (no nodes on the other), before
+    /// `top`: a whole class of objects that appeared or went away.
+    pub only_in: Option<DiffSide>,
+}
+
+/// One side of a comparison: A is the before snapshot (the first of a series), B the
+/// after one (the last of a series).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffSide {
+    A,
+    B,
+}
+
+impl DiffSide {
+    /// True when the constructor has nodes on this side and none on the other.
+    fn only_here(self, count_a: u64, count_b: u64) -> bool {
+        match self {
+            DiffSide::A => count_a > 0 && count_b == 0,
+            DiffSide::B => count_a == 0 && count_b > 0,
+        }
+    }
+}
+
+/// Row order, applied after the filters and before `top`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiffSort {
+    /// Largest absolute self size change first.
+    #[default]
+    SizeDelta,
+    /// Largest absolute count change first.
+    CountDelta,
+    /// Largest self size in B (the last snapshot of a series) first.
+    SizeB,
+    /// Largest `size_b / size_a` first; constructors new in B rank as infinite growth.
+    GrowthRatio,
+}
+
+impl DiffSort {
+    fn compare(self, a: &SortKey<'_>, b: &SortKey<'_>) -> Ordering {
+        let by_size_delta = || b.size_delta.abs().cmp(&a.size_delta.abs());
+        let by_count_delta = || b.count_delta.abs().cmp(&a.count_delta.abs());
+        let primary = match self {
+            DiffSort::SizeDelta => by_size_delta().then_with(by_count_delta),
+            DiffSort::CountDelta => by_count_delta().then_with(by_size_delta),
+            DiffSort::SizeB => b.size_b.cmp(&a.size_b).then_with(by_size_delta),
+            DiffSort::GrowthRatio => b
+                .growth_ratio()
+                .total_cmp(&a.growth_ratio())
+                .then_with(by_size_delta),
+        };
+        primary.then_with(|| a.name.cmp(b.name))
+    }
+}
+
+/// The fields every sort order looks at, shared by pair and series rows.
+struct SortKey<'a> {
+    name: &'a str,
+    count_delta: i64,
+    size_delta: i64,
+    size_a: i64,
+    size_b: i64,
+}
+
+impl<'a> From<&'a DiffRow> for SortKey<'a> {
+    fn from(row: &'a DiffRow) -> Self {
+        Self {
+            name: &row.name,
+            count_delta: row.count_delta,
+            size_delta: row.self_size_sum_delta,
+            size_a: row.self_size_sum_a,
+            size_b: row.self_size_sum_b,
+        }
+    }
+}
+
+impl<'a> From<&'a DiffSeriesRow> for SortKey<'a> {
+    fn from(row: &'a DiffSeriesRow) -> Self {
+        Self {
+            name: &row.name,
+            count_delta: row.count_delta,
+            size_delta: row.self_size_sum_delta,
+            size_a: row.self_size_sums.first().copied().unwrap_or(0),
+            size_b: row.self_size_sums.last().copied().unwrap_or(0),
+        }
+    }
+}
+
+impl SortKey<'_> {
+    fn growth_ratio(&self) -> f64 {
+        match (self.size_a, self.size_b) {
+            (0, 0) => 1.0,
+            (0, _) => f64::INFINITY,
+            (a, b) => b as f64 / a as f64,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -27,6 +128,8 @@ pub struct DiffRow {
 pub struct DiffResult {
     pub total_nodes_a: usize,
     pub total_nodes_b: usize,
+    pub snapshot_fingerprint_a: u64,
+    pub snapshot_fingerprint_b: u64,
     pub rows: Vec<DiffRow>,
 }
 
@@ -35,25 +138,24 @@ pub fn diff_summaries(
     snapshot_b: &SnapshotRaw,
     options: DiffOptions,
 ) -> Result<DiffResult, SnapshotError> {
-    let summary_a = summarize(
-        snapshot_a,
-        SummaryOptions {
-            top: usize::MAX,
-            contains: None,
-        },
-    )?;
-    let summary_b = summarize(
-        snapshot_b,
-        SummaryOptions {
-            top: usize::MAX,
-            contains: None,
-        },
-    )?;
+    let summary_a = summarize_all(snapshot_a)?;
+    let summary_b = summarize_all(snapshot_b)?;
+    Ok(diff_summary_results(&summary_a, &summary_b, options))
+}
 
+/// The comparison behind `diff_summaries`, for summaries that are already at hand, such
+/// as saved `summary --format json` files read back with `output::summary::parse_json`.
+/// A constructor missing from one summary counts as zero there, so summaries cut short
+/// by `--top` only compare the rows they list.
+pub fn diff_summary_results(
+    summary_a: &SummaryResult,
+    summary_b: &SummaryResult,
+    options: DiffOptions,
+) -> DiffResult {
     let map_a = map_by_name(&summary_a.rows);
     let map_b = map_by_name(&summary_b.rows);
 
-    let mut names: Vec<String> = map_a.keys().chain(map_b.keys()).cloned().collect();
+    let mut names: Vec<&str> = map_a.keys().chain(map_b.keys()).copied().collect();
     names.sort();
     names.dedup();
 
@@ -64,53 +166,264 @@ pub fn diff_summaries(
                 continue;
             }
         }
-        let row_a = map_a.get(&name);
-        let row_b = map_b.get(&name);
-        let count_a = row_a.map(|r| r.count).unwrap_or(0);
-        let count_b = row_b.map(|r| r.count).unwrap_or(0);
-        let self_size_sum_a = row_a.map(|r| r.self_size_sum).unwrap_or(0);
-        let self_size_sum_b = row_b.map(|r| r.self_size_sum).unwrap_or(0);
+        let (count_a, self_size_sum_a) = map_a.get(name).copied().unwrap_or_default();
+        let (count_b, self_size_sum_b) = map_b.get(name).copied().unwrap_or_default();
+        if options
+            .only_in
+            .is_some_and(|side| !side.only_here(count_a, count_b))
+        {
+            continue;
+        }
+        let count_delta = count_b as i64 - count_a as i64;
+        let self_size_sum_delta = self_size_sum_b - self_size_sum_a;
+        if options
+            .min_self_size
+            .is_some_and(|min| self_size_sum_delta.abs() < min)
+            || options
+                .min_count
+                .is_some_and(|min| count_delta.unsigned_abs() < min)
+        {
+            continue;
+        }
         rows.push(DiffRow {
-            name,
+            name: name.to_string(),
             count_a,
             count_b,
-            count_delta: count_b as i64 - count_a as i64,
+            count_delta,
             self_size_sum_a,
             self_size_sum_b,
-            self_size_sum_delta: self_size_sum_b - self_size_sum_a,
+            self_size_sum_delta,
         });
     }
 
-    rows.sort_by(|a, b| {
-        b.self_size_sum_delta
-            .abs()
-            .cmp(&a.self_size_sum_delta.abs())
-            .then_with(|| b.count_delta.abs().cmp(&a.count_delta.abs()))
-            .then_with(|| a.name.cmp(&b.name))
-    });
+    rows.sort_by(|a, b| options.sort.compare(&a.into(), &b.into()));
 
     if rows.len() > options.top {
         rows.truncate(options.top);
     }
 
-    Ok(DiffResult {
+    DiffResult {
         total_nodes_a: summary_a.total_nodes,
         total_nodes_b: summary_b.total_nodes,
+        snapshot_fingerprint_a: summary_a.snapshot_fingerprint,
+        snapshot_fingerprint_b: summary_b.snapshot_fingerprint,
+        rows,
+    }
+}
+
+/// How a constructor's self size moved across a series of snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trend {
+    /// Grew between every pair of consecutive snapshots: the signature of a leak.
+    Growing,
+    Shrinking,
+    Flat,
+    /// Went both up and down (or stalled part of the way).
+    Noisy,
+}
+
+impl Trend {
+    fn of(values: &[i64]) -> Self {
+        let steps = || values.windows(2).map(|pair| pair[1].cmp(&pair[0]));
+        if steps().all(|step| step.is_gt()) {
+            Trend::Growing
+        } else if steps().all(|step| step.is_lt()) {
+            Trend::Shrinking
+        } else if steps().all(|step| step.is_eq()) {
+            Trend::Flat
+        } else {
+            Trend::Noisy
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Trend::Growing => "↑",
+            Trend::Shrinking => "↓",
+            Trend::Flat => "=",
+            Trend::Noisy => "∿",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffSeriesRow {
+    pub name: String,
+    /// One entry per snapshot, in input order.
+    pub counts: Vec<u64>,
+    pub self_size_sums: Vec<i64>,
+    /// Last minus first snapshot.
+    pub count_delta: i64,
+    pub self_size_sum_delta: i64,
+    /// Judged on `self_size_sums`.
+    pub trend: Trend,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffSeriesResult {
+    pub total_nodes: Vec<usize>,
+    pub snapshot_fingerprints: Vec<u64>,
+    pub rows: Vec<DiffSeriesRow>,
+}
+
+/// Per-constructor counts and sizes across snapshots taken in order (t0, t1, ...).
+/// `options` filters and ranks on the change between the first and last snapshot,
+/// like `diff_summaries` does for a pair. Takes one summary per snapshot (see
+/// `summarize_all`), or saved summary JSON files read back (see `diff_summary_results`).
+pub fn diff_series_results(
+    summaries: &[SummaryResult],
+    options: DiffOptions,
+) -> Result<DiffSeriesResult, SnapshotError> {
+    check_series_len(summaries.len())?;
+    let maps: Vec<HashMap<&str, (u64, i64)>> = summaries
+        .iter()
+        .map(|summary| map_by_name(&summary.rows))
+        .collect();
+
+    let mut names: Vec<&str> = maps.iter().flat_map(|map| map.keys()).copied().collect();
+    names.sort();
+    names.dedup();
+
+    let mut rows = Vec::new();
+    for name in names {
+        if options
+            .contains
+            .as_deref()
+            .is_some_and(|filter| !name.contains(filter))
+        {
+            continue;
+        }
+        let counts: Vec<u64> = maps
+            .iter()
+            .map(|map| map.get(name).map(|(count, _)| *count).unwrap_or(0))
+            .collect();
+        let self_size_sums: Vec<i64> = maps
+            .iter()
+            .map(|map| map.get(name).map(|(_, size)| *size).unwrap_or(0))
+            .collect();
+        if options
+            .only_in
+            .is_some_and(|side| !side.only_here(counts[0], counts[counts.len() - 1]))
+        {
+            continue;
+        }
+        let count_delta = counts[counts.len() - 1] as i64 - counts[0] as i64;
+        let self_size_sum_delta = self_size_sums[self_size_sums.len() - 1] - self_size_sums[0];
+        if options
+            .min_self_size
+            .is_some_and(|min| self_size_sum_delta.abs() < min)
+            || options
+                .min_count
+                .is_some_and(|min| count_delta.unsigned_abs() < min)
+        {
+            continue;
+        }
+        rows.push(DiffSeriesRow {
+            name: name.to_string(),
+            trend: Trend::of(&self_size_sums),
+            counts,
+            self_size_sums,
+            count_delta,
+            self_size_sum_delta,
+        });
+    }
+
+    rows.sort_by(|a, b| options.sort.compare(&a.into(), &b.into()));
+    rows.truncate(options.top);
+
+    Ok(DiffSeriesResult {
+        total_nodes: summaries
+            .iter()
+            .map(|summary| summary.total_nodes)
+            .collect(),
+        snapshot_fingerprints: summaries
+            .iter()
+            .map(|summary| summary.snapshot_fingerprint)
+            .collect(),
         rows,
     })
 }
 
-fn map_by_name(rows: &[SummaryRow]) -> HashMap<String, SummaryRow> {
-    rows.iter()
-        .map(|row| {
-            (
-                row.name.clone(),
-                SummaryRow {
-                    name: row.name.clone(),
-                    count: row.count,
-                    self_size_sum: row.self_size_sum,
-                },
-            )
-        })
-        .collect()
+fn check_series_len(len: usize) -> Result<(), SnapshotError> {
+    if len < 2 {
+        return Err(SnapshotError::InvalidData {
+            details: format!("a diff series needs at least 2 snapshots (got {len})"),
+        });
+    }
+    Ok(())
+}
+
+/// Every constructor of `snapshot`, unfiltered, as the base of a diff. A series keeps
+/// only these summaries, so each snapshot can be dropped before the next is read.
+pub fn summarize_all(snapshot: &SnapshotRaw) -> Result<SummaryResult, SnapshotError> {
+    summarize(
+        snapshot,
+        SummaryOptions {
+            top: usize::MAX,
+            ..Default::default()
+        },
+    )
+}
+
+/// (count, self size sum) per constructor name. Rows whose names share the text but not
+/// the string-table index are added together, as a diff compares names.
+fn map_by_name(rows: &[SummaryRow]) -> HashMap<&str, (u64, i64)> {
+    let mut map: HashMap<&str, (u64, i64)> = HashMap::new();
+    for row in rows {
+        let entry = map.entry(row.name.as_str()).or_default();
+        entry.0 += row.count;
+        entry.1 += row.self_size_sum;
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str, count_delta: i64, size_a: i64, size_b: i64) -> SortKey<'_> {
+        SortKey {
+            name,
+            count_delta,
+            size_delta: size_b - size_a,
+            size_a,
+            size_b,
+        }
+    }
+
+    fn order(sort: DiffSort, keys: &[SortKey<'_>]) -> Vec<String> {
+        let mut indices: Vec<usize> = (0..keys.len()).collect();
+        indices.sort_by(|a, b| sort.compare(&keys[*a], &keys[*b]));
+        indices
+            .into_iter()
+            .map(|index| keys[index].name.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn growth_ratio_surfaces_small_doubling_and_new_constructors() {
+        let keys = [
+            key("Big", 1, 1_000_000, 1_100_000),
+            key("Doubled", 1, 100, 200),
+            key("New", 5, 0, 10),
+            key("Gone", -3, 0, 0),
+        ];
+        assert_eq!(
+            order(DiffSort::GrowthRatio, &keys),
+            vec!["New", "Doubled", "Big", "Gone"]
+        );
+        assert_eq!(
+            order(DiffSort::SizeDelta, &keys),
+            vec!["Big", "Doubled", "New", "Gone"]
+        );
+        assert_eq!(
+            order(DiffSort::CountDelta, &keys),
+            vec!["New", "Gone", "Big", "Doubled"]
+        );
+        assert_eq!(
+            order(DiffSort::SizeB, &keys),
+            vec!["Big", "Doubled", "New", "Gone"]
+        );
+    }
 }
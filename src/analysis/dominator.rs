@@ -1,14 +1,28 @@
 use std::sync::mpsc::Sender;
 
-use crate::analysis::retainers::find_roots;
+use crate::analysis::retainers::{RootOptions, find_roots};
+use crate::analysis::unreachable::{Unreachable, explain_unreachable};
 use crate::cancel::CancelToken;
 use crate::error::SnapshotError;
 use crate::snapshot::SnapshotRaw;
 
+/// Edge types left out of the dominator graph unless the caller overrides them.
+/// Weak edges do not keep their target alive, so DevTools excludes them from retained sizes.
+pub const DEFAULT_IGNORED_EDGE_TYPES: &[&str] = &["weak"];
+
 pub struct DominatorOptions {
     pub max_depth: usize,
     pub cancel: CancelToken,
     pub progress: Option<Sender<DominatorProgress>>,
+    /// Edge types skipped while building the graph (see `DEFAULT_IGNORED_EDGE_TYPES`).
+    pub ignore_edge_types: Vec<String>,
+}
+
+pub fn default_ignored_edge_types() -> Vec<String> {
+    DEFAULT_IGNORED_EDGE_TYPES
+        .iter()
+        .map(|value| value.to_string())
+        .collect()
 }
 
 #[derive(Debug, Clone)]
@@ -16,9 +30,34 @@ pub struct DominatorResult {
     pub target: usize,
     pub roots: Vec<usize>,
     pub chain: Vec<usize>,
+    /// Retained size of each `chain` entry (same order).
+    pub retained_sizes: Vec<i64>,
+    /// Sum of every node's self size, the denominator for retained percentages.
+    pub total_size: i64,
+    /// Set when the target is not in the dominator tree; `chain` is then just the target.
+    pub unreachable: Option<Unreachable>,
 }
 
+/// What a target holds onto: the nodes it immediately dominates, the other half of the
+/// chain that `DominatorResult` walks up to the roots.
 #[derive(Debug, Clone)]
+pub struct DominatedResult {
+    pub target: usize,
+    pub target_retained_size: i64,
+    /// Immediately dominated nodes with their retained sizes, largest first (lowest node
+    /// index on ties), at most `top` of them.
+    pub children: Vec<(usize, i64)>,
+    /// Immediately dominated nodes before `top` was applied.
+    pub total_children: usize,
+    /// Retained size of the children left out by `top`.
+    pub omitted_retained_size: i64,
+    /// Sum of every node's self size, the denominator for retained percentages.
+    pub total_size: i64,
+    /// Set when the target is not in the dominator tree, which leaves `children` empty.
+    pub unreachable: Option<Unreachable>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DominatorIndex {
     pub roots: Vec<usize>,
     pub idom: Vec<Option<usize>>,
@@ -47,20 +86,77 @@ pub fn dominator_chain(
     target: usize,
     options: DominatorOptions,
 ) -> Result<DominatorResult, SnapshotError> {
-    let index = compute_dominator_index(snapshot, options.cancel.clone(), options.progress)?;
-    dominator_chain_from_index(&index, target, options.max_depth, options.cancel)
+    let index = compute_dominator_index(
+        snapshot,
+        &options.ignore_edge_types,
+        options.cancel.clone(),
+        options.progress,
+    )?;
+    dominator_chain_from_index(snapshot, &index, target, options.max_depth, options.cancel)
 }
 
 pub fn compute_dominator_index(
     snapshot: &SnapshotRaw,
+    ignore_edge_types: &[String],
+    cancel: CancelToken,
+    progress: Option<Sender<DominatorProgress>>,
+) -> Result<DominatorIndex, SnapshotError> {
+    compute_dominator_index_with_roots(
+        snapshot,
+        ignore_edge_types,
+        &RootOptions::default(),
+        cancel,
+        progress,
+    )
+}
+
+/// `compute_dominator_index` rooted at the nodes `root_options` selects.
+pub fn compute_dominator_index_with_roots(
+    snapshot: &SnapshotRaw,
+    ignore_edge_types: &[String],
+    root_options: &RootOptions,
     cancel: CancelToken,
     progress: Option<Sender<DominatorProgress>>,
 ) -> Result<DominatorIndex, SnapshotError> {
-    let roots = find_roots(snapshot)?;
+    compute_dominator_index_capped(
+        snapshot,
+        ignore_edge_types,
+        root_options,
+        None,
+        cancel,
+        progress,
+    )
+    .map(|(index, _)| index)
+}
+
+/// `compute_dominator_index_with_roots` reading at most `max_edges_per_node` outgoing
+/// edges of each node, so one giant array cannot dominate the build time. Also returns
+/// how many nodes had edges left out; with any, the tree and retained sizes are only
+/// an approximation (the cut edges may be what keeps a node alive).
+pub fn compute_dominator_index_capped(
+    snapshot: &SnapshotRaw,
+    ignore_edge_types: &[String],
+    root_options: &RootOptions,
+    max_edges_per_node: Option<usize>,
+    cancel: CancelToken,
+    progress: Option<Sender<DominatorProgress>>,
+) -> Result<(DominatorIndex, usize), SnapshotError> {
+    let roots = find_roots(snapshot, root_options)?;
     let node_total = snapshot.node_count() as u64;
     let edge_total = snapshot.edge_count() as u64;
 
-    let (succs, preds) = build_graph(snapshot, progress.as_ref(), node_total, edge_total)?;
+    let Graph {
+        succs,
+        preds,
+        truncated_nodes,
+    } = build_graph(
+        snapshot,
+        ignore_edge_types,
+        max_edges_per_node,
+        progress.as_ref(),
+        node_total,
+        edge_total,
+    )?;
     if cancel.is_cancelled() {
         return Err(SnapshotError::Cancelled);
     }
@@ -122,14 +218,28 @@ pub fn compute_dominator_index(
         },
     );
 
-    Ok(DominatorIndex { roots, idom })
+    Ok((DominatorIndex { roots, idom }, truncated_nodes))
 }
 
 pub fn dominator_chain_from_index(
+    snapshot: &SnapshotRaw,
     index: &DominatorIndex,
     target: usize,
     max_depth: usize,
     cancel: CancelToken,
+) -> Result<DominatorResult, SnapshotError> {
+    let retained = retained_sizes(snapshot, index);
+    dominator_chain_with_retained(snapshot, index, &retained, target, max_depth, cancel)
+}
+
+/// Like `dominator_chain_from_index`, reusing retained sizes the caller already computed.
+pub fn dominator_chain_with_retained(
+    snapshot: &SnapshotRaw,
+    index: &DominatorIndex,
+    retained: &[i64],
+    target: usize,
+    max_depth: usize,
+    cancel: CancelToken,
 ) -> Result<DominatorResult, SnapshotError> {
     let mut chain = Vec::new();
     let mut current = target;
@@ -155,23 +265,127 @@ pub fn dominator_chain_from_index(
         });
     }
 
+    let unreachable = if index.idom.get(target).copied().flatten().is_none() {
+        Some(explain_unreachable(snapshot, index, target)?)
+    } else {
+        None
+    };
+
     chain.reverse();
+    let retained_sizes = chain
+        .iter()
+        .map(|node| retained.get(*node).copied().unwrap_or(0))
+        .collect();
     Ok(DominatorResult {
         target,
         roots: index.roots.clone(),
         chain,
+        retained_sizes,
+        total_size: snapshot
+            .nodes()
+            .map(|node| node.self_size().unwrap_or(0))
+            .sum(),
+        unreachable,
+    })
+}
+
+/// The `top` nodes `target` immediately dominates, by retained size. Inverts `idom` for
+/// the one target only, so no children map of the whole tree is built.
+pub fn dominated_children(
+    snapshot: &SnapshotRaw,
+    index: &DominatorIndex,
+    retained: &[i64],
+    target: usize,
+    top: usize,
+    cancel: CancelToken,
+) -> Result<DominatedResult, SnapshotError> {
+    if cancel.is_cancelled() {
+        return Err(SnapshotError::Cancelled);
+    }
+    let mut children: Vec<(usize, i64)> = index
+        .idom
+        .iter()
+        .enumerate()
+        .filter(|(node, parent)| **parent == Some(target) && *node != target)
+        .map(|(node, _)| (node, retained.get(node).copied().unwrap_or(0)))
+        .collect();
+    children.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let total_children = children.len();
+    let omitted_retained_size = children.iter().skip(top).map(|(_, size)| size).sum();
+    children.truncate(top);
+
+    let unreachable = if index.idom.get(target).copied().flatten().is_none() {
+        Some(explain_unreachable(snapshot, index, target)?)
+    } else {
+        None
+    };
+
+    Ok(DominatedResult {
+        target,
+        target_retained_size: retained.get(target).copied().unwrap_or(0),
+        children,
+        total_children,
+        omitted_retained_size,
+        total_size: snapshot
+            .nodes()
+            .map(|node| node.self_size().unwrap_or(0))
+            .sum(),
+        unreachable,
     })
 }
 
+/// Retained size per node: its self size plus everything it dominates.
+/// Nodes unreachable from the roots keep only their own self size.
+pub fn retained_sizes(snapshot: &SnapshotRaw, index: &DominatorIndex) -> Vec<i64> {
+    let mut retained: Vec<i64> = snapshot
+        .nodes()
+        .map(|node| node.self_size().unwrap_or(0))
+        .collect();
+    let n = retained.len().min(index.idom.len());
+
+    let mut children = vec![Vec::new(); n];
+    let mut stack = Vec::new();
+    for node in 0..n {
+        match index.idom[node] {
+            Some(parent) if parent == node => stack.push(node),
+            Some(parent) if parent < n => children[parent].push(node),
+            _ => {}
+        }
+    }
+
+    // Preorder over the dominator tree; walking it backwards visits children before parents.
+    let mut order = Vec::with_capacity(n);
+    while let Some(node) = stack.pop() {
+        order.push(node);
+        stack.extend_from_slice(&children[node]);
+    }
+    for &node in order.iter().rev() {
+        if let Some(parent) = index.idom[node].filter(|parent| *parent != node) {
+            retained[parent] += retained[node];
+        }
+    }
+    retained
+}
+
+struct Graph {
+    succs: Vec<Vec<usize>>,
+    preds: Vec<Vec<usize>>,
+    /// Nodes with more outgoing edges than `max_edges_per_node`.
+    truncated_nodes: usize,
+}
+
 fn build_graph(
     snapshot: &SnapshotRaw,
+    ignore_edge_types: &[String],
+    max_edges_per_node: Option<usize>,
     progress: Option<&Sender<DominatorProgress>>,
     nodes_total: u64,
     edges_total: u64,
-) -> Result<(Vec<Vec<usize>>, Vec<Vec<usize>>), SnapshotError> {
+) -> Result<Graph, SnapshotError> {
     let node_count = snapshot.node_count();
     let mut succs = vec![Vec::new(); node_count];
     let mut preds = vec![Vec::new(); node_count];
+    let mut truncated_nodes = 0usize;
 
     emit_progress(
         progress,
@@ -185,28 +399,18 @@ fn build_graph(
         },
     );
 
-    let edge_offsets = compute_edge_offsets(snapshot)?;
     let mut processed_edges = 0u64;
 
-    for (node_index, start_edge) in edge_offsets.iter().enumerate() {
-        let node = snapshot
-            .node_view(node_index)
-            .ok_or_else(|| SnapshotError::InvalidData {
-                details: format!("node index out of range: {node_index}"),
-            })?;
-        let edge_count = node.edge_count().unwrap_or(0);
-        let edge_count = usize::try_from(edge_count).map_err(|_| SnapshotError::InvalidData {
-            details: format!("edge_count negative at node {node_index}"),
-        })?;
-
-        for offset in 0..edge_count {
-            let edge_index = start_edge + offset;
-            let edge =
-                snapshot
-                    .edge_view(edge_index)
-                    .ok_or_else(|| SnapshotError::InvalidData {
-                        details: format!("edge index out of range: {edge_index}"),
-                    })?;
+    for node in snapshot.nodes() {
+        let node_index = node.node_index();
+        let edges = node.outgoing_edges()?;
+        let edge_count = edges.len();
+        let read = max_edges_per_node.map_or(edge_count, |max| max.min(edge_count));
+        if read < edge_count {
+            truncated_nodes += 1;
+        }
+
+        for edge in edges.take(read) {
             let to_node = match edge.to_node_index() {
                 Some(value) => value,
                 None => continue,
@@ -214,6 +418,11 @@ fn build_graph(
             if to_node >= node_count {
                 continue;
             }
+            if edge.edge_type().is_some_and(|edge_type| {
+                ignore_edge_types.iter().any(|ignored| ignored == edge_type)
+            }) {
+                continue;
+            }
             succs[node_index].push(to_node);
             preds[to_node].push(node_index);
         }
@@ -234,7 +443,11 @@ fn build_graph(
         }
     }
 
-    Ok((succs, preds))
+    Ok(Graph {
+        succs,
+        preds,
+        truncated_nodes,
+    })
 }
 
 struct LtState {
@@ -431,37 +644,6 @@ fn eval(v: usize, ancestor: &mut [usize], label: &mut [usize], semi: &[usize]) -
     label[v]
 }
 
-fn compute_edge_offsets(snapshot: &SnapshotRaw) -> Result<Vec<usize>, SnapshotError> {
-    let mut offsets = Vec::with_capacity(snapshot.node_count());
-    let mut cursor = 0usize;
-
-    for node_index in 0..snapshot.node_count() {
-        offsets.push(cursor);
-        let node = snapshot
-            .node_view(node_index)
-            .ok_or_else(|| SnapshotError::InvalidData {
-                details: format!("node index out of range: {node_index}"),
-            })?;
-        let edge_count = node.edge_count().unwrap_or(0);
-        let edge_count = usize::try_from(edge_count).map_err(|_| SnapshotError::InvalidData {
-            details: format!("edge_count negative at node {node_index}"),
-        })?;
-        cursor = cursor.saturating_add(edge_count);
-    }
-
-    if cursor != snapshot.edge_count() {
-        return Err(SnapshotError::InvalidData {
-            details: format!(
-                "edge_count sum ({}) does not match edges length ({})",
-                cursor,
-                snapshot.edge_count()
-            ),
-        });
-    }
-
-    Ok(offsets)
-}
-
 fn emit_progress(progress: Option<&Sender<DominatorProgress>>, update: DominatorProgress) {
     if let Some(tx) = progress {
         let _ = tx.send(update);
@@ -490,9 +672,187 @@ mod tests {
                 max_depth: 10,
                 cancel: CancelToken::new(),
                 progress: None,
+                ignore_edge_types: default_ignored_edge_types(),
             },
         )
         .expect("dominator");
         assert!(result.chain.len() >= 2);
+        assert_eq!(result.retained_sizes.len(), result.chain.len());
+    }
+
+    #[test]
+    fn retained_sizes_sum_dominated_subtrees() {
+        let snapshot = read_snapshot_file(
+            Path::new("fixtures/small.heapsnapshot"),
+            ReadOptions::new(false, CancelToken::new()),
+        )
+        .expect("snapshot");
+        let index =
+            compute_dominator_index(&snapshot, &[], CancelToken::new(), None).expect("index");
+        let retained = retained_sizes(&snapshot, &index);
+
+        let total: i64 = snapshot
+            .nodes()
+            .map(|node| node.self_size().unwrap_or(0))
+            .sum();
+        // GC roots dominates the whole heap; every node retains at least itself.
+        assert_eq!(retained[index.roots[0]], total);
+        for node in snapshot.nodes() {
+            assert!(retained[node.node_index()] >= node.self_size().unwrap_or(0));
+        }
+    }
+
+    fn weak_edge_snapshot() -> SnapshotRaw {
+        use crate::snapshot::{MetaType, SnapshotMeta};
+
+        let meta = SnapshotMeta {
+            node_fields: vec![
+                "type".to_string(),
+                "name".to_string(),
+                "id".to_string(),
+                "self_size".to_string(),
+                "edge_count".to_string(),
+            ],
+            node_types: vec![
+                MetaType::Array(vec!["synthetic".to_string(), "object".to_string()]),
+                MetaType::String("string".to_string()),
+                MetaType::String("number".to_string()),
+                MetaType::String("number".to_string()),
+                MetaType::String("number".to_string()),
+            ],
+            edge_fields: vec![
+                "type".to_string(),
+                "name_or_index".to_string(),
+                "to_node".to_string(),
+            ],
+            edge_types: vec![
+                MetaType::Array(vec!["property".to_string(), "weak".to_string()]),
+                MetaType::String("string_or_number".to_string()),
+                MetaType::String("node".to_string()),
+            ],
+            location_fields: Vec::new(),
+        };
+        let index = meta.validate().expect("meta ok");
+        SnapshotRaw::new(
+            vec![
+                0, 0, 1, 0, 2, // node 0: GC roots -> A, B
+                1, 1, 2, 10, 1, // node 1: A -> C
+                1, 2, 3, 10, 1, // node 2: B -(weak)-> C
+                1, 3, 4, 10, 0, // node 3: C
+            ],
+            vec![
+                0, 1, 5, // root -> A
+                0, 2, 10, // root -> B
+                0, 3, 15, // A -> C
+                1, 3, 15, // B -(weak)-> C
+            ],
+            vec![
+                "GC roots".to_string(),
+                "A".to_string(),
+                "B".to_string(),
+                "C".to_string(),
+            ],
+            meta,
+            index,
+        )
+    }
+
+    #[test]
+    fn weak_edges_are_ignored_by_default() {
+        let snapshot = weak_edge_snapshot();
+
+        let all_edges =
+            compute_dominator_index(&snapshot, &[], CancelToken::new(), None).expect("index");
+        assert_eq!(all_edges.idom[3], Some(0));
+
+        let strong_only = compute_dominator_index(
+            &snapshot,
+            &default_ignored_edge_types(),
+            CancelToken::new(),
+            None,
+        )
+        .expect("index");
+        assert_eq!(strong_only.idom[3], Some(1));
+        assert_eq!(retained_sizes(&snapshot, &strong_only)[1], 20);
+    }
+
+    #[test]
+    fn max_edges_per_node_cuts_the_graph_and_counts_cut_nodes() {
+        let snapshot = weak_edge_snapshot();
+        let roots = RootOptions::default();
+
+        let (full, cut_nodes) =
+            compute_dominator_index_capped(&snapshot, &[], &roots, None, CancelToken::new(), None)
+                .expect("index");
+        assert_eq!((full.idom[2], cut_nodes), (Some(0), 0));
+
+        // Only root -> A is read, so B is no longer reachable.
+        let (capped, cut_nodes) = compute_dominator_index_capped(
+            &snapshot,
+            &[],
+            &roots,
+            Some(1),
+            CancelToken::new(),
+            None,
+        )
+        .expect("index");
+        assert_eq!((capped.idom[2], cut_nodes), (None, 1));
+        assert_eq!(capped.idom[3], Some(1));
+    }
+
+    #[test]
+    fn unreachable_target_is_explained_by_ignored_edges() {
+        use crate::analysis::unreachable::UnreachableReason;
+
+        let mut snapshot = weak_edge_snapshot();
+        // A -> C becomes weak too, so C is held only through weak edges.
+        snapshot.edges[6] = 1;
+        let index = compute_dominator_index(
+            &snapshot,
+            &default_ignored_edge_types(),
+            CancelToken::new(),
+            None,
+        )
+        .expect("index");
+        let retained = retained_sizes(&snapshot, &index);
+
+        let result =
+            dominator_chain_with_retained(&snapshot, &index, &retained, 3, 10, CancelToken::new())
+                .expect("dominator");
+        assert_eq!(result.chain, vec![3]);
+        let unreachable = result.unreachable.expect("explanation");
+        assert_eq!(
+            unreachable.reason,
+            UnreachableReason::IgnoredEdges {
+                edge_types: vec!["weak".to_string()]
+            }
+        );
+        let holders: Vec<(usize, bool)> = unreachable
+            .retainers
+            .iter()
+            .map(|retainer| (retainer.node, retainer.reachable))
+            .collect();
+        assert_eq!(holders, vec![(1, true), (2, true)]);
+
+        let reachable =
+            dominator_chain_with_retained(&snapshot, &index, &retained, 1, 10, CancelToken::new())
+                .expect("dominator");
+        assert!(reachable.unreachable.is_none());
+    }
+
+    #[test]
+    fn unreachable_target_in_detached_island() {
+        use crate::analysis::unreachable::{UnreachableReason, explain_unreachable};
+
+        let mut snapshot = weak_edge_snapshot();
+        // GC roots' edges point back at itself: nothing reaches A and B, which hold C.
+        snapshot.edges[2] = 0;
+        snapshot.edges[5] = 0;
+        let index =
+            compute_dominator_index(&snapshot, &[], CancelToken::new(), None).expect("index");
+
+        let unreachable = explain_unreachable(&snapshot, &index, 3).expect("explanation");
+        assert_eq!(unreachable.reason, UnreachableReason::Island { size: 3 });
+        assert_eq!(unreachable.explored, 2);
     }
 }
@@ -0,0 +1,274 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::mpsc::Sender;
+
+use crate::analysis::dominator::{
+    DominatorIndex, DominatorProgress, compute_dominator_index_with_roots,
+};
+use crate::analysis::retainers::RootOptions;
+use crate::cancel::CancelToken;
+use crate::error::SnapshotError;
+use crate::output::write::write_atomic_bytes;
+use crate::snapshot::SnapshotRaw;
+
+// File layout (little endian):
+//   magic[8] | cache key u64 | roots_len u64 | roots u64* | idom_len u64 | idom u64*
+// The cache key mixes the snapshot fingerprint with the ignored edge types
+// (and the root name when it is not the default).
+// `None` entries in idom are stored as u64::MAX.
+const MAGIC: &[u8; 8] = b"HSDOM\x00\x02\x00";
+const NONE_MARKER: u64 = u64::MAX;
+
+/// Loads the dominator index from `path` when it was written for the same snapshot,
+/// otherwise computes it and rewrites the cache file.
+pub fn load_or_compute(
+    snapshot: &SnapshotRaw,
+    path: &Path,
+    ignore_edge_types: &[String],
+    roots: &RootOptions,
+    cancel: CancelToken,
+    progress: Option<Sender<DominatorProgress>>,
+) -> Result<DominatorIndex, SnapshotError> {
+    let key = cache_key(snapshot, ignore_edge_types, roots);
+    if let Some(index) = read_cache(path, key, snapshot.node_count())? {
+        return Ok(index);
+    }
+    let index =
+        compute_dominator_index_with_roots(snapshot, ignore_edge_types, roots, cancel, progress)?;
+    write_cache(path, key, &index)?;
+    Ok(index)
+}
+
+/// Returns `None` when the file is missing, stale (fingerprint mismatch) or not a cache file.
+pub fn load(
+    snapshot: &SnapshotRaw,
+    path: &Path,
+    ignore_edge_types: &[String],
+    roots: &RootOptions,
+) -> Result<Option<DominatorIndex>, SnapshotError> {
+    read_cache(
+        path,
+        cache_key(snapshot, ignore_edge_types, roots),
+        snapshot.node_count(),
+    )
+}
+
+pub fn save(
+    snapshot: &SnapshotRaw,
+    path: &Path,
+    ignore_edge_types: &[String],
+    roots: &RootOptions,
+    index: &DominatorIndex,
+) -> Result<(), SnapshotError> {
+    write_cache(path, cache_key(snapshot, ignore_edge_types, roots), index)
+}
+
+/// FNV-1a over the snapshot fingerprint and the sorted ignored edge types, so an index
+/// computed with a different edge filter is never reused. A non-default root name is
+/// mixed in after a separator; the default leaves existing cache files valid.
+fn cache_key(snapshot: &SnapshotRaw, ignore_edge_types: &[String], roots: &RootOptions) -> u64 {
+    let mut types: Vec<&str> = ignore_edge_types.iter().map(String::as_str).collect();
+    types.sort_unstable();
+    types.dedup();
+
+    let root_name = (!roots.is_default()).then_some(roots.root_name.as_str());
+    let mut hash = snapshot.fingerprint();
+    let parts = types.into_iter().map(str::as_bytes).chain(
+        root_name
+            .map(|name| [b"\x01".as_slice(), name.as_bytes()])
+            .into_iter()
+            .flatten(),
+    );
+    for value in parts {
+        for byte in value.iter().copied().chain(std::iter::once(0)) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+fn read_cache(
+    path: &Path,
+    fingerprint: u64,
+    node_count: usize,
+) -> Result<Option<DominatorIndex>, SnapshotError> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(SnapshotError::Io(err)),
+    };
+    Ok(decode(&bytes, fingerprint, node_count))
+}
+
+fn write_cache(path: &Path, fingerprint: u64, index: &DominatorIndex) -> Result<(), SnapshotError> {
+    let mut bytes =
+        Vec::with_capacity(MAGIC.len() + 8 * (3 + index.roots.len() + index.idom.len()));
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&fingerprint.to_le_bytes());
+    bytes.extend_from_slice(&(index.roots.len() as u64).to_le_bytes());
+    for root in &index.roots {
+        bytes.extend_from_slice(&(*root as u64).to_le_bytes());
+    }
+    bytes.extend_from_slice(&(index.idom.len() as u64).to_le_bytes());
+    for dom in &index.idom {
+        let value = dom.map(|value| value as u64).unwrap_or(NONE_MARKER);
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    write_atomic_bytes(path, &bytes)
+}
+
+fn decode(bytes: &[u8], fingerprint: u64, node_count: usize) -> Option<DominatorIndex> {
+    let rest = bytes.strip_prefix(MAGIC.as_slice())?;
+    let mut reader = U64Reader { bytes: rest };
+    if reader.next()? != fingerprint {
+        return None;
+    }
+
+    let roots_len = usize::try_from(reader.next()?).ok()?;
+    let mut roots = Vec::with_capacity(roots_len.min(node_count));
+    for _ in 0..roots_len {
+        roots.push(to_node_index(reader.next()?, node_count)?);
+    }
+
+    let idom_len = usize::try_from(reader.next()?).ok()?;
+    if idom_len != node_count {
+        return None;
+    }
+    let mut idom = Vec::with_capacity(idom_len);
+    for _ in 0..idom_len {
+        let value = reader.next()?;
+        if value == NONE_MARKER {
+            idom.push(None);
+        } else {
+            idom.push(Some(to_node_index(value, node_count)?));
+        }
+    }
+
+    if !reader.bytes.is_empty() {
+        return None;
+    }
+    Some(DominatorIndex { roots, idom })
+}
+
+fn to_node_index(value: u64, node_count: usize) -> Option<usize> {
+    usize::try_from(value)
+        .ok()
+        .filter(|index| *index < node_count)
+}
+
+struct U64Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl U64Reader<'_> {
+    fn next(&mut self) -> Option<u64> {
+        let (head, tail) = self.bytes.split_first_chunk::<8>()?;
+        self.bytes = tail;
+        Some(u64::from_le_bytes(*head))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::dominator::compute_dominator_index;
+    use crate::parser::{ReadOptions, read_snapshot_file};
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> SnapshotRaw {
+        read_snapshot_file(
+            Path::new(&format!("fixtures/{name}.heapsnapshot")),
+            ReadOptions::new(false, CancelToken::new()),
+        )
+        .expect("snapshot")
+    }
+
+    fn cache_path(label: &str) -> PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!(
+            "heapsnap-domcache-{}-{label}.bin",
+            std::process::id()
+        ));
+        path
+    }
+
+    #[test]
+    fn cache_roundtrip_is_identity() {
+        let snapshot = fixture("small");
+        let path = cache_path("roundtrip");
+        let _ = fs::remove_file(&path);
+
+        let computed = load_or_compute(
+            &snapshot,
+            &path,
+            &[],
+            &RootOptions::default(),
+            CancelToken::new(),
+            None,
+        )
+        .expect("index");
+        assert!(path.exists());
+        let loaded = load(&snapshot, &path, &[], &RootOptions::default())
+            .expect("load")
+            .expect("cache hit");
+        assert_eq!(loaded, computed);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn cache_recomputes_on_fingerprint_mismatch() {
+        let small = fixture("small");
+        let medium = fixture("medium");
+        let path = cache_path("mismatch");
+        let _ = fs::remove_file(&path);
+
+        load_or_compute(
+            &small,
+            &path,
+            &[],
+            &RootOptions::default(),
+            CancelToken::new(),
+            None,
+        )
+        .expect("small index");
+        assert!(
+            load(&medium, &path, &[], &RootOptions::default())
+                .expect("load")
+                .is_none()
+        );
+
+        let recomputed = load_or_compute(
+            &medium,
+            &path,
+            &[],
+            &RootOptions::default(),
+            CancelToken::new(),
+            None,
+        )
+        .expect("index");
+        let expected =
+            compute_dominator_index(&medium, &[], CancelToken::new(), None).expect("index");
+        assert_eq!(recomputed, expected);
+        assert_eq!(
+            load(&medium, &path, &[], &RootOptions::default()).expect("load"),
+            Some(expected)
+        );
+        assert!(
+            load(&small, &path, &[], &RootOptions::default())
+                .expect("load")
+                .is_none()
+        );
+
+        let weak = vec!["weak".to_string()];
+        assert!(
+            load(&medium, &path, &weak, &RootOptions::default())
+                .expect("load")
+                .is_none()
+        );
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,132 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::analysis::detail::{edge_name, edge_name_matches, named_nodes};
+use crate::error::SnapshotError;
+use crate::snapshot::{EdgeView, NodeView, SnapshotRaw};
+
+#[derive(Debug)]
+pub struct EdgesOptions {
+    /// Constructor name; every node with exactly this name is expanded.
+    pub name: String,
+    /// List the edges pointing at the nodes instead of the edges leaving them.
+    pub incoming: bool,
+    /// Keep only edges whose edge name contains this string. Applied before paging.
+    pub edge_name_filter: Option<String>,
+    pub skip: usize,
+    pub limit: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeRow {
+    pub edge_index: usize,
+    pub from_index: usize,
+    pub from_id: Option<i64>,
+    pub from_name: Option<String>,
+    pub edge_type: Option<String>,
+    pub edge_name: Option<String>,
+    pub to_index: usize,
+    pub to_id: Option<i64>,
+    pub to_name: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct EdgesResult {
+    pub name: String,
+    pub incoming: bool,
+    pub edge_name_filter: Option<String>,
+    /// Nodes named `name`.
+    pub matched_nodes: usize,
+    /// Edges that passed the filter, before `skip` / `limit`.
+    pub total_edges: u64,
+    pub skip: usize,
+    pub limit: usize,
+    /// Rows in node order, then edge order within a node.
+    pub rows: Vec<EdgeRow>,
+    /// `SnapshotRaw::quick_fingerprint` of the inspected snapshot.
+    pub snapshot_fingerprint: u64,
+}
+
+impl EdgesResult {
+    /// `skip` for the following page, or `None` when this page is the last one.
+    pub fn next_skip(&self) -> Option<usize> {
+        let next = self.skip + self.rows.len();
+        ((next as u64) < self.total_edges).then_some(next)
+    }
+}
+
+pub fn edges(snapshot: &SnapshotRaw, options: EdgesOptions) -> Result<EdgesResult, SnapshotError> {
+    let nodes = named_nodes(snapshot, None, &options.name);
+    let filter = options.edge_name_filter.as_deref();
+    let end = options.skip.saturating_add(options.limit);
+    let mut rows = Vec::new();
+    let mut total_edges: u64 = 0;
+
+    let mut visit = |from: NodeView<'_>, edge: EdgeView<'_>| {
+        let Some(to_index) = edge.to_node_index() else {
+            return;
+        };
+        let name = edge_name(snapshot, edge);
+        if !edge_name_matches(name.as_deref(), filter) {
+            return;
+        }
+        let position = total_edges as usize;
+        total_edges += 1;
+        if position < options.skip || position >= end {
+            return;
+        }
+        let to = snapshot.node_view(to_index);
+        rows.push(EdgeRow {
+            edge_index: edge.edge_index(),
+            from_index: from.node_index(),
+            from_id: from.id(),
+            from_name: from.name().map(str::to_string),
+            edge_type: edge.edge_type().map(str::to_string),
+            edge_name: name.map(Cow::into_owned),
+            to_index,
+            to_id: to.and_then(|node| node.id()),
+            to_name: to.and_then(|node| node.name()).map(str::to_string),
+        });
+    };
+
+    for node in nodes.iter().filter_map(|index| snapshot.node_view(*index)) {
+        if options.incoming {
+            for (holder, edge) in node.retainers()? {
+                visit(holder, edge);
+            }
+        } else {
+            for edge in node.outgoing_edges()? {
+                visit(node, edge);
+            }
+        }
+    }
+
+    Ok(EdgesResult {
+        name: options.name,
+        incoming: options.incoming,
+        edge_name_filter: options.edge_name_filter,
+        matched_nodes: nodes.len(),
+        total_edges,
+        skip: options.skip,
+        limit: options.limit,
+        rows,
+        snapshot_fingerprint: snapshot.quick_fingerprint(),
+    })
+}
+
+/// Edge count per edge type over the whole snapshot, largest first (ties by type name).
+/// Edges whose type cannot be resolved are counted as `"unknown"`.
+pub fn edge_type_histogram(snapshot: &SnapshotRaw) -> Vec<(String, u64)> {
+    let mut counts: HashMap<&str, u64> = HashMap::new();
+    for edge in snapshot.edges() {
+        *counts
+            .entry(edge.edge_type().unwrap_or("unknown"))
+            .or_default() += 1;
+    }
+    let mut histogram: Vec<(String, u64)> = counts
+        .into_iter()
+        .map(|(edge_type, count)| (edge_type.to_string(), count))
+        .collect();
+    histogram.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    histogram
+}
@@ -0,0 +1,104 @@
+use crate::error::SnapshotError;
+use crate::snapshot::SnapshotRaw;
+
+/// Only named edges are compared; `element` / `hidden` edges carry an index, not a name.
+const NAMED_EDGE_TYPES: &[&str] = &["property", "internal"];
+
+#[derive(Debug)]
+pub struct FindOptions {
+    /// Exact edge name, e.g. `url`.
+    pub edge_name: String,
+    /// Exact name of the edge target. String nodes carry their value as the name, so this
+    /// matches string properties by value.
+    pub value: String,
+    pub limit: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FindMatch {
+    pub owner_index: usize,
+    pub owner_id: Option<i64>,
+    pub owner_name: Option<String>,
+    pub edge_index: usize,
+    pub edge_type: Option<String>,
+    pub target_index: usize,
+    pub target_id: Option<i64>,
+    pub target_type: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct FindResult {
+    pub edge_name: String,
+    pub value: String,
+    /// Every matching edge, including the ones past `limit`.
+    pub total_matches: u64,
+    pub limit: usize,
+    /// Matches in node order, then edge order within a node.
+    pub matches: Vec<FindMatch>,
+    /// `SnapshotRaw::quick_fingerprint` of the inspected snapshot.
+    pub snapshot_fingerprint: u64,
+}
+
+/// Finds the nodes holding a `property` / `internal` edge named `edge_name` whose target
+/// is named `value`. Both are resolved to string-table indices up front, so the scan
+/// compares integers only.
+pub fn find(snapshot: &SnapshotRaw, options: FindOptions) -> Result<FindResult, SnapshotError> {
+    let edge_names = snapshot.string_indices_of(&options.edge_name);
+    let values = snapshot.string_indices_of(&options.value);
+    let mut matches = Vec::new();
+    let mut total_matches: u64 = 0;
+
+    if !edge_names.is_empty() && !values.is_empty() {
+        for owner in snapshot.nodes() {
+            for edge in owner.outgoing_edges()? {
+                let Some(edge_type) = edge.edge_type() else {
+                    continue;
+                };
+                if !NAMED_EDGE_TYPES.contains(&edge_type) {
+                    continue;
+                }
+                let Some(name) = edge.name_or_index().and_then(|n| usize::try_from(n).ok()) else {
+                    continue;
+                };
+                if !edge_names.contains(&name) {
+                    continue;
+                }
+                let Some(target) = edge
+                    .to_node_index()
+                    .and_then(|index| snapshot.node_view(index))
+                else {
+                    continue;
+                };
+                if !target
+                    .name_index()
+                    .is_some_and(|index| values.contains(&index))
+                {
+                    continue;
+                }
+                total_matches += 1;
+                if matches.len() >= options.limit {
+                    continue;
+                }
+                matches.push(FindMatch {
+                    owner_index: owner.node_index(),
+                    owner_id: owner.id(),
+                    owner_name: owner.name().map(str::to_string),
+                    edge_index: edge.edge_index(),
+                    edge_type: Some(edge_type.to_string()),
+                    target_index: target.node_index(),
+                    target_id: target.id(),
+                    target_type: target.node_type().map(str::to_string),
+                });
+            }
+        }
+    }
+
+    Ok(FindResult {
+        edge_name: options.edge_name,
+        value: options.value,
+        total_matches,
+        limit: options.limit,
+        matches,
+        snapshot_fingerprint: snapshot.quick_fingerprint(),
+    })
+}
@@ -0,0 +1,170 @@
+use crate::analysis::summary::resolve_node_type;
+use crate::error::SnapshotError;
+use crate::snapshot::SnapshotRaw;
+
+/// Lower bounds (bytes) of the default buckets: 0, 1-31, 32-127, ..., 32768+.
+pub const DEFAULT_BUCKET_BOUNDS: &[i64] = &[0, 1, 32, 128, 512, 2048, 8192, 32768];
+
+#[derive(Debug)]
+pub struct HistogramOptions {
+    /// Only count nodes whose name contains this string.
+    pub name: Option<String>,
+    /// Only count nodes of this V8 node type (validated by `resolve_node_type`).
+    pub node_type: Option<String>,
+    /// Bucket lower bounds; see `SizeHistogram::new`.
+    pub bounds: Vec<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SizeBucket {
+    pub label: String,
+    pub min: i64,
+    pub max: Option<i64>,
+    pub count: u64,
+    pub self_size_sum: i64,
+}
+
+#[derive(Debug)]
+pub struct HistogramResult {
+    pub total_nodes: usize,
+    /// Nodes that passed the filters and were binned.
+    pub matched_nodes: u64,
+    pub self_size_sum: i64,
+    pub name: Option<String>,
+    pub node_type: Option<String>,
+    pub buckets: Vec<SizeBucket>,
+    /// `SnapshotRaw::quick_fingerprint` of the binned snapshot.
+    pub snapshot_fingerprint: u64,
+}
+
+/// Self-size buckets with ascending lower bounds; each bucket ends one byte below the next.
+#[derive(Debug, Clone)]
+pub struct SizeHistogram {
+    buckets: Vec<SizeBucket>,
+}
+
+impl SizeHistogram {
+    /// `bounds` must be strictly ascending. A `0` bound is prepended when missing so that
+    /// every non-negative size lands in a bucket.
+    pub fn new(bounds: &[i64]) -> Result<Self, SnapshotError> {
+        let mut bounds = bounds.to_vec();
+        if bounds.first().is_none_or(|first| *first > 0) {
+            bounds.insert(0, 0);
+        }
+        if bounds[0] < 0 || bounds.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(SnapshotError::InvalidData {
+                details: format!(
+                    "bucket bounds must be non-negative and strictly ascending (got {bounds:?})"
+                ),
+            });
+        }
+
+        let buckets = bounds
+            .iter()
+            .enumerate()
+            .map(|(index, min)| {
+                let max = bounds.get(index + 1).map(|next| next - 1);
+                SizeBucket {
+                    label: bucket_label(*min, max),
+                    min: *min,
+                    max,
+                    count: 0,
+                    self_size_sum: 0,
+                }
+            })
+            .collect();
+        Ok(Self { buckets })
+    }
+
+    /// Counts `size` in its bucket; negative sizes are ignored.
+    pub fn add(&mut self, size: i64) {
+        let position = self.buckets.partition_point(|bucket| bucket.min <= size);
+        if let Some(bucket) = position
+            .checked_sub(1)
+            .and_then(|index| self.buckets.get_mut(index))
+        {
+            bucket.count += 1;
+            bucket.self_size_sum += size;
+        }
+    }
+
+    pub fn into_buckets(self) -> Vec<SizeBucket> {
+        self.buckets
+    }
+}
+
+/// Bins the self size of every node that passes the `--name` / `--node-type` filters.
+pub fn histogram(
+    snapshot: &SnapshotRaw,
+    options: HistogramOptions,
+) -> Result<HistogramResult, SnapshotError> {
+    let node_type_filter = options
+        .node_type
+        .as_deref()
+        .map(|name| resolve_node_type(snapshot, name))
+        .transpose()?;
+    let mut histogram = SizeHistogram::new(&options.bounds)?;
+    let mut matched_nodes = 0;
+    let mut self_size_sum = 0;
+
+    for node in snapshot.nodes() {
+        if let Some(filter) = options.name.as_deref()
+            && !node.name().unwrap_or("").contains(filter)
+        {
+            continue;
+        }
+        if let Some(filter) = node_type_filter
+            && node.node_type() != Some(filter)
+        {
+            continue;
+        }
+        let size = node.self_size().unwrap_or(0);
+        histogram.add(size);
+        matched_nodes += 1;
+        self_size_sum += size;
+    }
+
+    Ok(HistogramResult {
+        total_nodes: snapshot.node_count(),
+        matched_nodes,
+        self_size_sum,
+        name: options.name,
+        node_type: node_type_filter.map(str::to_string),
+        buckets: histogram.into_buckets(),
+        snapshot_fingerprint: snapshot.quick_fingerprint(),
+    })
+}
+
+fn bucket_label(min: i64, max: Option<i64>) -> String {
+    match max {
+        Some(max) => format!("{min}-{max}"),
+        None => format!("{min}+"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_land_in_the_bucket_below_the_next_bound() {
+        let mut histogram = SizeHistogram::new(&[32, 128]).expect("bounds");
+        for size in [0, 31, 32, 127, 128, 5000, -1] {
+            histogram.add(size);
+        }
+        let buckets = histogram.into_buckets();
+
+        let labels: Vec<&str> = buckets.iter().map(|bucket| bucket.label.as_str()).collect();
+        assert_eq!(labels, vec!["0-31", "32-127", "128+"]);
+        let counts: Vec<u64> = buckets.iter().map(|bucket| bucket.count).collect();
+        assert_eq!(counts, vec![2, 2, 2]);
+        assert_eq!(buckets[2].self_size_sum, 5128);
+    }
+
+    #[test]
+    fn unordered_bounds_are_rejected() {
+        assert!(SizeHistogram::new(&[0, 128, 32]).is_err());
+        assert!(SizeHistogram::new(&[0, 0]).is_err());
+        assert!(SizeHistogram::new(&[-8, 0]).is_err());
+    }
+}
@@ -1,5 +1,18 @@
+pub mod budget;
 pub mod detail;
 pub mod diff;
 pub mod dominator;
+pub mod dominator_cache;
+pub mod edges;
+pub mod find;
+pub mod histogram;
+pub mod name_index;
+pub mod reachability;
 pub mod retainers;
+pub mod strings;
+pub mod suggest;
 pub mod summary;
+pub mod tree;
+pub mod triage;
+pub mod unreachable;
+pub mod verify;
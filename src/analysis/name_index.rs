@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use crate::snapshot::SnapshotRaw;
+
+/// Node indices grouped by constructor name, built in one pass over the nodes so that
+/// repeated name lookups (e.g. every `serve` click) skip the full node scan.
+///
+/// Only names and index lists are stored, which is far smaller than the node array.
+/// String-table duplicates of one name share a single entry.
+#[derive(Debug, Default)]
+pub struct NameIndex {
+    by_name: HashMap<String, Vec<usize>>,
+    /// Nodes whose name index does not resolve to a string.
+    unnamed: Vec<usize>,
+}
+
+impl NameIndex {
+    pub fn new(snapshot: &SnapshotRaw) -> Self {
+        let mut by_index: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut unnamed = Vec::new();
+        for node in snapshot.nodes() {
+            match node
+                .name_index()
+                .filter(|index| *index < snapshot.strings.len())
+            {
+                Some(index) => by_index.entry(index).or_default().push(node.node_index()),
+                None => unnamed.push(node.node_index()),
+            }
+        }
+
+        let mut by_name: HashMap<String, Vec<usize>> = HashMap::with_capacity(by_index.len());
+        for (index, nodes) in by_index {
+            let name = &snapshot.strings[index];
+            match by_name.get_mut(name.as_str()) {
+                Some(existing) => {
+                    existing.extend(nodes);
+                    existing.sort_unstable();
+                }
+                None => {
+                    by_name.insert(name.clone(), nodes);
+                }
+            }
+        }
+        Self { by_name, unnamed }
+    }
+
+    /// Nodes named exactly `name`, in node order.
+    pub fn nodes(&self, name: &str) -> &[usize] {
+        self.by_name.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Nodes without a resolvable name, in node order.
+    pub fn unnamed(&self) -> &[usize] {
+        &self.unnamed
+    }
+
+    /// Every distinct name with its nodes, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[usize])> {
+        self.by_name
+            .iter()
+            .map(|(name, nodes)| (name.as_str(), nodes.as_slice()))
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+
+use crate::analysis::dominator::DEFAULT_IGNORED_EDGE_TYPES;
+use crate::analysis::retainers::{RootOptions, find_roots};
+use crate::error::SnapshotError;
+use crate::snapshot::SnapshotRaw;
+
+/// How a node is held from the GC roots. Retained sizes and the dominator tree only
+/// follow strong edges, so weak-only nodes are collectable and count for themselves only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Reachability {
+    /// Some path from a root uses no weak edge.
+    Strong,
+    /// Every path from a root goes through at least one weak edge.
+    WeakOnly,
+    /// No path from a root at all.
+    Unreachable,
+}
+
+impl Reachability {
+    /// Identifier used in JSON.
+    pub fn kind(self) -> &'static str {
+        match self {
+            Reachability::Strong => "strong",
+            Reachability::WeakOnly => "weak_only",
+            Reachability::Unreachable => "unreachable",
+        }
+    }
+
+    /// Label for the text outputs.
+    pub fn label(self) -> &'static str {
+        match self {
+            Reachability::Strong => "strong",
+            Reachability::WeakOnly => "weak-only",
+            Reachability::Unreachable => "unreachable",
+        }
+    }
+}
+
+/// Classifies every node with two walks from the roots: one over strong edges only and
+/// one over every edge. Indexed by node index.
+pub fn classify_reachability(snapshot: &SnapshotRaw) -> Result<Vec<Reachability>, SnapshotError> {
+    let roots = find_roots(snapshot, &RootOptions::default())?;
+    let strong = walk(snapshot, &roots, false, None)?;
+    let any = walk(snapshot, &roots, true, None)?;
+    Ok(strong
+        .iter()
+        .zip(&any)
+        .map(|(&strong, &any)| classify(strong, any))
+        .collect())
+}
+
+/// `classify_reachability` for one node. Each walk stops as soon as it meets the node,
+/// so strongly held nodes near a root are cheap; the others cost a full walk.
+pub fn reachability_of(
+    snapshot: &SnapshotRaw,
+    node_index: usize,
+) -> Result<Reachability, SnapshotError> {
+    let roots = find_roots(snapshot, &RootOptions::default())?;
+    if walk(snapshot, &roots, false, Some(node_index))?[node_index] {
+        return Ok(Reachability::Strong);
+    }
+    let any = walk(snapshot, &roots, true, Some(node_index))?[node_index];
+    Ok(classify(false, any))
+}
+
+fn classify(strong: bool, any: bool) -> Reachability {
+    match (strong, any) {
+        (true, _) => Reachability::Strong,
+        (false, true) => Reachability::WeakOnly,
+        (false, false) => Reachability::Unreachable,
+    }
+}
+
+/// Breadth-first walk from `roots`; `follow_weak` decides whether edges of the types in
+/// `DEFAULT_IGNORED_EDGE_TYPES` are followed. Returns the visited flags.
+fn walk(
+    snapshot: &SnapshotRaw,
+    roots: &[usize],
+    follow_weak: bool,
+    stop_at: Option<usize>,
+) -> Result<Vec<bool>, SnapshotError> {
+    let mut visited = vec![false; snapshot.node_count()];
+    let mut queue = VecDeque::new();
+    for &root in roots {
+        if !visited[root] {
+            visited[root] = true;
+            queue.push_back(root);
+        }
+    }
+    while let Some(node_index) = queue.pop_front() {
+        if stop_at == Some(node_index) {
+            break;
+        }
+        for edge in snapshot.edges_of(node_index)? {
+            if !follow_weak
+                && edge
+                    .edge_type()
+                    .is_some_and(|edge_type| DEFAULT_IGNORED_EDGE_TYPES.contains(&edge_type))
+            {
+                continue;
+            }
+            let Some(to) = edge.to_node_index() else {
+                continue;
+            };
+            if to < visited.len() && !visited[to] {
+                visited[to] = true;
+                queue.push_back(to);
+            }
+        }
+    }
+    Ok(visited)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::read_snapshot;
+
+    // GC roots -> A (property), GC roots -> B (weak), B -> C (property), D unreferenced.
+    fn weak_snapshot() -> SnapshotRaw {
+        let json = r#"
+        {
+          "snapshot": {
+            "meta": {
+              "node_fields": ["type","name","id","self_size","edge_count"],
+              "node_types": [["object"],"string","number","number","number"],
+              "edge_fields": ["type","name_or_index","to_node"],
+              "edge_types": [["property","weak"],"string_or_number","node"]
+            }
+          },
+          "nodes": [0,0,1,0,2, 0,1,3,10,0, 0,2,5,20,1, 0,3,7,30,0, 0,4,9,40,0],
+          "edges": [0,5,5, 1,5,10, 0,5,15],
+          "strings": ["GC roots","A","B","C","D","x"]
+        }
+        "#;
+        let mut reader = json.as_bytes();
+        read_snapshot(&mut reader).expect("snapshot")
+    }
+
+    #[test]
+    fn weak_edges_only_reach_weak_only_nodes() {
+        let snapshot = weak_snapshot();
+        let classes = classify_reachability(&snapshot).expect("classes");
+        assert_eq!(
+            classes,
+            vec![
+                Reachability::Strong,
+                Reachability::Strong,
+                Reachability::WeakOnly,
+                Reachability::WeakOnly,
+                Reachability::Unreachable,
+            ]
+        );
+        for (index, class) in classes.iter().enumerate() {
+            assert_eq!(reachability_of(&snapshot, index).expect("class"), *class);
+        }
+    }
+}
@@ -1,17 +1,47 @@
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 
+use crate::analysis::name_index::NameIndex;
+use crate::analysis::suggest;
 use crate::cancel::CancelToken;
 use crate::error::SnapshotError;
 use crate::snapshot::{NodeView, SnapshotRaw};
 
 #[derive(Debug)]
-pub struct RetainersOptions {
+pub struct RetainersOptions<'a> {
     pub max_paths: usize,
     pub max_depth: usize,
+    /// Return at most one path per GC root node.
+    pub distinct_roots: bool,
+    /// Order of the returned paths. Ranks other than `Shortest` look at up to
+    /// `RANK_CANDIDATES_PER_PATH` times `max_paths` paths before truncating.
+    pub rank: PathRank<'a>,
+    /// Record how many partial paths were still open at each depth
+    /// (`RetainersResult::depth_histogram`).
+    pub depth_histogram: bool,
     pub cancel: CancelToken,
 }
 
-#[derive(Debug, Clone, Copy)]
+/// How `RetainerSearch::find` orders the paths it keeps. Ties fall back to the shorter
+/// path, then to discovery order.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum PathRank<'a> {
+    /// Discovery order, which the breadth-first search already makes shortest first.
+    #[default]
+    Shortest,
+    /// Largest retained size first, measured at the node the path leaves the root
+    /// through (the root itself is shared by most paths). Indexed by node
+    /// (see `dominator::retained_sizes`).
+    LargestRetainer(&'a [i64]),
+    /// Fewest `element` edges first: named properties usually say more than array indices.
+    FewestElementEdges,
+}
+
+/// Paths collected per requested path when a rank other than `PathRank::Shortest`
+/// has to choose among them.
+pub const RANK_CANDIDATES_PER_PATH: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RetainerLink {
     pub from_node: usize,
     pub edge_index: usize,
@@ -23,6 +53,56 @@ pub struct RetainersResult {
     pub target: usize,
     pub roots: Vec<usize>,
     pub paths: Vec<Vec<RetainerLink>>,
+    pub stats: RetainerStats,
+    /// Paths each entry of `paths` stands for after `dedupe_by_constructor` (itself
+    /// included); empty when the paths were not deduplicated.
+    pub similar: Vec<usize>,
+    /// Partial paths still searching for a root after 0, 1, 2, ... steps, up to the depth
+    /// where the search stopped; empty unless `RetainersOptions::depth_histogram` was set.
+    pub depth_histogram: Vec<usize>,
+}
+
+impl RetainersResult {
+    /// How many paths `paths[index]` represents (1 unless deduplicated).
+    pub fn similar_count(&self, index: usize) -> usize {
+        self.similar.get(index).copied().unwrap_or(1)
+    }
+}
+
+/// Shape of the paths `RetainerSearch::find` returned and whether its limits cut it short.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetainerStats {
+    /// Steps in the shortest path (`None` when no path was found).
+    pub min_len: Option<usize>,
+    pub max_len: Option<usize>,
+    pub distinct_roots_reached: usize,
+    pub total_paths_found: usize,
+    /// Partial paths were still open when `max_depth` was reached.
+    pub hit_max_depth: bool,
+    /// `max_paths` was reached while edges were left to explore.
+    pub hit_max_paths: bool,
+}
+
+impl RetainerStats {
+    fn from_paths(paths: &[Vec<RetainerLink>], hit_max_depth: bool, hit_max_paths: bool) -> Self {
+        let roots: HashSet<usize> = paths
+            .iter()
+            .filter_map(|path| path.first().map(|step| step.from_node))
+            .collect();
+        Self {
+            min_len: paths.iter().map(Vec::len).min(),
+            max_len: paths.iter().map(Vec::len).max(),
+            distinct_roots_reached: roots.len(),
+            total_paths_found: paths.len(),
+            hit_max_depth,
+            hit_max_paths,
+        }
+    }
+
+    /// True when the limits stopped the search, so more paths may exist.
+    pub fn truncated(&self) -> bool {
+        self.hit_max_depth || self.hit_max_paths
+    }
 }
 
 pub fn find_target_by_id(snapshot: &SnapshotRaw, node_id: u64) -> Result<usize, SnapshotError> {
@@ -36,7 +116,7 @@ pub fn find_target_by_id(snapshot: &SnapshotRaw, node_id: u64) -> Result<usize,
             return Ok(index);
         }
     }
-    Err(SnapshotError::InvalidData {
+    Err(SnapshotError::NotFound {
         details: format!(
             "node id not found: {node_id} (use --name to select a constructor or verify the id)"
         ),
@@ -46,39 +126,158 @@ pub fn find_target_by_id(snapshot: &SnapshotRaw, node_id: u64) -> Result<usize,
 pub fn find_target_by_name(
     snapshot: &SnapshotRaw,
     name_filter: &str,
-    pick: PickStrategy,
+    pick: PickStrategy<'_>,
 ) -> Result<usize, SnapshotError> {
-    let mut candidates: HashMap<String, NameCandidate> = HashMap::new();
+    Ok(match_target_by_name(snapshot, name_filter, pick, false)?.node_index)
+}
 
-    for index in 0..snapshot.node_count() {
-        let node = snapshot
-            .node_view(index)
-            .ok_or_else(|| SnapshotError::InvalidData {
-                details: format!("node index out of range: {index}"),
-            })?;
-        let name = node.name().unwrap_or("<unknown>");
-        if !name.contains(name_filter) {
+/// Like `find_target_by_name`, but also reports every matching constructor so callers
+/// can warn about an ambiguous filter. With `exact`, only constructors named exactly
+/// `name_filter` match; string-table duplicates of one name still count as one constructor.
+pub fn match_target_by_name(
+    snapshot: &SnapshotRaw,
+    name_filter: &str,
+    pick: PickStrategy<'_>,
+    exact: bool,
+) -> Result<TargetMatch, SnapshotError> {
+    let matches = |value: &str| {
+        if exact {
+            value == name_filter
+        } else {
+            value.contains(name_filter)
+        }
+    };
+    // The name test runs once per string-table entry; nodes are then grouped by
+    // their integer name index and merged by text afterwards.
+    let string_matches: Vec<bool> = snapshot
+        .strings
+        .iter()
+        .map(|value| matches(value))
+        .collect();
+    let unknown_matches = matches("<unknown>");
+    let mut by_index: HashMap<Option<usize>, NameCandidate> = HashMap::new();
+    let mut most_retained: Option<(i64, usize)> = None;
+    // Names the nodes use, for suggestions when nothing matches.
+    let mut used_names = vec![false; string_matches.len()];
+
+    for node in snapshot.nodes() {
+        let name_index = node
+            .name_index()
+            .filter(|index| *index < string_matches.len());
+        let matched = match name_index {
+            Some(index) => string_matches[index],
+            None => unknown_matches,
+        };
+        if !matched {
+            if let Some(index) = name_index {
+                used_names[index] = true;
+            }
             continue;
         }
 
-        let entry = candidates
-            .entry(name.to_string())
-            .or_insert_with(|| NameCandidate::new(name.to_string()));
-        entry.count += 1;
-        entry.self_size_sum += node.self_size().unwrap_or(0);
-        let self_size = node.self_size().unwrap_or(0);
-        if self_size > entry.largest_self_size {
-            entry.largest_self_size = self_size;
-            entry.largest_node_index = index;
+        by_index
+            .entry(name_index)
+            .or_insert_with(|| NameCandidate::new(node.name().unwrap_or("<unknown>").to_string()))
+            .add(&node);
+        if let PickStrategy::RetainedSize(retained_sizes) = pick {
+            let retained = retained_sizes.get(node.node_index()).copied().unwrap_or(0);
+            if most_retained.is_none_or(|(best, _)| retained > best) {
+                most_retained = Some((retained, node.node_index()));
+            }
+        }
+    }
+
+    let mut candidates: HashMap<String, NameCandidate> = HashMap::new();
+    for candidate in by_index.into_values() {
+        match candidates.get_mut(&candidate.name) {
+            Some(entry) => entry.merge(candidate),
+            None => {
+                candidates.insert(candidate.name.clone(), candidate);
+            }
+        }
+    }
+    pick_target(candidates, most_retained, name_filter, pick, exact, || {
+        suggest::used_names(snapshot, &used_names)
+    })
+}
+
+/// Like `match_target_by_name`, but tests each distinct name of a prebuilt `NameIndex`
+/// once and visits only the nodes of the matching constructors.
+pub fn match_target_with_names(
+    snapshot: &SnapshotRaw,
+    names: &NameIndex,
+    name_filter: &str,
+    pick: PickStrategy<'_>,
+    exact: bool,
+) -> Result<TargetMatch, SnapshotError> {
+    let matches = |value: &str| {
+        if exact {
+            value == name_filter
+        } else {
+            value.contains(name_filter)
+        }
+    };
+    let unnamed = matches("<unknown>").then(|| ("<unknown>", names.unnamed()));
+    let mut candidates: HashMap<String, NameCandidate> = HashMap::new();
+    let mut most_retained: Option<(i64, usize)> = None;
+
+    for (name, nodes) in names
+        .iter()
+        .filter(|(name, _)| matches(name))
+        .chain(unnamed)
+    {
+        let mut candidate = NameCandidate::new(name.to_string());
+        for node in nodes.iter().filter_map(|index| snapshot.node_view(*index)) {
+            candidate.add(&node);
+            if let PickStrategy::RetainedSize(retained_sizes) = pick {
+                let retained = retained_sizes.get(node.node_index()).copied().unwrap_or(0);
+                // Names are visited in no particular order; the lowest node index wins
+                // ties, as in the node-order scan.
+                if most_retained.is_none_or(|(best, best_index)| {
+                    retained > best || (retained == best && node.node_index() < best_index)
+                }) {
+                    most_retained = Some((retained, node.node_index()));
+                }
+            }
+        }
+        if candidate.count == 0 {
+            continue;
+        }
+        match candidates.get_mut(&candidate.name) {
+            Some(entry) => entry.merge(candidate),
+            None => {
+                candidates.insert(candidate.name.clone(), candidate);
+            }
         }
     }
+    pick_target(candidates, most_retained, name_filter, pick, exact, || {
+        names.iter().map(|(name, _)| name).collect()
+    })
+}
 
+/// Ranks the matched constructors and picks the target node. `known_names` lists the
+/// names to suggest from when nothing matched.
+fn pick_target<'a>(
+    candidates: HashMap<String, NameCandidate>,
+    most_retained: Option<(i64, usize)>,
+    name_filter: &str,
+    pick: PickStrategy<'_>,
+    exact: bool,
+    known_names: impl FnOnce() -> Vec<&'a str>,
+) -> Result<TargetMatch, SnapshotError> {
     if candidates.is_empty() {
-        return Err(SnapshotError::InvalidData {
-            details: format!(
-                "no nodes match name filter: {name_filter} (try a different substring or use --id)"
-            ),
-        });
+        let suggestions =
+            suggest::did_you_mean(&suggest::similar_names(name_filter, known_names()));
+        let details = if exact {
+            format!(
+                "no constructor is named exactly: {name_filter} (drop --exact to match substrings){suggestions}"
+            )
+        } else {
+            format!(
+                "no nodes match name filter: {name_filter} (try a different substring or use --id){suggestions}"
+            )
+        };
+        return Err(SnapshotError::NotFound { details });
     }
 
     let mut items: Vec<NameCandidate> = candidates.into_values().collect();
@@ -88,20 +287,53 @@ pub fn find_target_by_name(
             .cmp(&a.self_size_sum)
             .then_with(|| b.count.cmp(&a.count))
             .then_with(|| a.name.cmp(&b.name)),
-        PickStrategy::Count => b
+        PickStrategy::Count | PickStrategy::RetainedSize(_) => b
             .count
             .cmp(&a.count)
             .then_with(|| b.self_size_sum.cmp(&a.self_size_sum))
             .then_with(|| a.name.cmp(&b.name)),
     });
 
-    Ok(items[0].largest_node_index)
+    let node_index = match most_retained {
+        Some((_, node_index)) => node_index,
+        None => items[0].largest_node_index,
+    };
+    Ok(TargetMatch {
+        node_index,
+        candidates: items
+            .into_iter()
+            .map(|item| NameMatch {
+                name: item.name,
+                count: item.count,
+                self_size_sum: item.self_size_sum,
+            })
+            .collect(),
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct TargetMatch {
+    pub node_index: usize,
+    /// Every matching constructor in pick order. With `RetainedSize` the picked node
+    /// may belong to any of them.
+    pub candidates: Vec<NameMatch>,
+}
+
+/// A constructor matched by a name filter, merged across duplicate string-table entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameMatch {
+    pub name: String,
+    pub count: u64,
+    pub self_size_sum: i64,
 }
 
 #[derive(Debug, Clone, Copy)]
-pub enum PickStrategy {
+pub enum PickStrategy<'a> {
     Largest,
     Count,
+    /// The single matching node with the greatest retained size, indexed by node
+    /// (see `dominator::retained_sizes`).
+    RetainedSize(&'a [i64]),
 }
 
 #[derive(Debug)]
@@ -123,6 +355,28 @@ impl NameCandidate {
             largest_node_index: 0,
         }
     }
+
+    fn add(&mut self, node: &NodeView<'_>) {
+        let self_size = node.self_size().unwrap_or(0);
+        self.count += 1;
+        self.self_size_sum += self_size;
+        if self_size > self.largest_self_size {
+            self.largest_self_size = self_size;
+            self.largest_node_index = node.node_index();
+        }
+    }
+
+    fn merge(&mut self, other: NameCandidate) {
+        self.count += other.count;
+        self.self_size_sum += other.self_size_sum;
+        let other_wins = other.largest_self_size > self.largest_self_size
+            || (other.largest_self_size == self.largest_self_size
+                && other.largest_node_index < self.largest_node_index);
+        if other_wins {
+            self.largest_self_size = other.largest_self_size;
+            self.largest_node_index = other.largest_node_index;
+        }
+    }
 }
 
 pub fn find_retaining_paths(
@@ -130,65 +384,268 @@ pub fn find_retaining_paths(
     target: usize,
     options: RetainersOptions,
 ) -> Result<RetainersResult, SnapshotError> {
-    let roots = find_roots(snapshot)?;
-    let root_set: HashSet<usize> = roots.iter().copied().collect();
-    let edge_offsets = compute_edge_offsets(snapshot)?;
-    let mut incoming = IncomingIndex::new(snapshot, edge_offsets);
+    RetainerSearch::new(snapshot)?.find(snapshot, target, &options)
+}
 
-    if root_set.contains(&target) {
-        return Ok(RetainersResult {
-            target,
+/// Like `find_retaining_paths`, but reads incoming edges from the snapshot's own inbound
+/// index (`SnapshotRaw::retainers_of`) instead of scanning every edge per BFS layer. The
+/// index is built on the first call and kept with the snapshot, so repeated searches on a
+/// long-lived snapshot (e.g. under `serve`) skip the edge scans entirely.
+pub fn find_retaining_paths_indexed(
+    snapshot: &SnapshotRaw,
+    target: usize,
+    options: RetainersOptions,
+) -> Result<RetainersResult, SnapshotError> {
+    RetainerSearch::new(snapshot)?
+        .with_snapshot_index()
+        .find(snapshot, target, &options)
+}
+
+/// Retaining-path search that keeps the GC roots and the inbound edge index
+/// between targets, so several targets can be analysed with one edge scan each layer.
+/// `find` must be given the same snapshot the search was created from.
+#[derive(Debug)]
+pub struct RetainerSearch {
+    roots: Vec<usize>,
+    root_set: HashSet<usize>,
+    incoming: Incoming,
+}
+
+/// Where `RetainerSearch` reads the edges pointing at a node from.
+#[derive(Debug)]
+enum Incoming {
+    /// Built for just the nodes of each BFS layer, one edge scan per layer.
+    Partial(IncomingIndex),
+    /// The snapshot's full inbound index, shared by every search on that snapshot.
+    Snapshot,
+}
+
+impl RetainerSearch {
+    pub fn new(snapshot: &SnapshotRaw) -> Result<Self, SnapshotError> {
+        Self::with_roots(snapshot, &RootOptions::default())
+    }
+
+    pub fn with_roots(snapshot: &SnapshotRaw, roots: &RootOptions) -> Result<Self, SnapshotError> {
+        let roots = find_roots(snapshot, roots)?;
+        let root_set: HashSet<usize> = roots.iter().copied().collect();
+        // Validate edge counts up front rather than on the first `find`.
+        snapshot.edge_offsets()?;
+        Ok(Self {
             roots,
-            paths: vec![vec![]],
-        });
+            root_set,
+            incoming: Incoming::Partial(IncomingIndex::new()),
+        })
     }
 
-    let mut paths: Vec<Vec<RetainerLink>> = Vec::new();
-    let mut layer: Vec<PathState> = vec![PathState::new(target)];
-    let mut depth = 0usize;
+    /// Uses `SnapshotRaw::retainers_of` instead of the per-layer partial index. Costs two
+    /// `usize` per edge for the snapshot's lifetime, but later searches scan no edges.
+    pub fn with_snapshot_index(mut self) -> Self {
+        self.incoming = Incoming::Snapshot;
+        self
+    }
 
-    while depth < options.max_depth && !layer.is_empty() && paths.len() < options.max_paths {
-        if options.cancel.is_cancelled() {
-            return Err(SnapshotError::Cancelled);
+    pub fn find(
+        &mut self,
+        snapshot: &SnapshotRaw,
+        target: usize,
+        options: &RetainersOptions,
+    ) -> Result<RetainersResult, SnapshotError> {
+        if self.root_set.contains(&target) {
+            return Ok(RetainersResult {
+                target,
+                roots: self.roots.clone(),
+                paths: vec![vec![]],
+                stats: RetainerStats {
+                    min_len: Some(0),
+                    max_len: Some(0),
+                    distinct_roots_reached: 1,
+                    total_paths_found: 1,
+                    ..RetainerStats::default()
+                },
+                similar: Vec::new(),
+                depth_histogram: Vec::new(),
+            });
         }
-        let targets: Vec<usize> = layer.iter().map(|state| state.node).collect();
-        incoming.build_for_targets(&targets)?;
-
-        let mut next_layer = Vec::new();
-        for state in layer {
-            let incoming_edges = incoming.get(state.node)?;
-            for edge in incoming_edges {
-                if options.cancel.is_cancelled() {
-                    return Err(SnapshotError::Cancelled);
-                }
-                if paths.len() >= options.max_paths {
-                    break;
-                }
-                if state.visited.contains(&edge.from_node) {
-                    continue;
-                }
-                let next_state = state.extend(*edge);
-                if root_set.contains(&edge.from_node) {
-                    let mut steps = next_state.steps.clone();
-                    steps.reverse();
-                    paths.push(steps);
-                } else {
-                    next_layer.push(next_state);
+
+        let max_paths = match options.rank {
+            PathRank::Shortest => options.max_paths,
+            _ => options.max_paths.saturating_mul(RANK_CANDIDATES_PER_PATH),
+        };
+        let mut paths: Vec<Vec<RetainerLink>> = Vec::new();
+        // Edge-index sequences already returned, so `max_paths` is never spent twice on one path.
+        let mut seen_paths: HashSet<Vec<usize>> = HashSet::new();
+        let mut seen_roots: HashSet<usize> = HashSet::new();
+        let mut layer: Vec<PathState> = vec![PathState::new(target)];
+        let mut depth = 0usize;
+        let mut hit_max_paths = false;
+        let mut depth_histogram = Vec::new();
+
+        while depth < options.max_depth && !layer.is_empty() && paths.len() < max_paths {
+            if options.cancel.is_cancelled() {
+                return Err(SnapshotError::Cancelled);
+            }
+            if options.depth_histogram {
+                depth_histogram.push(layer.len());
+            }
+            if let Incoming::Partial(index) = &mut self.incoming {
+                let targets: Vec<usize> = layer.iter().map(|state| state.node).collect();
+                index.build_for_targets(snapshot, &targets)?;
+            }
+
+            let mut next_layer = Vec::new();
+            for state in layer {
+                let incoming_edges = self.incoming.get(snapshot, state.node)?;
+                for edge in incoming_edges.iter() {
+                    if options.cancel.is_cancelled() {
+                        return Err(SnapshotError::Cancelled);
+                    }
+                    if paths.len() >= max_paths {
+                        hit_max_paths = true;
+                        break;
+                    }
+                    if state.visited.contains(&edge.from_node) {
+                        continue;
+                    }
+                    let next_state = state.extend(*edge);
+                    if self.root_set.contains(&edge.from_node) {
+                        if options.distinct_roots && seen_roots.contains(&edge.from_node) {
+                            continue;
+                        }
+                        let mut steps = next_state.steps;
+                        steps.reverse();
+                        let key = steps.iter().map(|step| step.edge_index).collect();
+                        if !seen_paths.insert(key) {
+                            continue;
+                        }
+                        seen_roots.insert(edge.from_node);
+                        paths.push(steps);
+                    } else {
+                        next_layer.push(next_state);
+                    }
                 }
             }
+            layer = next_layer;
+            depth += 1;
         }
-        layer = next_layer;
-        depth += 1;
+        // Whatever is left in `layer` is an unexplored partial path.
+        let open = !layer.is_empty();
+        if options.depth_histogram && open {
+            depth_histogram.push(layer.len());
+        }
+        let hit_max_paths = hit_max_paths || (open && paths.len() >= max_paths);
+        let hit_max_depth = open && !hit_max_paths && depth >= options.max_depth;
+        // A rank that collected extra candidates drops the lower-ranked ones here.
+        let hit_max_paths = hit_max_paths || paths.len() > options.max_paths;
+        rank_paths(snapshot, &mut paths, options.rank);
+        paths.truncate(options.max_paths);
+
+        let stats = RetainerStats::from_paths(&paths, hit_max_depth, hit_max_paths);
+        Ok(RetainersResult {
+            target,
+            roots: self.roots.clone(),
+            paths,
+            stats,
+            similar: Vec::new(),
+            depth_histogram,
+        })
     }
+}
 
-    Ok(RetainersResult {
-        target,
-        roots,
-        paths,
-    })
+/// Collapses paths that read the same once instances are forgotten: equal sequences of
+/// node names and edge types (edge names and element indices are ignored). The first
+/// path of each group is kept in place as its representative and `similar` records the
+/// group sizes. `stats` still describes the paths the search found.
+pub fn dedupe_by_constructor(snapshot: &SnapshotRaw, result: &mut RetainersResult) {
+    // (node name, edge type) per step; the target at the end is shared by every path.
+    type Shape<'a> = Vec<(Option<&'a str>, Option<&'a str>)>;
+    let mut groups: HashMap<Shape<'_>, usize> = HashMap::new();
+    let mut kept: Vec<Vec<RetainerLink>> = Vec::new();
+    let mut similar: Vec<usize> = Vec::new();
+    for (index, path) in std::mem::take(&mut result.paths).into_iter().enumerate() {
+        let key: Shape<'_> = path
+            .iter()
+            .map(|step| {
+                (
+                    snapshot
+                        .node_view(step.from_node)
+                        .and_then(|node| node.name()),
+                    snapshot
+                        .edge_view(step.edge_index)
+                        .and_then(|edge| edge.edge_type()),
+                )
+            })
+            .collect();
+        let count = result.similar.get(index).copied().unwrap_or(1);
+        match groups.get(&key) {
+            Some(&group) => similar[group] += count,
+            None => {
+                groups.insert(key, kept.len());
+                kept.push(path);
+                similar.push(count);
+            }
+        }
+    }
+    result.paths = kept;
+    result.similar = similar;
+}
+
+/// Stable sort, so equally ranked paths keep their (shortest first) discovery order.
+fn rank_paths(snapshot: &SnapshotRaw, paths: &mut [Vec<RetainerLink>], rank: PathRank<'_>) {
+    match rank {
+        PathRank::Shortest => paths.sort_by_key(Vec::len),
+        PathRank::LargestRetainer(retained_sizes) => paths.sort_by_key(|path| {
+            let retained = path
+                .first()
+                .and_then(|step| retained_sizes.get(step.to_node))
+                .copied()
+                .unwrap_or(0);
+            (std::cmp::Reverse(retained), path.len())
+        }),
+        PathRank::FewestElementEdges => paths.sort_by_key(|path| {
+            let element_edges = path
+                .iter()
+                .filter(|step| {
+                    snapshot
+                        .edge_view(step.edge_index)
+                        .and_then(|edge| edge.edge_type())
+                        == Some("element")
+                })
+                .count();
+            (element_edges, path.len())
+        }),
+    }
+}
+
+pub const DEFAULT_ROOT_NAME: &str = "GC roots";
+
+/// How `find_roots` recognises the synthetic root node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootOptions {
+    /// Name of the root node(s). V8 writes "GC roots"; other engines and localized
+    /// snapshots may use something else.
+    pub root_name: String,
+}
+
+impl Default for RootOptions {
+    fn default() -> Self {
+        Self {
+            root_name: DEFAULT_ROOT_NAME.to_string(),
+        }
+    }
+}
+
+impl RootOptions {
+    pub fn is_default(&self) -> bool {
+        self.root_name == DEFAULT_ROOT_NAME
+    }
 }
 
-pub fn find_roots(snapshot: &SnapshotRaw) -> Result<Vec<usize>, SnapshotError> {
+/// Nodes named `options.root_name`, or node 0 when there is none.
+pub fn find_roots(
+    snapshot: &SnapshotRaw,
+    options: &RootOptions,
+) -> Result<Vec<usize>, SnapshotError> {
+    snapshot.require_nodes()?;
     let mut roots = Vec::new();
     for index in 0..snapshot.node_count() {
         let node = snapshot
@@ -196,47 +653,62 @@ pub fn find_roots(snapshot: &SnapshotRaw) -> Result<Vec<usize>, SnapshotError> {
             .ok_or_else(|| SnapshotError::InvalidData {
                 details: format!("node index out of range: {index}"),
             })?;
-        if is_gc_root(&node) {
+        if node.name() == Some(options.root_name.as_str()) {
             roots.push(index);
         }
     }
 
     if roots.is_empty() {
-        if snapshot.node_count() > 0 {
-            roots.push(0);
-        }
-    }
-
-    if roots.is_empty() {
-        return Err(SnapshotError::InvalidData {
-            details: "GC roots not found in snapshot (expected name \"GC roots\")".to_string(),
-        });
+        roots.push(0);
     }
     Ok(roots)
 }
 
-fn is_gc_root(node: &NodeView<'_>) -> bool {
-    matches!(node.name(), Some("GC roots"))
+pub(crate) fn is_gc_root(node: &NodeView<'_>) -> bool {
+    node.name() == Some(DEFAULT_ROOT_NAME)
 }
 
-struct IncomingIndex<'a> {
-    snapshot: &'a SnapshotRaw,
-    edge_offsets: Vec<usize>,
+impl Incoming {
+    fn get<'a>(
+        &'a self,
+        snapshot: &SnapshotRaw,
+        node_index: usize,
+    ) -> Result<Cow<'a, [RetainerLink]>, SnapshotError> {
+        match self {
+            Incoming::Partial(index) => index.get(node_index).map(Cow::Borrowed),
+            Incoming::Snapshot => Ok(Cow::Owned(
+                snapshot
+                    .retainers_of(node_index)?
+                    .map(|(from, edge)| RetainerLink {
+                        from_node: from.node_index(),
+                        edge_index: edge.edge_index(),
+                        to_node: node_index,
+                    })
+                    .collect(),
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct IncomingIndex {
     built: HashSet<usize>,
     incoming: HashMap<usize, Vec<RetainerLink>>,
 }
 
-impl<'a> IncomingIndex<'a> {
-    fn new(snapshot: &'a SnapshotRaw, edge_offsets: Vec<usize>) -> Self {
+impl IncomingIndex {
+    fn new() -> Self {
         Self {
-            snapshot,
-            edge_offsets,
             built: HashSet::new(),
             incoming: HashMap::new(),
         }
     }
 
-    fn build_for_targets(&mut self, targets: &[usize]) -> Result<(), SnapshotError> {
+    fn build_for_targets(
+        &mut self,
+        snapshot: &SnapshotRaw,
+        targets: &[usize],
+    ) -> Result<(), SnapshotError> {
         let needed: HashSet<usize> = targets
             .iter()
             .copied()
@@ -246,25 +718,10 @@ impl<'a> IncomingIndex<'a> {
             return Ok(());
         }
 
-        for (node_index, start_edge) in self.edge_offsets.iter().enumerate() {
-            let node =
-                self.snapshot
-                    .node_view(node_index)
-                    .ok_or_else(|| SnapshotError::InvalidData {
-                        details: format!("node index out of range: {node_index}"),
-                    })?;
-            let edge_count = node.edge_count().unwrap_or(0);
-            let edge_count =
-                usize::try_from(edge_count).map_err(|_| SnapshotError::InvalidData {
-                    details: format!("edge_count negative at node {node_index}"),
-                })?;
-            for offset in 0..edge_count {
-                let edge_index = start_edge + offset;
-                let edge = self.snapshot.edge_view(edge_index).ok_or_else(|| {
-                    SnapshotError::InvalidData {
-                        details: format!("edge index out of range: {edge_index}"),
-                    }
-                })?;
+        for node in snapshot.nodes() {
+            let node_index = node.node_index();
+            for edge in node.outgoing_edges()? {
+                let edge_index = edge.edge_index();
                 let to_node = match edge.to_node_index() {
                     Some(value) => value,
                     None => continue,
@@ -296,37 +753,6 @@ impl<'a> IncomingIndex<'a> {
     }
 }
 
-fn compute_edge_offsets(snapshot: &SnapshotRaw) -> Result<Vec<usize>, SnapshotError> {
-    let mut offsets = Vec::with_capacity(snapshot.node_count());
-    let mut cursor = 0usize;
-
-    for node_index in 0..snapshot.node_count() {
-        offsets.push(cursor);
-        let node = snapshot
-            .node_view(node_index)
-            .ok_or_else(|| SnapshotError::InvalidData {
-                details: format!("node index out of range: {node_index}"),
-            })?;
-        let edge_count = node.edge_count().unwrap_or(0);
-        let edge_count = usize::try_from(edge_count).map_err(|_| SnapshotError::InvalidData {
-            details: format!("edge_count negative at node {node_index}"),
-        })?;
-        cursor = cursor.saturating_add(edge_count);
-    }
-
-    if cursor != snapshot.edge_count() {
-        return Err(SnapshotError::InvalidData {
-            details: format!(
-                "edge_count sum ({}) does not match edges length ({})",
-                cursor,
-                snapshot.edge_count()
-            ),
-        });
-    }
-
-    Ok(offsets)
-}
-
 #[derive(Debug, Clone)]
 struct PathState {
     node: usize,
@@ -387,21 +813,22 @@ mod tests {
                 MetaType::String("string_or_number".to_string()),
                 MetaType::String("node".to_string()),
             ],
+            location_fields: Vec::new(),
         };
         let index = meta.validate().expect("meta ok");
 
-        SnapshotRaw {
-            nodes: vec![
+        SnapshotRaw::new(
+            vec![
                 0, 0, 1, 0, 1, // node 0: GC roots
                 1, 1, 2, 0, 0, // node 1: App
             ],
-            edges: vec![
+            vec![
                 0, 1, 5, // edge 0: from node 0 to node 1
             ],
-            strings: vec!["GC roots".to_string(), "App".to_string()],
+            vec!["GC roots".to_string(), "App".to_string()],
             meta,
             index,
-        }
+        )
     }
 
     #[test]
@@ -413,6 +840,9 @@ mod tests {
             RetainersOptions {
                 max_paths: 5,
                 max_depth: 5,
+                distinct_roots: false,
+                rank: PathRank::Shortest,
+                depth_histogram: false,
                 cancel: CancelToken::new(),
             },
         )
@@ -423,4 +853,85 @@ mod tests {
         assert_eq!(result.paths[0][0].from_node, 0);
         assert_eq!(result.paths[0][0].to_node, 1);
     }
+
+    #[test]
+    fn distinct_roots_keeps_one_path_per_root() {
+        let mut snapshot = sample_snapshot();
+        // A second root with one edge to App, and a second edge from the first root.
+        snapshot.nodes = vec![
+            0, 0, 1, 0, 2, // node 0: GC roots
+            1, 1, 2, 0, 0, // node 1: App
+            0, 0, 3, 0, 1, // node 2: GC roots
+        ];
+        snapshot.edges = vec![
+            0, 1, 5, // edge 0: node 0 -> App
+            0, 0, 5, // edge 1: node 0 -> App
+            0, 1, 5, // edge 2: node 2 -> App
+        ];
+        let options = |distinct_roots| RetainersOptions {
+            max_paths: 5,
+            max_depth: 5,
+            distinct_roots,
+            rank: PathRank::Shortest,
+            depth_histogram: false,
+            cancel: CancelToken::new(),
+        };
+
+        let all = find_retaining_paths(&snapshot, 1, options(false)).expect("paths");
+        assert_eq!(all.paths.len(), 3);
+
+        let distinct = find_retaining_paths(&snapshot, 1, options(true)).expect("paths");
+        let roots: Vec<usize> = distinct
+            .paths
+            .iter()
+            .map(|path| path[0].from_node)
+            .collect();
+        assert_eq!(roots, vec![0, 2]);
+        assert_eq!(distinct.paths[0][0].edge_index, 0);
+    }
+
+    #[test]
+    fn root_name_selects_custom_roots() {
+        let mut snapshot = sample_snapshot();
+        snapshot.nodes = vec![
+            0, 0, 1, 0, 1, // node 0: GC roots
+            1, 1, 2, 0, 0, // node 1: App
+            0, 2, 3, 0, 1, // node 2: (Root)
+        ];
+        snapshot.edges = vec![
+            0, 1, 5, // edge 0: node 0 -> App
+            0, 1, 5, // edge 1: node 2 -> App
+        ];
+        snapshot.strings.push("(Root)".to_string());
+        let custom = RootOptions {
+            root_name: "(Root)".to_string(),
+        };
+
+        assert_eq!(
+            find_roots(&snapshot, &RootOptions::default()).unwrap(),
+            vec![0]
+        );
+        assert_eq!(find_roots(&snapshot, &custom).unwrap(), vec![2]);
+
+        let options = RetainersOptions {
+            max_paths: 5,
+            max_depth: 5,
+            distinct_roots: false,
+            rank: PathRank::Shortest,
+            depth_histogram: false,
+            cancel: CancelToken::new(),
+        };
+        let result = RetainerSearch::with_roots(&snapshot, &custom)
+            .expect("search")
+            .find(&snapshot, 1, &options)
+            .expect("paths");
+        assert_eq!(result.paths.len(), 1);
+        assert_eq!(result.paths[0][0].from_node, 2);
+
+        // Without a match the first node is still used as the root.
+        let missing = RootOptions {
+            root_name: "Missing".to_string(),
+        };
+        assert_eq!(find_roots(&snapshot, &missing).unwrap(), vec![0]);
+    }
 }
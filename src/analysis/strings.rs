@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use crate::snapshot::SnapshotRaw;
+
+/// Characters kept in a preview before it is cut with `…`.
+pub const PREVIEW_CHARS: usize = 80;
+
+#[derive(Debug)]
+pub struct StringsOptions {
+    /// Rows listed in each of the largest-strings and duplicate tables.
+    pub top: usize,
+    /// Keep a preview of each listed string. Off by default because the string table can
+    /// hold tokens, URLs with credentials and other user data.
+    pub previews: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringEntry {
+    /// Index in the strings table.
+    pub index: usize,
+    /// UTF-8 length.
+    pub bytes: u64,
+    pub preview: Option<String>,
+}
+
+/// A text stored at more than one index of the strings table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateEntry {
+    /// Index of the first copy.
+    pub first_index: usize,
+    pub count: u64,
+    /// UTF-8 length of one copy.
+    pub bytes: u64,
+    pub preview: Option<String>,
+}
+
+impl DuplicateEntry {
+    /// Bytes the copies after the first take up.
+    pub fn wasted_bytes(&self) -> u64 {
+        self.bytes * (self.count - 1)
+    }
+}
+
+#[derive(Debug)]
+pub struct StringsResult {
+    pub string_count: usize,
+    /// UTF-8 bytes of every string in the table.
+    pub total_bytes: u64,
+    /// Texts that occur at more than one index.
+    pub duplicate_values: u64,
+    /// Copies beyond the first of each duplicated text.
+    pub duplicate_strings: u64,
+    /// Bytes of those extra copies.
+    pub duplicate_bytes: u64,
+    /// Largest strings first (ties by index).
+    pub largest: Vec<StringEntry>,
+    /// Duplicated texts by wasted bytes, largest first (ties by first index).
+    pub duplicates: Vec<DuplicateEntry>,
+    /// Whether the entries carry previews (`StringsOptions::previews`).
+    pub previews: bool,
+    /// `SnapshotRaw::quick_fingerprint` of the inspected snapshot.
+    pub snapshot_fingerprint: u64,
+}
+
+/// Sizes the strings table and finds the texts stored more than once. Lengths are UTF-8
+/// bytes as read from the file, not V8's in-heap string sizes.
+pub fn strings(snapshot: &SnapshotRaw, options: StringsOptions) -> StringsResult {
+    let preview = |index: usize| {
+        options
+            .previews
+            .then(|| string_preview(&snapshot.strings[index]))
+    };
+
+    let mut total_bytes = 0u64;
+    // Text -> (first index, count).
+    let mut seen: HashMap<&str, (usize, u64)> = HashMap::with_capacity(snapshot.strings.len());
+    for (index, value) in snapshot.strings.iter().enumerate() {
+        total_bytes += value.len() as u64;
+        seen.entry(value.as_str()).or_insert((index, 0)).1 += 1;
+    }
+
+    let mut by_size: Vec<usize> = (0..snapshot.strings.len()).collect();
+    by_size.sort_by(|a, b| {
+        snapshot.strings[*b]
+            .len()
+            .cmp(&snapshot.strings[*a].len())
+            .then_with(|| a.cmp(b))
+    });
+    let largest = by_size
+        .into_iter()
+        .take(options.top)
+        .map(|index| StringEntry {
+            index,
+            bytes: snapshot.strings[index].len() as u64,
+            preview: preview(index),
+        })
+        .collect();
+
+    let mut duplicates: Vec<DuplicateEntry> = seen
+        .into_iter()
+        .filter(|(_, (_, count))| *count > 1)
+        .map(|(value, (first_index, count))| DuplicateEntry {
+            first_index,
+            count,
+            bytes: value.len() as u64,
+            preview: None,
+        })
+        .collect();
+    let duplicate_values = duplicates.len() as u64;
+    let duplicate_strings = duplicates.iter().map(|entry| entry.count - 1).sum();
+    let duplicate_bytes = duplicates.iter().map(DuplicateEntry::wasted_bytes).sum();
+    duplicates.sort_by(|a, b| {
+        b.wasted_bytes()
+            .cmp(&a.wasted_bytes())
+            .then_with(|| a.first_index.cmp(&b.first_index))
+    });
+    duplicates.truncate(options.top);
+    for entry in &mut duplicates {
+        entry.preview = preview(entry.first_index);
+    }
+
+    StringsResult {
+        string_count: snapshot.strings.len(),
+        total_bytes,
+        duplicate_values,
+        duplicate_strings,
+        duplicate_bytes,
+        largest,
+        duplicates,
+        previews: options.previews,
+        snapshot_fingerprint: snapshot.quick_fingerprint(),
+    }
+}
+
+/// The first `PREVIEW_CHARS` characters with control characters escaped, so a preview
+/// always fits on one line.
+fn string_preview(value: &str) -> String {
+    let mut preview = String::new();
+    for (count, ch) in value.chars().enumerate() {
+        if count == PREVIEW_CHARS {
+            preview.push('…');
+            break;
+        }
+        if ch.is_control() {
+            preview.extend(ch.escape_default());
+        } else {
+            preview.push(ch);
+        }
+    }
+    preview
+}
@@ -0,0 +1,131 @@
+//! "Did you mean" suggestions for a `--name` that matched no constructor.
+
+use std::collections::HashSet;
+
+use crate::snapshot::SnapshotRaw;
+
+/// Suggestions listed in an error at most.
+pub const MAX_SUGGESTIONS: usize = 5;
+/// Longer names (string contents, source text) are never suggested.
+const MAX_NAME_CHARS: usize = 200;
+
+/// Constructor names closest to `query`, best first: names within a small edit distance
+/// (ignoring case), names sharing most of the query as a prefix, and names containing
+/// the query in another case. Unrelated names are left out, so the list may be empty.
+pub fn similar_names<'a>(query: &str, names: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let query_text = query.to_lowercase();
+    let query: Vec<char> = query_text.chars().collect();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let max_distance = (query.len() / 3).max(1);
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut scored: Vec<(usize, &str)> = Vec::new();
+    for name in names {
+        if name.is_empty() || name.len() > MAX_NAME_CHARS * 4 || !seen.insert(name) {
+            continue;
+        }
+        let lower_text = name.to_lowercase();
+        let lower: Vec<char> = lower_text.chars().collect();
+        if lower.len() > MAX_NAME_CHARS {
+            continue;
+        }
+        let prefix = query.iter().zip(&lower).take_while(|(a, b)| a == b).count();
+        // Short queries share prefixes and substrings with too many names to be useful.
+        let related =
+            query.len() >= 3 && (prefix * 3 >= query.len() * 2 || lower_text.contains(&query_text));
+        if !related && lower.len().abs_diff(query.len()) > max_distance {
+            continue;
+        }
+        let distance = edit_distance(&query, &lower);
+        if related || distance <= max_distance {
+            scored.push((distance, name));
+        }
+    }
+    scored.sort_by(|a, b| {
+        a.0.cmp(&b.0)
+            .then_with(|| a.1.len().cmp(&b.1.len()))
+            .then_with(|| a.1.cmp(b.1))
+    });
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, name)| name)
+        .collect()
+}
+
+/// "; did you mean 'React', 'ReactDOM'?" for an error message, or "" without suggestions.
+pub fn did_you_mean(suggestions: &[&str]) -> String {
+    if suggestions.is_empty() {
+        return String::new();
+    }
+    let quoted: Vec<String> = suggestions.iter().map(|name| format!("'{name}'")).collect();
+    format!("; did you mean {}?", quoted.join(", "))
+}
+
+/// Distinct names of the snapshot's nodes, for callers that did not collect them while
+/// scanning.
+pub fn node_names(snapshot: &SnapshotRaw) -> Vec<&str> {
+    let mut used = vec![false; snapshot.strings.len()];
+    for node in snapshot.nodes() {
+        if let Some(index) = node.name_index().filter(|index| *index < used.len()) {
+            used[index] = true;
+        }
+    }
+    used_names(snapshot, &used)
+}
+
+/// Strings whose flag in `used` (indexed like the string table) is set.
+pub(crate) fn used_names<'a>(snapshot: &'a SnapshotRaw, used: &[bool]) -> Vec<&'a str> {
+    snapshot
+        .strings
+        .iter()
+        .zip(used)
+        .filter(|(_, used)| **used)
+        .map(|(name, _)| name.as_str())
+        .collect()
+}
+
+/// Levenshtein distance over characters.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggests_typos_and_shared_prefixes_but_not_unrelated_names() {
+        let names = ["React", "ReactDOM", "Array", "Map", "React"];
+        assert_eq!(
+            similar_names("Reactt", names.iter().copied()),
+            vec!["React", "ReactDOM"]
+        );
+        assert_eq!(similar_names("fooStore", ["FooStore"]), vec!["FooStore"]);
+        assert!(similar_names("Zebra", names.iter().copied()).is_empty());
+        assert_eq!(
+            did_you_mean(&["React", "ReactDOM"]),
+            "; did you mean 'React', 'ReactDOM'?"
+        );
+        assert_eq!(did_you_mean(&[]), "");
+    }
+
+    #[test]
+    fn edit_distance_counts_single_character_edits() {
+        let chars = |value: &str| value.chars().collect::<Vec<_>>();
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(edit_distance(&chars(""), &chars("abc")), 3);
+        assert_eq!(edit_distance(&chars("名前"), &chars("名前")), 0);
+    }
+}
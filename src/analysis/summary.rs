@@ -1,29 +1,124 @@
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap};
+use std::ops::Range;
 
 use serde::Serialize;
 
+use crate::analysis::reachability::{Reachability, classify_reachability};
 use crate::error::SnapshotError;
-use crate::snapshot::SnapshotRaw;
+use crate::snapshot::{DETACHEDNESS_DETACHED, MetaIndex, SnapshotRaw, StreamedSnapshot};
 
 #[derive(Debug)]
 pub struct SummaryOptions {
     pub top: usize,
     pub contains: Option<String>,
+    /// Rows to skip after sorting the full constructor set.
+    pub skip: usize,
+    /// Page size after `skip`; falls back to `top` when unset.
+    pub limit: Option<usize>,
+    /// Skip nodes whose type is in `SYNTHETIC_NODE_TYPES` and report them in `excluded`.
+    pub exclude_synthetic: bool,
+    /// Only aggregate nodes of this V8 node type (validated by `resolve_node_type`).
+    pub node_type: Option<String>,
+    /// Drop rows whose self size sum is below this, before ranking and `top`.
+    pub min_self_size: Option<i64>,
+    /// Drop rows with fewer nodes than this, before ranking and `top`.
+    pub min_count: Option<u64>,
+    /// Only aggregate detached DOM nodes; errors when the snapshot has no `detachedness` field.
+    pub detached_only: bool,
+    /// Threads used to aggregate nodes; 0 and 1 both mean single-threaded, and more
+    /// threads than the machine runs in parallel are not started.
+    pub jobs: usize,
+    /// Emit one `(empty: <node type>)` row per node type instead of a single row for
+    /// all empty-named nodes.
+    pub split_empty: bool,
+    /// Classify nodes with `reachability::classify_reachability` and fill
+    /// `SummaryRow::weak_only_count`.
+    pub reachability: bool,
+    /// Only aggregate nodes that `reachability::classify_reachability` finds unreachable
+    /// even through weak edges: snapshot-timing leftovers or garbage the collector missed.
+    pub unreachable_only: bool,
+    /// Node ids collected per row as `SummaryRow::examples`, first in node order; 0 skips
+    /// collecting them.
+    pub samples: usize,
 }
 
+/// The `summary` command's defaults: top 50, single-threaded, no filters.
+impl Default for SummaryOptions {
+    fn default() -> Self {
+        Self {
+            top: 50,
+            contains: None,
+            skip: 0,
+            limit: None,
+            exclude_synthetic: false,
+            node_type: None,
+            min_self_size: None,
+            min_count: None,
+            detached_only: false,
+            jobs: 1,
+            split_empty: false,
+            reachability: false,
+            unreachable_only: false,
+            samples: 0,
+        }
+    }
+}
+
+/// Node types hidden by `--no-synthetic`: engine-internal nodes such as `(system)`,
+/// `(compiled code)` and `(internal)` rather than application objects.
+pub const SYNTHETIC_NODE_TYPES: &[&str] = &["synthetic", "hidden", "code"];
+
 #[derive(Debug, Serialize)]
 pub struct SummaryRow {
     pub name: String,
     pub count: u64,
     pub self_size_sum: i64,
+    /// Id of the constructor's largest instance (lowest node index on ties), for
+    /// linking straight to `detail --id`.
+    pub sample_id: Option<i64>,
+    /// Nodes reachable from the roots only through weak edges; set for
+    /// `SummaryOptions::reachability`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weak_only_count: Option<u64>,
+    /// String-table index of the name; `None` for `(empty: type)` rows. Identical text
+    /// can sit at several indices, each aggregated as its own row.
+    #[serde(skip)]
+    pub name_index: Option<usize>,
+    /// Ids of the first `SummaryOptions::samples` instances in node order; empty when
+    /// samples were not requested.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<i64>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct SummaryResult {
     pub total_nodes: usize,
+    /// Number of constructor rows before skip/limit were applied.
+    pub total_rows: usize,
+    pub skip: usize,
     pub rows: Vec<SummaryRow>,
     #[serde(skip)]
     pub empty_name_types: Vec<EmptyTypeSummary>,
+    /// Nodes left out by `exclude_synthetic`; `None` when nothing was excluded on purpose.
+    pub excluded: Option<ExcludedNodes>,
+    /// The `node_type` filter that was applied, if any.
+    pub node_type: Option<String>,
+    pub detached_only: bool,
+    pub unreachable_only: bool,
+    /// `edges::edge_type_histogram` of the whole snapshot, filled in for
+    /// `--edge-type-summary`. Row filters do not apply to it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edge_types: Option<Vec<(String, u64)>>,
+    /// `SnapshotRaw::quick_fingerprint` of the summarized snapshot.
+    pub snapshot_fingerprint: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExcludedNodes {
+    pub count: u64,
+    pub self_size_sum: i64,
 }
 
 #[derive(Debug, Clone)]
@@ -37,20 +132,441 @@ pub fn summarize(
     snapshot: &SnapshotRaw,
     options: SummaryOptions,
 ) -> Result<SummaryResult, SnapshotError> {
-    let mut map: HashMap<usize, SummaryRow> = HashMap::new();
-    let mut empty_types: HashMap<String, EmptyTypeSummary> = HashMap::new();
+    let node_type_filter = options
+        .node_type
+        .as_deref()
+        .map(|name| resolve_node_type(snapshot, name))
+        .transpose()?;
+    if options.detached_only && snapshot.index.node_field_index.detachedness_idx.is_none() {
+        return Err(no_detachedness_error());
+    }
 
-    for index in 0..snapshot.node_count() {
-        let node = snapshot
-            .node_view(index)
-            .ok_or_else(|| SnapshotError::InvalidData {
-                details: format!("node index out of range: {index}"),
-            })?;
+    let reachability = if options.reachability || options.unreachable_only {
+        Some(classify_reachability(snapshot)?)
+    } else {
+        None
+    };
+    let filter = NodeFilter {
+        contains: options.contains.as_deref(),
+        node_type: node_type_filter,
+        detached_only: options.detached_only,
+        exclude_synthetic: options.exclude_synthetic,
+        reachability: reachability.as_deref(),
+        unreachable_only: options.unreachable_only,
+    };
+    let aggregate = aggregate_nodes(snapshot, &filter, options.jobs, options.samples)?;
+    Ok(summary_result(
+        aggregate,
+        &snapshot.strings,
+        options,
+        snapshot.node_count(),
+        snapshot.quick_fingerprint(),
+    ))
+}
+
+/// `summarize` fed by `parser::stream_snapshot_file`: pass `add_node` as the node
+/// callback, then `finish` with the streamed snapshot. Totals are kept per name index and
+/// node type until the string table, which V8 writes after `nodes`, is known. Options
+/// that walk edges (`reachability`, `unreachable_only`) are rejected and `jobs` is unused.
+pub struct StreamingSummary {
+    options: SummaryOptions,
+    /// (name index, node type code) -> totals of those nodes
+    rows: HashMap<(usize, i64), RowAggregate>,
+    node_count: usize,
+    /// First invalid node or option; later nodes are skipped and `finish` returns it.
+    error: Option<SnapshotError>,
+}
+
+impl StreamingSummary {
+    pub fn new(options: SummaryOptions) -> Result<Self, SnapshotError> {
+        if options.reachability || options.unreachable_only {
+            return Err(SnapshotError::InvalidData {
+                details: "reachability needs the edges, which a streaming summary does not keep"
+                    .to_string(),
+            });
+        }
+        Ok(Self {
+            options,
+            rows: HashMap::new(),
+            node_count: 0,
+            error: None,
+        })
+    }
+
+    /// Aggregates the next node, given as its `node_fields` values.
+    pub fn add_node(&mut self, index: &MetaIndex, fields: &[i64]) {
+        let node_index = self.node_count;
+        self.node_count += 1;
+        if node_index == 0
+            && let Err(err) = self.check_meta(index)
+        {
+            self.error = Some(err);
+        }
+        if self.error.is_some() {
+            return;
+        }
+        let field = &index.node_field_index;
+        let Ok(name_index) = usize::try_from(fields[field.name_idx]) else {
+            self.error = Some(SnapshotError::InvalidData {
+                details: format!("node missing name index: {node_index}"),
+            });
+            return;
+        };
+        if self.options.detached_only
+            && field
+                .detachedness_idx
+                .map(|detachedness| fields[detachedness])
+                != Some(DETACHEDNESS_DETACHED)
+        {
+            return;
+        }
+        let node = NodeRef {
+            index: node_index,
+            id: Some(fields[field.id_idx]),
+        };
+        let self_size = fields[field.self_size_idx];
+        let samples = self.options.samples;
+        match self.rows.entry((name_index, fields[field.type_idx])) {
+            Entry::Occupied(mut entry) => entry.get_mut().add(node, self_size, false, samples),
+            Entry::Vacant(entry) => {
+                entry.insert(RowAggregate::new(node, self_size, false, samples));
+            }
+        }
+    }
+
+    /// The option checks `summarize` makes up front, run once the meta is known.
+    fn check_meta(&self, index: &MetaIndex) -> Result<(), SnapshotError> {
+        if let Some(name) = self.options.node_type.as_deref() {
+            find_node_type(&index.node_type_names, name)?;
+        }
+        if self.options.detached_only && index.node_field_index.detachedness_idx.is_none() {
+            return Err(no_detachedness_error());
+        }
+        Ok(())
+    }
+
+    pub fn finish(self, snapshot: &StreamedSnapshot) -> Result<SummaryResult, SnapshotError> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        let options = self.options;
+        let samples = options.samples;
+        let mut aggregate = Aggregate::default();
+        for ((name_index, type_code), row) in self.rows {
+            let name =
+                snapshot
+                    .strings
+                    .get(name_index)
+                    .ok_or_else(|| SnapshotError::InvalidData {
+                        details: format!("name index out of range: {name_index}"),
+                    })?;
+            if options
+                .contains
+                .as_deref()
+                .is_some_and(|wanted| !name.contains(wanted))
+            {
+                continue;
+            }
+            let node_type = usize::try_from(type_code)
+                .ok()
+                .and_then(|code| snapshot.index.node_type_names.get(code))
+                .map(String::as_str);
+            if options
+                .node_type
+                .as_deref()
+                .is_some_and(|wanted| node_type != Some(wanted))
+            {
+                continue;
+            }
+            if options.exclude_synthetic
+                && node_type.is_some_and(|node_type| SYNTHETIC_NODE_TYPES.contains(&node_type))
+            {
+                aggregate.excluded.count += row.count;
+                aggregate.excluded.self_size_sum += row.self_size_sum;
+                continue;
+            }
+            if name.is_empty() {
+                match aggregate.empty_types.entry(node_type.unwrap_or("unknown")) {
+                    Entry::Occupied(mut entry) => entry.get_mut().merge(row.clone(), samples),
+                    Entry::Vacant(entry) => {
+                        entry.insert(row.clone());
+                    }
+                }
+            }
+            match aggregate.rows.entry(name_index) {
+                Entry::Occupied(mut entry) => entry.get_mut().merge(row, samples),
+                Entry::Vacant(entry) => {
+                    entry.insert(row);
+                }
+            }
+        }
+        Ok(summary_result(
+            aggregate,
+            &snapshot.strings,
+            options,
+            snapshot.node_count,
+            snapshot.quick_fingerprint(),
+        ))
+    }
+}
+
+fn no_detachedness_error() -> SnapshotError {
+    SnapshotError::InvalidData {
+        details: "snapshot has no detachedness node field; --detached-only needs a snapshot taken by a V8 version that records detached DOM nodes".to_string(),
+    }
+}
+
+/// Ranks and pages the aggregated rows. Every row key must index `strings`.
+fn summary_result(
+    aggregate: Aggregate<'_>,
+    strings: &[String],
+    options: SummaryOptions,
+    total_nodes: usize,
+    snapshot_fingerprint: u64,
+) -> SummaryResult {
+    let excluded = aggregate.excluded;
+    let empty_types = aggregate.empty_types;
+    let summary_row = |name: String, name_index: Option<usize>, row: &RowAggregate| SummaryRow {
+        name,
+        name_index,
+        count: row.count,
+        self_size_sum: row.self_size_sum,
+        sample_id: row.largest_node.id,
+        weak_only_count: options.reachability.then_some(row.weak_only),
+        examples: row.examples.iter().filter_map(|node| node.id).collect(),
+    };
+    let mut rows: Vec<SummaryRow> = aggregate
+        .rows
+        .iter()
+        .map(|(name_index, row)| summary_row(strings[*name_index].clone(), Some(*name_index), row))
+        .filter(|row| !(options.split_empty && row.name.is_empty()))
+        .collect();
+    if options.split_empty {
+        rows.extend(
+            empty_types
+                .iter()
+                .map(|(node_type, row)| summary_row(format!("(empty: {node_type})"), None, row)),
+        );
+    }
+
+    // Rank against the whole set first so that a page (skip/limit) is a stable slice of the ranking.
+    let rows: Vec<SummaryRow> = rows
+        .into_iter()
+        .filter(|row| {
+            options
+                .min_self_size
+                .is_none_or(|min| row.self_size_sum >= min)
+                && options.min_count.is_none_or(|min| row.count >= min)
+        })
+        .collect();
+    let total_rows = rows.len();
+    let limit = options.limit.unwrap_or(options.top);
+    let rows: Vec<SummaryRow> = top_rows(rows, options.skip.saturating_add(limit))
+        .into_iter()
+        .skip(options.skip)
+        .take(limit)
+        .collect();
+
+    let mut empty_name_types: Vec<EmptyTypeSummary> = empty_types
+        .into_iter()
+        .map(|(node_type, row)| EmptyTypeSummary {
+            node_type: node_type.to_string(),
+            count: row.count,
+            self_size_sum: row.self_size_sum,
+        })
+        .collect();
+    empty_name_types.sort_by(|a, b| {
+        b.self_size_sum
+            .cmp(&a.self_size_sum)
+            .then_with(|| b.count.cmp(&a.count))
+            .then_with(|| a.node_type.cmp(&b.node_type))
+    });
+
+    SummaryResult {
+        total_nodes,
+        total_rows,
+        skip: options.skip,
+        rows,
+        empty_name_types,
+        excluded: options.exclude_synthetic.then_some(excluded),
+        node_type: options.node_type,
+        detached_only: options.detached_only,
+        unreachable_only: options.unreachable_only,
+        edge_types: None,
+        snapshot_fingerprint,
+    }
+}
+
+/// Per-node filters shared by every aggregation thread.
+struct NodeFilter<'a> {
+    contains: Option<&'a str>,
+    node_type: Option<&'a str>,
+    detached_only: bool,
+    exclude_synthetic: bool,
+    /// Per-node classification when weak-only nodes are counted or unreachable nodes
+    /// are selected.
+    reachability: Option<&'a [Reachability]>,
+    unreachable_only: bool,
+}
+
+/// A node as remembered by a row: its index orders ties and examples, its id is printed.
+#[derive(Debug, Clone, Copy)]
+struct NodeRef {
+    index: usize,
+    id: Option<i64>,
+}
+
+/// Per-constructor totals plus its largest instance and its first few instances.
+#[derive(Clone)]
+struct RowAggregate {
+    count: u64,
+    self_size_sum: i64,
+    weak_only: u64,
+    largest_node: NodeRef,
+    largest_size: i64,
+    examples: Vec<NodeRef>,
+}
+
+impl RowAggregate {
+    fn new(node: NodeRef, self_size: i64, weak_only: bool, samples: usize) -> Self {
+        let mut examples = Vec::new();
+        if samples > 0 {
+            examples.push(node);
+        }
+        Self {
+            count: 1,
+            self_size_sum: self_size,
+            weak_only: u64::from(weak_only),
+            largest_node: node,
+            largest_size: self_size,
+            examples,
+        }
+    }
+
+    /// Counts a later node of the same row; the earlier node wins ties.
+    fn add(&mut self, node: NodeRef, self_size: i64, weak_only: bool, samples: usize) {
+        self.count += 1;
+        self.self_size_sum += self_size;
+        self.weak_only += u64::from(weak_only);
+        if self_size > self.largest_size {
+            self.largest_node = node;
+            self.largest_size = self_size;
+        }
+        if self.examples.len() < samples {
+            self.examples.push(node);
+        }
+    }
+
+    /// Folds in the totals of other nodes, in either order; the lower node index wins
+    /// ties and the examples stay the first in node order.
+    fn merge(&mut self, other: RowAggregate, samples: usize) {
+        self.count += other.count;
+        self.self_size_sum += other.self_size_sum;
+        self.weak_only += other.weak_only;
+        if (other.largest_size, self.largest_node.index)
+            > (self.largest_size, other.largest_node.index)
+        {
+            self.largest_node = other.largest_node;
+            self.largest_size = other.largest_size;
+        }
+        self.examples.extend(other.examples);
+        self.examples.sort_by_key(|node| node.index);
+        self.examples.truncate(samples);
+    }
+}
+
+/// Counts and self size sums for one node range; ranges are merged in index order.
+#[derive(Default)]
+struct Aggregate<'a> {
+    /// name_index -> totals of that constructor
+    rows: HashMap<usize, RowAggregate>,
+    /// Node type of empty-named nodes -> totals of that type
+    empty_types: HashMap<&'a str, RowAggregate>,
+    excluded: ExcludedNodes,
+}
+
+impl<'a> Aggregate<'a> {
+    fn merge(&mut self, other: Aggregate<'a>, samples: usize) {
+        for (name_index, row) in other.rows {
+            match self.rows.entry(name_index) {
+                Entry::Occupied(mut entry) => entry.get_mut().merge(row, samples),
+                Entry::Vacant(entry) => {
+                    entry.insert(row);
+                }
+            }
+        }
+        for (node_type, row) in other.empty_types {
+            match self.empty_types.entry(node_type) {
+                Entry::Occupied(mut entry) => entry.get_mut().merge(row, samples),
+                Entry::Vacant(entry) => {
+                    entry.insert(row);
+                }
+            }
+        }
+        self.excluded.count += other.excluded.count;
+        self.excluded.self_size_sum += other.excluded.self_size_sum;
+    }
+}
+
+/// Splits the node range into `jobs` contiguous chunks aggregated on scoped threads.
+/// The merged sums do not depend on `jobs`, and on invalid data the error of the
+/// lowest node index is returned, as in the single-threaded pass.
+fn aggregate_nodes<'a>(
+    snapshot: &'a SnapshotRaw,
+    filter: &NodeFilter<'_>,
+    jobs: usize,
+    samples: usize,
+) -> Result<Aggregate<'a>, SnapshotError> {
+    let node_count = snapshot.node_count();
+    // Extra threads past the core count only add spawn cost; never start one per node.
+    let cores = std::thread::available_parallelism().map_or(1, usize::from);
+    let jobs = jobs.min(cores).clamp(1, node_count.max(1));
+    if jobs == 1 {
+        return aggregate_range(snapshot, 0..node_count, filter, samples);
+    }
+
+    let chunk = node_count.div_ceil(jobs);
+    let results: Vec<Result<Aggregate<'a>, SnapshotError>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..node_count)
+            .step_by(chunk)
+            .map(|start| {
+                let end = (start + chunk).min(node_count);
+                scope.spawn(move || aggregate_range(snapshot, start..end, filter, samples))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    });
+
+    let mut total = Aggregate::default();
+    for result in results {
+        total.merge(result?, samples);
+    }
+    Ok(total)
+}
+
+fn aggregate_range<'a>(
+    snapshot: &'a SnapshotRaw,
+    range: Range<usize>,
+    filter: &NodeFilter<'_>,
+    samples: usize,
+) -> Result<Aggregate<'a>, SnapshotError> {
+    let mut aggregate = Aggregate::default();
+    for node_index in range {
+        let Some(node) = snapshot.node_view(node_index) else {
+            break;
+        };
         let name_index = match node.name_index() {
             Some(value) => value,
             None => {
                 return Err(SnapshotError::InvalidData {
-                    details: format!("node missing name index: {index}"),
+                    details: format!("node missing name index: {node_index}"),
                 });
             }
         };
@@ -62,61 +578,141 @@ pub fn summarize(
                 details: format!("name index out of range: {name_index}"),
             })?;
 
-        if let Some(filter) = options.contains.as_deref() {
-            if !name.contains(filter) {
-                continue;
-            }
+        if filter.contains.is_some_and(|wanted| !name.contains(wanted)) {
+            continue;
+        }
+
+        if filter
+            .node_type
+            .is_some_and(|wanted| node.node_type() != Some(wanted))
+        {
+            continue;
+        }
+
+        if filter.detached_only && !node.is_detached() {
+            continue;
         }
 
-        let entry = map.entry(name_index).or_insert_with(|| SummaryRow {
-            name: name.to_string(),
-            count: 0,
-            self_size_sum: 0,
-        });
+        if filter.unreachable_only
+            && filter
+                .reachability
+                .is_some_and(|classes| classes.get(node_index) != Some(&Reachability::Unreachable))
+        {
+            continue;
+        }
+
+        let self_size = node.self_size().unwrap_or(0);
+        if filter.exclude_synthetic
+            && node
+                .node_type()
+                .is_some_and(|node_type| SYNTHETIC_NODE_TYPES.contains(&node_type))
+        {
+            aggregate.excluded.count += 1;
+            aggregate.excluded.self_size_sum += self_size;
+            continue;
+        }
 
-        entry.count += 1;
-        entry.self_size_sum += node.self_size().unwrap_or(0);
+        let weak_only = filter
+            .reachability
+            .is_some_and(|classes| classes.get(node_index) == Some(&Reachability::WeakOnly));
+        let node_ref = NodeRef {
+            index: node_index,
+            id: node.id(),
+        };
+        match aggregate.rows.entry(name_index) {
+            Entry::Occupied(mut entry) => {
+                entry.get_mut().add(node_ref, self_size, weak_only, samples)
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(RowAggregate::new(node_ref, self_size, weak_only, samples));
+            }
+        }
 
         if name.is_empty() {
             let node_type = node.node_type().unwrap_or("unknown");
-            let type_entry =
-                empty_types
-                    .entry(node_type.to_string())
-                    .or_insert_with(|| EmptyTypeSummary {
-                        node_type: node_type.to_string(),
-                        count: 0,
-                        self_size_sum: 0,
-                    });
-            type_entry.count += 1;
-            type_entry.self_size_sum += node.self_size().unwrap_or(0);
+            match aggregate.empty_types.entry(node_type) {
+                Entry::Occupied(mut entry) => {
+                    entry.get_mut().add(node_ref, self_size, weak_only, samples)
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(RowAggregate::new(node_ref, self_size, weak_only, samples));
+                }
+            }
         }
     }
+    Ok(aggregate)
+}
 
-    let mut rows: Vec<SummaryRow> = map.into_values().collect();
-    rows.sort_by(|a, b| {
-        b.self_size_sum
-            .cmp(&a.self_size_sum)
-            .then_with(|| b.count.cmp(&a.count))
-            .then_with(|| a.name.cmp(&b.name))
-    });
+/// Checks `name` against the snapshot's `meta.node_types`; the error lists the valid names.
+pub fn resolve_node_type<'a>(
+    snapshot: &'a SnapshotRaw,
+    name: &str,
+) -> Result<&'a str, SnapshotError> {
+    find_node_type(&snapshot.index.node_type_names, name)
+}
+
+fn find_node_type<'a>(names: &'a [String], name: &str) -> Result<&'a str, SnapshotError> {
+    names
+        .iter()
+        .find(|candidate| candidate.as_str() == name)
+        .map(String::as_str)
+        .ok_or_else(|| SnapshotError::InvalidData {
+            details: format!(
+                "unknown node type: {name} (valid types: {})",
+                names.join(", ")
+            ),
+        })
+}
+
+/// Summary ranking: larger self size first, then larger count, then name.
+fn rank_order(a: &SummaryRow, b: &SummaryRow) -> Ordering {
+    b.self_size_sum
+        .cmp(&a.self_size_sum)
+        .then_with(|| b.count.cmp(&a.count))
+        .then_with(|| a.name.cmp(&b.name))
+}
 
-    if rows.len() > options.top {
-        rows.truncate(options.top);
+/// Heap entry whose greatest element is the lowest-ranked row, so popping evicts it.
+struct RankedRow(SummaryRow);
+
+impl PartialEq for RankedRow {
+    fn eq(&self, other: &Self) -> bool {
+        rank_order(&self.0, &other.0) == Ordering::Equal
     }
+}
 
-    let mut empty_name_types: Vec<EmptyTypeSummary> = empty_types.into_values().collect();
-    empty_name_types.sort_by(|a, b| {
-        b.self_size_sum
-            .cmp(&a.self_size_sum)
-            .then_with(|| b.count.cmp(&a.count))
-            .then_with(|| a.node_type.cmp(&b.node_type))
-    });
+impl Eq for RankedRow {}
 
-    Ok(SummaryResult {
-        total_nodes: snapshot.node_count(),
-        rows,
-        empty_name_types,
-    })
+impl PartialOrd for RankedRow {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RankedRow {
+    fn cmp(&self, other: &Self) -> Ordering {
+        rank_order(&self.0, &other.0)
+    }
+}
+
+/// The `keep` best rows in rank order. Uses a bounded heap (O(n log keep)) when only a
+/// prefix is needed, and a full sort otherwise.
+fn top_rows(mut rows: Vec<SummaryRow>, keep: usize) -> Vec<SummaryRow> {
+    if keep >= rows.len() {
+        rows.sort_by(rank_order);
+        return rows;
+    }
+    let mut heap: BinaryHeap<RankedRow> = BinaryHeap::with_capacity(keep + 1);
+    for row in rows {
+        heap.push(RankedRow(row));
+        if heap.len() > keep {
+            heap.pop();
+        }
+    }
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|ranked| ranked.0)
+        .collect()
 }
 
 #[cfg(test)]
@@ -134,7 +730,7 @@ mod tests {
                 "edge_count".to_string(),
             ],
             node_types: vec![
-                MetaType::Array(vec!["object".to_string()]),
+                MetaType::Array(vec!["object".to_string(), "synthetic".to_string()]),
                 MetaType::String("string".to_string()),
                 MetaType::String("number".to_string()),
                 MetaType::String("number".to_string()),
@@ -150,20 +746,21 @@ mod tests {
                 MetaType::String("string_or_number".to_string()),
                 MetaType::String("node".to_string()),
             ],
+            location_fields: Vec::new(),
         };
         let index = meta.validate().expect("meta valid");
 
-        SnapshotRaw {
-            nodes: vec![
+        SnapshotRaw::new(
+            vec![
                 0, 0, 1, 10, 0, // node 0: name index 0
                 0, 1, 2, 20, 0, // node 1: name index 1
                 0, 0, 3, 5, 0, // node 2: name index 0
             ],
-            edges: vec![],
-            strings: vec!["Foo".to_string(), "Bar".to_string()],
+            vec![],
+            vec!["Foo".to_string(), "Bar".to_string()],
             meta,
             index,
-        }
+        )
     }
 
     #[test]
@@ -173,7 +770,7 @@ mod tests {
             &snapshot,
             SummaryOptions {
                 top: 10,
-                contains: None,
+                ..Default::default()
             },
         )
         .expect("summary");
@@ -184,6 +781,8 @@ mod tests {
         assert_eq!(result.rows[0].self_size_sum, 20);
         assert_eq!(result.rows[1].name, "Foo");
         assert_eq!(result.rows[1].count, 2);
+        // Node 0 (10 bytes) is the larger of the two Foo instances.
+        assert_eq!(result.rows[1].sample_id, Some(1));
     }
 
     #[test]
@@ -194,6 +793,7 @@ mod tests {
             SummaryOptions {
                 top: 10,
                 contains: Some("Fo".to_string()),
+                ..Default::default()
             },
         )
         .expect("summary");
@@ -211,10 +811,136 @@ mod tests {
             SummaryOptions {
                 top: 10,
                 contains: Some("foo".to_string()),
+                ..Default::default()
             },
         )
         .expect("summary");
 
         assert!(result.rows.is_empty());
     }
+
+    #[test]
+    fn summarize_skip_limit_pages_full_ranking() {
+        let snapshot = minimal_snapshot();
+        let result = summarize(
+            &snapshot,
+            SummaryOptions {
+                top: 1,
+                skip: 1,
+                limit: Some(5),
+                ..Default::default()
+            },
+        )
+        .expect("summary");
+
+        assert_eq!(result.total_nodes, 3);
+        assert_eq!(result.total_rows, 2);
+        assert_eq!(result.skip, 1);
+        assert_eq!(result.rows.len(), 1);
+        assert_eq!(result.rows[0].name, "Foo");
+    }
+
+    #[test]
+    fn summarize_exclude_synthetic_reports_hidden_total() {
+        let mut snapshot = minimal_snapshot();
+        // node 1 (Bar) becomes a synthetic node
+        snapshot.nodes[5] = 1;
+        let options = |exclude_synthetic| SummaryOptions {
+            top: 10,
+            exclude_synthetic,
+            ..Default::default()
+        };
+
+        let result = summarize(&snapshot, options(false)).expect("summary");
+        assert_eq!(result.rows.len(), 2);
+        assert!(result.excluded.is_none());
+
+        let result = summarize(&snapshot, options(true)).expect("summary");
+        assert_eq!(result.total_nodes, 3);
+        assert_eq!(result.rows.len(), 1);
+        assert_eq!(result.rows[0].name, "Foo");
+        let excluded = result.excluded.expect("excluded");
+        assert_eq!(excluded.count, 1);
+        assert_eq!(excluded.self_size_sum, 20);
+    }
+
+    #[test]
+    fn top_rows_matches_full_sort() {
+        let rows = || {
+            (0..200u64)
+                .map(|i| SummaryRow {
+                    name: format!("C{}", i % 37),
+                    count: i % 5,
+                    self_size_sum: ((i * 7919) % 23) as i64,
+                    sample_id: None,
+                    weak_only_count: None,
+                    name_index: None,
+                    examples: Vec::new(),
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut expected = rows();
+        expected.sort_by(rank_order);
+        let key = |row: &SummaryRow| (row.name.clone(), row.count, row.self_size_sum);
+
+        for keep in [0, 1, 5, 50, 199, 200, 500] {
+            let actual: Vec<_> = top_rows(rows(), keep).iter().map(key).collect();
+            let wanted: Vec<_> = expected.iter().take(keep).map(key).collect();
+            assert_eq!(actual, wanted, "keep={keep}");
+        }
+    }
+
+    #[test]
+    fn summarize_node_type_filter_validates_and_restricts() {
+        let mut snapshot = minimal_snapshot();
+        snapshot.nodes[5] = 1;
+        let options = |node_type: &str| SummaryOptions {
+            top: 10,
+            node_type: Some(node_type.to_string()),
+            ..Default::default()
+        };
+
+        let result = summarize(&snapshot, options("synthetic")).expect("summary");
+        assert_eq!(result.rows.len(), 1);
+        assert_eq!(result.rows[0].name, "Bar");
+
+        let err = summarize(&snapshot, options("closure")).expect_err("unknown type");
+        let message = err.to_string();
+        assert!(message.contains("unknown node type: closure"));
+        assert!(message.contains("valid types: object, synthetic"));
+    }
+
+    #[test]
+    fn summarize_split_empty_emits_one_row_per_node_type() {
+        let mut snapshot = minimal_snapshot();
+        // "Foo" becomes the empty name; node 2 (5 bytes) becomes synthetic.
+        snapshot.strings[0] = String::new();
+        snapshot.nodes[10] = 1;
+        let options = |split_empty| SummaryOptions {
+            top: 10,
+            split_empty,
+            ..Default::default()
+        };
+
+        let result = summarize(&snapshot, options(false)).expect("summary");
+        let names: Vec<&str> = result.rows.iter().map(|row| row.name.as_str()).collect();
+        assert_eq!(names, vec!["Bar", ""]);
+
+        let result = summarize(&snapshot, options(true)).expect("summary");
+        let rows: Vec<(&str, u64, i64)> = result
+            .rows
+            .iter()
+            .map(|row| (row.name.as_str(), row.count, row.self_size_sum))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("Bar", 1, 20),
+                ("(empty: object)", 1, 10),
+                ("(empty: synthetic)", 1, 5)
+            ]
+        );
+        assert_eq!(result.total_rows, 3);
+        assert_eq!(result.rows[2].sample_id, Some(3));
+    }
 }
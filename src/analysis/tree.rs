@@ -0,0 +1,157 @@
+use crate::analysis::dominator::DominatorIndex;
+use crate::analysis::unreachable::explain_unreachable;
+use crate::cancel::CancelToken;
+use crate::error::SnapshotError;
+use crate::snapshot::SnapshotRaw;
+
+#[derive(Debug)]
+pub struct TreeOptions {
+    /// Levels below the target to expand (0 prints the target alone).
+    pub max_depth: usize,
+    /// Children kept per node, largest retained size first.
+    pub max_children: usize,
+    pub cancel: CancelToken,
+}
+
+#[derive(Debug, Clone)]
+pub struct TreeNode {
+    pub node_index: usize,
+    pub retained_size: i64,
+    pub children: Vec<TreeNode>,
+    /// Children cut by `max_children`, or every child once `max_depth` is reached.
+    pub omitted_children: usize,
+    pub omitted_retained_size: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TreeResult {
+    pub root: TreeNode,
+    /// Sum of every node's self size, the denominator for retained percentages.
+    pub total_size: i64,
+}
+
+/// Dominator subtree rooted at `target`: what would be freed if `target` were collected.
+pub fn dominator_subtree(
+    snapshot: &SnapshotRaw,
+    index: &DominatorIndex,
+    retained: &[i64],
+    target: usize,
+    options: TreeOptions,
+) -> Result<TreeResult, SnapshotError> {
+    if index.idom.get(target).copied().flatten().is_none() {
+        let unreachable = explain_unreachable(snapshot, index, target)?;
+        return Err(SnapshotError::InvalidData {
+            details: format!(
+                "target is not reachable from roots: {} (run `dominator` on it for the nearest retainers)",
+                unreachable.reason.describe()
+            ),
+        });
+    }
+
+    let children = dominated_children(index, retained);
+    let root = build_node(&children, retained, target, 0, &options)?;
+    Ok(TreeResult {
+        root,
+        total_size: snapshot
+            .nodes()
+            .map(|node| node.self_size().unwrap_or(0))
+            .sum(),
+    })
+}
+
+/// Immediately dominated nodes of every node, sorted by retained size (descending).
+fn dominated_children(index: &DominatorIndex, retained: &[i64]) -> Vec<Vec<usize>> {
+    let mut children = vec![Vec::new(); index.idom.len()];
+    for (node, parent) in index.idom.iter().enumerate() {
+        if let Some(parent) = parent.filter(|parent| *parent != node) {
+            children[parent].push(node);
+        }
+    }
+    let retained_of = |node: usize| retained.get(node).copied().unwrap_or(0);
+    for list in &mut children {
+        list.sort_by(|a, b| retained_of(*b).cmp(&retained_of(*a)).then_with(|| a.cmp(b)));
+    }
+    children
+}
+
+fn build_node(
+    children: &[Vec<usize>],
+    retained: &[i64],
+    node_index: usize,
+    depth: usize,
+    options: &TreeOptions,
+) -> Result<TreeNode, SnapshotError> {
+    if options.cancel.is_cancelled() {
+        return Err(SnapshotError::Cancelled);
+    }
+    let retained_of = |node: usize| retained.get(node).copied().unwrap_or(0);
+    let dominated = &children[node_index];
+    let keep = if depth < options.max_depth {
+        options.max_children.min(dominated.len())
+    } else {
+        0
+    };
+
+    let mut kept = Vec::with_capacity(keep);
+    for child in &dominated[..keep] {
+        kept.push(build_node(children, retained, *child, depth + 1, options)?);
+    }
+    Ok(TreeNode {
+        node_index,
+        retained_size: retained_of(node_index),
+        children: kept,
+        omitted_children: dominated.len() - keep,
+        omitted_retained_size: dominated[keep..]
+            .iter()
+            .map(|node| retained_of(*node))
+            .sum(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(idom: Vec<Option<usize>>) -> DominatorIndex {
+        DominatorIndex {
+            roots: vec![0],
+            idom,
+        }
+    }
+
+    fn options(max_depth: usize, max_children: usize) -> TreeOptions {
+        TreeOptions {
+            max_depth,
+            max_children,
+            cancel: CancelToken::new(),
+        }
+    }
+
+    #[test]
+    fn children_are_sorted_and_capped() {
+        // 0 dominates 1, 2, 3; 2 dominates 4.
+        let idom = index(vec![Some(0), Some(0), Some(0), Some(0), Some(2)]);
+        let retained = [100, 10, 50, 30, 40];
+        let children = dominated_children(&idom, &retained);
+        let root = build_node(&children, &retained, 0, 0, &options(5, 2)).expect("tree");
+
+        let order: Vec<usize> = root.children.iter().map(|child| child.node_index).collect();
+        assert_eq!(order, vec![2, 3]);
+        assert_eq!(root.omitted_children, 1);
+        assert_eq!(root.omitted_retained_size, 10);
+        assert_eq!(root.children[0].children[0].node_index, 4);
+    }
+
+    #[test]
+    fn max_depth_stops_expansion() {
+        let idom = index(vec![Some(0), Some(0), Some(1)]);
+        let retained = [30, 20, 10];
+        let children = dominated_children(&idom, &retained);
+        let root = build_node(&children, &retained, 0, 0, &options(1, 10)).expect("tree");
+
+        assert_eq!(root.children.len(), 1);
+        assert!(root.children[0].children.is_empty());
+        assert_eq!(root.children[0].omitted_children, 1);
+        assert_eq!(root.children[0].omitted_retained_size, 10);
+    }
+}
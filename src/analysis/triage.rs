@@ -0,0 +1,66 @@
+use crate::analysis::retainers::{PathRank, RetainerSearch, RetainersOptions, RetainersResult};
+use crate::cancel::CancelToken;
+use crate::error::SnapshotError;
+use crate::snapshot::SnapshotRaw;
+
+#[derive(Debug)]
+pub struct TriageOptions {
+    /// Number of biggest nodes (by self size) to analyse.
+    pub top: usize,
+    pub max_paths: usize,
+    pub max_depth: usize,
+    pub cancel: CancelToken,
+}
+
+#[derive(Debug)]
+pub struct TriageEntry {
+    pub self_size: i64,
+    pub retainers: RetainersResult,
+}
+
+#[derive(Debug)]
+pub struct TriageResult {
+    pub total_nodes: usize,
+    pub entries: Vec<TriageEntry>,
+}
+
+/// Node indexes of the `top` largest nodes by self size (ties broken by index).
+pub fn find_biggest(snapshot: &SnapshotRaw, top: usize) -> Vec<(usize, i64)> {
+    let mut nodes: Vec<(usize, i64)> = snapshot
+        .nodes()
+        .map(|node| (node.node_index(), node.self_size().unwrap_or(0)))
+        .collect();
+    nodes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    nodes.truncate(top);
+    nodes
+}
+
+pub fn triage(
+    snapshot: &SnapshotRaw,
+    options: TriageOptions,
+) -> Result<TriageResult, SnapshotError> {
+    let biggest = find_biggest(snapshot, options.top);
+    let mut search = RetainerSearch::new(snapshot)?;
+    let retainers_options = RetainersOptions {
+        max_paths: options.max_paths,
+        max_depth: options.max_depth,
+        distinct_roots: false,
+        rank: PathRank::Shortest,
+        depth_histogram: false,
+        cancel: options.cancel,
+    };
+
+    let mut entries = Vec::with_capacity(biggest.len());
+    for (node_index, self_size) in biggest {
+        let retainers = search.find(snapshot, node_index, &retainers_options)?;
+        entries.push(TriageEntry {
+            self_size,
+            retainers,
+        });
+    }
+
+    Ok(TriageResult {
+        total_nodes: snapshot.node_count(),
+        entries,
+    })
+}
@@ -0,0 +1,137 @@
+use std::collections::{BTreeSet, HashSet};
+
+use crate::analysis::dominator::DominatorIndex;
+use crate::error::SnapshotError;
+use crate::snapshot::SnapshotRaw;
+
+/// Steps `explain_unreachable` walks back from the target.
+pub const EXPLAIN_MAX_DEPTH: usize = 3;
+/// Retainers listed in an explanation at most; the walk itself is not capped.
+pub const EXPLAIN_MAX_RETAINERS: usize = 20;
+
+/// Why the dominator tree has no chain for a node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnreachableReason {
+    /// No edge points at the target.
+    NoRetainers,
+    /// Reachable nodes hold the target (or its retainers) only through edge types the
+    /// dominator tree ignores, e.g. `weak`.
+    IgnoredEdges { edge_types: Vec<String> },
+    /// Every retainer was visited without meeting a reachable node: the target sits in a
+    /// detached island of `size` nodes (including itself).
+    Island { size: usize },
+    /// No reachable retainer within `EXPLAIN_MAX_DEPTH` steps.
+    DepthLimit,
+}
+
+impl UnreachableReason {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            UnreachableReason::NoRetainers => "no_retainers",
+            UnreachableReason::IgnoredEdges { .. } => "ignored_edges",
+            UnreachableReason::Island { .. } => "island",
+            UnreachableReason::DepthLimit => "depth_limit",
+        }
+    }
+
+    /// One-line explanation for the text outputs.
+    pub fn describe(&self) -> String {
+        match self {
+            UnreachableReason::NoRetainers => "nothing references the target".to_string(),
+            UnreachableReason::IgnoredEdges { edge_types } => format!(
+                "reachable nodes hold it only through ignored edge types ({})",
+                edge_types.join(", ")
+            ),
+            UnreachableReason::Island { size } => {
+                format!("it sits in a detached island of {size} nodes that no root references")
+            }
+            UnreachableReason::DepthLimit => format!(
+                "no reachable retainer within {EXPLAIN_MAX_DEPTH} steps; try `retainers` on it"
+            ),
+        }
+    }
+}
+
+/// A node found by walking incoming edges back from the target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NearbyRetainer {
+    pub node: usize,
+    /// Steps from the target; 1 for a direct retainer.
+    pub depth: usize,
+    /// The edge from `node` toward the target.
+    pub edge_index: usize,
+    /// Whether `node` itself is in the dominator tree.
+    pub reachable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unreachable {
+    pub reason: UnreachableReason,
+    /// Nearest retainers first, at most `EXPLAIN_MAX_RETAINERS`.
+    pub retainers: Vec<NearbyRetainer>,
+    /// Distinct retainers the walk visited, including those not listed.
+    pub explored: usize,
+}
+
+/// Walks incoming edges back from `target` (which `index` does not reach) for up to
+/// `EXPLAIN_MAX_DEPTH` steps. Reachable retainers end the walk on their branch: the
+/// edge that led from them must have been ignored when the tree was built.
+pub fn explain_unreachable(
+    snapshot: &SnapshotRaw,
+    index: &DominatorIndex,
+    target: usize,
+) -> Result<Unreachable, SnapshotError> {
+    let mut visited: HashSet<usize> = HashSet::from([target]);
+    let mut retainers = Vec::new();
+    let mut ignored_edge_types: BTreeSet<String> = BTreeSet::new();
+    let mut frontier = vec![target];
+    let mut depth = 0;
+
+    while !frontier.is_empty() && depth < EXPLAIN_MAX_DEPTH {
+        depth += 1;
+        let mut next = Vec::new();
+        for node in frontier {
+            for (holder, edge) in snapshot.retainers_of(node)? {
+                let holder_index = holder.node_index();
+                if !visited.insert(holder_index) {
+                    continue;
+                }
+                let reachable = index.idom.get(holder_index).copied().flatten().is_some();
+                if reachable {
+                    ignored_edge_types.insert(edge.edge_type().unwrap_or("unknown").to_string());
+                } else {
+                    next.push(holder_index);
+                }
+                if retainers.len() < EXPLAIN_MAX_RETAINERS {
+                    retainers.push(NearbyRetainer {
+                        node: holder_index,
+                        depth,
+                        edge_index: edge.edge_index(),
+                        reachable,
+                    });
+                }
+            }
+        }
+        frontier = next;
+    }
+
+    let explored = visited.len() - 1;
+    let reason = if explored == 0 {
+        UnreachableReason::NoRetainers
+    } else if !ignored_edge_types.is_empty() {
+        UnreachableReason::IgnoredEdges {
+            edge_types: ignored_edge_types.into_iter().collect(),
+        }
+    } else if frontier.is_empty() {
+        UnreachableReason::Island {
+            size: visited.len(),
+        }
+    } else {
+        UnreachableReason::DepthLimit
+    };
+    Ok(Unreachable {
+        reason,
+        retainers,
+        explored,
+    })
+}
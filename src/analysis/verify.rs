@@ -0,0 +1,215 @@
+use crate::analysis::retainers::is_gc_root;
+use crate::snapshot::{EdgeLayout, SnapshotRaw};
+
+#[derive(Debug, Clone)]
+pub struct VerifyReport {
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub string_count: usize,
+    pub declared_node_count: Option<u64>,
+    pub declared_edge_count: Option<u64>,
+    pub edge_count_sum: u64,
+    pub negative_edge_count_nodes: u64,
+    pub out_of_range_to_node: u64,
+    pub name_index_out_of_range: u64,
+    pub gc_root_count: u64,
+    pub checks: Vec<VerifyCheck>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VerifyCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub details: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    /// Suspicious but analysis can still proceed (e.g. root fallback to node 0).
+    Warn,
+    Fail,
+}
+
+impl CheckStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "pass",
+            CheckStatus::Warn => "warn",
+            CheckStatus::Fail => "fail",
+        }
+    }
+}
+
+impl VerifyReport {
+    pub fn passed(&self) -> bool {
+        self.failed_count() == 0
+    }
+
+    pub fn failed_count(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Fail)
+            .count()
+    }
+
+    /// True when the snapshot does not declare a node count or declares the real one.
+    pub fn node_count_matches(&self) -> bool {
+        declared_matches(self.declared_node_count, self.node_count)
+    }
+
+    pub fn edge_count_matches(&self) -> bool {
+        declared_matches(self.declared_edge_count, self.edge_count)
+    }
+
+    /// The per-node `edge_count` values are all non-negative and add up to the edges array.
+    pub fn edge_sum_matches(&self) -> bool {
+        self.edge_count_sum == self.edge_count as u64 && self.negative_edge_count_nodes == 0
+    }
+
+    pub fn has_gc_roots(&self) -> bool {
+        self.gc_root_count > 0
+    }
+}
+
+/// Scans the whole snapshot once and records every integrity problem instead of
+/// stopping at the first one, so a single run reports all issues.
+pub fn verify(snapshot: &SnapshotRaw) -> VerifyReport {
+    let node_count = snapshot.node_count();
+    let edge_count = snapshot.edge_count();
+
+    let mut edge_count_sum = 0u64;
+    let mut negative_edge_count_nodes = 0u64;
+    let mut name_index_out_of_range = 0u64;
+    let mut gc_root_count = 0u64;
+    for node in snapshot.nodes() {
+        match node.edge_count() {
+            Some(value) if value >= 0 => edge_count_sum += value as u64,
+            _ => negative_edge_count_nodes += 1,
+        }
+        match node.name_index() {
+            Some(index) if index < snapshot.strings.len() => {}
+            _ => name_index_out_of_range += 1,
+        }
+        if is_gc_root(&node) {
+            gc_root_count += 1;
+        }
+    }
+
+    let mut out_of_range_to_node = 0u64;
+    for edge in snapshot.edges() {
+        // to_node_index() rejects negative and misaligned offsets.
+        let in_range = matches!(edge.to_node_index(), Some(index) if index < node_count);
+        if !in_range {
+            out_of_range_to_node += 1;
+        }
+    }
+
+    let mut checks = Vec::new();
+    checks.push(declared_check(
+        "node_count",
+        snapshot.declared_node_count,
+        node_count,
+        "nodes",
+    ));
+    checks.push(declared_check(
+        "edge_count",
+        snapshot.declared_edge_count,
+        edge_count,
+        "edges",
+    ));
+    checks.push(VerifyCheck {
+        name: "edge_count_sum",
+        status: if edge_count_sum == edge_count as u64 && negative_edge_count_nodes == 0 {
+            CheckStatus::Pass
+        } else {
+            CheckStatus::Fail
+        },
+        details: match snapshot.index.node_field_index.edge_layout {
+            EdgeLayout::EdgeCount(_) => format!(
+                "sum of node edge_count = {edge_count_sum}, edges = {edge_count}, negative edge_count nodes = {negative_edge_count_nodes}"
+            ),
+            EdgeLayout::EdgesIndex(_) => format!(
+                "edges delimited by node edges_index = {edge_count_sum}, edges = {edge_count}, nodes with decreasing or misaligned edges_index = {negative_edge_count_nodes}"
+            ),
+        },
+    });
+    checks.push(count_check(
+        "to_node_range",
+        out_of_range_to_node,
+        "edges with out-of-range or misaligned to_node",
+    ));
+    checks.push(count_check(
+        "name_index_range",
+        name_index_out_of_range,
+        "nodes with name index outside strings",
+    ));
+    checks.push(VerifyCheck {
+        name: "gc_roots",
+        status: if gc_root_count > 0 {
+            CheckStatus::Pass
+        } else {
+            CheckStatus::Warn
+        },
+        details: if gc_root_count > 0 {
+            format!("{gc_root_count} node(s) named \"GC roots\"")
+        } else {
+            "no node named \"GC roots\"; retainers/dominator fall back to node 0".to_string()
+        },
+    });
+
+    VerifyReport {
+        node_count,
+        edge_count,
+        string_count: snapshot.strings.len(),
+        declared_node_count: snapshot.declared_node_count,
+        declared_edge_count: snapshot.declared_edge_count,
+        edge_count_sum,
+        negative_edge_count_nodes,
+        out_of_range_to_node,
+        name_index_out_of_range,
+        gc_root_count,
+        checks,
+    }
+}
+
+fn declared_matches(declared: Option<u64>, actual: usize) -> bool {
+    declared.is_none_or(|value| value == actual as u64)
+}
+
+fn declared_check(
+    name: &'static str,
+    declared: Option<u64>,
+    actual: usize,
+    label: &str,
+) -> VerifyCheck {
+    match declared {
+        Some(value) if value == actual as u64 => VerifyCheck {
+            name,
+            status: CheckStatus::Pass,
+            details: format!("declared {value}, {label} array holds {actual}"),
+        },
+        Some(value) => VerifyCheck {
+            name,
+            status: CheckStatus::Fail,
+            details: format!("declared {value}, {label} array holds {actual}"),
+        },
+        None => VerifyCheck {
+            name,
+            status: CheckStatus::Pass,
+            details: format!("not declared in snapshot, {label} array holds {actual}"),
+        },
+    }
+}
+
+fn count_check(name: &'static str, count: u64, label: &str) -> VerifyCheck {
+    VerifyCheck {
+        name,
+        status: if count == 0 {
+            CheckStatus::Pass
+        } else {
+            CheckStatus::Fail
+        },
+        details: format!("{label}: {count}"),
+    }
+}
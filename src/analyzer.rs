@@ -0,0 +1,163 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::analysis::detail::{self, DetailOptions, DetailResult};
+use crate::analysis::dominator::{self, DominatorIndex, DominatorOptions, DominatorResult};
+use crate::analysis::retainers::{RetainerSearch, RetainersOptions, RetainersResult};
+use crate::analysis::summary::{self, SummaryOptions, SummaryResult};
+use crate::cancel::CancelToken;
+use crate::error::SnapshotError;
+use crate::parser::{self, ReadOptions};
+use crate::snapshot::SnapshotRaw;
+
+/// A loaded snapshot that answers many queries, building shared indices on first use.
+///
+/// Indices (id map, inbound edges, dominator tree and retained sizes) are cached behind
+/// `&self`, so one `Analyzer` can be shared between threads with `Arc`.
+#[derive(Debug)]
+pub struct Analyzer {
+    snapshot: SnapshotRaw,
+    retainer_search: Mutex<Option<RetainerSearch>>,
+    dominator: Mutex<Option<CachedDominator>>,
+}
+
+#[derive(Debug)]
+struct CachedDominator {
+    /// Sorted, deduplicated `ignore_edge_types` the index was built with.
+    ignore_edge_types: Vec<String>,
+    index: Arc<DominatorIndex>,
+    retained_sizes: Arc<Vec<i64>>,
+}
+
+impl Analyzer {
+    pub fn new(snapshot: SnapshotRaw) -> Self {
+        Self {
+            snapshot,
+            retainer_search: Mutex::new(None),
+            dominator: Mutex::new(None),
+        }
+    }
+
+    pub fn open(path: &Path, options: ReadOptions) -> Result<Self, SnapshotError> {
+        Ok(Self::new(parser::read_snapshot_file(path, options)?))
+    }
+
+    pub fn snapshot(&self) -> &SnapshotRaw {
+        &self.snapshot
+    }
+
+    pub fn into_snapshot(self) -> SnapshotRaw {
+        self.snapshot
+    }
+
+    /// First edge index of every node (see `SnapshotRaw::edge_offsets`).
+    pub fn edge_offsets(&self) -> Result<&[usize], SnapshotError> {
+        self.snapshot.edge_offsets()
+    }
+
+    /// Node index for a heap object id (see `SnapshotRaw::node_by_id`).
+    pub fn node_index_by_id(&self, node_id: u64) -> Option<usize> {
+        let node_id = i64::try_from(node_id).ok()?;
+        Some(self.snapshot.node_by_id(node_id)?.node_index())
+    }
+
+    pub fn summary(&self, options: SummaryOptions) -> Result<SummaryResult, SnapshotError> {
+        summary::summarize(&self.snapshot, options)
+    }
+
+    pub fn detail(&self, options: DetailOptions) -> Result<DetailResult, SnapshotError> {
+        detail::detail(&self.snapshot, options)
+    }
+
+    /// Retaining paths for `target`. The snapshot's inbound edge index is built on the
+    /// first call and reused by later ones.
+    pub fn retainers(
+        &self,
+        target: usize,
+        options: RetainersOptions,
+    ) -> Result<RetainersResult, SnapshotError> {
+        let mut guard = lock(&self.retainer_search);
+        if guard.is_none() {
+            *guard = Some(RetainerSearch::new(&self.snapshot)?.with_snapshot_index());
+        }
+        let search = guard.as_mut().expect("retainer search initialised above");
+        search.find(&self.snapshot, target, &options)
+    }
+
+    /// Dominator chain for `target`. The dominator tree is reused while
+    /// `options.ignore_edge_types` stays the same and rebuilt when it changes.
+    pub fn dominator(
+        &self,
+        target: usize,
+        options: DominatorOptions,
+    ) -> Result<DominatorResult, SnapshotError> {
+        let cancel = options.cancel.clone();
+        let max_depth = options.max_depth;
+        let (index, retained) = self.dominator_index(options)?;
+        dominator::dominator_chain_with_retained(
+            &self.snapshot,
+            &index,
+            &retained,
+            target,
+            max_depth,
+            cancel,
+        )
+    }
+
+    /// Retained size of every node, computed with the given ignored edge types.
+    pub fn retained_sizes(
+        &self,
+        ignore_edge_types: &[String],
+        cancel: CancelToken,
+    ) -> Result<Arc<Vec<i64>>, SnapshotError> {
+        let options = DominatorOptions {
+            max_depth: 0,
+            cancel,
+            progress: None,
+            ignore_edge_types: ignore_edge_types.to_vec(),
+        };
+        Ok(self.dominator_index(options)?.1)
+    }
+
+    fn dominator_index(
+        &self,
+        options: DominatorOptions,
+    ) -> Result<(Arc<DominatorIndex>, Arc<Vec<i64>>), SnapshotError> {
+        let mut ignore_edge_types = options.ignore_edge_types;
+        ignore_edge_types.sort();
+        ignore_edge_types.dedup();
+
+        let mut guard = lock(&self.dominator);
+        if let Some(cached) = guard
+            .as_ref()
+            .filter(|cached| cached.ignore_edge_types == ignore_edge_types)
+        {
+            return Ok((
+                Arc::clone(&cached.index),
+                Arc::clone(&cached.retained_sizes),
+            ));
+        }
+
+        let index = dominator::compute_dominator_index(
+            &self.snapshot,
+            &ignore_edge_types,
+            options.cancel,
+            options.progress,
+        )?;
+        let retained_sizes = Arc::new(dominator::retained_sizes(&self.snapshot, &index));
+        let index = Arc::new(index);
+        *guard = Some(CachedDominator {
+            ignore_edge_types,
+            index: Arc::clone(&index),
+            retained_sizes: Arc::clone(&retained_sizes),
+        });
+        Ok((index, retained_sizes))
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
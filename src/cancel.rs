@@ -1,5 +1,7 @@
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, OnceLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
 
 use crate::error::SnapshotError;
 
@@ -18,6 +20,29 @@ impl CancelToken {
     pub fn cancel(&self) {
         self.0.store(true, Ordering::SeqCst);
     }
+
+    /// Sleeps for `duration`, waking early once cancelled. Returns whether it was cancelled.
+    pub fn sleep(&self, duration: Duration) -> bool {
+        const SLICE: Duration = Duration::from_millis(100);
+        let deadline = std::time::Instant::now() + duration;
+        while !self.is_cancelled() {
+            let now = std::time::Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            std::thread::sleep(SLICE.min(deadline - now));
+        }
+        true
+    }
+}
+
+/// Cancels `token` once `timeout` has elapsed, so long analyses stop the same way as Ctrl-C.
+/// The watcher thread is detached in normal use; the handle is only joined by tests.
+pub fn spawn_timeout(token: CancelToken, timeout: Duration) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        std::thread::sleep(timeout);
+        token.cancel();
+    })
 }
 
 pub fn install_ctrlc_handler() -> Result<CancelToken, SnapshotError> {
@@ -39,3 +64,20 @@ pub fn install_ctrlc_handler() -> Result<CancelToken, SnapshotError> {
     let _ = TOKEN.set(flag.clone());
     Ok(CancelToken(flag))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sleep_returns_early_when_cancelled() {
+        let token = CancelToken::new();
+        assert!(!token.sleep(Duration::from_millis(10)));
+
+        let handle = spawn_timeout(token.clone(), Duration::from_millis(20));
+        let started = std::time::Instant::now();
+        assert!(token.sleep(Duration::from_secs(30)));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        handle.join().expect("timeout thread");
+    }
+}
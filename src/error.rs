@@ -3,19 +3,78 @@ use std::fmt;
 #[derive(Debug)]
 pub enum SnapshotError {
     Io(std::io::Error),
-    Json(serde_json::Error),
-    MetaMismatch { details: String },
-    InvalidData { details: String },
+    Json {
+        error: serde_json::Error,
+        /// Approximate byte offset of the error in the original snapshot file, when it
+        /// was read through `LenientJsonReader`.
+        offset: Option<u64>,
+    },
+    MetaMismatch {
+        details: String,
+    },
+    InvalidData {
+        details: String,
+    },
+    /// A requested node (by id or name) does not exist in the snapshot.
+    NotFound {
+        details: String,
+    },
     Cancelled,
 }
 
+impl SnapshotError {
+    /// Stable snake_case identifier used in machine-readable error bodies.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SnapshotError::Io(_) => "io",
+            SnapshotError::Json { .. } => "json",
+            SnapshotError::MetaMismatch { .. } => "meta_mismatch",
+            SnapshotError::InvalidData { .. } => "invalid_data",
+            SnapshotError::NotFound { .. } => "not_found",
+            SnapshotError::Cancelled => "cancelled",
+        }
+    }
+
+    /// HTTP status for `serve`: caller mistakes are 4xx, problems reading the snapshot are 5xx.
+    pub fn http_status(&self) -> u16 {
+        match self {
+            SnapshotError::InvalidData { .. } => 400,
+            SnapshotError::NotFound { .. } => 404,
+            SnapshotError::Cancelled => 503,
+            SnapshotError::Io(_)
+            | SnapshotError::Json { .. }
+            | SnapshotError::MetaMismatch { .. } => 500,
+        }
+    }
+
+    /// Process exit code for the CLI. 130 mirrors the shell convention for SIGINT,
+    /// shared by Ctrl-C and `--timeout`.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            SnapshotError::Cancelled => 130,
+            _ => 1,
+        }
+    }
+}
+
 impl fmt::Display for SnapshotError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             SnapshotError::Io(err) => write!(f, "I/O error: {err}"),
-            SnapshotError::Json(err) => write!(f, "JSON parse error: {err}"),
+            SnapshotError::Json {
+                error,
+                offset: None,
+            } => write!(f, "JSON parse error: {error}"),
+            SnapshotError::Json {
+                error,
+                offset: Some(offset),
+            } => write!(
+                f,
+                "JSON parse error: {error} (near byte {offset} of the file)"
+            ),
             SnapshotError::MetaMismatch { details } => write!(f, "meta mismatch: {details}"),
             SnapshotError::InvalidData { details } => write!(f, "invalid data: {details}"),
+            SnapshotError::NotFound { details } => write!(f, "not found: {details}"),
             SnapshotError::Cancelled => write!(f, "cancelled by user"),
         }
     }
@@ -31,6 +90,33 @@ impl From<std::io::Error> for SnapshotError {
 
 impl From<serde_json::Error> for SnapshotError {
     fn from(value: serde_json::Error) -> Self {
-        SnapshotError::Json(value)
+        SnapshotError::Json {
+            error: value,
+            offset: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http_status_and_kind_follow_variant() {
+        let invalid = SnapshotError::InvalidData {
+            details: "x".to_string(),
+        };
+        let missing = SnapshotError::NotFound {
+            details: "x".to_string(),
+        };
+        let io = SnapshotError::Io(std::io::Error::other("x"));
+        assert_eq!(
+            (invalid.http_status(), invalid.kind()),
+            (400, "invalid_data")
+        );
+        assert_eq!((missing.http_status(), missing.kind()), (404, "not_found"));
+        assert_eq!((io.http_status(), io.kind()), (500, "io"));
+        assert_eq!(SnapshotError::Cancelled.exit_code(), 130);
+        assert_eq!(invalid.exit_code(), 1);
     }
 }
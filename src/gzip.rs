@@ -0,0 +1,326 @@
+//! Minimal gzip encoder (RFC 1952) for `serve` responses.
+//!
+//! Uses a single fixed-Huffman DEFLATE block (RFC 1951 §3.2.6) with hash-chain LZ77.
+//! The ratio is below zlib's, but the generated HTML tables are highly repetitive and
+//! this avoids adding a compression dependency.
+
+const WINDOW_SIZE: usize = 32 * 1024;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 32;
+const HASH_BITS: u32 = 15;
+const WINDOW_MASK: usize = WINDOW_SIZE - 1;
+const NO_POS: usize = usize::MAX;
+
+pub(crate) const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+pub(crate) const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+pub(crate) const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+pub(crate) const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Wraps `data` in a gzip member (no file name, mtime 0, OS = unknown).
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x1f, 0x8b, 0x08, 0x00, 0, 0, 0, 0, 0x00, 0xff];
+    let mut bits = BitWriter::new(out.len() + data.len() / 2);
+    bits.out.append(&mut out);
+    deflate_fixed(data, &mut bits);
+    let mut out = bits.finish();
+    out.extend_from_slice(&crc32(data).to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    crc32_update(0, data)
+}
+
+/// Continues `crc` (the CRC-32 of the bytes before `data`, 0 for none) over `data`.
+pub fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    let mut crc = !crc;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn deflate_fixed(data: &[u8], bits: &mut BitWriter) {
+    // BFINAL = 1, BTYPE = 01 (fixed Huffman)
+    bits.write(1, 1);
+    bits.write(1, 2);
+
+    let mut head = vec![NO_POS; 1 << HASH_BITS];
+    // Chain links only for the last window; older slots are overwritten, and
+    // `longest_match` stops before following a link that far back.
+    let mut prev = vec![NO_POS; WINDOW_SIZE];
+    let mut pos = 0usize;
+    while pos < data.len() {
+        let (length, distance) = longest_match(data, pos, &head, &prev);
+        let step = if length >= MIN_MATCH {
+            write_match(bits, length, distance);
+            length
+        } else {
+            write_literal_or_length(bits, u16::from(data[pos]));
+            1
+        };
+        for index in pos..(pos + step) {
+            if index + MIN_MATCH <= data.len() {
+                let hash = hash3(&data[index..]);
+                prev[index & WINDOW_MASK] = head[hash];
+                head[hash] = index;
+            }
+        }
+        pos += step;
+    }
+    write_literal_or_length(bits, 256);
+}
+
+fn longest_match(data: &[u8], pos: usize, head: &[usize], prev: &[usize]) -> (usize, usize) {
+    if pos + MIN_MATCH > data.len() {
+        return (0, 0);
+    }
+    let max_len = MAX_MATCH.min(data.len() - pos);
+    let mut best = (0usize, 0usize);
+    let mut candidate = head[hash3(&data[pos..])];
+    let mut chain = 0;
+    while candidate != NO_POS && pos - candidate <= WINDOW_SIZE && chain < MAX_CHAIN {
+        let len = data[candidate..]
+            .iter()
+            .zip(&data[pos..pos + max_len])
+            .take_while(|(a, b)| a == b)
+            .count();
+        if len > best.0 {
+            best = (len, pos - candidate);
+            if len == max_len {
+                break;
+            }
+        }
+        candidate = prev[candidate & WINDOW_MASK];
+        chain += 1;
+    }
+    best
+}
+
+fn hash3(bytes: &[u8]) -> usize {
+    let value = (u32::from(bytes[0]) << 16) | (u32::from(bytes[1]) << 8) | u32::from(bytes[2]);
+    (value.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize
+}
+
+fn write_match(bits: &mut BitWriter, length: usize, distance: usize) {
+    let code = LENGTH_BASE
+        .iter()
+        .rposition(|base| usize::from(*base) <= length)
+        .unwrap_or(0);
+    write_literal_or_length(bits, 257 + code as u16);
+    bits.write(
+        (length - usize::from(LENGTH_BASE[code])) as u32,
+        LENGTH_EXTRA[code],
+    );
+
+    let code = DIST_BASE
+        .iter()
+        .rposition(|base| usize::from(*base) <= distance)
+        .unwrap_or(0);
+    bits.write_huffman(code as u32, 5);
+    bits.write(
+        (distance - usize::from(DIST_BASE[code])) as u32,
+        DIST_EXTRA[code],
+    );
+}
+
+fn write_literal_or_length(bits: &mut BitWriter, symbol: u16) {
+    let symbol = u32::from(symbol);
+    match symbol {
+        0..=143 => bits.write_huffman(0x30 + symbol, 8),
+        144..=255 => bits.write_huffman(0x190 + symbol - 144, 9),
+        256..=279 => bits.write_huffman(symbol - 256, 7),
+        _ => bits.write_huffman(0xc0 + symbol - 280, 8),
+    }
+}
+
+struct BitWriter {
+    out: Vec<u8>,
+    buffer: u64,
+    count: u8,
+}
+
+impl BitWriter {
+    fn new(capacity: usize) -> Self {
+        Self {
+            out: Vec::with_capacity(capacity),
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    /// Writes `len` bits of `value`, least significant bit first.
+    fn write(&mut self, value: u32, len: u8) {
+        self.buffer |= u64::from(value) << self.count;
+        self.count += len;
+        while self.count >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Huffman codes are packed starting from their most significant bit.
+    fn write_huffman(&mut self, code: u32, len: u8) {
+        let reversed = code.reverse_bits() >> (32 - u32::from(len));
+        self.write(reversed, len);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.buffer as u8);
+        }
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Inflates a single fixed-Huffman block; enough to check what `compress` emits.
+    fn inflate_fixed(data: &[u8]) -> Vec<u8> {
+        let mut reader = BitReader { data, pos: 0 };
+        assert_eq!(reader.bits(1), 1);
+        assert_eq!(reader.bits(2), 1);
+        let mut out: Vec<u8> = Vec::new();
+        loop {
+            let symbol = read_fixed_symbol(&mut reader);
+            match symbol {
+                0..=255 => out.push(symbol as u8),
+                256 => break,
+                _ => {
+                    let code = (symbol - 257) as usize;
+                    let length =
+                        usize::from(LENGTH_BASE[code]) + reader.bits(LENGTH_EXTRA[code]) as usize;
+                    let code = reader.huffman(5) as usize;
+                    let distance =
+                        usize::from(DIST_BASE[code]) + reader.bits(DIST_EXTRA[code]) as usize;
+                    for _ in 0..length {
+                        out.push(out[out.len() - distance]);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    fn read_fixed_symbol(reader: &mut BitReader<'_>) -> u32 {
+        let code = reader.huffman(7);
+        if code <= 0x17 {
+            return code + 256;
+        }
+        let code = (code << 1) | reader.bits(1);
+        if (0x30..=0xbf).contains(&code) {
+            return code - 0x30;
+        }
+        if (0xc0..=0xc7).contains(&code) {
+            return code - 0xc0 + 280;
+        }
+        let code = (code << 1) | reader.bits(1);
+        code - 0x190 + 144
+    }
+
+    struct BitReader<'a> {
+        data: &'a [u8],
+        pos: usize,
+    }
+
+    impl BitReader<'_> {
+        fn bits(&mut self, len: u8) -> u32 {
+            let mut value = 0;
+            for index in 0..len {
+                let bit = (self.data[self.pos / 8] >> (self.pos % 8)) & 1;
+                value |= u32::from(bit) << index;
+                self.pos += 1;
+            }
+            value
+        }
+
+        fn huffman(&mut self, len: u8) -> u32 {
+            let mut value = 0;
+            for _ in 0..len {
+                value = (value << 1) | self.bits(1);
+            }
+            value
+        }
+    }
+
+    #[test]
+    fn crc32_matches_reference_value() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32_update(crc32(b"1234"), b"56789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn compress_roundtrips_and_shrinks_repetitive_html() {
+        let mut html = String::new();
+        for index in 0..500 {
+            html.push_str(&format!(
+                "<tr><td><a href=\"/detail?name=Foo{index}\">Foo{index}</a></td><td>{index}</td></tr>\n"
+            ));
+        }
+        let gz = compress(html.as_bytes());
+        assert_eq!(&gz[..3], &[0x1f, 0x8b, 0x08]);
+        assert!(gz.len() * 3 < html.len());
+
+        let (body, trailer) = gz[10..].split_at(gz.len() - 18);
+        assert_eq!(inflate_fixed(body), html.as_bytes());
+        assert_eq!(trailer[..4], crc32(html.as_bytes()).to_le_bytes());
+        assert_eq!(trailer[4..], (html.len() as u32).to_le_bytes());
+    }
+
+    #[test]
+    fn compress_roundtrips_bodies_larger_than_the_window() {
+        // A pseudo-random block repeated exactly one window later, then text long
+        // enough that the chain ring wraps several times.
+        let mut state = 0x2545_f491_u32;
+        let mut body: Vec<u8> = (0..WINDOW_SIZE)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as u8
+            })
+            .collect();
+        body.extend_from_within(..WINDOW_SIZE);
+        for index in 0..5000 {
+            body.extend_from_slice(format!("<td>Foo{}</td>\n", index % 97).as_bytes());
+        }
+        assert!(body.len() > 3 * WINDOW_SIZE);
+
+        let gz = compress(&body);
+        let mut out = Vec::new();
+        std::io::Read::read_to_end(
+            &mut crate::inflate::Inflate::new(&gz[10..gz.len() - 8]),
+            &mut out,
+        )
+        .expect("inflate");
+        assert_eq!(out, body);
+        assert!(gz.len() < body.len());
+    }
+
+    #[test]
+    fn compress_handles_empty_and_tiny_input() {
+        for input in [&b""[..], b"a", b"ab", b"abcabcabc"] {
+            let gz = compress(input);
+            let body = &gz[10..gz.len() - 8];
+            assert_eq!(inflate_fixed(body), input);
+        }
+    }
+}
@@ -0,0 +1,379 @@
+//! Streaming DEFLATE decoder (RFC 1951) for snapshots stored in `.zip` archives.
+//!
+//! Handles stored, fixed-Huffman and dynamic-Huffman blocks with a 32 KiB window, so the
+//! decompressed snapshot is fed to the parser without ever being held in memory whole.
+//! Like `gzip.rs`, it exists so that reading archives does not add a compression
+//! dependency (see ADR-016).
+
+use std::io::{self, Read};
+
+use crate::gzip::{DIST_BASE, DIST_EXTRA, LENGTH_BASE, LENGTH_EXTRA};
+
+const WINDOW_SIZE: usize = 32 * 1024;
+const WINDOW_MASK: usize = WINDOW_SIZE - 1;
+const MAX_CODE_LEN: u32 = 15;
+const INPUT_BUFFER: usize = 64 * 1024;
+
+/// Order in which the code length code lengths of a dynamic block are stored.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Decompresses a raw DEFLATE stream (no zlib or gzip framing) read from `inner`.
+pub struct Inflate<R> {
+    input: BitReader<R>,
+    window: Box<[u8]>,
+    /// Bytes produced so far; also the write position in `window` (masked).
+    produced: u64,
+    state: State,
+    final_block: bool,
+    literals: Huffman,
+    distances: Huffman,
+    /// Remaining bytes of a back-reference that did not fit the caller's buffer.
+    copy_len: usize,
+    copy_distance: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    BlockHeader,
+    Stored { remaining: usize },
+    Compressed,
+    Done,
+}
+
+impl<R: Read> Inflate<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            input: BitReader::new(inner),
+            window: vec![0; WINDOW_SIZE].into_boxed_slice(),
+            produced: 0,
+            state: State::BlockHeader,
+            final_block: false,
+            literals: Huffman::default(),
+            distances: Huffman::default(),
+            copy_len: 0,
+            copy_distance: 0,
+        }
+    }
+
+    fn push(&mut self, out: &mut [u8], written: &mut usize, byte: u8) {
+        out[*written] = byte;
+        *written += 1;
+        self.window[self.produced as usize & WINDOW_MASK] = byte;
+        self.produced += 1;
+    }
+
+    fn read_block_header(&mut self) -> io::Result<()> {
+        if self.final_block {
+            self.state = State::Done;
+            return Ok(());
+        }
+        self.final_block = self.input.bits(1)? == 1;
+        match self.input.bits(2)? {
+            0 => {
+                self.input.align_to_byte();
+                let len = self.input.bits(16)?;
+                let nlen = self.input.bits(16)?;
+                if len != !nlen & 0xffff {
+                    return Err(invalid("stored block length does not match its complement"));
+                }
+                self.state = State::Stored {
+                    remaining: len as usize,
+                };
+            }
+            1 => {
+                let mut lengths = [0u8; 288 + 32];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..288].fill(8);
+                lengths[288..].fill(5);
+                self.literals = Huffman::new(&lengths[..288])?;
+                self.distances = Huffman::new(&lengths[288..])?;
+                self.state = State::Compressed;
+            }
+            2 => {
+                self.read_dynamic_tables()?;
+                self.state = State::Compressed;
+            }
+            _ => return Err(invalid("reserved block type 3")),
+        }
+        Ok(())
+    }
+
+    fn read_dynamic_tables(&mut self) -> io::Result<()> {
+        let literal_count = self.input.bits(5)? as usize + 257;
+        let distance_count = self.input.bits(5)? as usize + 1;
+        let code_length_count = self.input.bits(4)? as usize + 4;
+
+        let mut code_lengths = [0u8; 19];
+        for &symbol in &CODE_LENGTH_ORDER[..code_length_count] {
+            code_lengths[symbol] = self.input.bits(3)? as u8;
+        }
+        let code_length_codes = Huffman::new(&code_lengths)?;
+
+        let total = literal_count + distance_count;
+        let mut lengths = vec![0u8; total];
+        let mut index = 0;
+        while index < total {
+            let symbol = code_length_codes.decode(&mut self.input)?;
+            let (value, repeat) = match symbol {
+                0..=15 => (symbol as u8, 1),
+                16 => {
+                    let previous = index
+                        .checked_sub(1)
+                        .map(|prev| lengths[prev])
+                        .ok_or_else(|| invalid("repeat code with no previous length"))?;
+                    (previous, 3 + self.input.bits(2)? as usize)
+                }
+                17 => (0, 3 + self.input.bits(3)? as usize),
+                _ => (0, 11 + self.input.bits(7)? as usize),
+            };
+            if index + repeat > total {
+                return Err(invalid("code lengths overflow the table"));
+            }
+            lengths[index..index + repeat].fill(value);
+            index += repeat;
+        }
+        if lengths[256] == 0 {
+            return Err(invalid("block has no end-of-block code"));
+        }
+        self.literals = Huffman::new(&lengths[..literal_count])?;
+        self.distances = Huffman::new(&lengths[literal_count..])?;
+        Ok(())
+    }
+
+    /// Decodes one literal/length symbol; a back-reference is left in `copy_len`.
+    fn read_symbol(&mut self, out: &mut [u8], written: &mut usize) -> io::Result<()> {
+        let symbol = self.literals.decode(&mut self.input)? as usize;
+        match symbol {
+            0..=255 => self.push(out, written, symbol as u8),
+            256 => self.state = State::BlockHeader,
+            257..=285 => {
+                let code = symbol - 257;
+                let length = usize::from(LENGTH_BASE[code])
+                    + self.input.bits(u32::from(LENGTH_EXTRA[code]))? as usize;
+                let code = self.distances.decode(&mut self.input)? as usize;
+                if code >= DIST_BASE.len() {
+                    return Err(invalid("invalid distance code"));
+                }
+                let distance = usize::from(DIST_BASE[code])
+                    + self.input.bits(u32::from(DIST_EXTRA[code]))? as usize;
+                if distance as u64 > self.produced {
+                    return Err(invalid("distance reaches before the start of the stream"));
+                }
+                self.copy_len = length;
+                self.copy_distance = distance;
+            }
+            _ => return Err(invalid("invalid literal/length code")),
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for Inflate<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let mut written = 0;
+        while written < out.len() {
+            if self.copy_len > 0 {
+                let from = (self.produced as usize).wrapping_sub(self.copy_distance) & WINDOW_MASK;
+                let byte = self.window[from];
+                self.push(out, &mut written, byte);
+                self.copy_len -= 1;
+                continue;
+            }
+            match self.state {
+                State::Done => break,
+                State::BlockHeader => self.read_block_header()?,
+                State::Stored { remaining: 0 } => self.state = State::BlockHeader,
+                State::Stored { remaining } => {
+                    let byte = self.input.bits(8)? as u8;
+                    self.push(out, &mut written, byte);
+                    self.state = State::Stored {
+                        remaining: remaining - 1,
+                    };
+                }
+                State::Compressed => self.read_symbol(out, &mut written)?,
+            }
+        }
+        Ok(written)
+    }
+}
+
+/// Canonical Huffman code decoded with a single table indexed by the next `max_len`
+/// input bits (bit-reversed, as DEFLATE packs codes most significant bit first).
+#[derive(Default)]
+struct Huffman {
+    /// `(symbol, code length)`; a length of 0 marks bit patterns no code uses.
+    table: Vec<(u16, u8)>,
+    max_len: u32,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> io::Result<Self> {
+        let max_len = u32::from(lengths.iter().copied().max().unwrap_or(0));
+        if max_len > MAX_CODE_LEN {
+            return Err(invalid("code length above 15"));
+        }
+        let mut counts = [0u32; MAX_CODE_LEN as usize + 1];
+        for &len in lengths {
+            counts[usize::from(len)] += 1;
+        }
+        counts[0] = 0;
+        let mut next_code = [0u32; MAX_CODE_LEN as usize + 1];
+        let mut code = 0u32;
+        for len in 1..=MAX_CODE_LEN as usize {
+            code = (code + counts[len - 1]) << 1;
+            next_code[len] = code;
+        }
+
+        let mut table = vec![(0u16, 0u8); 1 << max_len];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len == 0 {
+                continue;
+            }
+            let len_bits = u32::from(len);
+            let code = next_code[usize::from(len)];
+            next_code[usize::from(len)] += 1;
+            if code >= 1 << len_bits {
+                return Err(invalid("over-subscribed Huffman code"));
+            }
+            let reversed = code.reverse_bits() >> (32 - len_bits);
+            let mut slot = reversed as usize;
+            while slot < table.len() {
+                table[slot] = (symbol as u16, len);
+                slot += 1 << len_bits;
+            }
+        }
+        Ok(Self { table, max_len })
+    }
+
+    fn decode<R: Read>(&self, input: &mut BitReader<R>) -> io::Result<u16> {
+        input.fill(self.max_len)?;
+        let peek = (input.buffer & ((1u64 << self.max_len) - 1)) as usize;
+        let (symbol, len) = self.table.get(peek).copied().unwrap_or((0, 0));
+        if len == 0 {
+            return Err(invalid("invalid Huffman code"));
+        }
+        if u32::from(len) > input.count {
+            return Err(truncated());
+        }
+        input.consume(u32::from(len));
+        Ok(symbol)
+    }
+}
+
+/// Least-significant-bit-first reader over a byte stream.
+struct BitReader<R> {
+    inner: R,
+    bytes: Box<[u8]>,
+    pos: usize,
+    end: usize,
+    buffer: u64,
+    count: u32,
+}
+
+impl<R: Read> BitReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            bytes: vec![0; INPUT_BUFFER].into_boxed_slice(),
+            pos: 0,
+            end: 0,
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    /// Tops the bit buffer up to at least `wanted` bits, or to whatever is left at the end
+    /// of the input.
+    fn fill(&mut self, wanted: u32) -> io::Result<()> {
+        while self.count < wanted {
+            if self.pos == self.end {
+                self.end = self.inner.read(&mut self.bytes)?;
+                self.pos = 0;
+                if self.end == 0 {
+                    return Ok(());
+                }
+            }
+            self.buffer |= u64::from(self.bytes[self.pos]) << self.count;
+            self.pos += 1;
+            self.count += 8;
+        }
+        Ok(())
+    }
+
+    fn consume(&mut self, len: u32) {
+        self.buffer >>= len;
+        self.count -= len;
+    }
+
+    fn bits(&mut self, len: u32) -> io::Result<u32> {
+        self.fill(len)?;
+        if self.count < len {
+            return Err(truncated());
+        }
+        let value = (self.buffer & ((1u64 << len) - 1)) as u32;
+        self.consume(len);
+        Ok(value)
+    }
+
+    fn align_to_byte(&mut self) {
+        self.consume(self.count % 8);
+    }
+}
+
+fn invalid(details: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("corrupt deflate stream: {details}"),
+    )
+}
+
+fn truncated() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "deflate stream ends before its final block",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inflate(data: &[u8]) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        Inflate::new(data).read_to_end(&mut out)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn inflates_fixed_blocks_written_by_gzip() {
+        let mut text = String::new();
+        for index in 0..2000 {
+            text.push_str(&format!(
+                "{{\"id\":{index},\"name\":\"Foo{}\"}},",
+                index % 7
+            ));
+        }
+        for input in [&b""[..], b"a", text.as_bytes()] {
+            let gz = crate::gzip::compress(input);
+            assert_eq!(inflate(&gz[10..gz.len() - 8]).expect("inflate"), input);
+        }
+    }
+
+    #[test]
+    fn inflates_stored_blocks_and_rejects_truncation() {
+        // Non-final stored "abc", then a final stored "de".
+        let data = [
+            0x00, 0x03, 0x00, 0xfc, 0xff, b'a', b'b', b'c', 0x01, 0x02, 0x00, 0xfd, 0xff, b'd',
+            b'e',
+        ];
+        assert_eq!(inflate(&data).expect("inflate"), b"abcde");
+        let err = inflate(&data[..data.len() - 1]).expect_err("truncated");
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let err = inflate(&[0x07]).expect_err("reserved block type");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
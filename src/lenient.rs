@@ -1,5 +1,15 @@
 use std::io::{self, Read};
 
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comment {
+    /// `// ...` up to the end of the line.
+    Line,
+    /// `/* ... */`
+    Block,
+}
+
 pub struct LenientJsonReader<'a, R: Read> {
     inner: &'a mut R,
     input: Vec<u8>,
@@ -7,7 +17,13 @@ pub struct LenientJsonReader<'a, R: Read> {
     output: Vec<u8>,
     in_string: bool,
     escape: bool,
+    comment: Option<Comment>,
+    /// A `,` held back until the next significant byte shows whether it trails a list.
+    pending_comma: bool,
     eof: bool,
+    bom_checked: bool,
+    /// Bytes of the original input consumed so far, BOM and comments included.
+    consumed: u64,
 }
 
 impl<'a, R: Read> LenientJsonReader<'a, R> {
@@ -19,10 +35,24 @@ impl<'a, R: Read> LenientJsonReader<'a, R> {
             output: Vec::with_capacity(8192),
             in_string: false,
             escape: false,
+            comment: None,
+            pending_comma: false,
             eof: false,
+            bom_checked: false,
+            consumed: 0,
         }
     }
 
+    /// Offset in the original input of the last byte handed out by `read`, for pointing
+    /// at a parse error: serde_json's line and column count the cleaned-up stream. Exact
+    /// unless comments or trailing commas were dropped within the last buffered chunk
+    /// (up to 8 KiB), which shifts it by at most their length.
+    pub fn last_read_offset(&self) -> Option<u64> {
+        self.consumed
+            .saturating_sub(self.output.len() as u64)
+            .checked_sub(1)
+    }
+
     fn ensure_available(&mut self, needed: usize) -> io::Result<bool> {
         while self.input.len().saturating_sub(self.input_pos) < needed && !self.eof {
             let mut buf = [0u8; 8192];
@@ -45,27 +75,108 @@ impl<'a, R: Read> LenientJsonReader<'a, R> {
 
     fn consume(&mut self, count: usize) {
         self.input_pos += count;
+        self.consumed += count as u64;
         if self.input_pos > 8192 && self.input_pos > self.input.len() / 2 {
             self.input.drain(0..self.input_pos);
             self.input_pos = 0;
         }
     }
 
+    // Files saved by some editors start with a UTF-8 BOM, which serde_json rejects.
+    fn skip_bom(&mut self) -> io::Result<()> {
+        self.bom_checked = true;
+        self.ensure_available(UTF8_BOM.len())?;
+        if self.input[self.input_pos..].starts_with(UTF8_BOM) {
+            self.consume(UTF8_BOM.len());
+        }
+        Ok(())
+    }
+
+    /// Handles one byte between JSON strings: strips comments and drops a `,` directly
+    /// followed (ignoring whitespace and comments) by `]` or `}`. Hand-edited and some
+    /// tool-generated snapshots carry both, and serde_json rejects them.
+    fn process_outside_string(&mut self, byte: u8) -> io::Result<()> {
+        if byte == b'/' {
+            let comment = match self.peek(2)?.map(|pair| pair[1]) {
+                Some(b'/') => Some(Comment::Line),
+                Some(b'*') => Some(Comment::Block),
+                _ => None,
+            };
+            if let Some(comment) = comment {
+                self.consume(2);
+                self.comment = Some(comment);
+                return Ok(());
+            }
+        }
+
+        self.consume(1);
+        if byte.is_ascii_whitespace() {
+            self.output.push(byte);
+            return Ok(());
+        }
+        if self.pending_comma {
+            self.pending_comma = false;
+            if byte != b']' && byte != b'}' {
+                self.output.push(b',');
+            }
+        }
+        if byte == b',' {
+            self.pending_comma = true;
+            return Ok(());
+        }
+        self.output.push(byte);
+        if byte == b'"' {
+            self.in_string = true;
+        }
+        Ok(())
+    }
+
+    fn skip_comment(&mut self, comment: Comment, byte: u8) -> io::Result<()> {
+        match comment {
+            Comment::Line => {
+                self.consume(1);
+                if byte == b'\n' {
+                    self.comment = None;
+                    self.output.push(byte);
+                }
+            }
+            Comment::Block => {
+                if byte == b'*' && self.peek(2)?.is_some_and(|pair| pair[1] == b'/') {
+                    self.consume(2);
+                    self.comment = None;
+                    // Keep the tokens on either side apart, as the comment did.
+                    self.output.push(b' ');
+                } else {
+                    self.consume(1);
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn process(&mut self) -> io::Result<()> {
         self.output.clear();
+        if !self.bom_checked {
+            self.skip_bom()?;
+        }
 
         while self.output.len() < 8192 {
             if !self.ensure_available(1)? {
+                if self.pending_comma {
+                    // Nothing follows; let serde_json report the stray comma.
+                    self.pending_comma = false;
+                    self.output.push(b',');
+                }
                 break;
             }
 
             let byte = self.input[self.input_pos];
+            if let Some(comment) = self.comment {
+                self.skip_comment(comment, byte)?;
+                continue;
+            }
             if !self.in_string {
-                self.consume(1);
-                self.output.push(byte);
-                if byte == b'"' {
-                    self.in_string = true;
-                }
+                self.process_outside_string(byte)?;
                 continue;
             }
 
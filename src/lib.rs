@@ -1,9 +1,16 @@
 pub mod analysis;
+pub mod analyzer;
 pub mod cancel;
 pub mod error;
+pub mod gzip;
+pub mod inflate;
 pub mod lenient;
+pub mod metrics;
 pub mod output;
 pub mod parser;
 pub mod progress;
 pub mod serve;
 pub mod snapshot;
+pub mod zip;
+
+pub use analyzer::Analyzer;
@@ -10,9 +10,40 @@ struct Cli {
     #[arg(long, default_value_t = false)]
     verbose: bool,
 
-    /// Progress output (default: true). Use --progress=false to disable.
-    #[arg(long, default_value_t = true)]
-    progress: bool,
+    /// Print nothing to stderr but errors: no progress, warnings, serve banner or timing
+    #[arg(long, default_value_t = false, conflicts_with_all = ["verbose", "progress"])]
+    quiet: bool,
+
+    /// Progress output: --progress=false disables it, --progress forces it even when
+    /// stderr is not a terminal (default: only on a terminal)
+    #[arg(
+        long,
+        value_name = "BOOL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    progress: Option<bool>,
+
+    /// Cancel the analysis after this many seconds (exits with the cancelled code)
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+
+    /// Print JSON output on a single line instead of indented
+    #[arg(long, default_value_t = false)]
+    compact: bool,
+
+    /// Write timing and size metrics as JSON to this file ("-" for stderr)
+    #[arg(long, value_name = "PATH")]
+    metrics: Option<PathBuf>,
+
+    /// Colour markdown output of summary/diff/detail (auto: only when stdout is a terminal)
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = ColorWhen::Auto)]
+    color: ColorWhen,
+
+    /// Use plain ASCII (`...`, `delta`, `->`) instead of Unicode glyphs in markdown output
+    #[arg(long, default_value_t = false)]
+    ascii: bool,
 
     #[command(subcommand)]
     command: Command,
@@ -27,6 +58,26 @@ enum Command {
     Dominator(DominatorArgs),
     Detail(DetailArgs),
     Serve(ServeArgs),
+    /// Print the field layout from snapshot.meta without loading nodes/edges
+    Peek(PeekArgs),
+    /// Print node / edge / string counts only, skipping every analysis pass
+    Info(InfoArgs),
+    /// Check structural integrity; exits non-zero when a check fails
+    Verify(VerifyArgs),
+    /// Find the biggest nodes and print retaining paths for each
+    Triage(TriageArgs),
+    /// Print the dominator subtree of a node (what it retains)
+    Tree(TreeArgs),
+    /// Bin the self size of every node into size buckets
+    Histogram(HistogramArgs),
+    /// List the outgoing (or incoming) edges of every node of a constructor
+    Edges(EdgesArgs),
+    /// Find the nodes whose named edge points at a given value (e.g. `url` = "https://...")
+    Find(FindArgs),
+    /// Report the size of the strings table, its largest entries and duplicated texts
+    Strings(StringsArgs),
+    /// Diff two saved `summary --format json` files instead of the snapshots themselves
+    DiffSummaries(DiffSummariesArgs),
 }
 
 #[derive(Args, Debug)]
@@ -49,6 +100,78 @@ struct SummaryArgs {
     /// Only include constructors containing this string
     #[arg(long = "search", alias = "contains")]
     search: Option<String>,
+
+    /// Skip first N constructors of the sorted ranking
+    #[arg(long, default_value_t = 0)]
+    skip: usize,
+
+    /// Show N constructors after --skip (overrides --top)
+    #[arg(long)]
+    limit: Option<usize>,
+
+    /// Exclude synthetic/hidden/code nodes and report their total separately
+    #[arg(long)]
+    no_synthetic: bool,
+
+    /// Only aggregate nodes of this V8 node type (e.g. string, closure)
+    #[arg(long = "node-type")]
+    node_type: Option<String>,
+
+    /// Drop constructors whose self size sum is below this many bytes (applied before --top)
+    #[arg(long = "min-size", value_name = "BYTES")]
+    min_size: Option<i64>,
+
+    /// Drop constructors with fewer nodes than this (applied before --top)
+    #[arg(long = "min-count")]
+    min_count: Option<u64>,
+
+    /// Only aggregate detached DOM nodes (needs the `detachedness` node field)
+    #[arg(long = "detached-only")]
+    detached_only: bool,
+
+    /// Split the empty-name row into one "(empty: <node type>)" row per node type
+    #[arg(long = "split-empty")]
+    split_empty: bool,
+
+    /// Add a column counting the nodes of each constructor that only weak edges reach
+    #[arg(long)]
+    reachability: bool,
+
+    /// Only aggregate nodes that no path from the GC roots reaches, weak edges included
+    #[arg(long)]
+    unreachable: bool,
+
+    /// Append edge counts per edge type (property / element / internal / weak ...) over the
+    /// whole snapshot
+    #[arg(long = "edge-type-summary")]
+    edge_type_summary: bool,
+
+    /// List the ids of the first K instances of each constructor (0 lists none)
+    #[arg(long, alias = "sample", value_name = "K", default_value_t = 0)]
+    samples: usize,
+
+    /// Threads used to aggregate constructors, at most the CPU count (1 keeps the single-threaded pass)
+    #[arg(long, default_value_t = 1)]
+    jobs: usize,
+
+    /// Aggregate nodes while parsing and skip the edges, for snapshots too large to load
+    #[arg(
+        long,
+        conflicts_with_all = ["reachability", "unreachable", "edge_type_summary", "budgets", "jobs"]
+    )]
+    streaming: bool,
+
+    /// Re-read the file and re-print the summary every N seconds until Ctrl-C
+    #[arg(long, value_name = "SECONDS")]
+    watch: Option<u64>,
+
+    /// Fail when a constructor exceeds a budget, e.g. "Foo<=10MB" or "Foo:retained<=1GB" (repeatable)
+    #[arg(long = "assert", value_name = "BUDGET")]
+    budgets: Vec<String>,
+
+    /// Field delimiter for --format csv (single character; `\t` or `tab` for TSV)
+    #[arg(long, default_value = ",")]
+    delimiter: String,
 }
 
 #[derive(Args, Debug)]
@@ -68,6 +191,14 @@ struct RetainersArgs {
     #[arg(long, value_enum, default_value_t = PickStrategy::Largest)]
     pick: PickStrategy,
 
+    /// Require --name to equal the constructor name instead of matching a substring
+    #[arg(long)]
+    exact: bool,
+
+    /// Name of the root node(s) paths start from (for snapshots that do not use "GC roots")
+    #[arg(long = "root-name", default_value = analysis::retainers::DEFAULT_ROOT_NAME)]
+    root_name: String,
+
     /// Max number of paths to output
     #[arg(long, default_value_t = 5)]
     paths: usize,
@@ -76,6 +207,27 @@ struct RetainersArgs {
     #[arg(long = "max-depth", default_value_t = 10)]
     max_depth: usize,
 
+    /// Return at most one path per GC root node
+    #[arg(long = "distinct-roots")]
+    distinct_roots: bool,
+
+    /// Order of the returned paths (ranks other than shortest pick from extra candidates)
+    #[arg(long, value_enum, default_value_t = PathRank::Shortest)]
+    rank: PathRank,
+
+    /// Collapse paths with the same node names and edge types into one, shown as "(×N similar)"
+    #[arg(long = "dedupe-paths-by-constructor")]
+    dedupe_paths_by_constructor: bool,
+
+    /// Show how many partial paths were still open at each depth, to judge whether a
+    /// larger --max-depth would reach the roots
+    #[arg(long = "depth-histogram", alias = "retainers-depth-histogram")]
+    depth_histogram: bool,
+
+    /// Print each path as its node ids only (`1 -> 5 -> 23`), for diffing two snapshots
+    #[arg(long = "ids-only", conflicts_with = "format")]
+    ids_only: bool,
+
     /// Output format
     #[arg(long, value_enum, default_value_t = OutputFormat::Md)]
     format: OutputFormat,
@@ -97,14 +249,73 @@ struct BuildArgs {
     /// Only include constructors containing this string
     #[arg(long)]
     contains: Option<String>,
+
+    /// Report files to write besides meta.json (json: summary.json and largest.json)
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = [BuildFormat::Json])]
+    formats: Vec<BuildFormat>,
 }
 
 #[derive(Args, Debug)]
 struct DiffArgs {
-    /// Before snapshot
+    /// Before snapshot, a glob such as `snaps/heap-*.heapsnapshot`, or a directory with --latest
+    before: PathBuf,
+
+    /// After snapshot(s), oldest first; three or more files in total switch to trend mode
+    /// (omit when `before` is a glob or --latest is used)
+    after: Vec<PathBuf>,
+
+    /// Compare the newest N .heapsnapshot files in the directory given as `before` (N > 2 is trend mode)
+    #[arg(long, value_name = "N")]
+    latest: Option<usize>,
+
+    /// The inputs are saved `summary --format json` files instead of snapshots
+    /// (--latest then picks .json files)
+    #[arg(long)]
+    summaries: bool,
+
+    /// Show top N constructors
+    #[arg(long, default_value_t = 50)]
+    top: usize,
+
+    /// Only include constructors containing this string
+    #[arg(long)]
+    contains: Option<String>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = DiffFormat::Md)]
+    format: DiffFormat,
+
+    /// Drop constructors whose absolute self size delta is below this many bytes (applied before --top)
+    #[arg(long = "min-size", value_name = "BYTES")]
+    min_size: Option<i64>,
+
+    /// Drop constructors whose absolute count delta is below this (applied before --top)
+    #[arg(long = "min-count")]
+    min_count: Option<u64>,
+
+    /// Row order (applied before --top)
+    #[arg(long, value_enum, default_value_t = DiffSort::SizeDelta)]
+    sort: DiffSort,
+
+    /// Only list constructors with nodes in the before snapshot and none in the after one
+    #[arg(long = "only-a", conflicts_with = "only_b")]
+    only_a: bool,
+
+    /// Only list constructors with nodes in the after snapshot and none in the before one
+    #[arg(long = "only-b")]
+    only_b: bool,
+
+    /// Field delimiter for --format csv (single character; `\t` or `tab` for TSV)
+    #[arg(long, default_value = ",")]
+    delimiter: String,
+}
+
+#[derive(Args, Debug)]
+struct DiffSummariesArgs {
+    /// Before summary (`heapsnap summary --format json` output)
     before: PathBuf,
 
-    /// After snapshot
+    /// After summary
     after: PathBuf,
 
     /// Show top N constructors
@@ -116,8 +327,32 @@ struct DiffArgs {
     contains: Option<String>,
 
     /// Output format
-    #[arg(long, value_enum, default_value_t = OutputFormat::Md)]
-    format: OutputFormat,
+    #[arg(long, value_enum, default_value_t = DiffFormat::Md)]
+    format: DiffFormat,
+
+    /// Drop constructors whose absolute self size delta is below this many bytes (applied before --top)
+    #[arg(long = "min-size", value_name = "BYTES")]
+    min_size: Option<i64>,
+
+    /// Drop constructors whose absolute count delta is below this (applied before --top)
+    #[arg(long = "min-count")]
+    min_count: Option<u64>,
+
+    /// Row order (applied before --top)
+    #[arg(long, value_enum, default_value_t = DiffSort::SizeDelta)]
+    sort: DiffSort,
+
+    /// Only list constructors with nodes in the before snapshot and none in the after one
+    #[arg(long = "only-a", conflicts_with = "only_b")]
+    only_a: bool,
+
+    /// Only list constructors with nodes in the after snapshot and none in the before one
+    #[arg(long = "only-b")]
+    only_b: bool,
+
+    /// Field delimiter for --format csv (single character; `\t` or `tab` for TSV)
+    #[arg(long, default_value = ",")]
+    delimiter: String,
 }
 
 #[derive(Args, Debug)]
@@ -137,13 +372,59 @@ struct DominatorArgs {
     #[arg(long, value_enum, default_value_t = PickStrategy::Largest)]
     pick: PickStrategy,
 
-    /// Max dominator depth
+    /// Require --name to equal the constructor name instead of matching a substring
+    #[arg(long)]
+    exact: bool,
+
+    /// Name of the root node(s) paths start from (for snapshots that do not use "GC roots")
+    #[arg(long = "root-name", default_value = analysis::retainers::DEFAULT_ROOT_NAME)]
+    root_name: String,
+
+    /// Max dominator depth (with --full-tree: levels below the roots, at most 500)
     #[arg(long = "max-depth", default_value_t = 50)]
     max_depth: usize,
 
+    /// Reuse/persist the dominator tree in this file (recomputed when the snapshot differs)
+    #[arg(long = "dom-cache")]
+    dom_cache: Option<PathBuf>,
+
+    /// Edge type to leave out of the dominator graph (repeatable; `none` keeps every edge)
+    #[arg(long = "ignore-edge", value_name = "TYPE", default_values_t = ["weak".to_string()])]
+    ignore_edge: Vec<String>,
+
+    /// Read at most N outgoing edges per node when building the dominator graph
+    /// (faster on giant arrays; retained sizes become approximate)
+    #[arg(
+        long = "max-edges-per-node",
+        value_name = "N",
+        conflicts_with = "dom_cache"
+    )]
+    max_edges_per_node: Option<usize>,
+
     /// Output format
     #[arg(long, value_enum, default_value_t = OutputFormat::Md)]
     format: OutputFormat,
+
+    /// Print the chain as its node ids only (`1 -> 5 -> 23`), for diffing two snapshots
+    #[arg(long = "ids-only", conflicts_with = "format")]
+    ids_only: bool,
+
+    /// Export the whole dominator tree as nested JSON instead of one target's chain
+    #[arg(long = "full-tree", conflicts_with_all = ["id", "name", "format", "ids_only"])]
+    full_tree: bool,
+
+    /// List the nodes the target immediately dominates (what it holds onto) instead of
+    /// its chain up to the roots
+    #[arg(long, conflicts_with_all = ["full_tree", "ids_only"])]
+    reverse: bool,
+
+    /// Max dominated nodes listed by --reverse (largest retained sizes first)
+    #[arg(long, default_value_t = 20, requires = "reverse")]
+    top: usize,
+
+    /// Max nodes written by --full-tree (largest retained sizes are kept)
+    #[arg(long = "max-nodes", default_value_t = 10_000, requires = "full_tree")]
+    max_nodes: usize,
 }
 
 #[derive(Args, Debug)]
@@ -151,14 +432,20 @@ struct DetailArgs {
     /// Path to .heapsnapshot
     file: PathBuf,
 
-    /// Target node id
-    #[arg(long)]
-    id: Option<u64>,
+    /// Target node id; repeat (`--id 3 --id 7`) or list (`--ids 3,7`) to report several
+    /// nodes from one parse
+    #[arg(long, visible_alias = "ids", value_delimiter = ',')]
+    id: Vec<u64>,
 
     /// Target constructor name
     #[arg(long)]
     name: Option<String>,
 
+    /// Target the constructor at this string-table index instead of by text, telling
+    /// apart names that appear several times in the table (see `summary --verbose`)
+    #[arg(long = "name-index", conflicts_with_all = ["id", "name"])]
+    name_index: Option<usize>,
+
     /// Output format
     #[arg(long, value_enum, default_value_t = OutputFormat::Md)]
     format: OutputFormat,
@@ -178,6 +465,242 @@ struct DetailArgs {
     /// Top N outgoing edges (id mode)
     #[arg(long = "top-edges", default_value_t = 10)]
     top_edges: usize,
+
+    /// Only show retainers/outgoing edges whose edge name contains this string (id mode)
+    #[arg(long = "edge-name")]
+    edge_name: Option<String>,
+
+    /// Dump the node's and its outgoing edges' raw field values (id mode)
+    #[arg(long)]
+    raw: bool,
+
+    /// Read at most N outgoing edges of the node (id mode), reporting "edges truncated"
+    #[arg(long = "max-edges-per-node", value_name = "N")]
+    max_edges_per_node: Option<usize>,
+
+    /// Field delimiter for --format csv (single character; `\t` or `tab` for TSV)
+    #[arg(long, default_value = ",")]
+    delimiter: String,
+}
+
+#[derive(Args, Debug)]
+struct EdgesArgs {
+    /// Path to .heapsnapshot
+    file: PathBuf,
+
+    /// Constructor name; every node with exactly this name is expanded
+    #[arg(long)]
+    name: String,
+
+    /// List retainers (edges pointing at the nodes) instead of outgoing edges
+    #[arg(long)]
+    incoming: bool,
+
+    /// Only list edges whose edge name contains this string
+    #[arg(long = "edge-name")]
+    edge_name: Option<String>,
+
+    /// Skip first N edges
+    #[arg(long, default_value_t = 0)]
+    skip: usize,
+
+    /// Max edges listed
+    #[arg(long, default_value_t = 200)]
+    limit: usize,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Md)]
+    format: OutputFormat,
+
+    /// Field delimiter for --format csv (single character; `\t` or `tab` for TSV)
+    #[arg(long, default_value = ",")]
+    delimiter: String,
+}
+
+#[derive(Args, Debug)]
+struct FindArgs {
+    /// Path to .heapsnapshot
+    file: PathBuf,
+
+    /// Exact property / internal edge name to look for
+    #[arg(long = "edge-name")]
+    edge_name: String,
+
+    /// Exact name of the edge target (the text of a string value)
+    #[arg(long)]
+    value: String,
+
+    /// Max matches listed
+    #[arg(long, default_value_t = 200)]
+    limit: usize,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Md)]
+    format: OutputFormat,
+
+    /// Field delimiter for --format csv (single character; `\t` or `tab` for TSV)
+    #[arg(long, default_value = ",")]
+    delimiter: String,
+}
+
+#[derive(Args, Debug)]
+struct StringsArgs {
+    /// Path to .heapsnapshot
+    file: PathBuf,
+
+    /// Rows listed for the largest strings and for the duplicated values
+    #[arg(long, default_value_t = 20)]
+    top: usize,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Md)]
+    format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+struct PeekArgs {
+    /// Path to .heapsnapshot
+    file: PathBuf,
+
+    /// Max bytes to read while looking for snapshot.meta
+    #[arg(long = "head-bytes", default_value_t = 1024 * 1024)]
+    head_bytes: u64,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Md)]
+    format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+struct InfoArgs {
+    /// Path to .heapsnapshot
+    file: PathBuf,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Md)]
+    format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+struct VerifyArgs {
+    /// Path to .heapsnapshot
+    file: PathBuf,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Md)]
+    format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+struct HistogramArgs {
+    /// Path to .heapsnapshot
+    file: PathBuf,
+
+    /// Only count nodes whose name contains this string
+    #[arg(long)]
+    name: Option<String>,
+
+    /// Only count nodes of this V8 node type (e.g. string, closure)
+    #[arg(long = "node-type")]
+    node_type: Option<String>,
+
+    /// Bucket lower bounds in bytes, ascending (e.g. 0,64,1024,65536)
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_values_t = analysis::histogram::DEFAULT_BUCKET_BOUNDS.to_vec()
+    )]
+    buckets: Vec<i64>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Md)]
+    format: OutputFormat,
+
+    /// Field delimiter for --format csv (single character; `\t` or `tab` for TSV)
+    #[arg(long, default_value = ",")]
+    delimiter: String,
+}
+
+#[derive(Args, Debug)]
+struct TriageArgs {
+    /// Path to .heapsnapshot
+    file: PathBuf,
+
+    /// Number of biggest nodes (by self size) to analyse
+    #[arg(long, default_value_t = 5)]
+    top: usize,
+
+    /// Max number of paths per node
+    #[arg(long, default_value_t = 3)]
+    paths: usize,
+
+    /// Max BFS depth
+    #[arg(long = "max-depth", default_value_t = 10)]
+    max_depth: usize,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Md)]
+    format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+struct TreeArgs {
+    /// Path to .heapsnapshot
+    file: PathBuf,
+
+    /// Target node id
+    #[arg(long)]
+    id: Option<u64>,
+
+    /// Target constructor name
+    #[arg(long)]
+    name: Option<String>,
+
+    /// Pick strategy when multiple targets match --name
+    #[arg(long, value_enum, default_value_t = PickStrategy::Largest)]
+    pick: PickStrategy,
+
+    /// Require --name to equal the constructor name instead of matching a substring
+    #[arg(long)]
+    exact: bool,
+
+    /// Name of the root node(s) paths start from (for snapshots that do not use "GC roots")
+    #[arg(long = "root-name", default_value = analysis::retainers::DEFAULT_ROOT_NAME)]
+    root_name: String,
+
+    /// Levels of dominated nodes to expand below the target
+    #[arg(long = "max-depth", default_value_t = 3)]
+    max_depth: usize,
+
+    /// Max children shown per node (largest retained size first)
+    #[arg(long, default_value_t = 10)]
+    children: usize,
+
+    /// Reuse/persist the dominator tree in this file (recomputed when the snapshot differs)
+    #[arg(long = "dom-cache")]
+    dom_cache: Option<PathBuf>,
+
+    /// Edge type to leave out of the dominator graph (repeatable; `none` keeps every edge)
+    #[arg(long = "ignore-edge", value_name = "TYPE", default_values_t = ["weak".to_string()])]
+    ignore_edge: Vec<String>,
+
+    /// Read at most N outgoing edges per node when building the dominator graph
+    /// (faster on giant arrays; retained sizes become approximate)
+    #[arg(
+        long = "max-edges-per-node",
+        value_name = "N",
+        conflicts_with = "dom_cache"
+    )]
+    max_edges_per_node: Option<usize>,
+
+    /// Print folded stacks (`root;child;grandchild bytes`) for flamegraph.pl / inferno;
+    /// without --id / --name the tree starts at the GC root
+    #[arg(long, conflicts_with = "format")]
+    folded: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Md)]
+    format: OutputFormat,
 }
 
 #[derive(Args, Debug)]
@@ -185,13 +708,25 @@ struct ServeArgs {
     /// Path to .heapsnapshot (default file for summary/detail/retainers/dominator)
     file: PathBuf,
 
-    /// Bind address (must be loopback)
+    /// Bind address (must be loopback: 127.0.0.1, ::1 or localhost)
     #[arg(long, default_value = "127.0.0.1")]
     bind: String,
 
     /// Port
     #[arg(long, default_value_t = 7878)]
     port: u16,
+
+    /// Reload the snapshot when the file's modification time changes
+    #[arg(long)]
+    follow: bool,
+
+    /// Stop adding table rows once a page reaches this many bytes
+    #[arg(long = "max-body-bytes", default_value_t = serve::DEFAULT_MAX_BODY_BYTES)]
+    max_body_bytes: usize,
+
+    /// Print `method path status duration_ms` to stderr for every handled request
+    #[arg(long)]
+    log: bool,
 }
 
 #[derive(Clone, Copy, Debug, ValueEnum)]
@@ -201,10 +736,83 @@ enum OutputFormat {
     Csv,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum BuildFormat {
+    /// summary.md
+    Md,
+    /// summary.csv
+    Csv,
+    /// summary.html
+    Html,
+    /// summary.json and largest.json
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum DiffFormat {
+    Md,
+    Json,
+    Csv,
+    /// One line per constructor marked `+` (grew), `-` (shrank) or `~` (unchanged)
+    Unified,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum DiffSort {
+    /// Largest absolute self size change
+    SizeDelta,
+    /// Largest absolute count change
+    CountDelta,
+    /// Largest self size in B
+    SizeB,
+    /// Largest B/A self size ratio; constructors new in B first
+    GrowthRatio,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ColorWhen {
+    Auto,
+    Always,
+    Never,
+}
+
+impl From<ColorWhen> for output::color::ColorChoice {
+    fn from(when: ColorWhen) -> Self {
+        match when {
+            ColorWhen::Auto => Self::Auto,
+            ColorWhen::Always => Self::Always,
+            ColorWhen::Never => Self::Never,
+        }
+    }
+}
+
+impl From<DiffSort> for analysis::diff::DiffSort {
+    fn from(sort: DiffSort) -> Self {
+        match sort {
+            DiffSort::SizeDelta => Self::SizeDelta,
+            DiffSort::CountDelta => Self::CountDelta,
+            DiffSort::SizeB => Self::SizeB,
+            DiffSort::GrowthRatio => Self::GrowthRatio,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, ValueEnum)]
 enum PickStrategy {
     Largest,
     Count,
+    /// Node with the greatest retained size (builds the dominator tree)
+    Retained,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum PathRank {
+    /// Shortest paths first (discovery order)
+    Shortest,
+    /// Paths leaving the root through the largest retained size first (builds the dominator tree)
+    LargestRetainer,
+    /// Paths through the fewest array index (element) edges first
+    FewestElementEdges,
 }
 
 // NOTE: External network access is prohibited. Localhost-only server is allowed.
@@ -218,86 +826,608 @@ fn main() {
         }
     };
 
+    let timeout = cli.timeout.map(std::time::Duration::from_secs);
+    if let Some(timeout) = timeout {
+        cancel::spawn_timeout(_cancel.clone(), timeout);
+    }
+    let started = std::time::Instant::now();
+
     if let Err(err) = run(cli, _cancel) {
-        eprintln!("error: {err}");
-        std::process::exit(1);
+        let (message, code) = failure(&err, timeout, started.elapsed());
+        eprintln!("error: {message}");
+        std::process::exit(code);
     }
 }
 
+/// Message and exit code for a failed run; a cancellation after `--timeout` elapsed is
+/// reported as a timeout.
+fn failure(
+    err: &error::SnapshotError,
+    timeout: Option<std::time::Duration>,
+    elapsed: std::time::Duration,
+) -> (String, i32) {
+    let message = match timeout {
+        Some(timeout) if matches!(err, error::SnapshotError::Cancelled) && elapsed >= timeout => {
+            format!("timed out after {}s", timeout.as_secs())
+        }
+        _ => err.to_string(),
+    };
+    (message, err.exit_code())
+}
+
 fn run(cli: Cli, cancel: cancel::CancelToken) -> Result<(), error::SnapshotError> {
+    let json = output::json::JsonStyle::from_compact(cli.compact);
+    let progress = heapsnap::progress::ProgressMode::from_flag(if cli.quiet {
+        Some(false)
+    } else {
+        cli.progress
+    });
+    let color = output::color::ColorChoice::from(cli.color).resolve();
+    let charset = output::charset::Charset::from_ascii_flag(cli.ascii);
+    let log = RunLog {
+        verbose: cli.verbose,
+        quiet: cli.quiet,
+        metrics: cli.metrics,
+    };
     match cli.command {
-        Command::Summary(args) => run_summary(cli.verbose, cli.progress, cancel, args),
-        Command::Retainers(args) => run_retainers(cli.verbose, cli.progress, cancel, args),
-        Command::Build(args) => run_build(cli.verbose, cli.progress, cancel, args),
-        Command::Diff(args) => run_diff(cli.verbose, cli.progress, cancel, args),
-        Command::Dominator(args) => run_dominator(cli.verbose, cli.progress, cancel, args),
-        Command::Detail(args) => run_detail(cli.verbose, cli.progress, cancel, args),
-        Command::Serve(args) => run_serve(cli.verbose, cli.progress, cancel, args),
+        Command::Summary(args) => run_summary(&log, progress, cancel, json, color, charset, args),
+        Command::Retainers(args) => run_retainers(&log, progress, cancel, json, charset, args),
+        Command::Build(args) => run_build(&log, progress, cancel, json, args),
+        Command::Diff(args) => run_diff(&log, progress, cancel, json, color, charset, args),
+        Command::Dominator(args) => run_dominator(&log, progress, cancel, json, args),
+        Command::Detail(args) => run_detail(&log, progress, cancel, json, color, charset, args),
+        Command::Serve(args) => run_serve(&log, progress, cancel, args),
+        Command::Peek(args) => run_peek(&log, json, args),
+        Command::Info(args) => run_info(&log, progress, cancel, json, args),
+        Command::Verify(args) => run_verify(&log, progress, cancel, json, args),
+        Command::Triage(args) => run_triage(&log, progress, cancel, json, charset, args),
+        Command::Tree(args) => run_tree(&log, progress, cancel, json, args),
+        Command::Histogram(args) => run_histogram(&log, progress, cancel, json, args),
+        Command::Edges(args) => run_edges(&log, progress, cancel, json, args),
+        Command::Find(args) => run_find(&log, progress, cancel, json, args),
+        Command::Strings(args) => run_strings(&log, progress, cancel, json, args),
+        Command::DiffSummaries(args) => run_diff_summaries(&log, json, color, charset, args),
     }
 }
 
-fn run_serve(
+/// `--verbose` and `--metrics`: how a command reports on its own run.
+#[derive(Debug)]
+struct RunLog {
     verbose: bool,
-    progress: bool,
+    /// `--quiet`: warnings are dropped along with everything `verbose` would add.
+    quiet: bool,
+    metrics: Option<PathBuf>,
+}
+
+impl RunLog {
+    /// Prints a `warning:` line to stderr unless `--quiet` was given.
+    fn warn(&self, message: impl std::fmt::Display) {
+        if !self.quiet {
+            eprintln!("warning: {message}");
+        }
+    }
+
+    /// `--verbose` lines describing the parsed snapshot.
+    fn loaded(&self, snapshot: &heapsnap::snapshot::SnapshotRaw) {
+        if !self.verbose {
+            return;
+        }
+        eprintln!(
+            "loaded snapshot: nodes={}, edges={}, strings={}",
+            snapshot.node_count(),
+            snapshot.edge_count(),
+            snapshot.strings.len()
+        );
+        eprintln!(
+            "approx memory: {}",
+            format_bytes(snapshot.memory_estimate_bytes())
+        );
+        let labels = &snapshot.labels;
+        if !labels.is_empty() {
+            eprintln!(
+                "snapshot labels: title={}, uid={}, captured={}",
+                labels.title.as_deref().unwrap_or("-"),
+                labels
+                    .uid
+                    .map(|uid| uid.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                labels.capture_time.as_deref().unwrap_or("-")
+            );
+        }
+    }
+
+    /// `--verbose` string-table index of each summary row, calling out names whose text
+    /// appears under several indices so they can be told apart with `detail --name-index`.
+    /// `loaded` for `summary --streaming`, which keeps no node or edge table.
+    fn streamed(&self, snapshot: &heapsnap::snapshot::StreamedSnapshot) {
+        if !self.verbose {
+            return;
+        }
+        eprintln!(
+            "streamed snapshot: nodes={}, edges={}, strings={} (nodes and edges not kept)",
+            snapshot.node_count,
+            snapshot.edge_count,
+            snapshot.strings.len()
+        );
+    }
+
+    fn name_indices(&self, rows: &[analysis::summary::SummaryRow]) {
+        if !self.verbose {
+            return;
+        }
+        let mut by_name: Vec<(&str, Vec<usize>)> = Vec::new();
+        for row in rows {
+            let Some(index) = row.name_index else {
+                continue;
+            };
+            match by_name.iter_mut().find(|(name, _)| *name == row.name) {
+                Some((_, indices)) => indices.push(index),
+                None => by_name.push((row.name.as_str(), vec![index])),
+            }
+        }
+        let listed: Vec<String> = by_name
+            .iter()
+            .flat_map(|(name, indices)| indices.iter().map(move |index| format!("{name}={index}")))
+            .collect();
+        if !listed.is_empty() {
+            eprintln!("name indices: {}", listed.join(", "));
+        }
+        for (name, indices) in by_name.iter().filter(|(_, indices)| indices.len() > 1) {
+            let indices: Vec<String> = indices.iter().map(usize::to_string).collect();
+            eprintln!(
+                "constructor {name:?} appears under string indices {} (select one with detail --name-index)",
+                indices.join(", ")
+            );
+        }
+    }
+
+    /// Prints the `--verbose` timing line and writes the `--metrics` report.
+    fn finish(
+        &self,
+        metrics: &heapsnap::metrics::Metrics,
+        json: output::json::JsonStyle,
+    ) -> Result<(), error::SnapshotError> {
+        if self.verbose {
+            eprintln!("{}", metrics.timing_line());
+        }
+        match self.metrics.as_deref() {
+            None => Ok(()),
+            Some(path) if path.as_os_str() == "-" => {
+                eprintln!("{}", metrics.to_json(json)?);
+                Ok(())
+            }
+            Some(path) => output::write::write_or_stdout(Some(path), &metrics.to_json(json)?),
+        }
+    }
+}
+
+fn run_triage(
+    log: &RunLog,
+    progress: heapsnap::progress::ProgressMode,
     cancel: cancel::CancelToken,
-    args: ServeArgs,
+    json: output::json::JsonStyle,
+    charset: output::charset::Charset,
+    args: TriageArgs,
+) -> Result<(), error::SnapshotError> {
+    let mut metrics = heapsnap::metrics::Metrics::new("triage");
+    let options = parser::ReadOptions::new(progress, cancel.clone());
+    let snapshot = parser::read_snapshot_file(&args.file, options)?;
+    metrics.lap("parse");
+    metrics.snapshot(&snapshot);
+
+    let result = analysis::triage::triage(
+        &snapshot,
+        analysis::triage::TriageOptions {
+            top: args.top,
+            max_paths: args.paths,
+            max_depth: args.max_depth,
+            cancel,
+        },
+    )?;
+    metrics.lap("triage");
+
+    let output = match args.format {
+        OutputFormat::Md => output::triage::format_markdown(&snapshot, &result, charset),
+        OutputFormat::Json => output::triage::format_json(&snapshot, &result, json)?,
+        OutputFormat::Csv => {
+            return Err(error::SnapshotError::InvalidData {
+                details: "triage output does not support csv".to_string(),
+            });
+        }
+    };
+    output::write::write_or_stdout(None, &output)?;
+
+    log.finish(&metrics, json)?;
+    Ok(())
+}
+
+fn run_histogram(
+    log: &RunLog,
+    progress: heapsnap::progress::ProgressMode,
+    cancel: cancel::CancelToken,
+    json: output::json::JsonStyle,
+    args: HistogramArgs,
+) -> Result<(), error::SnapshotError> {
+    let delimiter = output::csv::parse_delimiter(&args.delimiter)?;
+    let mut metrics = heapsnap::metrics::Metrics::new("histogram");
+    let options = parser::ReadOptions::new(progress, cancel);
+    let snapshot = parser::read_snapshot_file(&args.file, options)?;
+    metrics.lap("parse");
+    metrics.snapshot(&snapshot);
+
+    let result = analysis::histogram::histogram(
+        &snapshot,
+        analysis::histogram::HistogramOptions {
+            name: args.name,
+            node_type: args.node_type,
+            bounds: args.buckets,
+        },
+    )?;
+    metrics.lap("histogram");
+
+    let output = match args.format {
+        OutputFormat::Md => output::histogram::format_markdown(&result),
+        OutputFormat::Json => output::histogram::format_json(&result, json)?,
+        OutputFormat::Csv => output::histogram::format_csv(&result, delimiter),
+    };
+    output::write::write_or_stdout(None, &output)?;
+
+    log.finish(&metrics, json)?;
+    Ok(())
+}
+
+fn run_edges(
+    log: &RunLog,
+    progress: heapsnap::progress::ProgressMode,
+    cancel: cancel::CancelToken,
+    json: output::json::JsonStyle,
+    args: EdgesArgs,
+) -> Result<(), error::SnapshotError> {
+    let delimiter = output::csv::parse_delimiter(&args.delimiter)?;
+    let mut metrics = heapsnap::metrics::Metrics::new("edges");
+    let options = parser::ReadOptions::new(progress, cancel);
+    let snapshot = parser::read_snapshot_file(&args.file, options)?;
+    metrics.lap("parse");
+    metrics.snapshot(&snapshot);
+
+    let result = analysis::edges::edges(
+        &snapshot,
+        analysis::edges::EdgesOptions {
+            name: args.name,
+            incoming: args.incoming,
+            edge_name_filter: args.edge_name,
+            skip: args.skip,
+            limit: args.limit,
+        },
+    )?;
+    metrics.lap("edges");
+
+    let output = match args.format {
+        OutputFormat::Md => output::edges::format_markdown(&result),
+        OutputFormat::Json => output::edges::format_json(&result, json)?,
+        OutputFormat::Csv => output::edges::format_csv(&result, delimiter),
+    };
+    output::write::write_or_stdout(None, &output)?;
+
+    metrics.lap("output");
+    log.finish(&metrics, json)?;
+    Ok(())
+}
+
+fn run_find(
+    log: &RunLog,
+    progress: heapsnap::progress::ProgressMode,
+    cancel: cancel::CancelToken,
+    json: output::json::JsonStyle,
+    args: FindArgs,
 ) -> Result<(), error::SnapshotError> {
-    if args.bind != "127.0.0.1" && args.bind != "localhost" {
+    let delimiter = output::csv::parse_delimiter(&args.delimiter)?;
+    let mut metrics = heapsnap::metrics::Metrics::new("find");
+    let options = parser::ReadOptions::new(progress, cancel);
+    let snapshot = parser::read_snapshot_file(&args.file, options)?;
+    metrics.lap("parse");
+    metrics.snapshot(&snapshot);
+
+    let result = analysis::find::find(
+        &snapshot,
+        analysis::find::FindOptions {
+            edge_name: args.edge_name,
+            value: args.value,
+            limit: args.limit,
+        },
+    )?;
+    metrics.lap("find");
+
+    let output = match args.format {
+        OutputFormat::Md => output::find::format_markdown(&result),
+        OutputFormat::Json => output::find::format_json(&result, json)?,
+        OutputFormat::Csv => output::find::format_csv(&result, delimiter),
+    };
+    output::write::write_or_stdout(None, &output)?;
+
+    metrics.lap("output");
+    log.finish(&metrics, json)?;
+    Ok(())
+}
+
+fn run_verify(
+    log: &RunLog,
+    progress: heapsnap::progress::ProgressMode,
+    cancel: cancel::CancelToken,
+    json: output::json::JsonStyle,
+    args: VerifyArgs,
+) -> Result<(), error::SnapshotError> {
+    let mut metrics = heapsnap::metrics::Metrics::new("verify");
+    let options = parser::ReadOptions::new(progress, cancel);
+    let snapshot = parser::read_snapshot_file(&args.file, options)?;
+    metrics.lap("parse");
+    metrics.snapshot(&snapshot);
+
+    let report = analysis::verify::verify(&snapshot);
+    metrics.lap("verify");
+
+    let output = match args.format {
+        OutputFormat::Md => output::verify::format_markdown(&report),
+        OutputFormat::Json => output::verify::format_json(&report, json)?,
+        OutputFormat::Csv => {
+            return Err(error::SnapshotError::InvalidData {
+                details: "verify output does not support csv".to_string(),
+            });
+        }
+    };
+    output::write::write_or_stdout(None, &output)?;
+
+    log.finish(&metrics, json)?;
+
+    if !report.passed() {
         return Err(error::SnapshotError::InvalidData {
-            details: "serve only supports loopback bind (use --bind 127.0.0.1)".to_string(),
+            details: format!(
+                "verify failed: {} check(s) failed (see the report above)",
+                report.failed_count()
+            ),
         });
     }
+    Ok(())
+}
+
+fn run_info(
+    log: &RunLog,
+    progress: heapsnap::progress::ProgressMode,
+    cancel: cancel::CancelToken,
+    json: output::json::JsonStyle,
+    args: InfoArgs,
+) -> Result<(), error::SnapshotError> {
+    let mut metrics = heapsnap::metrics::Metrics::new("info");
+    // The parser still materializes the node / edge arrays; only the aggregation is skipped.
+    let options = parser::ReadOptions::new(progress, cancel);
+    let snapshot = parser::read_snapshot_file(&args.file, options)?;
+    metrics.lap("parse");
+    metrics.snapshot(&snapshot);
+
+    let output = match args.format {
+        OutputFormat::Md => output::info::format_markdown(&snapshot),
+        OutputFormat::Json => output::info::format_json(&snapshot, json)?,
+        OutputFormat::Csv => {
+            return Err(error::SnapshotError::InvalidData {
+                details: "info output does not support csv".to_string(),
+            });
+        }
+    };
+    output::write::write_or_stdout(None, &output)?;
+
+    metrics.lap("output");
+    log.finish(&metrics, json)?;
+    Ok(())
+}
+
+fn run_strings(
+    log: &RunLog,
+    progress: heapsnap::progress::ProgressMode,
+    cancel: cancel::CancelToken,
+    json: output::json::JsonStyle,
+    args: StringsArgs,
+) -> Result<(), error::SnapshotError> {
+    let mut metrics = heapsnap::metrics::Metrics::new("strings");
+    let options = parser::ReadOptions::new(progress, cancel);
+    let snapshot = parser::read_snapshot_file(&args.file, options)?;
+    metrics.lap("parse");
+    metrics.snapshot(&snapshot);
 
-    if verbose {
+    // String contents may be sensitive, so they are only printed under --verbose.
+    let result = analysis::strings::strings(
+        &snapshot,
+        analysis::strings::StringsOptions {
+            top: args.top,
+            previews: log.verbose,
+        },
+    );
+    metrics.lap("strings");
+
+    let output = match args.format {
+        OutputFormat::Md => output::strings::format_markdown(&result),
+        OutputFormat::Json => output::strings::format_json(&result, json)?,
+        OutputFormat::Csv => {
+            return Err(error::SnapshotError::InvalidData {
+                details: "strings output does not support csv".to_string(),
+            });
+        }
+    };
+    output::write::write_or_stdout(None, &output)?;
+
+    metrics.lap("output");
+    log.finish(&metrics, json)?;
+    Ok(())
+}
+
+fn run_peek(
+    log: &RunLog,
+    json: output::json::JsonStyle,
+    args: PeekArgs,
+) -> Result<(), error::SnapshotError> {
+    let mut metrics = heapsnap::metrics::Metrics::new("peek");
+    let meta = parser::read_snapshot_meta_file(&args.file, args.head_bytes)?;
+
+    let output = match args.format {
+        OutputFormat::Md => output::peek::format_markdown(&meta),
+        OutputFormat::Json => output::peek::format_json(&meta, json)?,
+        OutputFormat::Csv => {
+            return Err(error::SnapshotError::InvalidData {
+                details: "peek output does not support csv".to_string(),
+            });
+        }
+    };
+    output::write::write_or_stdout(None, &output)?;
+
+    metrics.lap("peek");
+    log.finish(&metrics, json)?;
+    Ok(())
+}
+
+fn run_serve(
+    log: &RunLog,
+    progress: heapsnap::progress::ProgressMode,
+    cancel: cancel::CancelToken,
+    args: ServeArgs,
+) -> Result<(), error::SnapshotError> {
+    let bind = serve::resolve_loopback_bind(&args.bind)?;
+
+    if log.verbose {
         eprintln!(
             "starting local server: file={}, bind={}, port={}",
             args.file.display(),
-            args.bind,
+            bind,
             args.port
         );
     }
 
     serve::run(serve::ServeOptions {
         file: args.file,
-        bind: "127.0.0.1".to_string(),
+        bind,
         port: args.port,
         progress,
         cancel,
+        follow: args.follow,
+        max_body_bytes: args.max_body_bytes,
+        log_requests: args.log,
+        quiet: log.quiet,
     })
 }
 
 fn run_summary(
-    verbose: bool,
-    progress: bool,
+    log: &RunLog,
+    progress: heapsnap::progress::ProgressMode,
     cancel: cancel::CancelToken,
+    json: output::json::JsonStyle,
+    color: output::color::Color,
+    charset: output::charset::Charset,
     args: SummaryArgs,
 ) -> Result<(), error::SnapshotError> {
-    let started = std::time::Instant::now();
-    let options = parser::ReadOptions::new(progress, cancel);
-    let snapshot = parser::read_snapshot_file(&args.file, options)?;
-    let parse_done = std::time::Instant::now();
+    let delimiter = output::csv::parse_delimiter(&args.delimiter)?;
+    let budgets = args
+        .budgets
+        .iter()
+        .map(|spec| analysis::budget::SizeBudget::parse(spec))
+        .collect::<Result<Vec<_>, _>>()?;
+    let style = SummaryStyle {
+        json,
+        color,
+        charset,
+        delimiter,
+    };
+    let Some(interval) = args.watch else {
+        return summary_once(log, progress, cancel, style, &args, &budgets);
+    };
+    if interval == 0 {
+        return Err(error::SnapshotError::InvalidData {
+            details: "--watch interval must be at least 1 second".to_string(),
+        });
+    }
 
-    if verbose {
-        eprintln!(
-            "loaded snapshot: nodes={}, edges={}, strings={}",
-            snapshot.node_count(),
-            snapshot.edge_count(),
-            snapshot.strings.len()
-        );
-        eprintln!(
-            "approx memory: {}",
-            format_bytes(snapshot.memory_estimate_bytes())
-        );
+    // Ctrl-C (or --timeout) ends the watch; a parse error is reported and retried on the
+    // next refresh, since the file may be caught mid-write.
+    loop {
+        if args.json.is_none() {
+            print!("\x1b[2J\x1b[H");
+        }
+        match summary_once(log, progress, cancel.clone(), style, &args, &budgets) {
+            Ok(()) => {}
+            Err(error::SnapshotError::Cancelled) => return Ok(()),
+            Err(err) => eprintln!("error: {err}"),
+        }
+        if cancel.sleep(std::time::Duration::from_secs(interval)) {
+            return Ok(());
+        }
     }
+}
 
-    let summary = analysis::summary::summarize(
-        &snapshot,
-        analysis::summary::SummaryOptions {
-            top: args.top,
-            contains: args.search,
-        },
-    )?;
-    let summary_done = std::time::Instant::now();
+/// Output settings resolved once in `run_summary` and reused on every `--watch` refresh.
+#[derive(Clone, Copy)]
+struct SummaryStyle {
+    json: output::json::JsonStyle,
+    color: output::color::Color,
+    charset: output::charset::Charset,
+    delimiter: char,
+}
+
+fn summary_once(
+    log: &RunLog,
+    progress: heapsnap::progress::ProgressMode,
+    cancel: cancel::CancelToken,
+    style: SummaryStyle,
+    args: &SummaryArgs,
+    budgets: &[analysis::budget::SizeBudget],
+) -> Result<(), error::SnapshotError> {
+    let SummaryStyle {
+        json,
+        color,
+        charset,
+        delimiter,
+    } = style;
+    let mut metrics = heapsnap::metrics::Metrics::new("summary");
+    let read_options = parser::ReadOptions::new(progress, cancel.clone());
+    let summary_options = analysis::summary::SummaryOptions {
+        top: args.top,
+        contains: args.search.clone(),
+        skip: args.skip,
+        limit: args.limit,
+        exclude_synthetic: args.no_synthetic,
+        node_type: args.node_type.clone(),
+        min_self_size: args.min_size,
+        min_count: args.min_count,
+        detached_only: args.detached_only,
+        jobs: args.jobs,
+        split_empty: args.split_empty,
+        reachability: args.reachability,
+        unreachable_only: args.unreachable,
+        samples: args.samples,
+    };
+
+    // --streaming conflicts with every option that needs the loaded snapshot afterwards.
+    let (summary, snapshot) = if args.streaming {
+        let mut streaming = analysis::summary::StreamingSummary::new(summary_options)?;
+        let streamed =
+            parser::stream_snapshot_file(&args.file, read_options, &mut |index, fields| {
+                streaming.add_node(index, fields)
+            })?;
+        log.streamed(&streamed);
+        let summary = streaming.finish(&streamed)?;
+        metrics.lap("parse+summary");
+        (summary, None)
+    } else {
+        let snapshot = parser::read_snapshot_file(&args.file, read_options)?;
+        metrics.lap("parse");
+        metrics.snapshot(&snapshot);
+
+        log.loaded(&snapshot);
+
+        let mut summary = analysis::summary::summarize(&snapshot, summary_options)?;
+        if args.edge_type_summary {
+            summary.edge_types = Some(analysis::edges::edge_type_histogram(&snapshot));
+        }
+        metrics.lap("summary");
+        (summary, Some(snapshot))
+    };
+    log.name_indices(&summary.rows);
 
     let format = if args.json.is_some() {
         OutputFormat::Json
@@ -305,32 +1435,80 @@ fn run_summary(
         args.format
     };
     let output = match format {
-        OutputFormat::Md => output::summary::format_markdown(&summary),
-        OutputFormat::Json => output::summary::format_json(&summary)?,
-        OutputFormat::Csv => output::summary::format_csv(&summary),
+        OutputFormat::Md => output::summary::format_markdown_colored(&summary, color, charset),
+        OutputFormat::Json => output::summary::format_json(&summary, json)?,
+        OutputFormat::Csv => output::summary::format_csv(&summary, delimiter),
     };
     let output_path = args.json.as_deref();
     output::write::write_or_stdout(output_path, &output)?;
 
-    if verbose {
-        let output_done = std::time::Instant::now();
+    metrics.lap("output");
+    log.finish(&metrics, json)?;
+
+    match snapshot {
+        Some(snapshot) if !budgets.is_empty() => check_budgets(&snapshot, budgets, cancel),
+        _ => Ok(()),
+    }
+}
+
+/// `--assert`: reports every exceeded budget on stderr and fails when there is one.
+fn check_budgets(
+    snapshot: &heapsnap::snapshot::SnapshotRaw,
+    budgets: &[analysis::budget::SizeBudget],
+    cancel: cancel::CancelToken,
+) -> Result<(), error::SnapshotError> {
+    let needs_retained = budgets
+        .iter()
+        .any(|budget| budget.metric == analysis::budget::BudgetMetric::Retained);
+    let dominators = if needs_retained {
+        let index = analysis::dominator::compute_dominator_index(
+            snapshot,
+            &analysis::dominator::default_ignored_edge_types(),
+            cancel,
+            None,
+        )?;
+        let retained_sizes = analysis::dominator::retained_sizes(snapshot, &index);
+        Some((index, retained_sizes))
+    } else {
+        None
+    };
+    let violations = analysis::budget::check_budgets(
+        snapshot,
+        budgets,
+        dominators
+            .as_ref()
+            .map(|(index, retained)| (index, retained.as_slice())),
+    )?;
+    if violations.is_empty() {
+        return Ok(());
+    }
+    for violation in &violations {
         eprintln!(
-            "timing: parse={:?}, summary={:?}, output={:?}",
-            parse_done.duration_since(started),
-            summary_done.duration_since(parse_done),
-            output_done.duration_since(summary_done)
+            "budget exceeded: {} {} is {} bytes (limit {} bytes)",
+            violation.budget.name,
+            violation.budget.metric.label(),
+            violation.actual_bytes,
+            violation.budget.max_bytes
         );
     }
-    Ok(())
+    Err(error::SnapshotError::InvalidData {
+        details: format!(
+            "{} of {} budget(s) exceeded (see above)",
+            violations.len(),
+            budgets.len()
+        ),
+    })
 }
 
 fn run_retainers(
-    verbose: bool,
-    progress: bool,
+    log: &RunLog,
+    progress: heapsnap::progress::ProgressMode,
     cancel: cancel::CancelToken,
+    json: output::json::JsonStyle,
+    charset: output::charset::Charset,
     args: RetainersArgs,
 ) -> Result<(), error::SnapshotError> {
-    let started = std::time::Instant::now();
+    let mut metrics = heapsnap::metrics::Metrics::new("retainers");
     if args.id.is_none() && args.name.is_none() {
         return Err(error::SnapshotError::InvalidData {
             details: "either --id or --name must be specified".to_string(),
@@ -344,188 +1522,721 @@ fn run_retainers(
 
     let options = parser::ReadOptions::new(progress, cancel.clone());
     let snapshot = parser::read_snapshot_file(&args.file, options)?;
-    let parse_done = std::time::Instant::now();
+    metrics.lap("parse");
+    metrics.snapshot(&snapshot);
 
-    if verbose {
-        eprintln!(
-            "loaded snapshot: nodes={}, edges={}, strings={}",
-            snapshot.node_count(),
-            snapshot.edge_count(),
-            snapshot.strings.len()
-        );
-        eprintln!(
-            "approx memory: {}",
-            format_bytes(snapshot.memory_estimate_bytes())
-        );
-    }
+    log.loaded(&snapshot);
 
+    let roots = analysis::retainers::RootOptions {
+        root_name: args.root_name.clone(),
+    };
+    let needs_retained = matches!(args.rank, PathRank::LargestRetainer)
+        || (args.id.is_none() && matches!(args.pick, PickStrategy::Retained));
+    let retained_sizes = if needs_retained {
+        let index = analysis::dominator::compute_dominator_index_with_roots(
+            &snapshot,
+            &analysis::dominator::default_ignored_edge_types(),
+            &roots,
+            cancel.clone(),
+            None,
+        )?;
+        analysis::dominator::retained_sizes(&snapshot, &index)
+    } else {
+        Vec::new()
+    };
     let target = if let Some(node_id) = args.id {
         analysis::retainers::find_target_by_id(&snapshot, node_id)?
     } else {
-        let pick = match args.pick {
-            PickStrategy::Largest => analysis::retainers::PickStrategy::Largest,
-            PickStrategy::Count => analysis::retainers::PickStrategy::Count,
-        };
-        analysis::retainers::find_target_by_name(
+        target_by_name(
+            log,
             &snapshot,
             args.name.as_deref().unwrap_or(""),
-            pick,
+            args.exact,
+            target_pick(args.pick, &retained_sizes),
         )?
     };
 
-    let result = analysis::retainers::find_retaining_paths(
+    let mut result = analysis::retainers::RetainerSearch::with_roots(&snapshot, &roots)?.find(
         &snapshot,
         target,
-        analysis::retainers::RetainersOptions {
+        &analysis::retainers::RetainersOptions {
             max_paths: args.paths,
             max_depth: args.max_depth,
+            distinct_roots: args.distinct_roots,
+            rank: path_rank(args.rank, &retained_sizes),
+            depth_histogram: args.depth_histogram,
             cancel,
         },
     )?;
-    let search_done = std::time::Instant::now();
+    if args.dedupe_paths_by_constructor {
+        analysis::retainers::dedupe_by_constructor(&snapshot, &mut result);
+    }
+    metrics.lap("retainers");
+
+    if args.ids_only {
+        let output = output::retainers::format_ids(&snapshot, &result);
+        output::write::write_or_stdout(None, &output)?;
+        metrics.lap("output");
+        log.finish(&metrics, json)?;
+        return Ok(());
+    }
 
-    let output = match args.format {
-        OutputFormat::Md => output::retainers::format_markdown(&snapshot, &result),
-        OutputFormat::Json => output::retainers::format_json(&snapshot, &result)?,
+    match args.format {
+        OutputFormat::Md => {
+            let output = output::retainers::format_markdown(&snapshot, &result, charset);
+            output::write::write_or_stdout(None, &output)?;
+        }
+        OutputFormat::Json => output::write::write_or_stdout_with(None, |writer| {
+            output::retainers::write_json(&snapshot, &result, writer, json)
+        })?,
         OutputFormat::Csv => {
             return Err(error::SnapshotError::InvalidData {
                 details: "retainers output does not support csv".to_string(),
             });
         }
-    };
-
-    output::write::write_or_stdout(None, &output)?;
-
-    if verbose {
-        let output_done = std::time::Instant::now();
-        eprintln!(
-            "timing: parse={:?}, retainers={:?}, output={:?}",
-            parse_done.duration_since(started),
-            search_done.duration_since(parse_done),
-            output_done.duration_since(search_done)
-        );
     }
+
+    metrics.lap("output");
+    log.finish(&metrics, json)?;
     Ok(())
 }
 
 fn run_build(
-    verbose: bool,
-    progress: bool,
+    log: &RunLog,
+    progress: heapsnap::progress::ProgressMode,
     cancel: cancel::CancelToken,
+    json: output::json::JsonStyle,
     args: BuildArgs,
 ) -> Result<(), error::SnapshotError> {
-    let started = std::time::Instant::now();
+    let mut metrics = heapsnap::metrics::Metrics::new("build");
     let options = parser::ReadOptions::new(progress, cancel);
     let snapshot = parser::read_snapshot_file(&args.file, options)?;
-    let parse_done = std::time::Instant::now();
+    metrics.lap("parse");
+    metrics.snapshot(&snapshot);
 
-    if verbose {
-        eprintln!(
-            "loaded snapshot: nodes={}, edges={}, strings={}",
-            snapshot.node_count(),
-            snapshot.edge_count(),
-            snapshot.strings.len()
-        );
-        eprintln!(
-            "approx memory: {}",
-            format_bytes(snapshot.memory_estimate_bytes())
-        );
-    }
+    log.loaded(&snapshot);
 
     let summary = analysis::summary::summarize(
         &snapshot,
         analysis::summary::SummaryOptions {
             top: args.top,
             contains: args.contains,
+            ..Default::default()
         },
     )?;
-    let summary_done = std::time::Instant::now();
+    metrics.lap("summary");
 
     std::fs::create_dir_all(&args.outdir).map_err(error::SnapshotError::Io)?;
-    let summary_path = args.outdir.join("summary.json");
-    let meta_path = args.outdir.join("meta.json");
-
-    let summary_json = output::summary::format_json(&summary)?;
-    output::write::write_or_stdout(Some(&summary_path), &summary_json)?;
+    let write =
+        |name: &str, content: &str| output::write::write_atomic(&args.outdir.join(name), content);
+
+    if args.formats.contains(&BuildFormat::Json) {
+        write(
+            "summary.json",
+            &output::summary::format_json(&summary, json)?,
+        )?;
+        let largest = output::build::BuildLargest::from_snapshot(&snapshot, args.top);
+        write("largest.json", &largest.to_json(json)?)?;
+    }
+    if args.formats.contains(&BuildFormat::Md) {
+        write("summary.md", &output::summary::format_markdown(&summary))?;
+    }
+    if args.formats.contains(&BuildFormat::Csv) {
+        write("summary.csv", &output::summary::format_csv(&summary, ','))?;
+    }
+    if args.formats.contains(&BuildFormat::Html) {
+        write(
+            "summary.html",
+            &output::summary::format_html(&summary, &args.file),
+        )?;
+    }
 
     let meta = output::build::BuildMeta::from_snapshot(&snapshot);
-    let meta_json = meta.to_json()?;
-    output::write::write_or_stdout(Some(&meta_path), &meta_json)?;
+    write("meta.json", &meta.to_json(json)?)?;
+
+    metrics.lap("output");
+    log.finish(&metrics, json)?;
+
+    Ok(())
+}
+
+fn run_diff(
+    log: &RunLog,
+    progress: heapsnap::progress::ProgressMode,
+    cancel: cancel::CancelToken,
+    json: output::json::JsonStyle,
+    color: output::color::Color,
+    charset: output::charset::Charset,
+    args: DiffArgs,
+) -> Result<(), error::SnapshotError> {
+    let mut metrics = heapsnap::metrics::Metrics::new("diff");
+    let delimiter = output::csv::parse_delimiter(&args.delimiter)?;
+    let inputs = resolve_diff_inputs(&args)?;
+    if args.summaries {
+        return run_diff_saved(log, json, color, charset, args, &inputs, delimiter);
+    }
+    if inputs.len() > 2 {
+        return run_diff_series(
+            log, progress, cancel, json, charset, args, &inputs, delimiter,
+        );
+    }
+    let (before, after) = (&inputs[0], &inputs[1]);
+    if log.verbose {
+        eprintln!("diff inputs: A={}, B={}", before.display(), after.display());
+    }
+
+    let options_a = parser::ReadOptions::new(progress, cancel.clone());
+    let snapshot_a = parser::read_snapshot_file(before, options_a)?;
+    metrics.lap("parse_a");
+    metrics.snapshot(&snapshot_a);
 
-    if verbose {
-        let output_done = std::time::Instant::now();
+    let options_b = parser::ReadOptions::new(progress, cancel);
+    let snapshot_b = parser::read_snapshot_file(after, options_b)?;
+    metrics.lap("parse_b");
+    metrics.snapshot(&snapshot_b);
+
+    if log.verbose {
         eprintln!(
-            "timing: parse={:?}, summary={:?}, output={:?}",
-            parse_done.duration_since(started),
-            summary_done.duration_since(parse_done),
-            output_done.duration_since(summary_done)
+            "loaded snapshots: A nodes={}, B nodes={}",
+            snapshot_a.node_count(),
+            snapshot_b.node_count()
         );
     }
 
+    let diff = analysis::diff::diff_summaries(
+        &snapshot_a,
+        &snapshot_b,
+        analysis::diff::DiffOptions {
+            top: args.top,
+            contains: args.contains,
+            min_self_size: args.min_size,
+            min_count: args.min_count,
+            sort: args.sort.into(),
+            only_in: only_side(args.only_a, args.only_b),
+        },
+    )?;
+    metrics.lap("diff");
+
+    let output = match args.format {
+        DiffFormat::Md => output::diff::format_markdown_colored(&diff, color, charset),
+        DiffFormat::Json => output::diff::format_json(&diff, json)?,
+        DiffFormat::Csv => output::diff::format_csv(&diff, delimiter),
+        DiffFormat::Unified => output::diff::format_unified(&diff, charset),
+    };
+    output::write::write_or_stdout(None, &output)?;
+
+    metrics.lap("output");
+    log.finish(&metrics, json)?;
+
+    Ok(())
+}
+
+fn run_diff_summaries(
+    log: &RunLog,
+    json: output::json::JsonStyle,
+    color: output::color::Color,
+    charset: output::charset::Charset,
+    args: DiffSummariesArgs,
+) -> Result<(), error::SnapshotError> {
+    let delimiter = output::csv::parse_delimiter(&args.delimiter)?;
+    let inputs = [args.before.clone(), args.after.clone()];
+    let args = DiffArgs {
+        before: args.before,
+        after: vec![args.after],
+        latest: None,
+        summaries: true,
+        top: args.top,
+        contains: args.contains,
+        format: args.format,
+        min_size: args.min_size,
+        min_count: args.min_count,
+        sort: args.sort,
+        only_a: args.only_a,
+        only_b: args.only_b,
+        delimiter: args.delimiter,
+    };
+    run_diff_saved(log, json, color, charset, args, &inputs, delimiter)
+}
+
+/// `--only-a` / `--only-b` as a filter side; clap keeps the two flags exclusive.
+fn only_side(only_a: bool, only_b: bool) -> Option<analysis::diff::DiffSide> {
+    if only_a {
+        Some(analysis::diff::DiffSide::A)
+    } else if only_b {
+        Some(analysis::diff::DiffSide::B)
+    } else {
+        None
+    }
+}
+
+/// `diff --summaries`: compares saved summary JSON files, two as a pair and three or more
+/// as a series, without loading any snapshot.
+fn run_diff_saved(
+    log: &RunLog,
+    json: output::json::JsonStyle,
+    color: output::color::Color,
+    charset: output::charset::Charset,
+    args: DiffArgs,
+    inputs: &[PathBuf],
+    delimiter: char,
+) -> Result<(), error::SnapshotError> {
+    let mut metrics = heapsnap::metrics::Metrics::new("diff");
+    let mut summaries = Vec::with_capacity(inputs.len());
+    for (index, path) in inputs.iter().enumerate() {
+        if log.verbose {
+            eprintln!("diff input: T{index}={}", path.display());
+        }
+        summaries.push(read_summary_json(log, path)?);
+    }
+    metrics.lap("parse");
+
+    let options = analysis::diff::DiffOptions {
+        top: args.top,
+        contains: args.contains,
+        min_self_size: args.min_size,
+        min_count: args.min_count,
+        sort: args.sort.into(),
+        only_in: only_side(args.only_a, args.only_b),
+    };
+    let output = if summaries.len() > 2 {
+        let series = analysis::diff::diff_series_results(&summaries, options)?;
+        metrics.lap("diff");
+        match args.format {
+            DiffFormat::Json => output::diff::format_series_json(&series, json)?,
+            DiffFormat::Csv => output::diff::format_series_csv(&series, delimiter),
+            DiffFormat::Md => output::diff::format_series_markdown(&series, charset),
+            DiffFormat::Unified => {
+                return Err(error::SnapshotError::InvalidData {
+                    details:
+                        "--format unified compares two summaries; use md, json or csv for a series"
+                            .to_string(),
+                });
+            }
+        }
+    } else {
+        let diff = analysis::diff::diff_summary_results(&summaries[0], &summaries[1], options);
+        metrics.lap("diff");
+        match args.format {
+            DiffFormat::Md => output::diff::format_markdown_colored(&diff, color, charset),
+            DiffFormat::Json => output::diff::format_json(&diff, json)?,
+            DiffFormat::Csv => output::diff::format_csv(&diff, delimiter),
+            DiffFormat::Unified => output::diff::format_unified(&diff, charset),
+        }
+    };
+    output::write::write_or_stdout(None, &output)?;
+
+    metrics.lap("output");
+    log.finish(&metrics, json)?;
+    Ok(())
+}
+
+/// Reads a saved summary JSON, warning when it lists only part of the constructors:
+/// the missing ones count as zero in a diff.
+fn read_summary_json(
+    log: &RunLog,
+    path: &std::path::Path,
+) -> Result<analysis::summary::SummaryResult, error::SnapshotError> {
+    let text = std::fs::read_to_string(path)?;
+    let summary = output::summary::parse_json(&text).map_err(|err| match err {
+        error::SnapshotError::InvalidData { details } => error::SnapshotError::InvalidData {
+            details: format!("{}: {details}", path.display()),
+        },
+        other => other,
+    })?;
+    if summary.skip > 0 || summary.rows.len() < summary.total_rows {
+        log.warn(format_args!(
+            "{} lists {} of {} constructors; the others count as 0 in the diff (save the summary with a larger --top)",
+            path.display(),
+            summary.rows.len(),
+            summary.total_rows
+        ));
+    }
+    Ok(summary)
+}
+
+/// Diff of three or more snapshots: one row per constructor with a column per snapshot.
+fn run_diff_series(
+    log: &RunLog,
+    progress: heapsnap::progress::ProgressMode,
+    cancel: cancel::CancelToken,
+    json: output::json::JsonStyle,
+    charset: output::charset::Charset,
+    args: DiffArgs,
+    inputs: &[PathBuf],
+    delimiter: char,
+) -> Result<(), error::SnapshotError> {
+    if matches!(args.format, DiffFormat::Unified) {
+        return Err(error::SnapshotError::InvalidData {
+            details: "--format unified compares two snapshots; use md, json or csv for a series"
+                .to_string(),
+        });
+    }
+    let mut metrics = heapsnap::metrics::Metrics::new("diff");
+    // One snapshot in memory at a time: each is reduced to its summary and dropped
+    // before the next file is read.
+    let mut summaries = Vec::with_capacity(inputs.len());
+    for (index, path) in inputs.iter().enumerate() {
+        if log.verbose {
+            eprintln!("diff input: T{index}={}", path.display());
+        }
+        let options = parser::ReadOptions::new(progress, cancel.clone());
+        let snapshot = parser::read_snapshot_file(path, options)?;
+        metrics.snapshot(&snapshot);
+        summaries.push(analysis::diff::summarize_all(&snapshot)?);
+    }
+    metrics.lap("parse");
+
+    let series = analysis::diff::diff_series_results(
+        &summaries,
+        analysis::diff::DiffOptions {
+            top: args.top,
+            contains: args.contains,
+            min_self_size: args.min_size,
+            min_count: args.min_count,
+            sort: args.sort.into(),
+            only_in: only_side(args.only_a, args.only_b),
+        },
+    )?;
+    metrics.lap("diff");
+
+    let output = match args.format {
+        DiffFormat::Json => output::diff::format_series_json(&series, json)?,
+        DiffFormat::Csv => output::diff::format_series_csv(&series, delimiter),
+        DiffFormat::Md | DiffFormat::Unified => {
+            output::diff::format_series_markdown(&series, charset)
+        }
+    };
+    output::write::write_or_stdout(None, &output)?;
+
+    metrics.lap("output");
+    log.finish(&metrics, json)?;
     Ok(())
 }
 
-fn run_diff(
-    verbose: bool,
-    progress: bool,
-    cancel: cancel::CancelToken,
-    args: DiffArgs,
-) -> Result<(), error::SnapshotError> {
-    let started = std::time::Instant::now();
-    let options_a = parser::ReadOptions::new(progress, cancel.clone());
-    let snapshot_a = parser::read_snapshot_file(&args.before, options_a)?;
-    let parse_a_done = std::time::Instant::now();
+/// Resolves the snapshots to compare, oldest first, from explicit paths, a glob, or
+/// `--latest N <dir>`. A glob picks the two most recent files by mtime; `--latest N`
+/// picks the N most recent.
+fn resolve_diff_inputs(args: &DiffArgs) -> Result<Vec<PathBuf>, error::SnapshotError> {
+    if let Some(count) = args.latest {
+        if count < 2 {
+            return Err(error::SnapshotError::InvalidData {
+                details: format!("--latest must be at least 2 for diff (got {count})"),
+            });
+        }
+        if !args.after.is_empty() {
+            return Err(error::SnapshotError::InvalidData {
+                details: "--latest takes a single directory; drop the second path".to_string(),
+            });
+        }
+        let extension = if args.summaries {
+            ".json"
+        } else {
+            ".heapsnapshot"
+        };
+        let candidates = list_dir_files(&args.before, |name| name.ends_with(extension))?;
+        return newest(
+            candidates,
+            count,
+            &format!("{}/*{extension}", args.before.display()),
+        );
+    }
+
+    let pattern = args.before.to_string_lossy();
+    let is_glob = pattern.contains(['*', '?']);
+    match (args.after.is_empty(), is_glob) {
+        (false, false) => Ok(std::iter::once(args.before.clone())
+            .chain(args.after.iter().cloned())
+            .collect()),
+        (false, true) => Err(error::SnapshotError::InvalidData {
+            details: "a glob expands to both snapshots; drop the second path".to_string(),
+        }),
+        (true, false) => Err(error::SnapshotError::InvalidData {
+            details: "diff needs two snapshots: <before> <after>, a glob, or --latest 2 <dir>"
+                .to_string(),
+        }),
+        (true, true) => {
+            let dir = args
+                .before
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .unwrap_or_else(|| std::path::Path::new("."));
+            let file_pattern = args
+                .before
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            if dir.to_string_lossy().contains(['*', '?']) {
+                return Err(error::SnapshotError::InvalidData {
+                    details: format!(
+                        "glob is only supported in the file name part: {}",
+                        args.before.display()
+                    ),
+                });
+            }
+            let candidates = list_dir_files(dir, |name| glob_match(&file_pattern, name))?;
+            newest(candidates, 2, &pattern)
+        }
+    }
+}
+
+fn list_dir_files(
+    dir: &std::path::Path,
+    filter: impl Fn(&str) -> bool,
+) -> Result<Vec<(std::time::SystemTime, PathBuf)>, error::SnapshotError> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() || !filter(&entry.file_name().to_string_lossy()) {
+            continue;
+        }
+        files.push((metadata.modified()?, entry.path()));
+    }
+    Ok(files)
+}
+
+/// The `count` most recent files by mtime, oldest first.
+fn newest(
+    mut files: Vec<(std::time::SystemTime, PathBuf)>,
+    count: usize,
+    pattern: &str,
+) -> Result<Vec<PathBuf>, error::SnapshotError> {
+    if files.len() < count {
+        return Err(error::SnapshotError::InvalidData {
+            details: format!(
+                "{pattern} matched {} file(s); diff needs at least {count}",
+                files.len()
+            ),
+        });
+    }
+    files.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+    Ok(files
+        .split_off(files.len() - count)
+        .into_iter()
+        .map(|(_, path)| path)
+        .collect())
+}
+
+/// Minimal `*` / `?` matcher for a single path component.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0usize, 0usize);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn run_dominator(
+    log: &RunLog,
+    progress: heapsnap::progress::ProgressMode,
+    cancel: cancel::CancelToken,
+    json: output::json::JsonStyle,
+    args: DominatorArgs,
+) -> Result<(), error::SnapshotError> {
+    if args.id.is_none() && args.name.is_none() && !args.full_tree {
+        return Err(error::SnapshotError::InvalidData {
+            details: "either --id or --name must be specified".to_string(),
+        });
+    }
+    if args.id.is_some() && args.name.is_some() {
+        return Err(error::SnapshotError::InvalidData {
+            details: "use either --id or --name, not both".to_string(),
+        });
+    }
+
+    let mut metrics = heapsnap::metrics::Metrics::new("dominator");
+    let options = parser::ReadOptions::new(progress, cancel.clone());
+    let snapshot = parser::read_snapshot_file(&args.file, options)?;
+    metrics.lap("parse");
+    metrics.snapshot(&snapshot);
+
+    log.loaded(&snapshot);
+
+    let ignore_edge_types: Vec<String> = args
+        .ignore_edge
+        .into_iter()
+        .filter(|value| value != "none")
+        .collect();
 
-    let options_b = parser::ReadOptions::new(progress, cancel);
-    let snapshot_b = parser::read_snapshot_file(&args.after, options_b)?;
-    let parse_b_done = std::time::Instant::now();
+    let roots = analysis::retainers::RootOptions {
+        root_name: args.root_name.clone(),
+    };
+    if args.full_tree {
+        let index = load_dominator_index(
+            log,
+            &snapshot,
+            args.dom_cache.as_deref(),
+            &ignore_edge_types,
+            &roots,
+            args.max_edges_per_node,
+            cancel,
+        )?;
+        metrics.lap("dominator");
+        let output = output::dominator::format_tree_json(
+            &snapshot,
+            &index,
+            args.max_nodes,
+            args.max_depth,
+            json,
+        )?;
+        output::write::write_or_stdout(None, &output)?;
+        metrics.lap("output");
+        log.finish(&metrics, json)?;
+        return Ok(());
+    }
+    // `--pick retained` needs the dominator tree before the target is known; the
+    // chain below then reuses it instead of building it a second time.
+    let picked_index = match (args.name.as_deref(), args.pick) {
+        (Some(_), PickStrategy::Retained) => {
+            let index = load_dominator_index(
+                log,
+                &snapshot,
+                args.dom_cache.as_deref(),
+                &ignore_edge_types,
+                &roots,
+                args.max_edges_per_node,
+                cancel.clone(),
+            )?;
+            let retained_sizes = analysis::dominator::retained_sizes(&snapshot, &index);
+            Some((index, retained_sizes))
+        }
+        _ => None,
+    };
 
-    if verbose {
-        eprintln!(
-            "loaded snapshots: A nodes={}, B nodes={}",
-            snapshot_a.node_count(),
-            snapshot_b.node_count()
-        );
+    let target = if let Some(node_id) = args.id {
+        analysis::retainers::find_target_by_id(&snapshot, node_id)?
+    } else {
+        let retained_sizes = picked_index
+            .as_ref()
+            .map(|(_, retained_sizes)| retained_sizes.as_slice())
+            .unwrap_or_default();
+        target_by_name(
+            log,
+            &snapshot,
+            args.name.as_deref().unwrap_or(""),
+            args.exact,
+            target_pick(args.pick, retained_sizes),
+        )?
+    };
+
+    if args.reverse {
+        let (index, retained_sizes) = match picked_index {
+            Some(picked) => picked,
+            None => {
+                let index = load_dominator_index(
+                    log,
+                    &snapshot,
+                    args.dom_cache.as_deref(),
+                    &ignore_edge_types,
+                    &roots,
+                    args.max_edges_per_node,
+                    cancel.clone(),
+                )?;
+                let retained_sizes = analysis::dominator::retained_sizes(&snapshot, &index);
+                (index, retained_sizes)
+            }
+        };
+        let result = analysis::dominator::dominated_children(
+            &snapshot,
+            &index,
+            &retained_sizes,
+            target,
+            args.top,
+            cancel,
+        )?;
+        metrics.lap("dominator");
+        let output = match args.format {
+            OutputFormat::Md => output::dominator::format_dominated_markdown(&snapshot, &result),
+            OutputFormat::Json => {
+                output::dominator::format_dominated_json(&snapshot, &result, json)?
+            }
+            OutputFormat::Csv => {
+                return Err(error::SnapshotError::InvalidData {
+                    details: "dominator output does not support csv".to_string(),
+                });
+            }
+        };
+        output::write::write_or_stdout(None, &output)?;
+        metrics.lap("output");
+        log.finish(&metrics, json)?;
+        return Ok(());
     }
 
-    let diff = analysis::diff::diff_summaries(
-        &snapshot_a,
-        &snapshot_b,
-        analysis::diff::DiffOptions {
-            top: args.top,
-            contains: args.contains,
-        },
-    )?;
-    let diff_done = std::time::Instant::now();
+    let result = match (picked_index, args.dom_cache.as_deref()) {
+        (Some((index, retained_sizes)), _) => analysis::dominator::dominator_chain_with_retained(
+            &snapshot,
+            &index,
+            &retained_sizes,
+            target,
+            args.max_depth,
+            cancel,
+        )?,
+        (None, dom_cache) => {
+            let index = load_dominator_index(
+                log,
+                &snapshot,
+                dom_cache,
+                &ignore_edge_types,
+                &roots,
+                args.max_edges_per_node,
+                cancel.clone(),
+            )?;
+            analysis::dominator::dominator_chain_from_index(
+                &snapshot,
+                &index,
+                target,
+                args.max_depth,
+                cancel,
+            )?
+        }
+    };
+    metrics.lap("dominator");
 
-    let output = match args.format {
-        OutputFormat::Md => output::diff::format_markdown(&diff),
-        OutputFormat::Json => output::diff::format_json(&diff)?,
-        OutputFormat::Csv => output::diff::format_csv(&diff),
+    let output = if args.ids_only {
+        output::dominator::format_ids(&snapshot, &result)
+    } else {
+        match args.format {
+            OutputFormat::Md => output::dominator::format_markdown(&snapshot, &result),
+            OutputFormat::Json => output::dominator::format_json(&snapshot, &result, json)?,
+            OutputFormat::Csv => {
+                return Err(error::SnapshotError::InvalidData {
+                    details: "dominator output does not support csv".to_string(),
+                });
+            }
+        }
     };
+
     output::write::write_or_stdout(None, &output)?;
 
-    if verbose {
-        let output_done = std::time::Instant::now();
-        eprintln!(
-            "timing: parse_a={:?}, parse_b={:?}, diff={:?}, output={:?}",
-            parse_a_done.duration_since(started),
-            parse_b_done.duration_since(parse_a_done),
-            diff_done.duration_since(parse_b_done),
-            output_done.duration_since(diff_done)
-        );
-    }
+    metrics.lap("output");
+    log.finish(&metrics, json)?;
 
     Ok(())
 }
 
-fn run_dominator(
-    verbose: bool,
-    progress: bool,
+fn run_tree(
+    log: &RunLog,
+    progress: heapsnap::progress::ProgressMode,
     cancel: cancel::CancelToken,
-    args: DominatorArgs,
+    json: output::json::JsonStyle,
+    args: TreeArgs,
 ) -> Result<(), error::SnapshotError> {
-    if args.id.is_none() && args.name.is_none() {
+    if args.id.is_none() && args.name.is_none() && !args.folded {
         return Err(error::SnapshotError::InvalidData {
             details: "either --id or --name must be specified".to_string(),
         });
@@ -536,87 +2247,198 @@ fn run_dominator(
         });
     }
 
-    let started = std::time::Instant::now();
+    let mut metrics = heapsnap::metrics::Metrics::new("tree");
     let options = parser::ReadOptions::new(progress, cancel.clone());
     let snapshot = parser::read_snapshot_file(&args.file, options)?;
-    let parse_done = std::time::Instant::now();
-
-    if verbose {
-        eprintln!(
-            "loaded snapshot: nodes={}, edges={}, strings={}",
-            snapshot.node_count(),
-            snapshot.edge_count(),
-            snapshot.strings.len()
-        );
-        eprintln!(
-            "approx memory: {}",
-            format_bytes(snapshot.memory_estimate_bytes())
-        );
-    }
+    metrics.lap("parse");
+    metrics.snapshot(&snapshot);
+
+    log.loaded(&snapshot);
+
+    let ignore_edge_types: Vec<String> = args
+        .ignore_edge
+        .into_iter()
+        .filter(|value| value != "none")
+        .collect();
+    let roots = analysis::retainers::RootOptions {
+        root_name: args.root_name.clone(),
+    };
+    let index = load_dominator_index(
+        log,
+        &snapshot,
+        args.dom_cache.as_deref(),
+        &ignore_edge_types,
+        &roots,
+        args.max_edges_per_node,
+        cancel.clone(),
+    )?;
+    let retained_sizes = analysis::dominator::retained_sizes(&snapshot, &index);
 
     let target = if let Some(node_id) = args.id {
         analysis::retainers::find_target_by_id(&snapshot, node_id)?
+    } else if args.name.is_none() {
+        // Only reachable with --folded: a flame graph of the whole heap.
+        index.roots.first().copied().unwrap_or(0)
     } else {
-        let pick = match args.pick {
-            PickStrategy::Largest => analysis::retainers::PickStrategy::Largest,
-            PickStrategy::Count => analysis::retainers::PickStrategy::Count,
-        };
-        analysis::retainers::find_target_by_name(
+        target_by_name(
+            log,
             &snapshot,
             args.name.as_deref().unwrap_or(""),
-            pick,
+            args.exact,
+            target_pick(args.pick, &retained_sizes),
         )?
     };
 
-    let result = analysis::dominator::dominator_chain(
+    let result = analysis::tree::dominator_subtree(
         &snapshot,
+        &index,
+        &retained_sizes,
         target,
-        analysis::dominator::DominatorOptions {
+        analysis::tree::TreeOptions {
             max_depth: args.max_depth,
+            max_children: args.children,
             cancel,
-            progress: None,
         },
     )?;
-    let dom_done = std::time::Instant::now();
+    metrics.lap("tree");
 
     let output = match args.format {
-        OutputFormat::Md => output::dominator::format_markdown(&snapshot, &result),
-        OutputFormat::Json => output::dominator::format_json(&snapshot, &result)?,
+        _ if args.folded => output::folded::format(&snapshot, &result),
+        OutputFormat::Md => output::tree::format_markdown(&snapshot, &result),
+        OutputFormat::Json => output::tree::format_json(&snapshot, &result, json)?,
         OutputFormat::Csv => {
             return Err(error::SnapshotError::InvalidData {
-                details: "dominator output does not support csv".to_string(),
+                details: "tree output does not support csv".to_string(),
             });
         }
     };
 
     output::write::write_or_stdout(None, &output)?;
 
-    if verbose {
-        let output_done = std::time::Instant::now();
+    metrics.lap("output");
+    log.finish(&metrics, json)?;
+
+    Ok(())
+}
+
+/// Builds the dominator tree, going through `--dom-cache` when a cache path is given.
+/// With `--max-edges-per-node` (never cached), warns when nodes had edges left out.
+fn load_dominator_index(
+    log: &RunLog,
+    snapshot: &heapsnap::snapshot::SnapshotRaw,
+    dom_cache: Option<&std::path::Path>,
+    ignore_edge_types: &[String],
+    roots: &analysis::retainers::RootOptions,
+    max_edges_per_node: Option<usize>,
+    cancel: cancel::CancelToken,
+) -> Result<analysis::dominator::DominatorIndex, error::SnapshotError> {
+    match (dom_cache, max_edges_per_node) {
+        (Some(cache_path), None) => analysis::dominator_cache::load_or_compute(
+            snapshot,
+            cache_path,
+            ignore_edge_types,
+            roots,
+            cancel,
+            None,
+        ),
+        (_, max_edges_per_node) => {
+            let (index, truncated_nodes) = analysis::dominator::compute_dominator_index_capped(
+                snapshot,
+                ignore_edge_types,
+                roots,
+                max_edges_per_node,
+                cancel,
+                None,
+            )?;
+            if truncated_nodes > 0 {
+                log.warn(format_args!(
+                    "edges truncated: {truncated_nodes} nodes have more than {} outgoing edges; retained sizes are approximate",
+                    max_edges_per_node.unwrap_or_default()
+                ));
+            }
+            Ok(index)
+        }
+    }
+}
+
+/// Alternatives listed when `--name` matches more than one constructor.
+const NAME_ALTERNATIVES: usize = 5;
+
+/// Resolves `--name` to a node, warning on stderr when the filter matched several
+/// constructors so a surprising pick does not go unnoticed.
+fn target_by_name(
+    log: &RunLog,
+    snapshot: &heapsnap::snapshot::SnapshotRaw,
+    name: &str,
+    exact: bool,
+    pick: analysis::retainers::PickStrategy<'_>,
+) -> Result<usize, error::SnapshotError> {
+    let matched = analysis::retainers::match_target_by_name(snapshot, name, pick, exact)?;
+    if matched.candidates.len() > 1 && !log.quiet {
+        let picked = snapshot
+            .node_view(matched.node_index)
+            .and_then(|node| node.name())
+            .unwrap_or("<unknown>");
         eprintln!(
-            "timing: parse={:?}, dominator={:?}, output={:?}",
-            parse_done.duration_since(started),
-            dom_done.duration_since(parse_done),
-            output_done.duration_since(dom_done)
+            "warning: --name {name:?} matches {} constructors; using {picked:?} (pass --exact to require an exact match)",
+            matched.candidates.len()
         );
+        let alternatives: Vec<_> = matched
+            .candidates
+            .iter()
+            .filter(|candidate| candidate.name != picked)
+            .collect();
+        for candidate in alternatives.iter().take(NAME_ALTERNATIVES) {
+            eprintln!(
+                "  {:?} count={} self_size={}",
+                candidate.name,
+                candidate.count,
+                format_bytes(candidate.self_size_sum.max(0) as u64)
+            );
+        }
+        if alternatives.len() > NAME_ALTERNATIVES {
+            eprintln!("  ... {} more", alternatives.len() - NAME_ALTERNATIVES);
+        }
     }
+    Ok(matched.node_index)
+}
 
-    Ok(())
+fn path_rank(rank: PathRank, retained_sizes: &[i64]) -> analysis::retainers::PathRank<'_> {
+    match rank {
+        PathRank::Shortest => analysis::retainers::PathRank::Shortest,
+        PathRank::LargestRetainer => analysis::retainers::PathRank::LargestRetainer(retained_sizes),
+        PathRank::FewestElementEdges => analysis::retainers::PathRank::FewestElementEdges,
+    }
+}
+
+fn target_pick(
+    pick: PickStrategy,
+    retained_sizes: &[i64],
+) -> analysis::retainers::PickStrategy<'_> {
+    match pick {
+        PickStrategy::Largest => analysis::retainers::PickStrategy::Largest,
+        PickStrategy::Count => analysis::retainers::PickStrategy::Count,
+        PickStrategy::Retained => analysis::retainers::PickStrategy::RetainedSize(retained_sizes),
+    }
 }
 
 fn run_detail(
-    verbose: bool,
-    progress: bool,
+    log: &RunLog,
+    progress: heapsnap::progress::ProgressMode,
     cancel: cancel::CancelToken,
+    json: output::json::JsonStyle,
+    color: output::color::Color,
+    charset: output::charset::Charset,
     args: DetailArgs,
 ) -> Result<(), error::SnapshotError> {
-    let started = std::time::Instant::now();
-    if args.id.is_none() && args.name.is_none() {
+    let mut metrics = heapsnap::metrics::Metrics::new("detail");
+    let delimiter = output::csv::parse_delimiter(&args.delimiter)?;
+    if args.id.is_empty() && args.name.is_none() && args.name_index.is_none() {
         return Err(error::SnapshotError::InvalidData {
-            details: "either --id or --name must be specified".to_string(),
+            details: "either --id, --name or --name-index must be specified".to_string(),
         });
     }
-    if args.id.is_some() && args.name.is_some() {
+    if !args.id.is_empty() && args.name.is_some() {
         return Err(error::SnapshotError::InvalidData {
             details: "use either --id or --name, not both".to_string(),
         });
@@ -624,50 +2446,55 @@ fn run_detail(
 
     let options = parser::ReadOptions::new(progress, cancel);
     let snapshot = parser::read_snapshot_file(&args.file, options)?;
-    let parse_done = std::time::Instant::now();
+    metrics.lap("parse");
+    metrics.snapshot(&snapshot);
 
-    if verbose {
-        eprintln!(
-            "loaded snapshot: nodes={}, edges={}, strings={}",
-            snapshot.node_count(),
-            snapshot.edge_count(),
-            snapshot.strings.len()
-        );
-        eprintln!(
-            "approx memory: {}",
-            format_bytes(snapshot.memory_estimate_bytes())
-        );
-    }
+    log.loaded(&snapshot);
 
-    let detail = analysis::detail::detail(
-        &snapshot,
-        analysis::detail::DetailOptions {
-            id: args.id,
-            name: args.name.clone(),
-            skip: args.skip,
-            limit: args.limit,
-            top_retainers: args.top_retainers,
-            top_edges: args.top_edges,
-        },
-    )?;
-    let detail_done = std::time::Instant::now();
+    let detail_for = |id: Option<u64>| {
+        analysis::detail::detail(
+            &snapshot,
+            analysis::detail::DetailOptions {
+                id,
+                name: args.name.clone(),
+                name_index: args.name_index,
+                skip: args.skip,
+                limit: args.limit,
+                top_retainers: args.top_retainers,
+                top_edges: args.top_edges,
+                edge_name_filter: args.edge_name.clone(),
+                raw: args.raw,
+                max_edges_per_node: args.max_edges_per_node,
+            },
+        )
+    };
 
-    let output = match args.format {
-        OutputFormat::Md => output::detail::format_markdown(&detail),
-        OutputFormat::Json => output::detail::format_json(&detail)?,
-        OutputFormat::Csv => output::detail::format_csv(&detail),
+    // Several ids share the parse; a single id keeps the single-object output.
+    let output = if args.id.len() > 1 {
+        let details = args
+            .id
+            .iter()
+            .map(|id| detail_for(Some(*id)))
+            .collect::<Result<Vec<_>, _>>()?;
+        metrics.lap("detail");
+        match args.format {
+            OutputFormat::Md => output::detail::format_markdown_many(&details, color, charset),
+            OutputFormat::Json => output::detail::format_json_many(&details, json)?,
+            OutputFormat::Csv => output::detail::format_csv_many(&details, delimiter),
+        }
+    } else {
+        let detail = detail_for(args.id.first().copied())?;
+        metrics.lap("detail");
+        match args.format {
+            OutputFormat::Md => output::detail::format_markdown_colored(&detail, color, charset),
+            OutputFormat::Json => output::detail::format_json(&detail, json)?,
+            OutputFormat::Csv => output::detail::format_csv(&detail, delimiter),
+        }
     };
     output::write::write_or_stdout(None, &output)?;
 
-    if verbose {
-        let output_done = std::time::Instant::now();
-        eprintln!(
-            "timing: parse={:?}, detail={:?}, output={:?}",
-            parse_done.duration_since(started),
-            detail_done.duration_since(parse_done),
-            output_done.duration_since(detail_done)
-        );
-    }
+    metrics.lap("output");
+    log.finish(&metrics, json)?;
     Ok(())
 }
 
@@ -709,6 +2536,31 @@ mod tests {
         assert!(args.is_ok());
     }
 
+    #[test]
+    fn help_parsing_summary_skip_limit() {
+        let args = Cli::try_parse_from([
+            "heapsnap",
+            "summary",
+            "input.heapsnapshot",
+            "--skip",
+            "50",
+            "--limit",
+            "50",
+        ]);
+        assert!(args.is_ok());
+    }
+
+    #[test]
+    fn help_parsing_summary_no_synthetic() {
+        let args = Cli::try_parse_from([
+            "heapsnap",
+            "summary",
+            "input.heapsnapshot",
+            "--no-synthetic",
+        ]);
+        assert!(args.is_ok());
+    }
+
     #[test]
     fn help_parsing_retainers() {
         let args =
@@ -716,6 +2568,48 @@ mod tests {
         assert!(args.is_ok());
     }
 
+    #[test]
+    fn help_parsing_detail_accepts_several_ids() {
+        let args = Cli::try_parse_from([
+            "heapsnap",
+            "detail",
+            "input.heapsnapshot",
+            "--id",
+            "3",
+            "--ids",
+            "7,12",
+        ])
+        .expect("args");
+        let Command::Detail(detail) = args.command else {
+            panic!("expected detail");
+        };
+        assert_eq!(detail.id, vec![3, 7, 12]);
+    }
+
+    #[test]
+    fn ids_only_conflicts_with_explicit_format() {
+        let args = Cli::try_parse_from([
+            "heapsnap",
+            "retainers",
+            "input.heapsnapshot",
+            "--id",
+            "123",
+            "--ids-only",
+        ]);
+        assert!(args.is_ok());
+        let args = Cli::try_parse_from([
+            "heapsnap",
+            "dominator",
+            "input.heapsnapshot",
+            "--id",
+            "123",
+            "--ids-only",
+            "--format",
+            "json",
+        ]);
+        assert!(args.is_err());
+    }
+
     #[test]
     fn help_parsing_build() {
         let args =
@@ -723,6 +2617,30 @@ mod tests {
         assert!(args.is_ok());
     }
 
+    #[test]
+    fn help_parsing_build_formats() {
+        let args = Cli::try_parse_from([
+            "heapsnap",
+            "build",
+            "input.heapsnapshot",
+            "--outdir",
+            "out",
+            "--formats",
+            "md,csv,html,json",
+        ]);
+        assert!(args.is_ok());
+        let args = Cli::try_parse_from([
+            "heapsnap",
+            "build",
+            "input.heapsnapshot",
+            "--outdir",
+            "out",
+            "--formats",
+            "pdf",
+        ]);
+        assert!(args.is_err());
+    }
+
     #[test]
     fn help_parsing_diff() {
         let args = Cli::try_parse_from([
@@ -736,6 +2654,101 @@ mod tests {
         assert!(args.is_ok());
     }
 
+    #[test]
+    fn help_parsing_diff_unified() {
+        let args = Cli::try_parse_from([
+            "heapsnap",
+            "diff",
+            "a.heapsnapshot",
+            "b.heapsnapshot",
+            "--format",
+            "unified",
+        ]);
+        assert!(args.is_ok());
+    }
+
+    #[test]
+    fn help_parsing_diff_glob_and_latest() {
+        let args = Cli::try_parse_from(["heapsnap", "diff", "snaps/heap-*.heapsnapshot"]);
+        assert!(args.is_ok());
+        let args = Cli::try_parse_from(["heapsnap", "diff", "--latest", "2", "snaps"]);
+        assert!(args.is_ok());
+    }
+
+    #[test]
+    fn glob_match_star_and_question() {
+        assert!(glob_match("heap-*.heapsnapshot", "heap-1.heapsnapshot"));
+        assert!(glob_match("heap-?.heapsnapshot", "heap-2.heapsnapshot"));
+        assert!(glob_match("*", "anything"));
+        assert!(!glob_match("heap-*.heapsnapshot", "heap-1.json"));
+        assert!(!glob_match("heap-?.heapsnapshot", "heap-10.heapsnapshot"));
+    }
+
+    #[test]
+    fn diff_glob_picks_two_newest_by_mtime() {
+        let mut dir = std::env::temp_dir();
+        dir.push(format!("heapsnap-diff-glob-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let base = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        for (name, offset) in [
+            ("heap-c", 10),
+            ("heap-a", 30),
+            ("heap-b", 20),
+            ("other", 40),
+        ] {
+            let path = dir.join(format!("{name}.heapsnapshot"));
+            let file = std::fs::File::create(&path).expect("create file");
+            file.set_modified(base + std::time::Duration::from_secs(offset))
+                .expect("set mtime");
+        }
+
+        let diff_args = |before: PathBuf, latest: Option<usize>| DiffArgs {
+            before,
+            after: Vec::new(),
+            latest,
+            top: 50,
+            contains: None,
+            format: DiffFormat::Md,
+            min_size: None,
+            min_count: None,
+            sort: DiffSort::SizeDelta,
+            delimiter: ",".to_string(),
+            summaries: false,
+            only_a: false,
+            only_b: false,
+        };
+
+        let inputs =
+            resolve_diff_inputs(&diff_args(dir.join("heap-*.heapsnapshot"), None)).expect("glob");
+        assert_eq!(
+            inputs,
+            vec![
+                dir.join("heap-b.heapsnapshot"),
+                dir.join("heap-a.heapsnapshot")
+            ]
+        );
+
+        let inputs = resolve_diff_inputs(&diff_args(dir.clone(), Some(2))).expect("latest");
+        assert_eq!(
+            inputs,
+            vec![
+                dir.join("heap-a.heapsnapshot"),
+                dir.join("other.heapsnapshot")
+            ]
+        );
+
+        let inputs = resolve_diff_inputs(&diff_args(dir.clone(), Some(3))).expect("latest 3");
+        assert_eq!(inputs.len(), 3);
+        assert_eq!(inputs[2], dir.join("other.heapsnapshot"));
+        assert!(resolve_diff_inputs(&diff_args(dir.clone(), Some(5))).is_err());
+
+        let err = resolve_diff_inputs(&diff_args(dir.join("none-*.heapsnapshot"), None));
+        assert!(err.is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn help_parsing_dominator() {
         let args =
@@ -743,12 +2756,121 @@ mod tests {
         assert!(args.is_ok());
     }
 
+    #[test]
+    fn help_parsing_dominator_cache() {
+        let args = Cli::try_parse_from([
+            "heapsnap",
+            "dominator",
+            "input.heapsnapshot",
+            "--id",
+            "123",
+            "--dom-cache",
+            "out/dom.cache",
+        ]);
+        assert!(args.is_ok());
+    }
+
+    #[test]
+    fn help_parsing_dominator_ignore_edge() {
+        let cli = Cli::try_parse_from([
+            "heapsnap",
+            "dominator",
+            "input.heapsnapshot",
+            "--id",
+            "1",
+            "--ignore-edge",
+            "weak",
+            "--ignore-edge",
+            "shortcut",
+        ])
+        .expect("parse");
+        match cli.command {
+            Command::Dominator(args) => assert_eq!(args.ignore_edge, ["weak", "shortcut"]),
+            other => panic!("unexpected command: {other:?}"),
+        }
+    }
+
     #[test]
     fn help_parsing_detail() {
         let args = Cli::try_parse_from(["heapsnap", "detail", "input.heapsnapshot", "--id", "123"]);
         assert!(args.is_ok());
     }
 
+    #[test]
+    fn help_parsing_peek() {
+        let args = Cli::try_parse_from([
+            "heapsnap",
+            "peek",
+            "input.heapsnapshot",
+            "--head-bytes",
+            "4096",
+        ]);
+        assert!(args.is_ok());
+    }
+
+    #[test]
+    fn help_parsing_verify() {
+        let args = Cli::try_parse_from([
+            "heapsnap",
+            "verify",
+            "input.heapsnapshot",
+            "--format",
+            "json",
+        ]);
+        assert!(args.is_ok());
+    }
+
+    #[test]
+    fn help_parsing_triage() {
+        let args = Cli::try_parse_from([
+            "heapsnap",
+            "triage",
+            "input.heapsnapshot",
+            "--top",
+            "3",
+            "--paths",
+            "2",
+            "--format",
+            "json",
+        ]);
+        assert!(args.is_ok());
+    }
+
+    #[test]
+    fn help_parsing_timeout() {
+        let cli = Cli::try_parse_from([
+            "heapsnap",
+            "--timeout",
+            "30",
+            "retainers",
+            "input.heapsnapshot",
+            "--id",
+            "1",
+        ])
+        .expect("parse");
+        assert_eq!(cli.timeout, Some(30));
+    }
+
+    #[test]
+    fn timeout_failure_reports_timed_out_with_cancelled_exit_code() {
+        let timeout = Some(std::time::Duration::from_secs(30));
+        let (message, code) = failure(
+            &error::SnapshotError::Cancelled,
+            timeout,
+            std::time::Duration::from_secs(31),
+        );
+        assert_eq!(message, "timed out after 30s");
+        assert_eq!(code, 130);
+
+        // Ctrl-C before the deadline is still reported as a cancellation.
+        let (message, code) = failure(
+            &error::SnapshotError::Cancelled,
+            timeout,
+            std::time::Duration::from_secs(1),
+        );
+        assert_eq!((message.as_str(), code), ("cancelled by user", 130));
+    }
+
     #[test]
     fn help_parsing_serve() {
         let args =
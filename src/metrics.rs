@@ -0,0 +1,128 @@
+//! Timing and size figures for one command run, reported by `--verbose` and `--metrics`
+//! so that parse and analysis performance can be tracked over time.
+
+use std::time::{Duration, Instant};
+
+use serde::{Serialize, Serializer};
+
+use crate::error::SnapshotError;
+use crate::output::header::TOOL_VERSION;
+use crate::output::json::JsonStyle;
+use crate::snapshot::SnapshotRaw;
+
+#[derive(Debug, Serialize)]
+pub struct Metrics {
+    pub version: u32,
+    pub tool_version: &'static str,
+    /// Subcommand name, e.g. `summary`.
+    pub command: String,
+    /// One entry per loaded snapshot (two for `diff`, none for `peek`).
+    pub snapshots: Vec<SnapshotMetrics>,
+    /// Phases in the order they ran.
+    pub phases: Vec<PhaseMetrics>,
+    #[serde(rename = "total_ms", serialize_with = "serialize_millis")]
+    pub total: Duration,
+    /// When the current `lap` started.
+    #[serde(skip)]
+    lap_started: Instant,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SnapshotMetrics {
+    pub nodes: usize,
+    pub edges: usize,
+    pub strings: usize,
+    /// `SnapshotRaw::memory_estimate_bytes`.
+    pub memory_estimate_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseMetrics {
+    pub name: String,
+    #[serde(rename = "ms", serialize_with = "serialize_millis")]
+    pub duration: Duration,
+}
+
+impl SnapshotMetrics {
+    pub fn of(snapshot: &SnapshotRaw) -> Self {
+        Self {
+            nodes: snapshot.node_count(),
+            edges: snapshot.edge_count(),
+            strings: snapshot.strings.len(),
+            memory_estimate_bytes: snapshot.memory_estimate_bytes(),
+        }
+    }
+}
+
+impl Metrics {
+    pub fn new(command: &str) -> Self {
+        Self {
+            version: 1,
+            tool_version: TOOL_VERSION,
+            command: command.to_string(),
+            snapshots: Vec::new(),
+            phases: Vec::new(),
+            total: Duration::ZERO,
+            lap_started: Instant::now(),
+        }
+    }
+
+    pub fn snapshot(&mut self, snapshot: &SnapshotRaw) {
+        self.snapshots.push(SnapshotMetrics::of(snapshot));
+    }
+
+    /// Appends a phase; `total` is the sum of all phases.
+    pub fn phase(&mut self, name: &str, duration: Duration) {
+        self.total += duration;
+        self.phases.push(PhaseMetrics {
+            name: name.to_string(),
+            duration,
+        });
+    }
+
+    /// Records the time since the previous lap (or `new`) as phase `name`.
+    pub fn lap(&mut self, name: &str) {
+        let now = Instant::now();
+        self.phase(name, now.duration_since(self.lap_started));
+        self.lap_started = now;
+    }
+
+    /// The `--verbose` line, e.g. `timing: parse=1.2ms, summary=300µs`.
+    pub fn timing_line(&self) -> String {
+        let phases: Vec<String> = self
+            .phases
+            .iter()
+            .map(|phase| format!("{}={:?}", phase.name, phase.duration))
+            .collect();
+        format!("timing: {}", phases.join(", "))
+    }
+
+    pub fn to_json(&self, style: JsonStyle) -> Result<String, SnapshotError> {
+        style.render(self)
+    }
+}
+
+fn serialize_millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phases_keep_their_order_and_sum_to_total() {
+        let mut metrics = Metrics::new("summary");
+        metrics.phase("parse", Duration::from_millis(3));
+        metrics.phase("summary", Duration::from_micros(500));
+
+        assert_eq!(metrics.timing_line(), "timing: parse=3ms, summary=500µs");
+        let value: serde_json::Value =
+            serde_json::from_str(&metrics.to_json(JsonStyle::Compact).expect("json"))
+                .expect("parse json");
+        assert_eq!(value["command"], "summary");
+        assert_eq!(value["phases"][0]["name"], "parse");
+        assert_eq!(value["phases"][0]["ms"], 3.0);
+        assert_eq!(value["total_ms"], 3.5);
+    }
+}
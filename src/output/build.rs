@@ -1,6 +1,8 @@
 use serde::Serialize;
 
+use crate::analysis::triage::find_biggest;
 use crate::error::SnapshotError;
+use crate::output::json::JsonStyle;
 use crate::snapshot::SnapshotRaw;
 
 #[derive(Debug, Serialize)]
@@ -9,6 +11,10 @@ pub struct BuildMeta {
     pub total_nodes: usize,
     pub total_edges: usize,
     pub total_strings: usize,
+    /// `snapshot.title` / `uid` / `timestamp` of the source file (null when absent).
+    pub title: Option<String>,
+    pub uid: Option<u64>,
+    pub capture_time: Option<String>,
 }
 
 impl BuildMeta {
@@ -18,10 +24,57 @@ impl BuildMeta {
             total_nodes: snapshot.node_count(),
             total_edges: snapshot.edge_count(),
             total_strings: snapshot.strings.len(),
+            title: snapshot.labels.title.clone(),
+            uid: snapshot.labels.uid,
+            capture_time: snapshot.labels.capture_time.clone(),
         }
     }
 
-    pub fn to_json(&self) -> Result<String, SnapshotError> {
-        serde_json::to_string_pretty(self).map_err(SnapshotError::Json)
+    pub fn to_json(&self, style: JsonStyle) -> Result<String, SnapshotError> {
+        style.render(self)
+    }
+}
+
+/// The `largest.json` payload: the biggest individual nodes by self size.
+#[derive(Debug, Serialize)]
+pub struct BuildLargest {
+    pub version: u32,
+    pub total_nodes: usize,
+    pub nodes: Vec<LargestNode>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LargestNode {
+    pub index: usize,
+    pub id: Option<i64>,
+    pub name: Option<String>,
+    pub node_type: Option<String>,
+    pub self_size_bytes: i64,
+}
+
+impl BuildLargest {
+    pub fn from_snapshot(snapshot: &SnapshotRaw, top: usize) -> Self {
+        let nodes = find_biggest(snapshot, top)
+            .into_iter()
+            .map(|(index, self_size)| {
+                let node = snapshot.node_view(index);
+                LargestNode {
+                    index,
+                    id: node.and_then(|node| node.id()),
+                    name: node.and_then(|node| node.name()).map(str::to_string),
+                    node_type: node.and_then(|node| node.node_type()).map(str::to_string),
+                    self_size_bytes: self_size,
+                }
+            })
+            .collect();
+        Self {
+            version: 1,
+            total_nodes: snapshot.node_count(),
+            nodes,
+        }
+    }
+
+    pub fn to_json(&self, style: JsonStyle) -> Result<String, SnapshotError> {
+        style.render(self)
     }
 }
@@ -0,0 +1,73 @@
+//! Glyphs used by the markdown formatters. `--ascii` swaps the Unicode ellipsis, delta and
+//! arrows for plain ASCII so the output survives CI logs and legacy Windows consoles.
+//! Names and strings taken from the snapshot are printed as they are.
+
+/// Character set of the decorations added around snapshot data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Charset {
+    #[default]
+    Unicode,
+    Ascii,
+}
+
+impl Charset {
+    pub fn from_ascii_flag(ascii: bool) -> Self {
+        if ascii {
+            Charset::Ascii
+        } else {
+            Charset::Unicode
+        }
+    }
+
+    /// Marks a value cut short.
+    pub fn ellipsis(self) -> &'static str {
+        match self {
+            Charset::Unicode => "…",
+            Charset::Ascii => "...",
+        }
+    }
+
+    /// Prefix of the change columns in diff tables (`Δ Count` / `delta Count`).
+    pub fn delta(self) -> &'static str {
+        match self {
+            Charset::Unicode => "Δ",
+            Charset::Ascii => "delta",
+        }
+    }
+
+    /// Separates a before and an after value (`3→5` / `3->5`).
+    pub fn arrow(self) -> &'static str {
+        match self {
+            Charset::Unicode => "→",
+            Charset::Ascii => "->",
+        }
+    }
+
+    /// Multiplier in front of a count (`×3` / `x3`).
+    pub fn times(self) -> &'static str {
+        match self {
+            Charset::Unicode => "×",
+            Charset::Ascii => "x",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_glyphs_are_ascii() {
+        let ascii = Charset::from_ascii_flag(true);
+        for glyph in [
+            ascii.ellipsis(),
+            ascii.delta(),
+            ascii.arrow(),
+            ascii.times(),
+        ] {
+            assert!(glyph.is_ascii(), "{glyph:?}");
+        }
+        assert_eq!(Charset::from_ascii_flag(false), Charset::Unicode);
+        assert_eq!(Charset::Unicode.ellipsis(), "…");
+    }
+}
@@ -0,0 +1,91 @@
+//! ANSI colouring for markdown printed to a terminal. Only the markdown formatters take a
+//! `Color`; JSON / CSV and files are always written without escape codes.
+
+use std::fmt::Display;
+use std::io::IsTerminal;
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
+
+/// A size is "large" (red) when it is at least this fraction of the largest one in the
+/// same table.
+const LARGE_FRACTION: f64 = 0.5;
+
+/// The `--color` setting before it is resolved against the output stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Colour only when stdout is a terminal and `NO_COLOR` is not set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn resolve(self) -> Color {
+        let enabled = match self {
+            ColorChoice::Auto => {
+                std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+            }
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        };
+        Color { enabled }
+    }
+}
+
+/// Wraps values in ANSI codes when enabled, and passes them through otherwise.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Color {
+    enabled: bool,
+}
+
+impl Color {
+    pub const OFF: Color = Color { enabled: false };
+    pub const ON: Color = Color { enabled: true };
+
+    /// `value`, red when it is large relative to `max` (see `LARGE_FRACTION`).
+    pub fn size(self, value: i64, max: i64) -> String {
+        if max > 0 && value as f64 >= max as f64 * LARGE_FRACTION {
+            self.paint(RED, value)
+        } else {
+            value.to_string()
+        }
+    }
+
+    /// `value` with growth in red and shrinkage in green; zero stays uncoloured.
+    pub fn delta(self, value: i64) -> String {
+        match value.signum() {
+            1 => self.paint(RED, value),
+            -1 => self.paint(GREEN, value),
+            _ => value.to_string(),
+        }
+    }
+
+    fn paint(self, code: &str, value: impl Display) -> String {
+        if self.enabled {
+            format!("{code}{value}{RESET}")
+        } else {
+            value.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paints_only_when_enabled() {
+        assert_eq!(Color::OFF.size(100, 100), "100");
+        assert_eq!(Color::OFF.delta(-5), "-5");
+        assert_eq!(Color::ON.size(60, 100), "\x1b[31m60\x1b[0m");
+        assert_eq!(Color::ON.size(40, 100), "40");
+        assert_eq!(Color::ON.delta(5), "\x1b[31m5\x1b[0m");
+        assert_eq!(Color::ON.delta(-5), "\x1b[32m-5\x1b[0m");
+        assert_eq!(Color::ON.delta(0), "0");
+        assert_eq!(ColorChoice::Never.resolve(), Color::OFF);
+        assert_eq!(ColorChoice::Always.resolve(), Color::ON);
+    }
+}
@@ -0,0 +1,104 @@
+use std::borrow::Cow;
+
+use crate::error::SnapshotError;
+
+pub const DEFAULT_DELIMITER: char = ',';
+
+/// CSV builder with RFC 4180 quoting shared by every `--format csv` output.
+/// Fields are quoted only when they contain the delimiter, a quote, CR or LF; embedded quotes are doubled.
+pub struct TableWriter {
+    output: String,
+    delimiter: char,
+}
+
+impl TableWriter {
+    pub fn new(header: &[&str]) -> Self {
+        Self::with_delimiter(header, DEFAULT_DELIMITER)
+    }
+
+    pub fn with_delimiter(header: &[&str], delimiter: char) -> Self {
+        let mut writer = Self {
+            output: String::new(),
+            delimiter,
+        };
+        writer.row(header);
+        writer
+    }
+
+    pub fn row(&mut self, fields: &[&str]) {
+        for (index, field) in fields.iter().enumerate() {
+            if index > 0 {
+                self.output.push(self.delimiter);
+            }
+            self.output
+                .push_str(&quote_field_with(field, self.delimiter));
+        }
+        self.output.push('\n');
+    }
+
+    pub fn finish(self) -> String {
+        self.output
+    }
+}
+
+fn quote_field_with(value: &str, delimiter: char) -> Cow<'_, str> {
+    if !value.contains([delimiter, '"', '\r', '\n']) {
+        return Cow::Borrowed(value);
+    }
+    Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+}
+
+/// Parses `--delimiter`: a single character, or `\t` / `tab` for TSV.
+/// Quotes and line breaks are rejected because they would make rows ambiguous.
+pub fn parse_delimiter(value: &str) -> Result<char, SnapshotError> {
+    if value == "\\t" || value.eq_ignore_ascii_case("tab") {
+        return Ok('\t');
+    }
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(delimiter), None) if !matches!(delimiter, '"' | '\r' | '\n') => Ok(delimiter),
+        _ => Err(SnapshotError::InvalidData {
+            details: format!(
+                "--delimiter must be a single character other than a quote or line break (got {value:?})"
+            ),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_only_when_needed() {
+        let mut writer = TableWriter::new(&["name", "count"]);
+        writer.row(&["Foo", "1"]);
+        writer.row(&["a,b", "2"]);
+        writer.row(&["say \"hi\"", "3"]);
+        writer.row(&["line\nbreak", "4"]);
+        assert_eq!(
+            writer.finish(),
+            "name,count\nFoo,1\n\"a,b\",2\n\"say \"\"hi\"\"\",3\n\"line\nbreak\",4\n"
+        );
+    }
+
+    #[test]
+    fn tab_delimiter_quotes_tabs_but_not_commas() {
+        let mut writer = TableWriter::with_delimiter(&["name", "count"], '\t');
+        writer.row(&["a,b", "1"]);
+        writer.row(&["a\tb", "2"]);
+        assert_eq!(writer.finish(), "name\tcount\na,b\t1\n\"a\tb\"\t2\n");
+    }
+
+    #[test]
+    fn parse_delimiter_accepts_single_characters_and_tab_aliases() {
+        assert_eq!(parse_delimiter(",").expect("comma"), ',');
+        assert_eq!(parse_delimiter(";").expect("semicolon"), ';');
+        assert_eq!(parse_delimiter("\t").expect("tab"), '\t');
+        assert_eq!(parse_delimiter("\\t").expect("escaped tab"), '\t');
+        assert_eq!(parse_delimiter("tab").expect("tab name"), '\t');
+        for invalid in ["", ",,", "\"", "\n"] {
+            assert!(parse_delimiter(invalid).is_err(), "{invalid:?}");
+        }
+    }
+}
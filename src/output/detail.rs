@@ -4,9 +4,16 @@ use std::path::Path;
 use serde::Serialize;
 
 use crate::analysis::detail::{
-    DetailById, DetailByName, DetailResult, OutgoingEdgeSummary, RetainerSummary, ShallowSizeBucket,
+    DetailById, DetailByName, DetailResult, EdgesTruncated, OutgoingEdgeSummary, RawRecords,
+    RetainerSummary,
 };
+use crate::analysis::histogram::SizeBucket;
 use crate::error::SnapshotError;
+use crate::output::charset::Charset;
+use crate::output::color::Color;
+use crate::output::csv::TableWriter;
+use crate::output::header::JsonHeader;
+use crate::output::json::JsonStyle;
 
 const HEADER_PREVIEW_MAX: usize = 50;
 const V8_HEAP_SNAPSHOT_STRING_LIMIT_DOC_URL: &str =
@@ -15,25 +22,66 @@ const V8_HEAP_SNAPSHOT_STRING_LIMIT_DOC_URL: &str =
 #[derive(Debug, Serialize)]
 struct DetailJson<'a> {
     version: u32,
+    #[serde(flatten)]
+    header: JsonHeader,
     mode: &'a str,
     #[serde(skip_serializing_if = "Option::is_none")]
     name: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    name_index: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     id: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     node_type: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
     self_size_bytes: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<LocationJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reachability: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     constructor_summary: Option<ConstructorSummaryJson>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    node_type_breakdown: Option<Vec<NodeTypeBreakdownJson>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     ids: Option<Vec<NodeRefJson>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     retainers: Option<Vec<RetainerJson>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    total_retainers: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     outgoing_edges: Option<Vec<OutgoingEdgeJson>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    edges_truncated: Option<EdgesTruncatedJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     shallow_size_distribution: Option<Vec<ShallowSizeBucketJson>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    raw: Option<RawRecordsJson>,
+}
+
+#[derive(Debug, Serialize)]
+struct RawRecordsJson {
+    node: Vec<RawFieldJson>,
+    edges: Vec<RawEdgeJson>,
+}
+
+#[derive(Debug, Serialize)]
+struct RawEdgeJson {
+    edge_index: usize,
+    fields: Vec<RawFieldJson>,
+}
+
+#[derive(Debug, Serialize)]
+struct RawFieldJson {
+    field: String,
+    value: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct LocationJson {
+    script_id: i64,
+    line: i64,
+    column: i64,
 }
 
 #[derive(Debug, Serialize)]
@@ -43,9 +91,22 @@ struct ConstructorSummaryJson {
     max_self_size_bytes: i64,
     min_self_size_bytes: i64,
     avg_self_size_bytes: f64,
+    median_self_size_bytes: i64,
+    p95_self_size_bytes: i64,
     skip: usize,
     limit: usize,
     total_ids: u64,
+    /// Whether ids remain after this page.
+    has_more: bool,
+    /// `skip` for the following page; `None` on the last page.
+    next_skip: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct NodeTypeBreakdownJson {
+    node_type: String,
+    count: u64,
+    self_size_sum_bytes: i64,
 }
 
 #[derive(Debug, Serialize)]
@@ -80,6 +141,12 @@ struct OutgoingEdgeJson {
     to_self_size_bytes: i64,
 }
 
+#[derive(Debug, Serialize)]
+struct EdgesTruncatedJson {
+    examined: usize,
+    total: usize,
+}
+
 #[derive(Debug, Serialize)]
 struct ShallowSizeBucketJson {
     label: String,
@@ -90,87 +157,148 @@ struct ShallowSizeBucketJson {
 }
 
 pub fn format_markdown(result: &DetailResult) -> String {
+    format_markdown_colored(result, Color::OFF, Charset::Unicode)
+}
+
+/// `format_markdown` with large self sizes in the node, retainer and edge tables
+/// highlighted when `color` is enabled, and a long constructor name cut with the
+/// `charset` ellipsis.
+pub fn format_markdown_colored(result: &DetailResult, color: Color, charset: Charset) -> String {
     match result {
-        DetailResult::ByName(payload) => format_markdown_name(payload),
-        DetailResult::ById(payload) => format_markdown_id(payload),
+        DetailResult::ByName(payload) => format_markdown_name(payload, color, charset),
+        DetailResult::ById(payload) => format_markdown_id(payload, color, charset),
     }
 }
 
-pub fn format_json(result: &DetailResult) -> Result<String, SnapshotError> {
-    let payload = match result {
+pub fn format_json(result: &DetailResult, style: JsonStyle) -> Result<String, SnapshotError> {
+    style.render(&detail_json(result))
+}
+
+/// Several `detail --id` results as one JSON array, each element shaped like `format_json`.
+pub fn format_json_many(
+    results: &[DetailResult],
+    style: JsonStyle,
+) -> Result<String, SnapshotError> {
+    let payload: Vec<DetailJson<'_>> = results.iter().map(detail_json).collect();
+    style.render(&payload)
+}
+
+/// Several results one after another, separated by a horizontal rule. Each section keeps
+/// the `# ... (id=N)` header of the single-result markdown.
+pub fn format_markdown_many(results: &[DetailResult], color: Color, charset: Charset) -> String {
+    results
+        .iter()
+        .map(|result| format_markdown_colored(result, color, charset))
+        .collect::<Vec<_>>()
+        .join("\n---\n\n")
+}
+
+fn detail_json(result: &DetailResult) -> DetailJson<'_> {
+    match result {
         DetailResult::ByName(detail) => DetailJson {
             version: 1,
+            header: JsonHeader::new(detail.snapshot_fingerprint),
             mode: "name",
             name: Some(detail.name.as_str()),
+            name_index: detail.name_index,
             id: None,
             node_type: None,
             self_size_bytes: None,
-            constructor_summary: Some(summary_json(
-                detail.total_count,
-                detail.self_size_sum,
-                detail.max_self_size,
-                detail.min_self_size,
-                detail.avg_self_size,
-                detail.skip,
-                detail.limit,
-                detail.total_ids,
-            )),
+            location: None,
+            reachability: None,
+            constructor_summary: Some(summary_json(detail)),
+            node_type_breakdown: Some(node_type_breakdown_json(&detail.node_type_breakdown)),
             ids: Some(node_refs_json(&detail.ids)),
             retainers: None,
+            total_retainers: None,
             outgoing_edges: None,
+            edges_truncated: None,
             shallow_size_distribution: None,
+            raw: None,
         },
         DetailResult::ById(detail) => DetailJson {
             version: 1,
+            header: JsonHeader::new(detail.snapshot_fingerprint),
             mode: "id",
             name: Some(detail.name.as_str()),
+            name_index: None,
             id: Some(detail.id),
             node_type: detail.node_type.as_deref(),
             self_size_bytes: Some(detail.self_size),
-            constructor_summary: Some(summary_json(
-                detail.total_count,
-                detail.self_size_sum,
-                detail.max_self_size,
-                detail.min_self_size,
-                detail.avg_self_size,
-                detail.skip,
-                detail.limit,
-                detail.total_ids,
-            )),
+            location: detail.location.map(|location| LocationJson {
+                script_id: location.script_id,
+                line: location.line,
+                column: location.column,
+            }),
+            reachability: Some(detail.reachability.kind()),
+            constructor_summary: Some(summary_json(detail)),
+            node_type_breakdown: None,
             ids: Some(node_refs_json(&detail.ids)),
             retainers: Some(retainers_json(&detail.retainers)),
+            total_retainers: Some(detail.total_retainers),
             outgoing_edges: Some(outgoing_edges_json(&detail.outgoing_edges)),
+            edges_truncated: detail.edges_truncated.map(|truncated| EdgesTruncatedJson {
+                examined: truncated.examined,
+                total: truncated.total,
+            }),
             shallow_size_distribution: Some(shallow_size_json(&detail.shallow_size_distribution)),
+            raw: detail.raw.as_ref().map(raw_records_json),
         },
-    };
-    serde_json::to_string_pretty(&payload).map_err(SnapshotError::Json)
+    }
 }
 
-pub fn format_csv(result: &DetailResult) -> String {
-    let mut output = String::new();
-    output.push_str("section,field,value,extra1,extra2,extra3,extra4,extra5,extra6\n");
+pub fn format_csv(result: &DetailResult, delimiter: char) -> String {
+    format_csv_many(std::slice::from_ref(result), delimiter)
+}
+
+/// Several results in one table under a single header; each result's rows start with its
+/// `summary` section, as in `format_csv`.
+pub fn format_csv_many(results: &[DetailResult], delimiter: char) -> String {
+    let mut output = TableWriter::with_delimiter(
+        &[
+            "section", "field", "value", "extra1", "extra2", "extra3", "extra4", "extra5", "extra6",
+        ],
+        delimiter,
+    );
+    for result in results {
+        write_csv(&mut output, result);
+    }
+    output.finish()
+}
+
+fn write_csv(output: &mut TableWriter, result: &DetailResult) {
     match result {
         DetailResult::ByName(detail) => {
-            csv_summary(&mut output, detail.name.as_str(), detail);
-            csv_ids(&mut output, &detail.ids);
+            csv_summary(output, detail.name.as_str(), detail);
+            csv_node_types(output, &detail.node_type_breakdown);
+            csv_ids(output, &detail.ids);
         }
         DetailResult::ById(detail) => {
-            csv_summary(&mut output, detail.name.as_str(), detail);
-            push_csv_row(&mut output, &["id", "", detail.id.to_string().as_str()]);
+            csv_summary(output, detail.name.as_str(), detail);
+            output.row(&["id", "", detail.id.to_string().as_str()]);
             if let Some(node_type) = detail.node_type.as_deref() {
-                push_csv_row(&mut output, &["node_type", "", node_type]);
+                output.row(&["node_type", "", node_type]);
+            }
+            output.row(&["self_size_bytes", "", detail.self_size.to_string().as_str()]);
+            output.row(&["reachability", "", detail.reachability.kind()]);
+            if let Some(location) = detail.location {
+                output.row(&[
+                    "location",
+                    "",
+                    location.script_id.to_string().as_str(),
+                    location.line.to_string().as_str(),
+                    location.column.to_string().as_str(),
+                ]);
+            }
+            csv_ids(output, &detail.ids);
+            csv_retainers(output, &detail.retainers);
+            csv_outgoing_edges(output, &detail.outgoing_edges);
+            csv_distribution(output, &detail.shallow_size_distribution);
+            if let Some(raw) = &detail.raw {
+                csv_raw(output, raw);
             }
-            push_csv_row(
-                &mut output,
-                &["self_size_bytes", "", detail.self_size.to_string().as_str()],
-            );
-            csv_ids(&mut output, &detail.ids);
-            csv_retainers(&mut output, &detail.retainers);
-            csv_outgoing_edges(&mut output, &detail.outgoing_edges);
-            csv_distribution(&mut output, &detail.shallow_size_distribution);
         }
     }
-    output
 }
 
 pub fn format_html(result: &DetailResult, source_path: &Path) -> String {
@@ -180,40 +308,102 @@ pub fn format_html(result: &DetailResult, source_path: &Path) -> String {
     }
 }
 
-fn format_markdown_name(detail: &DetailByName) -> String {
+fn format_markdown_name(detail: &DetailByName, color: Color, charset: Charset) -> String {
     let mut output = String::new();
-    write_markdown_constructor_header(&mut output, &detail.name, None);
+    write_markdown_constructor_header(&mut output, &detail.name, None, charset);
+    if let Some(name_index) = detail.name_index {
+        let _ = writeln!(output, "- String index: {name_index}");
+    }
     write_summary_markdown(&mut output, detail);
     let _ = writeln!(output, "");
+    let _ = writeln!(output, "## Node Types");
+    write_node_types_markdown(&mut output, &detail.node_type_breakdown);
+    let _ = writeln!(output);
     let _ = writeln!(output, "## Node IDs");
-    write_ids_markdown(&mut output, &detail.ids);
+    write_ids_markdown(&mut output, &detail.ids, color);
     output
 }
 
-fn format_markdown_id(detail: &DetailById) -> String {
+fn format_markdown_id(detail: &DetailById, color: Color, charset: Charset) -> String {
     let mut output = String::new();
-    write_markdown_constructor_header(&mut output, &detail.name, Some(detail.id));
+    write_markdown_constructor_header(&mut output, &detail.name, Some(detail.id), charset);
     if let Some(node_type) = detail.node_type.as_deref() {
         let _ = writeln!(output, "- Node type: {}", node_type);
     }
     let _ = writeln!(output, "- Self size: {}", detail.self_size);
+    let _ = writeln!(output, "- Reachability: {}", detail.reachability.label());
+    if let Some(location) = detail.location {
+        let _ = writeln!(output, "- Allocated at: {location}");
+    }
     write_summary_markdown(&mut output, detail);
     let _ = writeln!(output, "");
     let _ = writeln!(output, "## Node IDs");
-    write_ids_markdown(&mut output, &detail.ids);
+    write_ids_markdown(&mut output, &detail.ids, color);
     let _ = writeln!(output, "");
     let _ = writeln!(output, "## Top Retainers");
-    write_retainers_markdown(&mut output, &detail.retainers);
+    let _ = writeln!(output);
+    let _ = writeln!(
+        output,
+        "Showing top {} of {} retainers",
+        detail.retainers.len(),
+        detail.total_retainers
+    );
+    let _ = writeln!(output);
+    write_retainers_markdown(&mut output, &detail.retainers, color);
     let _ = writeln!(output, "");
     let _ = writeln!(output, "## Top Outgoing Edges");
-    write_outgoing_edges_markdown(&mut output, &detail.outgoing_edges);
+    if let Some(truncated) = detail.edges_truncated {
+        let _ = writeln!(output);
+        let _ = writeln!(output, "**{}**", edges_truncated_note(truncated));
+        let _ = writeln!(output);
+    }
+    write_outgoing_edges_markdown(&mut output, &detail.outgoing_edges, color);
     let _ = writeln!(output, "");
     let _ = writeln!(output, "## Shallow Size Distribution");
     write_distribution_markdown(&mut output, &detail.shallow_size_distribution);
+    if let Some(raw) = &detail.raw {
+        write_raw_markdown(&mut output, raw);
+    }
     output
 }
 
-fn write_markdown_constructor_header(output: &mut String, name: &str, id: Option<u64>) {
+fn write_raw_markdown(output: &mut String, raw: &RawRecords) {
+    let _ = writeln!(output);
+    let _ = writeln!(output, "## Raw Node");
+    let _ = writeln!(output, "| Field | Value |");
+    let _ = writeln!(output, "| --- | ---: |");
+    for (field, value) in &raw.node {
+        let _ = writeln!(output, "| {field} | {value} |");
+    }
+    let _ = writeln!(output);
+    let _ = writeln!(output, "## Raw Edges");
+    if raw.edges.is_empty() {
+        let _ = writeln!(output, "(none)");
+        return;
+    }
+    let fields: Vec<&str> = raw.edges[0]
+        .fields
+        .iter()
+        .map(|(field, _)| field.as_str())
+        .collect();
+    let _ = writeln!(output, "| Edge | {} |", fields.join(" | "));
+    let _ = writeln!(output, "| ---: |{}", " ---: |".repeat(fields.len()));
+    for edge in &raw.edges {
+        let values: Vec<String> = edge
+            .fields
+            .iter()
+            .map(|(_, value)| value.to_string())
+            .collect();
+        let _ = writeln!(output, "| {} | {} |", edge.edge_index, values.join(" | "));
+    }
+}
+
+fn write_markdown_constructor_header(
+    output: &mut String,
+    name: &str,
+    id: Option<u64>,
+    charset: Charset,
+) {
     let compact = normalize_header_name(name);
     let name_len = compact.chars().count();
 
@@ -227,10 +417,11 @@ fn write_markdown_constructor_header(output: &mut String, name: &str, id: Option
     }
 
     let preview = truncate_chars(&compact, HEADER_PREVIEW_MAX);
+    let ellipsis = charset.ellipsis();
     if let Some(id) = id {
-        let _ = writeln!(output, "# Detail: {}… (id={})", preview, id);
+        let _ = writeln!(output, "# Detail: {preview}{ellipsis} (id={id})");
     } else {
-        let _ = writeln!(output, "# Detail: {}…", preview);
+        let _ = writeln!(output, "# Detail: {preview}{ellipsis}");
     }
     let _ = writeln!(output, "- Constructor chars: {}", name_len);
     write_markdown_constructor_limit_note(output, name_len);
@@ -262,6 +453,8 @@ where
     let _ = writeln!(output, "- Max self size: {}", detail.max_self_size());
     let _ = writeln!(output, "- Min self size: {}", detail.min_self_size());
     let _ = writeln!(output, "- Avg self size: {:.2}", detail.avg_self_size());
+    let _ = writeln!(output, "- Median self size: {}", detail.median_self_size());
+    let _ = writeln!(output, "- P95 self size: {}", detail.p95_self_size());
     let _ = writeln!(
         output,
         "- IDs (showing {}..{} of {}):",
@@ -271,27 +464,33 @@ where
     );
 }
 
-fn write_ids_markdown(output: &mut String, ids: &[crate::analysis::detail::NodeRef]) {
+fn write_ids_markdown(output: &mut String, ids: &[crate::analysis::detail::NodeRef], color: Color) {
     let _ = writeln!(output, "| Index | Node ID | Self Size | Node Type |");
     let _ = writeln!(output, "| ---: | ---: | ---: | --- |");
+    let max_size = ids.iter().map(|item| item.self_size).max().unwrap_or(0);
     for item in ids {
         let _ = writeln!(
             output,
             "| {} | {} | {} | {} |",
             item.index,
             item.id.unwrap_or(-1),
-            item.self_size,
+            color.size(item.self_size, max_size),
             item.node_type.as_deref().unwrap_or("")
         );
     }
 }
 
-fn write_retainers_markdown(output: &mut String, retainers: &[RetainerSummary]) {
+fn write_retainers_markdown(output: &mut String, retainers: &[RetainerSummary], color: Color) {
     let _ = writeln!(
         output,
         "| From Index | From ID | From Name | From Type | From Self Size | Edge Type | Edge Name |"
     );
     let _ = writeln!(output, "| ---: | ---: | --- | --- | ---: | --- | --- |");
+    let max_size = retainers
+        .iter()
+        .map(|item| item.from_self_size)
+        .max()
+        .unwrap_or(0);
     for item in retainers {
         let _ = writeln!(
             output,
@@ -300,14 +499,22 @@ fn write_retainers_markdown(output: &mut String, retainers: &[RetainerSummary])
             item.from_id.unwrap_or(-1),
             item.from_name.as_deref().unwrap_or(""),
             item.from_node_type.as_deref().unwrap_or(""),
-            item.from_self_size,
+            color.size(item.from_self_size, max_size),
             item.edge_type.as_deref().unwrap_or(""),
             item.edge_name.as_deref().unwrap_or("")
         );
     }
 }
 
-fn write_outgoing_edges_markdown(output: &mut String, edges: &[OutgoingEdgeSummary]) {
+/// "Edges truncated: examined the first 1000 of 2500000 outgoing edges".
+fn edges_truncated_note(truncated: EdgesTruncated) -> String {
+    format!(
+        "Edges truncated: examined the first {} of {} outgoing edges (--max-edges-per-node)",
+        truncated.examined, truncated.total
+    )
+}
+
+fn write_outgoing_edges_markdown(output: &mut String, edges: &[OutgoingEdgeSummary], color: Color) {
     let _ = writeln!(
         output,
         "| Edge Index | Edge Type | Edge Name | To Index | To ID | To Name | To Type | To Self Size |"
@@ -316,6 +523,11 @@ fn write_outgoing_edges_markdown(output: &mut String, edges: &[OutgoingEdgeSumma
         output,
         "| ---: | --- | --- | ---: | ---: | --- | --- | ---: |"
     );
+    let max_size = edges
+        .iter()
+        .map(|item| item.to_self_size)
+        .max()
+        .unwrap_or(0);
     for item in edges {
         let _ = writeln!(
             output,
@@ -327,12 +539,20 @@ fn write_outgoing_edges_markdown(output: &mut String, edges: &[OutgoingEdgeSumma
             item.to_id.unwrap_or(-1),
             item.to_name.as_deref().unwrap_or(""),
             item.to_node_type.as_deref().unwrap_or(""),
-            item.to_self_size
+            color.size(item.to_self_size, max_size)
         );
     }
 }
 
-fn write_distribution_markdown(output: &mut String, buckets: &[ShallowSizeBucket]) {
+fn write_node_types_markdown(output: &mut String, breakdown: &[(String, u64, i64)]) {
+    let _ = writeln!(output, "| Node Type | Count | Self Size Sum (bytes) |");
+    let _ = writeln!(output, "| --- | ---: | ---: |");
+    for (node_type, count, self_size_sum) in breakdown {
+        let _ = writeln!(output, "| {node_type} | {count} | {self_size_sum} |");
+    }
+}
+
+fn write_distribution_markdown(output: &mut String, buckets: &[SizeBucket]) {
     let _ = writeln!(output, "| Bucket | Min | Max | Count |");
     let _ = writeln!(output, "| --- | ---: | ---: | ---: |");
     for item in buckets {
@@ -365,6 +585,8 @@ fn format_html_name(detail: &DetailByName, source_path: &Path) -> String {
     );
     write_html_constructor_header(&mut output, &detail.name, None);
     write_summary_html(&mut output, detail);
+    let _ = writeln!(output, "<h3>Node Types</h3>");
+    write_node_types_html(&mut output, &detail.node_type_breakdown);
     let _ = writeln!(output, "<h3>Node IDs</h3>");
     write_ids_html(&mut output, &detail.ids);
     let _ = writeln!(
@@ -402,12 +624,37 @@ fn format_html_id(detail: &DetailById, source_path: &Path) -> String {
         "<p><strong>Self size:</strong> {}</p>",
         detail.self_size
     );
+    let _ = writeln!(
+        output,
+        "<p><strong>Reachability:</strong> {}</p>",
+        detail.reachability.label()
+    );
+    if let Some(location) = detail.location {
+        let _ = writeln!(
+            output,
+            "<p><strong>Allocated at:</strong> {}</p>",
+            escape_html_inline(&location.to_string())
+        );
+    }
     write_summary_html(&mut output, detail);
     let _ = writeln!(output, "<h3>Node IDs</h3>");
     write_ids_html(&mut output, &detail.ids);
     let _ = writeln!(output, "<h3>Top Retainers</h3>");
+    let _ = writeln!(
+        output,
+        "<p>Showing top {} of {} retainers</p>",
+        detail.retainers.len(),
+        detail.total_retainers
+    );
     write_retainers_html(&mut output, &detail.retainers);
     let _ = writeln!(output, "<h3>Top Outgoing Edges</h3>");
+    if let Some(truncated) = detail.edges_truncated {
+        let _ = writeln!(
+            output,
+            "<p><strong>{}</strong></p>",
+            edges_truncated_note(truncated)
+        );
+    }
     write_outgoing_edges_html(&mut output, &detail.outgoing_edges);
     let _ = writeln!(output, "<h3>Shallow Size Distribution</h3>");
     write_distribution_html(&mut output, &detail.shallow_size_distribution);
@@ -486,6 +733,12 @@ where
         "<li>Avg self size: {:.2}</li>",
         detail.avg_self_size()
     );
+    let _ = writeln!(
+        output,
+        "<li>Median self size: {}</li>",
+        detail.median_self_size()
+    );
+    let _ = writeln!(output, "<li>P95 self size: {}</li>", detail.p95_self_size());
     let _ = writeln!(
         output,
         "<li>IDs (showing {}..{} of {}):</li>",
@@ -558,7 +811,24 @@ fn write_outgoing_edges_html(output: &mut String, edges: &[OutgoingEdgeSummary])
     let _ = writeln!(output, "</tbody></table>");
 }
 
-fn write_distribution_html(output: &mut String, buckets: &[ShallowSizeBucket]) {
+fn write_node_types_html(output: &mut String, breakdown: &[(String, u64, i64)]) {
+    let _ = writeln!(
+        output,
+        "<table><thead><tr><th>Node Type</th><th>Count</th><th>Self Size Sum (bytes)</th></tr></thead><tbody>"
+    );
+    for (node_type, count, self_size_sum) in breakdown {
+        let _ = writeln!(
+            output,
+            "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape_html_inline(node_type),
+            count,
+            self_size_sum
+        );
+    }
+    let _ = writeln!(output, "</tbody></table>");
+}
+
+fn write_distribution_html(output: &mut String, buckets: &[SizeBucket]) {
     let _ = writeln!(
         output,
         "<table><thead><tr><th>Bucket</th><th>Min</th><th>Max</th><th>Count</th></tr></thead><tbody>"
@@ -577,25 +847,25 @@ fn write_distribution_html(output: &mut String, buckets: &[ShallowSizeBucket]) {
     let _ = writeln!(output, "</tbody></table>");
 }
 
-fn summary_json(
-    total_count: u64,
-    self_size_sum: i64,
-    max_self_size: i64,
-    min_self_size: i64,
-    avg_self_size: f64,
-    skip: usize,
-    limit: usize,
-    total_ids: u64,
-) -> ConstructorSummaryJson {
+fn summary_json<T>(detail: &T) -> ConstructorSummaryJson
+where
+    T: DetailSummaryView,
+{
+    let next = detail.skip() + detail.ids().len();
+    let has_more = (next as u64) < detail.total_ids();
     ConstructorSummaryJson {
-        total_count,
-        self_size_sum_bytes: self_size_sum,
-        max_self_size_bytes: max_self_size,
-        min_self_size_bytes: min_self_size,
-        avg_self_size_bytes: avg_self_size,
-        skip,
-        limit,
-        total_ids,
+        total_count: detail.total_count(),
+        self_size_sum_bytes: detail.self_size_sum(),
+        max_self_size_bytes: detail.max_self_size(),
+        min_self_size_bytes: detail.min_self_size(),
+        avg_self_size_bytes: detail.avg_self_size(),
+        median_self_size_bytes: detail.median_self_size(),
+        p95_self_size_bytes: detail.p95_self_size(),
+        skip: detail.skip(),
+        limit: detail.limit(),
+        total_ids: detail.total_ids(),
+        has_more,
+        next_skip: has_more.then_some(next),
     }
 }
 
@@ -643,7 +913,18 @@ fn outgoing_edges_json(items: &[OutgoingEdgeSummary]) -> Vec<OutgoingEdgeJson> {
         .collect()
 }
 
-fn shallow_size_json(items: &[ShallowSizeBucket]) -> Vec<ShallowSizeBucketJson> {
+fn node_type_breakdown_json(breakdown: &[(String, u64, i64)]) -> Vec<NodeTypeBreakdownJson> {
+    breakdown
+        .iter()
+        .map(|(node_type, count, self_size_sum)| NodeTypeBreakdownJson {
+            node_type: node_type.clone(),
+            count: *count,
+            self_size_sum_bytes: *self_size_sum,
+        })
+        .collect()
+}
+
+fn shallow_size_json(items: &[SizeBucket]) -> Vec<ShallowSizeBucketJson> {
     items
         .iter()
         .map(|item| ShallowSizeBucketJson {
@@ -655,148 +936,159 @@ fn shallow_size_json(items: &[ShallowSizeBucket]) -> Vec<ShallowSizeBucketJson>
         .collect()
 }
 
-fn csv_summary<T>(output: &mut String, name: &str, detail: &T)
+fn raw_records_json(raw: &RawRecords) -> RawRecordsJson {
+    RawRecordsJson {
+        node: raw_fields_json(&raw.node),
+        edges: raw
+            .edges
+            .iter()
+            .map(|edge| RawEdgeJson {
+                edge_index: edge.edge_index,
+                fields: raw_fields_json(&edge.fields),
+            })
+            .collect(),
+    }
+}
+
+fn raw_fields_json(fields: &[(String, i64)]) -> Vec<RawFieldJson> {
+    fields
+        .iter()
+        .map(|(field, value)| RawFieldJson {
+            field: field.clone(),
+            value: *value,
+        })
+        .collect()
+}
+
+fn csv_summary<T>(output: &mut TableWriter, name: &str, detail: &T)
 where
     T: DetailSummaryView,
 {
-    push_csv_row(output, &["summary", "name", name]);
-    push_csv_row(
-        output,
-        &[
-            "summary",
-            "total_count",
-            detail.total_count().to_string().as_str(),
-        ],
-    );
-    push_csv_row(
-        output,
-        &[
-            "summary",
-            "self_size_sum_bytes",
-            detail.self_size_sum().to_string().as_str(),
-        ],
-    );
-    push_csv_row(
-        output,
-        &[
-            "summary",
-            "max_self_size_bytes",
-            detail.max_self_size().to_string().as_str(),
-        ],
-    );
-    push_csv_row(
-        output,
-        &[
-            "summary",
-            "min_self_size_bytes",
-            detail.min_self_size().to_string().as_str(),
-        ],
-    );
-    push_csv_row(
-        output,
-        &[
-            "summary",
-            "avg_self_size_bytes",
-            format!("{:.2}", detail.avg_self_size()).as_str(),
-        ],
-    );
-    push_csv_row(
-        output,
-        &["summary", "skip", detail.skip().to_string().as_str()],
-    );
-    push_csv_row(
-        output,
-        &["summary", "limit", detail.limit().to_string().as_str()],
-    );
-    push_csv_row(
-        output,
-        &[
-            "summary",
-            "total_ids",
-            detail.total_ids().to_string().as_str(),
-        ],
-    );
+    output.row(&["summary", "name", name]);
+    output.row(&[
+        "summary",
+        "total_count",
+        detail.total_count().to_string().as_str(),
+    ]);
+    output.row(&[
+        "summary",
+        "self_size_sum_bytes",
+        detail.self_size_sum().to_string().as_str(),
+    ]);
+    output.row(&[
+        "summary",
+        "max_self_size_bytes",
+        detail.max_self_size().to_string().as_str(),
+    ]);
+    output.row(&[
+        "summary",
+        "min_self_size_bytes",
+        detail.min_self_size().to_string().as_str(),
+    ]);
+    output.row(&[
+        "summary",
+        "avg_self_size_bytes",
+        format!("{:.2}", detail.avg_self_size()).as_str(),
+    ]);
+    output.row(&[
+        "summary",
+        "median_self_size_bytes",
+        detail.median_self_size().to_string().as_str(),
+    ]);
+    output.row(&[
+        "summary",
+        "p95_self_size_bytes",
+        detail.p95_self_size().to_string().as_str(),
+    ]);
+    output.row(&["summary", "skip", detail.skip().to_string().as_str()]);
+    output.row(&["summary", "limit", detail.limit().to_string().as_str()]);
+    output.row(&[
+        "summary",
+        "total_ids",
+        detail.total_ids().to_string().as_str(),
+    ]);
+}
+
+fn csv_node_types(output: &mut TableWriter, breakdown: &[(String, u64, i64)]) {
+    for (node_type, count, self_size_sum) in breakdown {
+        output.row(&[
+            "node_types",
+            node_type.as_str(),
+            count.to_string().as_str(),
+            self_size_sum.to_string().as_str(),
+        ]);
+    }
 }
 
-fn csv_ids(output: &mut String, ids: &[crate::analysis::detail::NodeRef]) {
+fn csv_ids(output: &mut TableWriter, ids: &[crate::analysis::detail::NodeRef]) {
     for item in ids {
-        push_csv_row(
-            output,
-            &[
-                "ids",
-                item.index.to_string().as_str(),
-                item.id.unwrap_or(-1).to_string().as_str(),
-                item.self_size.to_string().as_str(),
-                item.node_type.as_deref().unwrap_or(""),
-            ],
-        );
+        output.row(&[
+            "ids",
+            item.index.to_string().as_str(),
+            item.id.unwrap_or(-1).to_string().as_str(),
+            item.self_size.to_string().as_str(),
+            item.node_type.as_deref().unwrap_or(""),
+        ]);
     }
 }
 
-fn csv_retainers(output: &mut String, retainers: &[RetainerSummary]) {
+fn csv_retainers(output: &mut TableWriter, retainers: &[RetainerSummary]) {
     for item in retainers {
-        push_csv_row(
-            output,
-            &[
-                "retainers",
-                item.from_index.to_string().as_str(),
-                item.from_id.unwrap_or(-1).to_string().as_str(),
-                item.from_name.as_deref().unwrap_or(""),
-                item.from_node_type.as_deref().unwrap_or(""),
-                item.from_self_size.to_string().as_str(),
-                item.edge_type.as_deref().unwrap_or(""),
-                item.edge_name.as_deref().unwrap_or(""),
-            ],
-        );
+        output.row(&[
+            "retainers",
+            item.from_index.to_string().as_str(),
+            item.from_id.unwrap_or(-1).to_string().as_str(),
+            item.from_name.as_deref().unwrap_or(""),
+            item.from_node_type.as_deref().unwrap_or(""),
+            item.from_self_size.to_string().as_str(),
+            item.edge_type.as_deref().unwrap_or(""),
+            item.edge_name.as_deref().unwrap_or(""),
+        ]);
     }
 }
 
-fn csv_outgoing_edges(output: &mut String, edges: &[OutgoingEdgeSummary]) {
+fn csv_outgoing_edges(output: &mut TableWriter, edges: &[OutgoingEdgeSummary]) {
     for item in edges {
-        push_csv_row(
-            output,
-            &[
-                "outgoing_edges",
-                item.edge_index.to_string().as_str(),
-                item.edge_type.as_deref().unwrap_or(""),
-                item.edge_name.as_deref().unwrap_or(""),
-                item.to_index.to_string().as_str(),
-                item.to_id.unwrap_or(-1).to_string().as_str(),
-                item.to_name.as_deref().unwrap_or(""),
-                item.to_node_type.as_deref().unwrap_or(""),
-                item.to_self_size.to_string().as_str(),
-            ],
-        );
+        output.row(&[
+            "outgoing_edges",
+            item.edge_index.to_string().as_str(),
+            item.edge_type.as_deref().unwrap_or(""),
+            item.edge_name.as_deref().unwrap_or(""),
+            item.to_index.to_string().as_str(),
+            item.to_id.unwrap_or(-1).to_string().as_str(),
+            item.to_name.as_deref().unwrap_or(""),
+            item.to_node_type.as_deref().unwrap_or(""),
+            item.to_self_size.to_string().as_str(),
+        ]);
     }
 }
 
-fn csv_distribution(output: &mut String, buckets: &[ShallowSizeBucket]) {
+fn csv_distribution(output: &mut TableWriter, buckets: &[SizeBucket]) {
     for item in buckets {
-        push_csv_row(
-            output,
-            &[
-                "distribution",
-                item.label.as_str(),
-                item.min.to_string().as_str(),
-                item.max.map(|v| v.to_string()).unwrap_or_default().as_str(),
-                item.count.to_string().as_str(),
-            ],
-        );
+        output.row(&[
+            "distribution",
+            item.label.as_str(),
+            item.min.to_string().as_str(),
+            item.max.map(|v| v.to_string()).unwrap_or_default().as_str(),
+            item.count.to_string().as_str(),
+        ]);
     }
 }
 
-fn push_csv_row(output: &mut String, fields: &[&str]) {
-    let mut first = true;
-    for field in fields {
-        if !first {
-            output.push(',');
+fn csv_raw(output: &mut TableWriter, raw: &RawRecords) {
+    for (field, value) in &raw.node {
+        output.row(&["raw_node", field.as_str(), value.to_string().as_str()]);
+    }
+    for edge in &raw.edges {
+        for (field, value) in &edge.fields {
+            output.row(&[
+                "raw_edge",
+                field.as_str(),
+                value.to_string().as_str(),
+                edge.edge_index.to_string().as_str(),
+            ]);
         }
-        first = false;
-        output.push('"');
-        output.push_str(&field.replace('"', "\"\""));
-        output.push('"');
     }
-    output.push('\n');
 }
 
 trait DetailSummaryView {
@@ -805,6 +1097,8 @@ trait DetailSummaryView {
     fn max_self_size(&self) -> i64;
     fn min_self_size(&self) -> i64;
     fn avg_self_size(&self) -> f64;
+    fn median_self_size(&self) -> i64;
+    fn p95_self_size(&self) -> i64;
     fn ids(&self) -> &[crate::analysis::detail::NodeRef];
     fn skip(&self) -> usize;
     fn limit(&self) -> usize;
@@ -827,6 +1121,12 @@ impl DetailSummaryView for DetailByName {
     fn avg_self_size(&self) -> f64 {
         self.avg_self_size
     }
+    fn median_self_size(&self) -> i64 {
+        self.median_self_size
+    }
+    fn p95_self_size(&self) -> i64 {
+        self.p95_self_size
+    }
     fn ids(&self) -> &[crate::analysis::detail::NodeRef] {
         &self.ids
     }
@@ -857,6 +1157,12 @@ impl DetailSummaryView for DetailById {
     fn avg_self_size(&self) -> f64 {
         self.avg_self_size
     }
+    fn median_self_size(&self) -> i64 {
+        self.median_self_size
+    }
+    fn p95_self_size(&self) -> i64 {
+        self.p95_self_size
+    }
     fn ids(&self) -> &[crate::analysis::detail::NodeRef] {
         &self.ids
     }
@@ -1,13 +1,22 @@
+use std::cmp::Ordering;
 use std::fmt::Write as _;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::analysis::diff::DiffResult;
+use crate::analysis::diff::{DiffResult, DiffRow, DiffSeriesResult, Trend};
 use crate::error::SnapshotError;
+use crate::output::charset::Charset;
+use crate::output::color::Color;
+use crate::output::csv::TableWriter;
+use crate::output::header::{TOOL_VERSION, fingerprint_hex, parse_fingerprint};
+use crate::output::json::JsonStyle;
 
 #[derive(Debug, Serialize)]
 struct DiffJson<'a> {
     version: u32,
+    tool_version: &'static str,
+    snapshot_fingerprint_a: String,
+    snapshot_fingerprint_b: String,
     total_nodes_a: usize,
     total_nodes_b: usize,
     rows: Vec<DiffRowJson<'a>>,
@@ -27,7 +36,72 @@ struct DiffRowJson<'a> {
     self_size_sum_delta_bytes: i64,
 }
 
+/// Owned counterpart of `DiffJson` for reading a saved diff back.
+#[derive(Debug, Deserialize)]
+struct DiffJsonInput {
+    version: u32,
+    #[serde(default)]
+    snapshot_fingerprint_a: Option<String>,
+    #[serde(default)]
+    snapshot_fingerprint_b: Option<String>,
+    total_nodes_a: usize,
+    total_nodes_b: usize,
+    rows: Vec<DiffRowJsonInput>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DiffRowJsonInput {
+    name: String,
+    count_a: u64,
+    count_b: u64,
+    count_delta: i64,
+    self_size_sum_a_bytes: i64,
+    self_size_sum_b_bytes: i64,
+    self_size_sum_delta_bytes: i64,
+}
+
+/// Reads the output of `format_json` back into a `DiffResult`.
+pub fn parse_json(text: &str) -> Result<DiffResult, SnapshotError> {
+    let input: DiffJsonInput =
+        serde_json::from_str(text).map_err(|err| SnapshotError::InvalidData {
+            details: format!("not a heapsnap diff json: {err}"),
+        })?;
+    if input.version != 1 {
+        return Err(SnapshotError::InvalidData {
+            details: format!(
+                "unsupported diff json version {} (expected 1)",
+                input.version
+            ),
+        });
+    }
+    Ok(DiffResult {
+        total_nodes_a: input.total_nodes_a,
+        total_nodes_b: input.total_nodes_b,
+        snapshot_fingerprint_a: parse_fingerprint(input.snapshot_fingerprint_a.as_deref())?,
+        snapshot_fingerprint_b: parse_fingerprint(input.snapshot_fingerprint_b.as_deref())?,
+        rows: input
+            .rows
+            .into_iter()
+            .map(|row| DiffRow {
+                name: row.name,
+                count_a: row.count_a,
+                count_b: row.count_b,
+                count_delta: row.count_delta,
+                self_size_sum_a: row.self_size_sum_a_bytes,
+                self_size_sum_b: row.self_size_sum_b_bytes,
+                self_size_sum_delta: row.self_size_sum_delta_bytes,
+            })
+            .collect(),
+    })
+}
+
 pub fn format_markdown(result: &DiffResult) -> String {
+    format_markdown_colored(result, Color::OFF, Charset::Unicode)
+}
+
+/// `format_markdown` with growth in red and shrinkage in green when `color` is enabled,
+/// and the delta columns labelled in `charset`.
+pub fn format_markdown_colored(result: &DiffResult, color: Color, charset: Charset) -> String {
     let mut output = String::new();
     let _ = writeln!(output, "# HeapSnapshot Diff");
     let _ = writeln!(
@@ -36,9 +110,10 @@ pub fn format_markdown(result: &DiffResult) -> String {
         result.total_nodes_a, result.total_nodes_b
     );
     let _ = writeln!(output, "");
+    let delta = charset.delta();
     let _ = writeln!(
         output,
-        "| Constructor | Count A | Count B | Δ Count | Self Size A (bytes) | Self Size B (bytes) | Δ Self Size (bytes) |"
+        "| Constructor | Count A | Count B | {delta} Count | Self Size A (bytes) | Self Size B (bytes) | {delta} Self Size (bytes) |"
     );
     let _ = writeln!(output, "| --- | ---: | ---: | ---: | ---: | ---: | ---: |");
     for row in &result.rows {
@@ -48,16 +123,16 @@ pub fn format_markdown(result: &DiffResult) -> String {
             escape_table(row.name.as_str()),
             row.count_a,
             row.count_b,
-            row.count_delta,
+            color.delta(row.count_delta),
             row.self_size_sum_a,
             row.self_size_sum_b,
-            row.self_size_sum_delta
+            color.delta(row.self_size_sum_delta)
         );
     }
     output
 }
 
-pub fn format_json(result: &DiffResult) -> Result<String, SnapshotError> {
+pub fn format_json(result: &DiffResult, style: JsonStyle) -> Result<String, SnapshotError> {
     let rows = result
         .rows
         .iter()
@@ -73,39 +148,240 @@ pub fn format_json(result: &DiffResult) -> Result<String, SnapshotError> {
         .collect::<Vec<_>>();
     let payload = DiffJson {
         version: 1,
+        tool_version: TOOL_VERSION,
+        snapshot_fingerprint_a: fingerprint_hex(result.snapshot_fingerprint_a),
+        snapshot_fingerprint_b: fingerprint_hex(result.snapshot_fingerprint_b),
         total_nodes_a: result.total_nodes_a,
         total_nodes_b: result.total_nodes_b,
         rows,
     };
-    serde_json::to_string_pretty(&payload).map_err(SnapshotError::Json)
+    style.render(&payload)
 }
 
-pub fn format_csv(result: &DiffResult) -> String {
+pub fn format_csv(result: &DiffResult, delimiter: char) -> String {
+    let mut writer = TableWriter::with_delimiter(
+        &[
+            "constructor",
+            "count_a",
+            "count_b",
+            "count_delta",
+            "self_size_a_bytes",
+            "self_size_b_bytes",
+            "self_size_delta_bytes",
+        ],
+        delimiter,
+    );
+    for row in &result.rows {
+        writer.row(&[
+            row.name.as_str(),
+            row.count_a.to_string().as_str(),
+            row.count_b.to_string().as_str(),
+            row.count_delta.to_string().as_str(),
+            row.self_size_sum_a.to_string().as_str(),
+            row.self_size_sum_b.to_string().as_str(),
+            row.self_size_sum_delta.to_string().as_str(),
+        ]);
+    }
+    writer.finish()
+}
+
+#[derive(Debug, Serialize)]
+struct DiffSeriesJson<'a> {
+    version: u32,
+    tool_version: &'static str,
+    snapshot_fingerprints: Vec<String>,
+    total_nodes: &'a [usize],
+    rows: Vec<DiffSeriesRowJson<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct DiffSeriesRowJson<'a> {
+    name: &'a str,
+    counts: &'a [u64],
+    self_size_sums_bytes: &'a [i64],
+    count_delta: i64,
+    self_size_sum_delta_bytes: i64,
+    trend: Trend,
+}
+
+/// One column per snapshot (`T0`, `T1`, ...) plus the first-to-last delta and trend.
+/// With `Charset::Ascii` the trend is spelled out as in the JSON `trend` field.
+pub fn format_series_markdown(result: &DiffSeriesResult, charset: Charset) -> String {
     let mut output = String::new();
-    output.push_str(
-        "constructor,count_a,count_b,count_delta,self_size_a_bytes,self_size_b_bytes,self_size_delta_bytes\n",
+    let _ = writeln!(output, "# HeapSnapshot Diff Series");
+    let totals: Vec<String> = result
+        .total_nodes
+        .iter()
+        .enumerate()
+        .map(|(index, total)| format!("T{index}={total}"))
+        .collect();
+    let _ = writeln!(output, "- Total nodes: {}", totals.join(" / "));
+    if charset == Charset::Unicode {
+        let _ = writeln!(
+            output,
+            "- Trend: ↑ grew every snapshot, ↓ shrank every snapshot, = unchanged, ∿ noisy"
+        );
+    }
+    let _ = writeln!(output);
+
+    let columns: Vec<String> = (0..result.total_nodes.len())
+        .map(|index| format!("T{index}"))
+        .collect();
+    let delta = charset.delta();
+    let _ = writeln!(
+        output,
+        "| Constructor | Trend | {} | {delta} Count | {delta} Self Size (bytes) |",
+        columns
+            .iter()
+            .map(|column| format!("Count {column} | Self Size {column} (bytes)"))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    );
+    let _ = writeln!(
+        output,
+        "| --- | :---: |{} ---: | ---: |",
+        " ---: | ---: |".repeat(columns.len())
     );
     for row in &result.rows {
-        output.push('"');
-        output.push_str(&row.name.replace('"', "\"\""));
-        output.push('"');
-        output.push(',');
-        output.push_str(&row.count_a.to_string());
-        output.push(',');
-        output.push_str(&row.count_b.to_string());
-        output.push(',');
-        output.push_str(&row.count_delta.to_string());
-        output.push(',');
-        output.push_str(&row.self_size_sum_a.to_string());
-        output.push(',');
-        output.push_str(&row.self_size_sum_b.to_string());
-        output.push(',');
-        output.push_str(&row.self_size_sum_delta.to_string());
-        output.push('\n');
+        let cells: Vec<String> = row
+            .counts
+            .iter()
+            .zip(&row.self_size_sums)
+            .map(|(count, size)| format!("{count} | {size}"))
+            .collect();
+        let _ = writeln!(
+            output,
+            "| {} | {} | {} | {} | {} |",
+            escape_table(row.name.as_str()),
+            match charset {
+                Charset::Unicode => row.trend.symbol(),
+                Charset::Ascii => trend_label(row.trend),
+            },
+            cells.join(" | "),
+            row.count_delta,
+            row.self_size_sum_delta
+        );
     }
     output
 }
 
+pub fn format_series_json(
+    result: &DiffSeriesResult,
+    style: JsonStyle,
+) -> Result<String, SnapshotError> {
+    let payload = DiffSeriesJson {
+        version: 1,
+        tool_version: TOOL_VERSION,
+        snapshot_fingerprints: result
+            .snapshot_fingerprints
+            .iter()
+            .map(|fingerprint| fingerprint_hex(*fingerprint))
+            .collect(),
+        total_nodes: &result.total_nodes,
+        rows: result
+            .rows
+            .iter()
+            .map(|row| DiffSeriesRowJson {
+                name: row.name.as_str(),
+                counts: &row.counts,
+                self_size_sums_bytes: &row.self_size_sums,
+                count_delta: row.count_delta,
+                self_size_sum_delta_bytes: row.self_size_sum_delta,
+                trend: row.trend,
+            })
+            .collect(),
+    };
+    style.render(&payload)
+}
+
+pub fn format_series_csv(result: &DiffSeriesResult, delimiter: char) -> String {
+    let mut header = vec!["constructor".to_string(), "trend".to_string()];
+    for index in 0..result.total_nodes.len() {
+        header.push(format!("count_t{index}"));
+        header.push(format!("self_size_t{index}_bytes"));
+    }
+    header.push("count_delta".to_string());
+    header.push("self_size_delta_bytes".to_string());
+    let header: Vec<&str> = header.iter().map(String::as_str).collect();
+
+    let mut writer = TableWriter::with_delimiter(&header, delimiter);
+    for row in &result.rows {
+        let mut cells = vec![row.name.clone(), trend_label(row.trend).to_string()];
+        for (count, size) in row.counts.iter().zip(&row.self_size_sums) {
+            cells.push(count.to_string());
+            cells.push(size.to_string());
+        }
+        cells.push(row.count_delta.to_string());
+        cells.push(row.self_size_sum_delta.to_string());
+        let cells: Vec<&str> = cells.iter().map(String::as_str).collect();
+        writer.row(&cells);
+    }
+    writer.finish()
+}
+
+/// Same spelling as the JSON `trend` field.
+fn trend_label(trend: Trend) -> &'static str {
+    match trend {
+        Trend::Growing => "growing",
+        Trend::Shrinking => "shrinking",
+        Trend::Flat => "flat",
+        Trend::Noisy => "noisy",
+    }
+}
+
+/// Renders one line per constructor, prefixed like a unified diff by the sign of its
+/// delta: `+` grew, `-` shrank, `~` unchanged. The self size delta decides; the count
+/// delta breaks a tie.
+pub fn format_unified(result: &DiffResult, charset: Charset) -> String {
+    let arrow = charset.arrow();
+    let mut output = String::new();
+    let _ = writeln!(output, "--- A (nodes={})", result.total_nodes_a);
+    let _ = writeln!(output, "+++ B (nodes={})", result.total_nodes_b);
+    for row in &result.rows {
+        let marker = match row
+            .self_size_sum_delta
+            .cmp(&0)
+            .then(row.count_delta.cmp(&0))
+        {
+            Ordering::Greater => '+',
+            Ordering::Less => '-',
+            Ordering::Equal => '~',
+        };
+        let _ = writeln!(
+            output,
+            "{marker} {}  count {}{arrow}{}  size {}{arrow}{}",
+            single_line(row.name.as_str()),
+            row.count_a,
+            row.count_b,
+            format_size(row.self_size_sum_a),
+            format_size(row.self_size_sum_b)
+        );
+    }
+    output
+}
+
+fn single_line(value: &str) -> String {
+    value.replace('\r', "").replace('\n', " ")
+}
+
+fn format_size(bytes: i64) -> String {
+    const KIB: f64 = 1024.0;
+    const MIB: f64 = 1024.0 * 1024.0;
+    const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+    let value = bytes as f64;
+    let abs = value.abs();
+    if abs >= GIB {
+        format!("{:.1}GiB", value / GIB)
+    } else if abs >= MIB {
+        format!("{:.1}MiB", value / MIB)
+    } else if abs >= KIB {
+        format!("{:.1}KiB", value / KIB)
+    } else {
+        format!("{bytes}B")
+    }
+}
+
 fn escape_table(value: &str) -> String {
     value.replace('|', "\\|")
 }
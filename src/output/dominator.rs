@@ -1,16 +1,101 @@
+use std::borrow::Cow;
+use std::collections::BinaryHeap;
 use std::fmt::Write as _;
 
 use serde::Serialize;
 
-use crate::analysis::dominator::DominatorResult;
+use crate::analysis::dominator::{
+    DominatedResult, DominatorIndex, DominatorResult, retained_sizes,
+};
+use crate::analysis::unreachable::{NearbyRetainer, Unreachable, UnreachableReason};
 use crate::error::SnapshotError;
+use crate::output::header::JsonHeader;
+use crate::output::json::JsonStyle;
+use crate::output::retainers::{edge_name, node_id};
 use crate::snapshot::SnapshotRaw;
 
 #[derive(Debug, Serialize)]
 struct DominatorJson {
     version: u32,
+    #[serde(flatten)]
+    header: JsonHeader,
     target: NodeJson,
-    chain: Vec<NodeJson>,
+    total_size_bytes: i64,
+    chain: Vec<ChainNodeJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unreachable: Option<UnreachableJson>,
+}
+
+#[derive(Debug, Serialize)]
+struct DominatedJson {
+    version: u32,
+    #[serde(flatten)]
+    header: JsonHeader,
+    target: NodeJson,
+    retained_size_bytes: i64,
+    retained_percent: f64,
+    total_size_bytes: i64,
+    total_children: usize,
+    children: Vec<ChainNodeJson>,
+    omitted_children: usize,
+    omitted_retained_size_bytes: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unreachable: Option<UnreachableJson>,
+}
+
+#[derive(Debug, Serialize)]
+struct UnreachableJson {
+    reason: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    edge_types: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    island_size: Option<usize>,
+    explored: usize,
+    retainers: Vec<NearbyRetainerJson>,
+}
+
+#[derive(Debug, Serialize)]
+struct NearbyRetainerJson {
+    depth: usize,
+    #[serde(flatten)]
+    node: NodeJson,
+    edge_type: Option<String>,
+    edge_name: Option<String>,
+    reachable: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ChainNodeJson {
+    #[serde(flatten)]
+    node: NodeJson,
+    retained_size_bytes: i64,
+    retained_percent: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct FullTreeJson {
+    version: u32,
+    #[serde(flatten)]
+    header: JsonHeader,
+    total_size_bytes: i64,
+    /// Nodes in the dominator tree (reachable from the roots).
+    reachable_nodes: usize,
+    /// Nodes written below `roots`; less than `reachable_nodes` when `--max-nodes` cut it.
+    emitted_nodes: usize,
+    truncated: bool,
+    roots: Vec<FullTreeNodeJson>,
+}
+
+#[derive(Debug, Serialize)]
+struct FullTreeNodeJson {
+    id: Option<i64>,
+    name: Option<String>,
+    self_size: i64,
+    retained_size: i64,
+    children: Vec<FullTreeNodeJson>,
+    /// Dominated nodes left out by the node cap, with their retained size.
+    omitted_children: usize,
+    omitted_retained_size: i64,
 }
 
 #[derive(Debug, Serialize)]
@@ -30,29 +115,260 @@ pub fn format_markdown(snapshot: &SnapshotRaw, result: &DominatorResult) -> Stri
         output,
         "- Dominator chain for {target_name} (id={target_id})"
     );
-    for (idx, node_index) in result.chain.iter().enumerate() {
+    if let Some(unreachable) = &result.unreachable {
+        let _ = writeln!(
+            output,
+            "  - **Not reachable from the roots**: {}",
+            unreachable.reason.describe()
+        );
+        for retainer in &unreachable.retainers {
+            let _ = writeln!(output, "    - {}", retainer_line(snapshot, retainer));
+        }
+    }
+    for (idx, (node_index, retained)) in result.chain.iter().zip(&result.retained_sizes).enumerate()
+    {
         let node = snapshot.node_view(*node_index);
         let name = node.and_then(|value| value.name()).unwrap_or("<unknown>");
         let id = node.and_then(|value| value.id()).unwrap_or(-1);
-        let _ = writeln!(output, "  - #{} {} (id={})", idx + 1, name, id);
+        let _ = writeln!(
+            output,
+            "  - #{} {} (id={}) retained={} bytes ({:.1}%)",
+            idx + 1,
+            name,
+            id,
+            retained,
+            retained_percent(*retained, result.total_size)
+        );
     }
     output
 }
 
+/// The chain as node ids from the root to the target on one line, like
+/// `retainers::format_ids`.
+pub fn format_ids(snapshot: &SnapshotRaw, result: &DominatorResult) -> String {
+    let ids: Vec<String> = result
+        .chain
+        .iter()
+        .map(|index| node_id(snapshot, *index).to_string())
+        .collect();
+    format!("{}\n", ids.join(" -> "))
+}
+
 pub fn format_json(
     snapshot: &SnapshotRaw,
     result: &DominatorResult,
+    style: JsonStyle,
 ) -> Result<String, SnapshotError> {
     let payload = DominatorJson {
         version: 1,
+        header: JsonHeader::from_snapshot(snapshot),
         target: node_json(snapshot, result.target),
+        total_size_bytes: result.total_size,
         chain: result
             .chain
             .iter()
-            .map(|index| node_json(snapshot, *index))
+            .zip(&result.retained_sizes)
+            .map(|(index, retained)| ChainNodeJson {
+                node: node_json(snapshot, *index),
+                retained_size_bytes: *retained,
+                retained_percent: retained_percent(*retained, result.total_size),
+            })
             .collect(),
+        unreachable: result
+            .unreachable
+            .as_ref()
+            .map(|unreachable| unreachable_json(snapshot, unreachable)),
     };
-    serde_json::to_string_pretty(&payload).map_err(SnapshotError::Json)
+    style.render(&payload)
+}
+
+/// `dominator --reverse`: the nodes the target immediately dominates, one line each.
+pub fn format_dominated_markdown(snapshot: &SnapshotRaw, result: &DominatedResult) -> String {
+    let mut output = String::new();
+    let target = snapshot.node_view(result.target);
+    let target_name = target.and_then(|node| node.name()).unwrap_or("<unknown>");
+    let target_id = target.and_then(|node| node.id()).unwrap_or(-1);
+    let _ = writeln!(
+        output,
+        "- Dominated by {target_name} (id={target_id}) retained={} bytes ({:.1}%)",
+        result.target_retained_size,
+        retained_percent(result.target_retained_size, result.total_size)
+    );
+    if let Some(unreachable) = &result.unreachable {
+        let _ = writeln!(
+            output,
+            "  - **Not reachable from the roots**: {}",
+            unreachable.reason.describe()
+        );
+        for retainer in &unreachable.retainers {
+            let _ = writeln!(output, "    - {}", retainer_line(snapshot, retainer));
+        }
+    } else if result.children.is_empty() {
+        let _ = writeln!(output, "  - (dominates no other node)");
+    }
+    for (idx, (node_index, retained)) in result.children.iter().enumerate() {
+        let node = snapshot.node_view(*node_index);
+        let name = node.and_then(|value| value.name()).unwrap_or("<unknown>");
+        let id = node.and_then(|value| value.id()).unwrap_or(-1);
+        let _ = writeln!(
+            output,
+            "  - #{} {} (id={}) retained={} bytes ({:.1}%)",
+            idx + 1,
+            name,
+            id,
+            retained,
+            retained_percent(*retained, result.total_size)
+        );
+    }
+    let omitted = result.total_children - result.children.len();
+    if omitted > 0 {
+        let _ = writeln!(
+            output,
+            "  - {omitted} more (--top) retained={} bytes ({:.1}%)",
+            result.omitted_retained_size,
+            retained_percent(result.omitted_retained_size, result.total_size)
+        );
+    }
+    output
+}
+
+pub fn format_dominated_json(
+    snapshot: &SnapshotRaw,
+    result: &DominatedResult,
+    style: JsonStyle,
+) -> Result<String, SnapshotError> {
+    let payload = DominatedJson {
+        version: 1,
+        header: JsonHeader::from_snapshot(snapshot),
+        target: node_json(snapshot, result.target),
+        retained_size_bytes: result.target_retained_size,
+        retained_percent: retained_percent(result.target_retained_size, result.total_size),
+        total_size_bytes: result.total_size,
+        total_children: result.total_children,
+        children: result
+            .children
+            .iter()
+            .map(|(index, retained)| ChainNodeJson {
+                node: node_json(snapshot, *index),
+                retained_size_bytes: *retained,
+                retained_percent: retained_percent(*retained, result.total_size),
+            })
+            .collect(),
+        omitted_children: result.total_children - result.children.len(),
+        omitted_retained_size_bytes: result.omitted_retained_size,
+        unreachable: result
+            .unreachable
+            .as_ref()
+            .map(|unreachable| unreachable_json(snapshot, unreachable)),
+    };
+    style.render(&payload)
+}
+
+/// Deepest level `format_tree_json` writes whatever `max_depth` asks for; serializing the
+/// nesting is recursive, and long linked lists would otherwise overflow the stack.
+pub const FULL_TREE_DEPTH_LIMIT: usize = 500;
+
+/// The dominator tree nested from its roots, for tools that draw their own treemaps.
+/// At most `max_nodes` nodes down to `max_depth` levels below the roots are written: the
+/// tree is grown from the roots by always taking the largest retained size next, so a cut
+/// keeps the heaviest subtrees and each node reports what it lost in `omitted_children` /
+/// `omitted_retained_size`.
+pub fn format_tree_json(
+    snapshot: &SnapshotRaw,
+    index: &DominatorIndex,
+    max_nodes: usize,
+    max_depth: usize,
+    style: JsonStyle,
+) -> Result<String, SnapshotError> {
+    let max_depth = max_depth.min(FULL_TREE_DEPTH_LIMIT);
+    let retained = retained_sizes(snapshot, index);
+    let retained_of = |node: usize| retained.get(node).copied().unwrap_or(0);
+    let mut children = vec![Vec::new(); index.idom.len()];
+    let mut roots = Vec::new();
+    for (node, parent) in index.idom.iter().enumerate() {
+        match parent {
+            Some(parent) if *parent == node => roots.push(node),
+            Some(parent) => children[*parent].push(node),
+            None => {}
+        }
+    }
+    for list in children.iter_mut().chain(std::iter::once(&mut roots)) {
+        list.sort_by(|a, b| retained_of(*b).cmp(&retained_of(*a)).then_with(|| a.cmp(b)));
+    }
+    let reachable_nodes = index.idom.iter().filter(|parent| parent.is_some()).count();
+
+    let mut emitted = vec![false; index.idom.len()];
+    let mut emitted_nodes = 0usize;
+    let mut frontier: BinaryHeap<(i64, std::cmp::Reverse<usize>, usize)> = roots
+        .iter()
+        .map(|&node| (retained_of(node), std::cmp::Reverse(node), 0))
+        .collect();
+    while emitted_nodes < max_nodes {
+        let Some((_, std::cmp::Reverse(node), depth)) = frontier.pop() else {
+            break;
+        };
+        emitted[node] = true;
+        emitted_nodes += 1;
+        if depth < max_depth {
+            frontier.extend(
+                children[node]
+                    .iter()
+                    .map(|&child| (retained_of(child), std::cmp::Reverse(child), depth + 1)),
+            );
+        }
+    }
+
+    let tree = FullTree {
+        snapshot,
+        children: &children,
+        retained: &retained,
+        emitted: &emitted,
+    };
+    let payload = FullTreeJson {
+        version: 1,
+        header: JsonHeader::from_snapshot(snapshot),
+        total_size_bytes: snapshot
+            .nodes()
+            .map(|node| node.self_size().unwrap_or(0))
+            .sum(),
+        reachable_nodes,
+        emitted_nodes,
+        truncated: emitted_nodes < reachable_nodes,
+        roots: roots
+            .iter()
+            .filter(|node| emitted[**node])
+            .map(|node| tree.node_json(*node))
+            .collect(),
+    };
+    style.render(&payload)
+}
+
+struct FullTree<'a> {
+    snapshot: &'a SnapshotRaw,
+    children: &'a [Vec<usize>],
+    retained: &'a [i64],
+    emitted: &'a [bool],
+}
+
+impl FullTree<'_> {
+    fn node_json(&self, node_index: usize) -> FullTreeNodeJson {
+        let node = self.snapshot.node_view(node_index);
+        let (kept, omitted): (Vec<usize>, Vec<usize>) = self.children[node_index]
+            .iter()
+            .partition(|child| self.emitted[**child]);
+        FullTreeNodeJson {
+            id: node.and_then(|value| value.id()),
+            name: node.and_then(|value| value.name()).map(str::to_string),
+            self_size: node.and_then(|value| value.self_size()).unwrap_or(0),
+            retained_size: self.retained.get(node_index).copied().unwrap_or(0),
+            children: kept.iter().map(|child| self.node_json(*child)).collect(),
+            omitted_children: omitted.len(),
+            omitted_retained_size: omitted
+                .iter()
+                .map(|child| self.retained.get(*child).copied().unwrap_or(0))
+                .sum(),
+        }
+    }
 }
 
 pub fn format_html(snapshot: &SnapshotRaw, result: &DominatorResult) -> String {
@@ -75,21 +391,116 @@ pub fn format_html(snapshot: &SnapshotRaw, result: &DominatorResult) -> String {
         "<h1>{title}</h1><p><strong>Target:</strong> {} (id={})</p>",
         target_name, target_id
     );
-    let _ = writeln!(output, "<ol>");
-    for (idx, node_index) in result.chain.iter().enumerate() {
+    if let Some(unreachable) = &result.unreachable {
+        output.push_str(&unreachable_html(snapshot, unreachable));
+    }
+    let _ = writeln!(
+        output,
+        "<table><thead><tr><th>#</th><th>Name</th><th>ID</th><th>Retained (bytes)</th><th>Heap %</th></tr></thead><tbody>"
+    );
+    for (idx, (node_index, retained)) in result.chain.iter().zip(&result.retained_sizes).enumerate()
+    {
         let node = snapshot.node_view(*node_index);
         let name = node
             .and_then(|value| value.name())
             .map(escape_html_inline)
             .unwrap_or_else(|| "<unknown>".to_string());
         let id = node.and_then(|value| value.id()).unwrap_or(-1);
-        let _ = writeln!(output, "<li>#{} {} (id={})</li>", idx + 1, name, id);
+        let _ = writeln!(
+            output,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.1}%</td></tr>",
+            idx + 1,
+            name,
+            id,
+            retained,
+            retained_percent(*retained, result.total_size)
+        );
     }
-    let _ = writeln!(output, "</ol>");
+    let _ = writeln!(output, "</tbody></table>");
     let _ = writeln!(output, "</body></html>");
     output
 }
 
+pub(crate) fn retained_percent(retained: i64, total: i64) -> f64 {
+    if total <= 0 {
+        return 0.0;
+    }
+    // Rounded to 0.1 so JSON stays readable.
+    (retained as f64 * 1000.0 / total as f64).round() / 10.0
+}
+
+fn unreachable_json(snapshot: &SnapshotRaw, unreachable: &Unreachable) -> UnreachableJson {
+    let (edge_types, island_size) = match &unreachable.reason {
+        UnreachableReason::IgnoredEdges { edge_types } => (Some(edge_types.clone()), None),
+        UnreachableReason::Island { size } => (None, Some(*size)),
+        UnreachableReason::NoRetainers | UnreachableReason::DepthLimit => (None, None),
+    };
+    UnreachableJson {
+        reason: unreachable.reason.kind(),
+        edge_types,
+        island_size,
+        explored: unreachable.explored,
+        retainers: unreachable
+            .retainers
+            .iter()
+            .map(|retainer| {
+                let edge = snapshot.edge_view(retainer.edge_index);
+                NearbyRetainerJson {
+                    depth: retainer.depth,
+                    node: node_json(snapshot, retainer.node),
+                    edge_type: edge.and_then(|value| value.edge_type()).map(str::to_string),
+                    edge_name: edge_name(snapshot, edge).map(Cow::into_owned),
+                    reachable: retainer.reachable,
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Warning block for an unreachable target, shared with `serve`.
+pub(crate) fn unreachable_html(snapshot: &SnapshotRaw, unreachable: &Unreachable) -> String {
+    let mut output = String::new();
+    let _ = writeln!(
+        output,
+        "<p><strong>Not reachable from the roots:</strong> {}</p>",
+        escape_html_inline(&unreachable.reason.describe())
+    );
+    if unreachable.retainers.is_empty() {
+        return output;
+    }
+    let _ = writeln!(output, "<ul>");
+    for retainer in &unreachable.retainers {
+        let _ = writeln!(
+            output,
+            "<li>{}</li>",
+            escape_html_inline(&retainer_line(snapshot, retainer))
+        );
+    }
+    let _ = writeln!(output, "</ul>");
+    output
+}
+
+/// "depth 1: Foo (id=5) --(weak)cache--> (reachable)".
+fn retainer_line(snapshot: &SnapshotRaw, retainer: &NearbyRetainer) -> String {
+    let node = snapshot.node_view(retainer.node);
+    let name = node.and_then(|value| value.name()).unwrap_or("<unknown>");
+    let id = node.and_then(|value| value.id()).unwrap_or(-1);
+    let edge = snapshot.edge_view(retainer.edge_index);
+    let edge_type = edge
+        .and_then(|value| value.edge_type())
+        .unwrap_or("unknown");
+    let edge_name = edge_name(snapshot, edge).unwrap_or_default();
+    let state = if retainer.reachable {
+        "reachable"
+    } else {
+        "unreachable"
+    };
+    format!(
+        "depth {}: {name} (id={id}) --({edge_type}){edge_name}--> ({state})",
+        retainer.depth
+    )
+}
+
 fn node_json(snapshot: &SnapshotRaw, node_index: usize) -> NodeJson {
     let node = snapshot.node_view(node_index);
     NodeJson {
@@ -0,0 +1,149 @@
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::analysis::edges::EdgesResult;
+use crate::error::SnapshotError;
+use crate::output::csv::TableWriter;
+use crate::output::header::JsonHeader;
+use crate::output::json::JsonStyle;
+
+#[derive(Debug, Serialize)]
+struct EdgesJson<'a> {
+    version: u32,
+    #[serde(flatten)]
+    header: JsonHeader,
+    name: &'a str,
+    direction: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    edge_name_filter: Option<&'a str>,
+    matched_nodes: usize,
+    total_edges: u64,
+    skip: usize,
+    limit: usize,
+    has_more: bool,
+    next_skip: Option<usize>,
+    edges: Vec<EdgeJson<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct EdgeJson<'a> {
+    edge_index: usize,
+    from_index: usize,
+    from_id: Option<i64>,
+    from_name: Option<&'a str>,
+    edge_type: Option<&'a str>,
+    edge_name: Option<&'a str>,
+    to_index: usize,
+    to_id: Option<i64>,
+    to_name: Option<&'a str>,
+}
+
+fn direction(result: &EdgesResult) -> &'static str {
+    if result.incoming {
+        "incoming"
+    } else {
+        "outgoing"
+    }
+}
+
+pub fn format_markdown(result: &EdgesResult) -> String {
+    let mut output = String::new();
+    let _ = writeln!(output, "# HeapSnapshot Edges");
+    let _ = writeln!(output);
+    let _ = writeln!(output, "- Constructor: {}", escape_table(&result.name));
+    let _ = writeln!(output, "- Matched nodes: {}", result.matched_nodes);
+    let _ = writeln!(output, "- Direction: {}", direction(result));
+    if let Some(filter) = &result.edge_name_filter {
+        let _ = writeln!(output, "- Edge name contains: {filter}");
+    }
+    let _ = writeln!(
+        output,
+        "- Showing: {}..{} of {}",
+        result.skip,
+        result.skip + result.rows.len(),
+        result.total_edges
+    );
+    if let Some(next) = result.next_skip() {
+        let _ = writeln!(output, "- Next page: --skip {next}");
+    }
+    let _ = writeln!(output);
+    let _ = writeln!(
+        output,
+        "| From ID | From Name | Edge Type | Edge Name | To ID | To Name |"
+    );
+    let _ = writeln!(output, "| ---: | --- | --- | --- | ---: | --- |");
+    for row in &result.rows {
+        let _ = writeln!(
+            output,
+            "| {} | {} | {} | {} | {} | {} |",
+            row.from_id.unwrap_or(-1),
+            escape_table(row.from_name.as_deref().unwrap_or("")),
+            row.edge_type.as_deref().unwrap_or(""),
+            escape_table(row.edge_name.as_deref().unwrap_or("")),
+            row.to_id.unwrap_or(-1),
+            escape_table(row.to_name.as_deref().unwrap_or(""))
+        );
+    }
+    output
+}
+
+pub fn format_json(result: &EdgesResult, style: JsonStyle) -> Result<String, SnapshotError> {
+    let next_skip = result.next_skip();
+    let payload = EdgesJson {
+        version: 1,
+        header: JsonHeader::new(result.snapshot_fingerprint),
+        name: result.name.as_str(),
+        direction: direction(result),
+        edge_name_filter: result.edge_name_filter.as_deref(),
+        matched_nodes: result.matched_nodes,
+        total_edges: result.total_edges,
+        skip: result.skip,
+        limit: result.limit,
+        has_more: next_skip.is_some(),
+        next_skip,
+        edges: result
+            .rows
+            .iter()
+            .map(|row| EdgeJson {
+                edge_index: row.edge_index,
+                from_index: row.from_index,
+                from_id: row.from_id,
+                from_name: row.from_name.as_deref(),
+                edge_type: row.edge_type.as_deref(),
+                edge_name: row.edge_name.as_deref(),
+                to_index: row.to_index,
+                to_id: row.to_id,
+                to_name: row.to_name.as_deref(),
+            })
+            .collect(),
+    };
+    style.render(&payload)
+}
+
+pub fn format_csv(result: &EdgesResult, delimiter: char) -> String {
+    let mut writer = TableWriter::with_delimiter(
+        &["from_id", "edge_type", "edge_name", "to_id", "to_name"],
+        delimiter,
+    );
+    for row in &result.rows {
+        writer.row(&[
+            row.from_id
+                .map(|id| id.to_string())
+                .unwrap_or_default()
+                .as_str(),
+            row.edge_type.as_deref().unwrap_or(""),
+            row.edge_name.as_deref().unwrap_or(""),
+            row.to_id
+                .map(|id| id.to_string())
+                .unwrap_or_default()
+                .as_str(),
+            row.to_name.as_deref().unwrap_or(""),
+        ]);
+    }
+    writer.finish()
+}
+
+fn escape_table(value: &str) -> String {
+    value.replace('|', "\\|")
+}
@@ -0,0 +1,128 @@
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::analysis::find::FindResult;
+use crate::error::SnapshotError;
+use crate::output::csv::TableWriter;
+use crate::output::header::JsonHeader;
+use crate::output::json::JsonStyle;
+
+#[derive(Debug, Serialize)]
+struct FindJson<'a> {
+    version: u32,
+    #[serde(flatten)]
+    header: JsonHeader,
+    edge_name: &'a str,
+    value: &'a str,
+    total_matches: u64,
+    limit: usize,
+    matches: Vec<FindMatchJson<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct FindMatchJson<'a> {
+    owner_index: usize,
+    owner_id: Option<i64>,
+    owner_name: Option<&'a str>,
+    edge_index: usize,
+    edge_type: Option<&'a str>,
+    target_index: usize,
+    target_id: Option<i64>,
+    target_type: Option<&'a str>,
+}
+
+pub fn format_markdown(result: &FindResult) -> String {
+    let mut output = String::new();
+    let _ = writeln!(output, "# HeapSnapshot Find");
+    let _ = writeln!(output);
+    let _ = writeln!(output, "- Edge name: {}", escape_table(&result.edge_name));
+    let _ = writeln!(output, "- Value: {}", escape_table(&result.value));
+    let _ = writeln!(
+        output,
+        "- Matches: {} (showing {})",
+        result.total_matches,
+        result.matches.len()
+    );
+    let _ = writeln!(output);
+    if result.matches.is_empty() {
+        let _ = writeln!(output, "No matching edges.");
+        return output;
+    }
+    let _ = writeln!(
+        output,
+        "| Owner ID | Owner Name | Edge Type | Target ID | Target Type |"
+    );
+    let _ = writeln!(output, "| ---: | --- | --- | ---: | --- |");
+    for row in &result.matches {
+        let _ = writeln!(
+            output,
+            "| {} | {} | {} | {} | {} |",
+            row.owner_id.unwrap_or(-1),
+            escape_table(row.owner_name.as_deref().unwrap_or("")),
+            row.edge_type.as_deref().unwrap_or(""),
+            row.target_id.unwrap_or(-1),
+            row.target_type.as_deref().unwrap_or("")
+        );
+    }
+    output
+}
+
+pub fn format_json(result: &FindResult, style: JsonStyle) -> Result<String, SnapshotError> {
+    let payload = FindJson {
+        version: 1,
+        header: JsonHeader::new(result.snapshot_fingerprint),
+        edge_name: result.edge_name.as_str(),
+        value: result.value.as_str(),
+        total_matches: result.total_matches,
+        limit: result.limit,
+        matches: result
+            .matches
+            .iter()
+            .map(|row| FindMatchJson {
+                owner_index: row.owner_index,
+                owner_id: row.owner_id,
+                owner_name: row.owner_name.as_deref(),
+                edge_index: row.edge_index,
+                edge_type: row.edge_type.as_deref(),
+                target_index: row.target_index,
+                target_id: row.target_id,
+                target_type: row.target_type.as_deref(),
+            })
+            .collect(),
+    };
+    style.render(&payload)
+}
+
+pub fn format_csv(result: &FindResult, delimiter: char) -> String {
+    let mut writer = TableWriter::with_delimiter(
+        &[
+            "owner_id",
+            "owner_name",
+            "edge_type",
+            "target_id",
+            "target_type",
+        ],
+        delimiter,
+    );
+    for row in &result.matches {
+        writer.row(&[
+            row.owner_id
+                .map(|id| id.to_string())
+                .unwrap_or_default()
+                .as_str(),
+            row.owner_name.as_deref().unwrap_or(""),
+            row.edge_type.as_deref().unwrap_or(""),
+            row.target_id
+                .map(|id| id.to_string())
+                .unwrap_or_default()
+                .as_str(),
+            row.target_type.as_deref().unwrap_or(""),
+        ]);
+    }
+    writer.finish()
+}
+
+fn escape_table(value: &str) -> String {
+    value.replace('|', "\\|")
+}
@@ -0,0 +1,57 @@
+//! Folded stacks (`frame;frame;frame weight` per line) for `flamegraph.pl` / `inferno`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use crate::analysis::tree::{TreeNode, TreeResult};
+use crate::snapshot::SnapshotRaw;
+
+/// One line per distinct constructor-name path through the dominator subtree. A node's
+/// weight is its retained size minus that of the children listed under it, so each frame
+/// is as wide as its retained size; children cut by `max_depth` / `max_children` stay
+/// inside their parent's weight. Equal paths (e.g. many `Array` siblings) are summed and
+/// zero weights are left out.
+pub fn format(snapshot: &SnapshotRaw, tree: &TreeResult) -> String {
+    let mut stacks: BTreeMap<String, i64> = BTreeMap::new();
+    let mut stack = String::new();
+    collect(snapshot, &tree.root, &mut stack, &mut stacks);
+
+    let mut output = String::new();
+    for (stack, weight) in stacks {
+        if weight > 0 {
+            let _ = writeln!(output, "{stack} {weight}");
+        }
+    }
+    output
+}
+
+fn collect(
+    snapshot: &SnapshotRaw,
+    node: &TreeNode,
+    stack: &mut String,
+    stacks: &mut BTreeMap<String, i64>,
+) {
+    let parent_len = stack.len();
+    if !stack.is_empty() {
+        stack.push(';');
+    }
+    stack.push_str(&frame_name(snapshot, node.node_index));
+
+    let children: i64 = node.children.iter().map(|child| child.retained_size).sum();
+    *stacks.entry(stack.clone()).or_default() += (node.retained_size - children).max(0);
+    for child in &node.children {
+        collect(snapshot, child, stack, stacks);
+    }
+    stack.truncate(parent_len);
+}
+
+/// Constructor name with the characters the folded format reserves replaced: `;`
+/// separates frames, and a line must not break.
+fn frame_name(snapshot: &SnapshotRaw, node_index: usize) -> String {
+    let name = snapshot
+        .node_view(node_index)
+        .and_then(|node| node.name())
+        .filter(|name| !name.is_empty())
+        .unwrap_or("<unknown>");
+    name.replace(';', ",").replace(['\n', '\r'], " ")
+}
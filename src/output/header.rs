@@ -0,0 +1,45 @@
+//! Fields shared by the analysis JSON outputs, so a result can be traced back to the
+//! snapshot and tool version that produced it.
+
+use serde::Serialize;
+
+use crate::error::SnapshotError;
+use crate::snapshot::SnapshotRaw;
+
+pub(crate) const TOOL_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Flattened into a JSON payload right after `version`.
+#[derive(Debug, Serialize)]
+pub(crate) struct JsonHeader {
+    tool_version: &'static str,
+    snapshot_fingerprint: String,
+}
+
+impl JsonHeader {
+    pub(crate) fn new(fingerprint: u64) -> Self {
+        Self {
+            tool_version: TOOL_VERSION,
+            snapshot_fingerprint: fingerprint_hex(fingerprint),
+        }
+    }
+
+    pub(crate) fn from_snapshot(snapshot: &SnapshotRaw) -> Self {
+        Self::new(snapshot.quick_fingerprint())
+    }
+}
+
+/// `SnapshotRaw::quick_fingerprint` as 16 hex digits; a JSON number would lose
+/// precision above 2^53 in JavaScript consumers.
+pub fn fingerprint_hex(fingerprint: u64) -> String {
+    format!("{fingerprint:016x}")
+}
+
+/// Reverse of `fingerprint_hex`; a missing field (output of an older version) reads as 0.
+pub(crate) fn parse_fingerprint(hex: Option<&str>) -> Result<u64, SnapshotError> {
+    match hex {
+        Some(hex) => u64::from_str_radix(hex, 16).map_err(|_| SnapshotError::InvalidData {
+            details: format!("invalid snapshot fingerprint in json: {hex:?}"),
+        }),
+        None => Ok(0),
+    }
+}
@@ -0,0 +1,156 @@
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::analysis::histogram::HistogramResult;
+use crate::error::SnapshotError;
+use crate::output::csv::TableWriter;
+use crate::output::header::JsonHeader;
+use crate::output::json::JsonStyle;
+
+/// Width in characters of the longest bar in the markdown chart.
+const BAR_WIDTH: usize = 30;
+
+#[derive(Debug, Serialize)]
+struct HistogramJson<'a> {
+    version: u32,
+    #[serde(flatten)]
+    header: JsonHeader,
+    total_nodes: usize,
+    matched_nodes: u64,
+    self_size_sum_bytes: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    node_type: Option<&'a str>,
+    buckets: Vec<BucketJson<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct BucketJson<'a> {
+    label: &'a str,
+    min: i64,
+    max: Option<i64>,
+    count: u64,
+    self_size_sum_bytes: i64,
+}
+
+pub fn format_markdown(result: &HistogramResult) -> String {
+    let mut output = String::new();
+    let _ = writeln!(output, "# HeapSnapshot Histogram");
+    let _ = writeln!(output);
+    let _ = writeln!(output, "- Total nodes: {}", result.total_nodes);
+    let _ = writeln!(
+        output,
+        "- Matched nodes: {} ({} bytes)",
+        result.matched_nodes, result.self_size_sum
+    );
+    if let Some(name) = &result.name {
+        let _ = writeln!(output, "- Name contains: {name}");
+    }
+    if let Some(node_type) = &result.node_type {
+        let _ = writeln!(output, "- Node type: {node_type}");
+    }
+    let _ = writeln!(output);
+    let _ = writeln!(output, "| Bucket (bytes) | Count | Self Size Sum (bytes) |");
+    let _ = writeln!(output, "| --- | ---: | ---: |");
+    for bucket in &result.buckets {
+        let _ = writeln!(
+            output,
+            "| {} | {} | {} |",
+            bucket.label, bucket.count, bucket.self_size_sum
+        );
+    }
+
+    let label_width = result
+        .buckets
+        .iter()
+        .map(|bucket| bucket.label.len())
+        .max()
+        .unwrap_or(0)
+        .max("Bucket".len());
+    let max_count = result
+        .buckets
+        .iter()
+        .map(|bucket| bucket.count)
+        .max()
+        .unwrap_or(0);
+    let max_size = result
+        .buckets
+        .iter()
+        .map(|bucket| bucket.self_size_sum)
+        .max()
+        .unwrap_or(0);
+    let _ = writeln!(output);
+    let _ = writeln!(output, "```text");
+    let _ = writeln!(
+        output,
+        "{:<label_width$} | {:<BAR_WIDTH$} | Bytes",
+        "Bucket", "Count"
+    );
+    for bucket in &result.buckets {
+        let line = format!(
+            "{:<label_width$} | {:<BAR_WIDTH$} | {}",
+            bucket.label,
+            bar(bucket.count as f64, max_count as f64),
+            bar(bucket.self_size_sum as f64, max_size as f64)
+        );
+        let _ = writeln!(output, "{}", line.trim_end());
+    }
+    let _ = writeln!(output, "```");
+    output
+}
+
+/// `#` bar scaled against `max`; any non-zero value gets at least one character.
+fn bar(value: f64, max: f64) -> String {
+    if value <= 0.0 || max <= 0.0 {
+        return String::new();
+    }
+    let width = ((value / max) * BAR_WIDTH as f64).round() as usize;
+    "#".repeat(width.clamp(1, BAR_WIDTH))
+}
+
+pub fn format_json(result: &HistogramResult, style: JsonStyle) -> Result<String, SnapshotError> {
+    let payload = HistogramJson {
+        version: 1,
+        header: JsonHeader::new(result.snapshot_fingerprint),
+        total_nodes: result.total_nodes,
+        matched_nodes: result.matched_nodes,
+        self_size_sum_bytes: result.self_size_sum,
+        name: result.name.as_deref(),
+        node_type: result.node_type.as_deref(),
+        buckets: result
+            .buckets
+            .iter()
+            .map(|bucket| BucketJson {
+                label: bucket.label.as_str(),
+                min: bucket.min,
+                max: bucket.max,
+                count: bucket.count,
+                self_size_sum_bytes: bucket.self_size_sum,
+            })
+            .collect(),
+    };
+    style.render(&payload)
+}
+
+pub fn format_csv(result: &HistogramResult, delimiter: char) -> String {
+    let mut writer = TableWriter::with_delimiter(
+        &["bucket", "min", "max", "count", "self_size_sum_bytes"],
+        delimiter,
+    );
+    for bucket in &result.buckets {
+        writer.row(&[
+            bucket.label.as_str(),
+            bucket.min.to_string().as_str(),
+            bucket
+                .max
+                .map(|max| max.to_string())
+                .unwrap_or_default()
+                .as_str(),
+            bucket.count.to_string().as_str(),
+            bucket.self_size_sum.to_string().as_str(),
+        ]);
+    }
+    writer.finish()
+}
@@ -0,0 +1,46 @@
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::error::SnapshotError;
+use crate::output::header::JsonHeader;
+use crate::output::json::JsonStyle;
+use crate::snapshot::SnapshotRaw;
+
+#[derive(Debug, Serialize)]
+struct InfoJson {
+    version: u32,
+    #[serde(flatten)]
+    header: JsonHeader,
+    node_count: usize,
+    edge_count: usize,
+    string_count: usize,
+    memory_estimate_bytes: u64,
+}
+
+pub fn format_markdown(snapshot: &SnapshotRaw) -> String {
+    let mut output = String::new();
+    let _ = writeln!(output, "# HeapSnapshot Info");
+    let _ = writeln!(output);
+    let _ = writeln!(output, "- Nodes: {}", snapshot.node_count());
+    let _ = writeln!(output, "- Edges: {}", snapshot.edge_count());
+    let _ = writeln!(output, "- Strings: {}", snapshot.strings.len());
+    let _ = writeln!(
+        output,
+        "- Approx memory: {} bytes",
+        snapshot.memory_estimate_bytes()
+    );
+    output
+}
+
+pub fn format_json(snapshot: &SnapshotRaw, style: JsonStyle) -> Result<String, SnapshotError> {
+    let payload = InfoJson {
+        version: 1,
+        header: JsonHeader::from_snapshot(snapshot),
+        node_count: snapshot.node_count(),
+        edge_count: snapshot.edge_count(),
+        string_count: snapshot.strings.len(),
+        memory_estimate_bytes: snapshot.memory_estimate_bytes(),
+    };
+    style.render(&payload)
+}
@@ -0,0 +1,68 @@
+use std::io;
+
+use serde::Serialize;
+
+use crate::error::SnapshotError;
+
+/// Layout of JSON output: indented for reading, or one line (`--compact`) for piping
+/// into other programs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonStyle {
+    #[default]
+    Pretty,
+    Compact,
+}
+
+impl JsonStyle {
+    pub fn from_compact(compact: bool) -> Self {
+        if compact {
+            JsonStyle::Compact
+        } else {
+            JsonStyle::Pretty
+        }
+    }
+
+    pub fn render<T: Serialize + ?Sized>(self, value: &T) -> Result<String, SnapshotError> {
+        match self {
+            JsonStyle::Pretty => serde_json::to_string_pretty(value),
+            JsonStyle::Compact => serde_json::to_string(value),
+        }
+        .map_err(SnapshotError::from)
+    }
+
+    pub fn write<W: io::Write, T: Serialize + ?Sized>(
+        self,
+        writer: W,
+        value: &T,
+    ) -> Result<(), SnapshotError> {
+        match self {
+            JsonStyle::Pretty => serde_json::to_writer_pretty(writer, value),
+            JsonStyle::Compact => serde_json::to_writer(writer, value),
+        }
+        .map_err(SnapshotError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compact_is_one_line_with_the_same_content() {
+        let value = serde_json::json!({ "version": 1, "rows": [{ "name": "Foo", "count": 2 }] });
+        let pretty = JsonStyle::Pretty.render(&value).expect("pretty");
+        let compact = JsonStyle::Compact.render(&value).expect("compact");
+
+        assert!(pretty.contains('\n'));
+        assert!(!compact.contains('\n'));
+        assert!(compact.len() < pretty.len());
+        let reparsed: serde_json::Value = serde_json::from_str(&compact).expect("parse");
+        assert_eq!(reparsed, value);
+
+        let mut written = Vec::new();
+        JsonStyle::Compact
+            .write(&mut written, &value)
+            .expect("write");
+        assert_eq!(written, compact.as_bytes());
+    }
+}
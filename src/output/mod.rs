@@ -1,7 +1,22 @@
 pub mod build;
+pub mod charset;
+pub mod color;
+pub mod csv;
 pub mod detail;
 pub mod diff;
 pub mod dominator;
+pub mod edges;
+pub mod find;
+pub mod folded;
+pub mod header;
+pub mod histogram;
+pub mod info;
+pub mod json;
+pub mod peek;
 pub mod retainers;
+pub mod strings;
 pub mod summary;
+pub mod tree;
+pub mod triage;
+pub mod verify;
 pub mod write;
@@ -0,0 +1,122 @@
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::error::SnapshotError;
+use crate::output::json::JsonStyle;
+use crate::snapshot::{MetaType, SnapshotMeta};
+
+#[derive(Debug, Serialize)]
+struct PeekJson<'a> {
+    version: u32,
+    valid: bool,
+    problem: Option<String>,
+    /// Node field delimiting each node's edges; `None` when the meta is invalid.
+    edge_layout: Option<&'static str>,
+    node_fields: Vec<FieldJson<'a>>,
+    edge_fields: Vec<FieldJson<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct FieldJson<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    field_type: Vec<&'a str>,
+}
+
+pub fn format_markdown(meta: &SnapshotMeta) -> String {
+    let mut output = String::new();
+    let _ = writeln!(output, "# HeapSnapshot Peek");
+    let _ = writeln!(output);
+    match meta.validate() {
+        Ok(index) => {
+            let _ = writeln!(output, "- Structure: ok");
+            let _ = writeln!(
+                output,
+                "- Edge layout: {}",
+                index.node_field_index.edge_layout.field_name()
+            );
+        }
+        Err(err) => {
+            let _ = writeln!(output, "- Structure: invalid ({err})");
+        }
+    }
+    write_fields_markdown(
+        &mut output,
+        "Node fields",
+        &meta.node_fields,
+        &meta.node_types,
+    );
+    write_fields_markdown(
+        &mut output,
+        "Edge fields",
+        &meta.edge_fields,
+        &meta.edge_types,
+    );
+    output
+}
+
+pub fn format_json(meta: &SnapshotMeta, style: JsonStyle) -> Result<String, SnapshotError> {
+    let validated = meta.validate();
+    let edge_layout = validated
+        .as_ref()
+        .ok()
+        .map(|index| index.node_field_index.edge_layout.field_name());
+    let problem = validated.err().map(|err| err.to_string());
+    let payload = PeekJson {
+        version: 1,
+        valid: problem.is_none(),
+        problem,
+        edge_layout,
+        node_fields: fields_json(&meta.node_fields, &meta.node_types),
+        edge_fields: fields_json(&meta.edge_fields, &meta.edge_types),
+    };
+    style.render(&payload)
+}
+
+fn write_fields_markdown(output: &mut String, title: &str, fields: &[String], types: &[MetaType]) {
+    let _ = writeln!(output);
+    let _ = writeln!(output, "## {title}");
+    let _ = writeln!(output);
+    let _ = writeln!(output, "| # | Field | Type |");
+    let _ = writeln!(output, "| ---: | --- | --- |");
+    for (index, field) in fields.iter().enumerate() {
+        let field_type = types
+            .get(index)
+            .map(type_label)
+            .unwrap_or_else(|| "(missing)".to_string());
+        let _ = writeln!(
+            output,
+            "| {} | {} | {} |",
+            index,
+            escape_table(field),
+            escape_table(&field_type)
+        );
+    }
+}
+
+fn fields_json<'a>(fields: &'a [String], types: &'a [MetaType]) -> Vec<FieldJson<'a>> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| FieldJson {
+            name: field.as_str(),
+            field_type: match types.get(index) {
+                Some(MetaType::String(value)) => vec![value.as_str()],
+                Some(MetaType::Array(values)) => values.iter().map(String::as_str).collect(),
+                None => Vec::new(),
+            },
+        })
+        .collect()
+}
+
+fn type_label(value: &MetaType) -> String {
+    match value {
+        MetaType::String(value) => value.clone(),
+        MetaType::Array(values) => format!("[{}]", values.join(", ")),
+    }
+}
+
+fn escape_table(value: &str) -> String {
+    value.replace('|', "\\|")
+}
@@ -1,20 +1,112 @@
+use std::borrow::Cow;
 use std::fmt::Write as _;
+use std::io;
 
 use serde::Serialize;
+use serde::ser::{SerializeSeq, SerializeStruct, Serializer};
 
-use crate::analysis::retainers::{RetainerLink, RetainersResult};
+use crate::analysis::detail;
+use crate::analysis::retainers::{RetainerLink, RetainerStats, RetainersResult};
 use crate::error::SnapshotError;
+use crate::output::charset::Charset;
+use crate::output::header::JsonHeader;
+use crate::output::json::JsonStyle;
 use crate::snapshot::{EdgeView, SnapshotRaw};
 
 #[derive(Debug, Serialize)]
-struct RetainersJson {
+struct RetainersJson<'a> {
     version: u32,
+    #[serde(flatten)]
+    header: JsonHeader,
     target: NodeJson,
-    paths: Vec<PathJson>,
+    stats: StatsJson,
+    /// `RetainersResult::depth_histogram`, written only when it was collected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    depth_histogram: Option<&'a [usize]>,
+    paths: PathsStream<'a>,
 }
 
 #[derive(Debug, Serialize)]
-struct PathJson {
+struct StatsJson {
+    min_len: Option<usize>,
+    max_len: Option<usize>,
+    distinct_roots_reached: usize,
+    total_paths_found: usize,
+    hit_max_depth: bool,
+    hit_max_paths: bool,
+    truncated: bool,
+}
+
+impl From<&RetainerStats> for StatsJson {
+    fn from(stats: &RetainerStats) -> Self {
+        Self {
+            min_len: stats.min_len,
+            max_len: stats.max_len,
+            distinct_roots_reached: stats.distinct_roots_reached,
+            total_paths_found: stats.total_paths_found,
+            hit_max_depth: stats.hit_max_depth,
+            hit_max_paths: stats.hit_max_paths,
+            truncated: stats.truncated(),
+        }
+    }
+}
+
+/// Serializes like `Vec<PathJson>`, but builds each step only while it is written.
+#[derive(Debug)]
+struct PathsStream<'a> {
+    snapshot: &'a SnapshotRaw,
+    paths: &'a [Vec<RetainerLink>],
+    /// Group sizes from `dedupe_by_constructor`; empty writes no `similar` field.
+    similar: &'a [usize],
+}
+
+impl Serialize for PathsStream<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.paths.len()))?;
+        for (index, path) in self.paths.iter().enumerate() {
+            seq.serialize_element(&PathStream {
+                snapshot: self.snapshot,
+                path,
+                similar: self.similar.get(index).copied(),
+            })?;
+        }
+        seq.end()
+    }
+}
+
+struct PathStream<'a> {
+    snapshot: &'a SnapshotRaw,
+    path: &'a [RetainerLink],
+    similar: Option<usize>,
+}
+
+impl Serialize for PathStream<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let fields = if self.similar.is_some() { 2 } else { 1 };
+        let mut state = serializer.serialize_struct("PathJson", fields)?;
+        if let Some(similar) = self.similar {
+            state.serialize_field("similar", &similar)?;
+        }
+        state.serialize_field("steps", &StepsStream(self))?;
+        state.end()
+    }
+}
+
+struct StepsStream<'a, 'b>(&'b PathStream<'a>);
+
+impl Serialize for StepsStream<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let PathStream { snapshot, path, .. } = self.0;
+        let mut seq = serializer.serialize_seq(Some(path.len()))?;
+        for step in path.iter() {
+            seq.serialize_element(&step_json(snapshot, step))?;
+        }
+        seq.end()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct PathJson {
     steps: Vec<StepJson>,
 }
 
@@ -26,7 +118,7 @@ struct StepJson {
 }
 
 #[derive(Debug, Serialize)]
-struct NodeJson {
+pub(crate) struct NodeJson {
     index: usize,
     id: Option<i64>,
     name: Option<String>,
@@ -41,12 +133,17 @@ struct EdgeJson {
     name: Option<String>,
 }
 
-pub fn format_markdown(snapshot: &SnapshotRaw, result: &RetainersResult) -> String {
+/// Long names are folded into `<details>` with a summary cut by the `charset` ellipsis.
+pub fn format_markdown(
+    snapshot: &SnapshotRaw,
+    result: &RetainersResult,
+    charset: Charset,
+) -> String {
     let mut output = String::new();
     let target = snapshot.node_view(result.target);
     let target_name = target
         .and_then(|node| node.name())
-        .map(escape_inline_with_details)
+        .map(|name| escape_inline_with_details(name, charset))
         .unwrap_or_else(|| "<unknown>".to_string());
     let target_id = target.and_then(|node| node.id()).unwrap_or(-1);
     let _ = writeln!(
@@ -54,11 +151,21 @@ pub fn format_markdown(snapshot: &SnapshotRaw, result: &RetainersResult) -> Stri
         "- Retaining paths for {} (id={})",
         target_name, target_id
     );
+    let _ = writeln!(output, "  - {}", stats_line(&result.stats));
+    if let Some(warning) = truncation_warning(&result.stats) {
+        let _ = writeln!(output, "  - **{warning}**");
+    }
+    write_depth_histogram(&mut output, result);
 
     for (index, path) in result.paths.iter().enumerate() {
-        let _ = writeln!(output, "  - Path #{}", index + 1);
+        let _ = writeln!(
+            output,
+            "  - Path #{}{}",
+            index + 1,
+            similar_note(result, index, charset)
+        );
         for step in path {
-            let line = format_step(snapshot, step);
+            let line = format_step(snapshot, step, charset);
             let _ = writeln!(output, "    - {line}");
         }
     }
@@ -66,29 +173,65 @@ pub fn format_markdown(snapshot: &SnapshotRaw, result: &RetainersResult) -> Stri
     output
 }
 
+/// One line per path with just the node ids from the root to the target, e.g.
+/// `1 -> 5 -> 23`. Ids are stable across snapshots (array indices are not), so the output
+/// of two snapshots can be compared with a plain text diff. Missing ids print as `-1`.
+pub fn format_ids(snapshot: &SnapshotRaw, result: &RetainersResult) -> String {
+    let mut output = String::new();
+    for path in &result.paths {
+        let Some(first) = path.first() else {
+            continue;
+        };
+        let ids: Vec<String> = std::iter::once(first.from_node)
+            .chain(path.iter().map(|step| step.to_node))
+            .map(|index| node_id(snapshot, index).to_string())
+            .collect();
+        let _ = writeln!(output, "{}", ids.join(" -> "));
+    }
+    output
+}
+
+pub(crate) fn node_id(snapshot: &SnapshotRaw, index: usize) -> i64 {
+    snapshot
+        .node_view(index)
+        .and_then(|node| node.id())
+        .unwrap_or(-1)
+}
+
 pub fn format_json(
     snapshot: &SnapshotRaw,
     result: &RetainersResult,
+    style: JsonStyle,
 ) -> Result<String, SnapshotError> {
-    let target = node_json(snapshot, result.target);
-    let mut paths = Vec::new();
-    for path in &result.paths {
-        let mut steps = Vec::new();
-        for step in path {
-            let from = node_json(snapshot, step.from_node);
-            let to = node_json(snapshot, step.to_node);
-            let edge = edge_json(snapshot, step.edge_index);
-            steps.push(StepJson { from, edge, to });
-        }
-        paths.push(PathJson { steps });
-    }
+    let mut buffer = Vec::new();
+    write_json(snapshot, result, &mut buffer, style)?;
+    String::from_utf8(buffer).map_err(|err| SnapshotError::InvalidData {
+        details: format!("retainers json is not utf-8: {err}"),
+    })
+}
 
+/// Same output as `format_json`, streamed to `writer` path by path so peak memory does
+/// not grow with `--paths` / `--max-depth`.
+pub fn write_json<W: io::Write>(
+    snapshot: &SnapshotRaw,
+    result: &RetainersResult,
+    writer: W,
+    style: JsonStyle,
+) -> Result<(), SnapshotError> {
     let payload = RetainersJson {
         version: 1,
-        target,
-        paths,
+        header: JsonHeader::from_snapshot(snapshot),
+        target: node_json(snapshot, result.target),
+        stats: StatsJson::from(&result.stats),
+        depth_histogram: (!result.depth_histogram.is_empty())
+            .then_some(result.depth_histogram.as_slice()),
+        paths: PathsStream {
+            snapshot,
+            paths: &result.paths,
+            similar: &result.similar,
+        },
     };
-    serde_json::to_string_pretty(&payload).map_err(SnapshotError::Json)
+    style.write(writer, &payload)
 }
 
 pub fn format_html(snapshot: &SnapshotRaw, result: &RetainersResult) -> String {
@@ -111,12 +254,21 @@ pub fn format_html(snapshot: &SnapshotRaw, result: &RetainersResult) -> String {
         "<h1>{title}</h1><p><strong>Target:</strong> {} (id={})</p>",
         target_name, target_id
     );
+    let _ = writeln!(output, "<p>{}</p>", stats_line(&result.stats));
+    if let Some(warning) = truncation_warning(&result.stats) {
+        let _ = writeln!(output, "<p><strong>{warning}</strong></p>");
+    }
 
     for (index, path) in result.paths.iter().enumerate() {
-        let _ = writeln!(output, "<h2>Path #{}</h2>", index + 1);
+        let _ = writeln!(
+            output,
+            "<h2>Path #{}{}</h2>",
+            index + 1,
+            similar_note(result, index, Charset::Unicode)
+        );
         let _ = writeln!(output, "<ol>");
         for step in path {
-            let line = format_step(snapshot, step);
+            let line = format_step(snapshot, step, Charset::Unicode);
             let _ = writeln!(output, "<li>{line}</li>");
         }
         let _ = writeln!(output, "</ol>");
@@ -126,7 +278,80 @@ pub fn format_html(snapshot: &SnapshotRaw, result: &RetainersResult) -> String {
     output
 }
 
-fn node_json(snapshot: &SnapshotRaw, node_index: usize) -> NodeJson {
+pub(crate) fn paths_json(snapshot: &SnapshotRaw, paths: &[Vec<RetainerLink>]) -> Vec<PathJson> {
+    let mut output = Vec::new();
+    for path in paths {
+        let mut steps = Vec::new();
+        for step in path {
+            steps.push(step_json(snapshot, step));
+        }
+        output.push(PathJson { steps });
+    }
+    output
+}
+
+/// "Paths found: 3 (length 2-5, 2 distinct roots)".
+pub(crate) fn stats_line(stats: &RetainerStats) -> String {
+    let lengths = match (stats.min_len, stats.max_len) {
+        (Some(min), Some(max)) if min == max => format!("length {min}"),
+        (Some(min), Some(max)) => format!("length {min}-{max}"),
+        _ => "no path to a root".to_string(),
+    };
+    let roots = match stats.distinct_roots_reached {
+        1 => "1 distinct root".to_string(),
+        count => format!("{count} distinct roots"),
+    };
+    format!(
+        "Paths found: {} ({lengths}, {roots})",
+        stats.total_paths_found
+    )
+}
+
+/// Open partial paths per depth. The last depth of a search stopped by `--max-depth`
+/// is marked, since those paths are the ones a larger limit would continue.
+fn write_depth_histogram(output: &mut String, result: &RetainersResult) {
+    let Some(last) = result.depth_histogram.len().checked_sub(1) else {
+        return;
+    };
+    let _ = writeln!(output, "  - Open paths by depth:");
+    for (depth, open) in result.depth_histogram.iter().enumerate() {
+        let note = if depth == last && result.stats.hit_max_depth {
+            " (cut off by --max-depth)"
+        } else {
+            ""
+        };
+        let _ = writeln!(output, "    - {depth}: {open}{note}");
+    }
+}
+
+/// " (×3 similar)" after a path that stands for others collapsed into it.
+fn similar_note(result: &RetainersResult, index: usize, charset: Charset) -> String {
+    match result.similar_count(index) {
+        0 | 1 => String::new(),
+        count => format!(" ({}{count} similar)", charset.times()),
+    }
+}
+
+/// Names the limit that stopped the search, if any.
+pub(crate) fn truncation_warning(stats: &RetainerStats) -> Option<&'static str> {
+    if stats.hit_max_paths {
+        Some("Truncated: --paths limit reached; more paths may exist")
+    } else if stats.hit_max_depth {
+        Some("Truncated: --max-depth reached; longer paths may exist")
+    } else {
+        None
+    }
+}
+
+fn step_json(snapshot: &SnapshotRaw, step: &RetainerLink) -> StepJson {
+    StepJson {
+        from: node_json(snapshot, step.from_node),
+        edge: edge_json(snapshot, step.edge_index),
+        to: node_json(snapshot, step.to_node),
+    }
+}
+
+pub(crate) fn node_json(snapshot: &SnapshotRaw, node_index: usize) -> NodeJson {
     let node = snapshot.node_view(node_index);
     NodeJson {
         index: node_index,
@@ -143,56 +368,43 @@ fn edge_json(snapshot: &SnapshotRaw, edge_index: usize) -> EdgeJson {
         index: edge_index,
         edge_type: edge.and_then(|value| value.edge_type()).map(str::to_string),
         name_or_index,
-        name: edge_name(snapshot, edge),
+        name: edge_name(snapshot, edge).map(Cow::into_owned),
     }
 }
 
-fn format_step(snapshot: &SnapshotRaw, step: &RetainerLink) -> String {
+fn format_step(snapshot: &SnapshotRaw, step: &RetainerLink, charset: Charset) -> String {
     let from = snapshot.node_view(step.from_node);
     let to = snapshot.node_view(step.to_node);
     let edge = snapshot.edge_view(step.edge_index);
 
     let from_name = from
         .and_then(|node| node.name())
-        .map(escape_inline_with_details)
+        .map(|value| escape_inline_with_details(value, charset))
         .unwrap_or_else(|| "<unknown>".to_string());
     let to_name = to
         .and_then(|node| node.name())
-        .map(escape_inline_with_details)
+        .map(|value| escape_inline_with_details(value, charset))
         .unwrap_or_else(|| "<unknown>".to_string());
     let edge_type = edge
         .and_then(|value| value.edge_type())
-        .map(escape_inline_with_details)
+        .map(|value| escape_inline_with_details(value, charset))
         .unwrap_or_else(|| "unknown".to_string());
     let edge_name = edge_name(snapshot, edge)
         .as_deref()
-        .map(escape_inline_with_details)
+        .map(|value| escape_inline_with_details(value, charset))
         .unwrap_or_else(|| "<unknown>".to_string());
 
     format!("{from_name} --({edge_type}){edge_name}--> {to_name}")
 }
 
-fn edge_name(snapshot: &SnapshotRaw, edge: Option<EdgeView<'_>>) -> Option<String> {
-    let edge = edge?;
-    let edge_type = edge.edge_type().unwrap_or("unknown");
-    let name_or_index = edge.name_or_index().unwrap_or(-1);
-
-    if edge_type == "element" {
-        return Some(format!("[{name_or_index}]"));
-    }
-
-    if name_or_index >= 0 {
-        let idx = name_or_index as usize;
-        if let Some(name) = snapshot.strings.get(idx) {
-            return Some(name.to_string());
-        }
-        return Some(format!("<string:{name_or_index}>"));
-    }
-
-    Some(format!("<name:{name_or_index}>"))
+pub(crate) fn edge_name<'a>(
+    snapshot: &'a SnapshotRaw,
+    edge: Option<EdgeView<'_>>,
+) -> Option<Cow<'a, str>> {
+    detail::edge_name(snapshot, edge?)
 }
 
-fn escape_inline_with_details(value: &str) -> String {
+fn escape_inline_with_details(value: &str, charset: Charset) -> String {
     const MAX_LEN: usize = 120;
     let normalized = normalize_whitespace(value);
     if normalized.chars().count() <= MAX_LEN {
@@ -201,7 +413,8 @@ fn escape_inline_with_details(value: &str) -> String {
     let summary = truncate_chars(&normalized, MAX_LEN);
     let summary = escape_html_inline(&summary);
     let full = escape_html_inline(&normalized);
-    format!("<details><summary>{summary}…</summary><div>{full}</div></details>")
+    let ellipsis = charset.ellipsis();
+    format!("<details><summary>{summary}{ellipsis}</summary><div>{full}</div></details>")
 }
 
 fn escape_inline(value: &str) -> String {
@@ -0,0 +1,145 @@
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::analysis::strings::StringsResult;
+use crate::error::SnapshotError;
+use crate::output::header::JsonHeader;
+use crate::output::json::JsonStyle;
+
+#[derive(Debug, Serialize)]
+struct StringsJson<'a> {
+    version: u32,
+    #[serde(flatten)]
+    header: JsonHeader,
+    string_count: usize,
+    total_bytes: u64,
+    duplicate_values: u64,
+    duplicate_strings: u64,
+    duplicate_bytes: u64,
+    largest: Vec<StringJson<'a>>,
+    duplicates: Vec<DuplicateJson<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct StringJson<'a> {
+    index: usize,
+    bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    preview: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+struct DuplicateJson<'a> {
+    first_index: usize,
+    count: u64,
+    bytes: u64,
+    wasted_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    preview: Option<&'a str>,
+}
+
+pub fn format_markdown(result: &StringsResult) -> String {
+    let previews = result.previews;
+    let mut output = String::new();
+    let _ = writeln!(output, "# HeapSnapshot Strings");
+    let _ = writeln!(output);
+    let _ = writeln!(output, "- Strings: {}", result.string_count);
+    let _ = writeln!(output, "- Total bytes: {}", result.total_bytes);
+    let _ = writeln!(
+        output,
+        "- Duplicated values: {} ({} extra copies, {} bytes)",
+        result.duplicate_values, result.duplicate_strings, result.duplicate_bytes
+    );
+    if !previews {
+        let _ = writeln!(output, "- Contents hidden; pass --verbose to show previews");
+    }
+    let _ = writeln!(output);
+
+    let _ = writeln!(output, "## Largest Strings");
+    let _ = writeln!(output);
+    if previews {
+        let _ = writeln!(output, "| Index | Bytes | Preview |");
+        let _ = writeln!(output, "| ---: | ---: | --- |");
+    } else {
+        let _ = writeln!(output, "| Index | Bytes |");
+        let _ = writeln!(output, "| ---: | ---: |");
+    }
+    for entry in &result.largest {
+        let _ = write!(output, "| {} | {} |", entry.index, entry.bytes);
+        if let Some(preview) = &entry.preview {
+            let _ = write!(output, " {} |", escape_table(preview));
+        }
+        let _ = writeln!(output);
+    }
+
+    let _ = writeln!(output);
+    let _ = writeln!(output, "## Duplicated Values");
+    let _ = writeln!(output);
+    if result.duplicates.is_empty() {
+        let _ = writeln!(output, "No duplicated values.");
+        return output;
+    }
+    if previews {
+        let _ = writeln!(
+            output,
+            "| First Index | Copies | Bytes | Wasted Bytes | Preview |"
+        );
+        let _ = writeln!(output, "| ---: | ---: | ---: | ---: | --- |");
+    } else {
+        let _ = writeln!(output, "| First Index | Copies | Bytes | Wasted Bytes |");
+        let _ = writeln!(output, "| ---: | ---: | ---: | ---: |");
+    }
+    for entry in &result.duplicates {
+        let _ = write!(
+            output,
+            "| {} | {} | {} | {} |",
+            entry.first_index,
+            entry.count,
+            entry.bytes,
+            entry.wasted_bytes()
+        );
+        if let Some(preview) = &entry.preview {
+            let _ = write!(output, " {} |", escape_table(preview));
+        }
+        let _ = writeln!(output);
+    }
+    output
+}
+
+pub fn format_json(result: &StringsResult, style: JsonStyle) -> Result<String, SnapshotError> {
+    let payload = StringsJson {
+        version: 1,
+        header: JsonHeader::new(result.snapshot_fingerprint),
+        string_count: result.string_count,
+        total_bytes: result.total_bytes,
+        duplicate_values: result.duplicate_values,
+        duplicate_strings: result.duplicate_strings,
+        duplicate_bytes: result.duplicate_bytes,
+        largest: result
+            .largest
+            .iter()
+            .map(|entry| StringJson {
+                index: entry.index,
+                bytes: entry.bytes,
+                preview: entry.preview.as_deref(),
+            })
+            .collect(),
+        duplicates: result
+            .duplicates
+            .iter()
+            .map(|entry| DuplicateJson {
+                first_index: entry.first_index,
+                count: entry.count,
+                bytes: entry.bytes,
+                wasted_bytes: entry.wasted_bytes(),
+                preview: entry.preview.as_deref(),
+            })
+            .collect(),
+    };
+    style.render(&payload)
+}
+
+fn escape_table(value: &str) -> String {
+    value.replace('|', "\\|")
+}
@@ -1,52 +1,318 @@
 use std::fmt::Write as _;
 use std::path::Path;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::analysis::summary::SummaryResult;
+use crate::analysis::summary::{ExcludedNodes, SYNTHETIC_NODE_TYPES, SummaryResult, SummaryRow};
 use crate::error::SnapshotError;
+use crate::output::charset::Charset;
+use crate::output::color::Color;
+use crate::output::csv::TableWriter;
+use crate::output::header::{JsonHeader, parse_fingerprint};
+use crate::output::json::JsonStyle;
 
 #[derive(Debug, Serialize)]
 struct SummaryJson<'a> {
     version: u32,
+    #[serde(flatten)]
+    header: JsonHeader,
     total_nodes: usize,
+    total_rows: usize,
+    skip: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    node_type: Option<&'a str>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    detached_only: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    unreachable_only: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    excluded: Option<ExcludedJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    edge_types: Option<EdgeTypesJson<'a>>,
     rows: Vec<SummaryRowJson<'a>>,
 }
 
+#[derive(Debug, Serialize)]
+struct EdgeTypesJson<'a> {
+    total_edges: u64,
+    types: Vec<EdgeTypeJson<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct EdgeTypeJson<'a> {
+    edge_type: &'a str,
+    count: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct ExcludedJson {
+    node_types: &'static [&'static str],
+    count: u64,
+    self_size_sum_bytes: i64,
+}
+
 #[derive(Debug, Serialize)]
 struct SummaryRowJson<'a> {
     name: &'a str,
     count: u64,
     #[serde(rename = "self_size_sum_bytes")]
     self_size_sum_bytes: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sample_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    weak_only_count: Option<u64>,
+    #[serde(skip_serializing_if = "<[i64]>::is_empty")]
+    examples: &'a [i64],
+}
+
+/// Owned counterpart of `SummaryJson` for reading a saved summary back. Fields added in
+/// later versions are optional so older files still parse.
+#[derive(Debug, Deserialize)]
+struct SummaryJsonInput {
+    version: u32,
+    #[serde(default)]
+    snapshot_fingerprint: Option<String>,
+    total_nodes: usize,
+    total_rows: usize,
+    #[serde(default)]
+    skip: usize,
+    #[serde(default)]
+    node_type: Option<String>,
+    #[serde(default)]
+    detached_only: bool,
+    #[serde(default)]
+    unreachable_only: bool,
+    #[serde(default)]
+    excluded: Option<ExcludedJsonInput>,
+    #[serde(default)]
+    edge_types: Option<EdgeTypesJsonInput>,
+    rows: Vec<SummaryRowJsonInput>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExcludedJsonInput {
+    count: u64,
+    self_size_sum_bytes: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct EdgeTypesJsonInput {
+    types: Vec<EdgeTypeJsonInput>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EdgeTypeJsonInput {
+    edge_type: String,
+    count: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct SummaryRowJsonInput {
+    name: String,
+    count: u64,
+    self_size_sum_bytes: i64,
+    #[serde(default)]
+    sample_id: Option<i64>,
+    #[serde(default)]
+    weak_only_count: Option<u64>,
+    #[serde(default)]
+    examples: Vec<i64>,
+}
+
+/// Reads the output of `format_json` back into a `SummaryResult`, e.g. to diff two saved
+/// summaries without their snapshots. Only the listed rows come back: a summary written
+/// with `--top` keeps `total_rows` but not the rows past the cut.
+pub fn parse_json(text: &str) -> Result<SummaryResult, SnapshotError> {
+    let input: SummaryJsonInput =
+        serde_json::from_str(text).map_err(|err| SnapshotError::InvalidData {
+            details: format!("not a heapsnap summary json: {err}"),
+        })?;
+    if input.version != 1 {
+        return Err(SnapshotError::InvalidData {
+            details: format!(
+                "unsupported summary json version {} (expected 1)",
+                input.version
+            ),
+        });
+    }
+    let snapshot_fingerprint = parse_fingerprint(input.snapshot_fingerprint.as_deref())?;
+    Ok(SummaryResult {
+        total_nodes: input.total_nodes,
+        total_rows: input.total_rows,
+        skip: input.skip,
+        rows: input
+            .rows
+            .into_iter()
+            .map(|row| SummaryRow {
+                name: row.name,
+                count: row.count,
+                self_size_sum: row.self_size_sum_bytes,
+                sample_id: row.sample_id,
+                weak_only_count: row.weak_only_count,
+                name_index: None,
+                examples: row.examples,
+            })
+            .collect(),
+        empty_name_types: Vec::new(),
+        excluded: input.excluded.map(|excluded| ExcludedNodes {
+            count: excluded.count,
+            self_size_sum: excluded.self_size_sum_bytes,
+        }),
+        node_type: input.node_type,
+        detached_only: input.detached_only,
+        unreachable_only: input.unreachable_only,
+        edge_types: input.edge_types.map(|edge_types| {
+            edge_types
+                .types
+                .into_iter()
+                .map(|edge_type| (edge_type.edge_type, edge_type.count))
+                .collect()
+        }),
+        snapshot_fingerprint,
+    })
 }
 
 pub fn format_markdown(result: &SummaryResult) -> String {
+    format_markdown_colored(result, Color::OFF, Charset::Unicode)
+}
+
+/// `format_markdown` with large self sizes highlighted when `color` is enabled, and
+/// truncation marks spelled in `charset`.
+pub fn format_markdown_colored(result: &SummaryResult, color: Color, charset: Charset) -> String {
     let mut output = String::new();
     let _ = writeln!(output, "# HeapSnapshot Summary");
     let _ = writeln!(output, "");
     let _ = writeln!(output, "- Total nodes: {}", result.total_nodes);
+    if result.skip > 0 {
+        let _ = writeln!(
+            output,
+            "- Rows: {}..{} of {}",
+            result.skip,
+            result.skip + result.rows.len(),
+            result.total_rows
+        );
+    }
+    if let Some(node_type) = &result.node_type {
+        let _ = writeln!(output, "- Node type: {node_type}");
+    }
+    if result.detached_only {
+        let _ = writeln!(output, "- Detached DOM nodes only");
+    }
+    if result.unreachable_only {
+        let _ = writeln!(
+            output,
+            "- Unreachable nodes only (no path from the GC roots)"
+        );
+    }
+    if let Some(excluded) = &result.excluded {
+        let _ = writeln!(
+            output,
+            "- Excluded {} nodes: {} ({} bytes)",
+            SYNTHETIC_NODE_TYPES.join("/"),
+            excluded.count,
+            excluded.self_size_sum
+        );
+    }
     let _ = writeln!(output, "");
-    let _ = writeln!(output, "| Constructor | Count | Self Size Sum (bytes) |");
-    let _ = writeln!(output, "| --- | ---: | ---: |");
+    let weak_only = has_weak_only_counts(result);
+    let examples = result.rows.iter().any(|row| !row.examples.is_empty());
+    let _ = write!(output, "| Constructor | Count | Self Size Sum (bytes) |");
+    if weak_only {
+        let _ = write!(output, " Weak-only |");
+    }
+    if examples {
+        let _ = write!(output, " Examples |");
+    }
+    let _ = writeln!(output);
+    let _ = write!(output, "| --- | ---: | ---: |");
+    if weak_only {
+        let _ = write!(output, " ---: |");
+    }
+    if examples {
+        let _ = write!(output, " --- |");
+    }
+    let _ = writeln!(output);
+    let max_size = result
+        .rows
+        .iter()
+        .map(|row| row.self_size_sum)
+        .max()
+        .unwrap_or(0);
     for row in &result.rows {
         let name = if row.name.is_empty() {
-            format_empty_name(&result.empty_name_types)
+            format_empty_name(&result.empty_name_types, charset)
         } else {
             row.name.clone()
         };
-        let _ = writeln!(
+        let _ = write!(
             output,
             "| {} | {} | {} |",
-            escape_table_cell(name.as_str()),
+            escape_table_cell(name.as_str(), charset),
             row.count,
-            row.self_size_sum
+            color.size(row.self_size_sum, max_size)
         );
+        if weak_only {
+            let _ = write!(output, " {} |", row.weak_only_count.unwrap_or(0));
+        }
+        if examples {
+            let _ = write!(output, " {} |", format_examples(&row.examples, charset));
+        }
+        let _ = writeln!(output);
+    }
+    if let Some(edge_types) = &result.edge_types {
+        write_edge_types_markdown(&mut output, edge_types, charset);
     }
     output
 }
 
-pub fn format_json(result: &SummaryResult) -> Result<String, SnapshotError> {
+/// Example ids shown in a markdown cell at most; JSON lists all of them.
+const MARKDOWN_EXAMPLES: usize = 3;
+
+/// "1, 3, 5, …": the first few example ids of a row, for `detail --id`.
+fn format_examples(ids: &[i64], charset: Charset) -> String {
+    let mut text = ids
+        .iter()
+        .take(MARKDOWN_EXAMPLES)
+        .map(i64::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    if ids.len() > MARKDOWN_EXAMPLES {
+        let _ = write!(text, ", {}", charset.ellipsis());
+    }
+    text
+}
+
+/// Rows carry `weak_only_count` only when the summary was run with `reachability`.
+fn has_weak_only_counts(result: &SummaryResult) -> bool {
+    result.rows.iter().any(|row| row.weak_only_count.is_some())
+}
+
+fn write_edge_types_markdown(output: &mut String, edge_types: &[(String, u64)], charset: Charset) {
+    let total: u64 = edge_types.iter().map(|(_, count)| count).sum();
+    let _ = writeln!(output);
+    let _ = writeln!(output, "## Edge Types");
+    let _ = writeln!(output);
+    let _ = writeln!(output, "- Total edges: {total}");
+    let _ = writeln!(output);
+    let _ = writeln!(output, "| Edge Type | Count | Share |");
+    let _ = writeln!(output, "| --- | ---: | ---: |");
+    for (edge_type, count) in edge_types {
+        let share = if total == 0 {
+            0.0
+        } else {
+            *count as f64 * 100.0 / total as f64
+        };
+        let _ = writeln!(
+            output,
+            "| {} | {} | {:.1}% |",
+            escape_table_cell(edge_type, charset),
+            count,
+            share
+        );
+    }
+}
+
+pub fn format_json(result: &SummaryResult, style: JsonStyle) -> Result<String, SnapshotError> {
     let rows = result
         .rows
         .iter()
@@ -54,30 +320,58 @@ pub fn format_json(result: &SummaryResult) -> Result<String, SnapshotError> {
             name: row.name.as_str(),
             count: row.count,
             self_size_sum_bytes: row.self_size_sum,
+            sample_id: row.sample_id,
+            weak_only_count: row.weak_only_count,
+            examples: &row.examples,
         })
         .collect::<Vec<_>>();
     let payload = SummaryJson {
         version: 1,
+        header: JsonHeader::new(result.snapshot_fingerprint),
         total_nodes: result.total_nodes,
+        total_rows: result.total_rows,
+        skip: result.skip,
+        node_type: result.node_type.as_deref(),
+        detached_only: result.detached_only,
+        unreachable_only: result.unreachable_only,
+        excluded: result.excluded.as_ref().map(|excluded| ExcludedJson {
+            node_types: SYNTHETIC_NODE_TYPES,
+            count: excluded.count,
+            self_size_sum_bytes: excluded.self_size_sum,
+        }),
+        edge_types: result.edge_types.as_ref().map(|edge_types| EdgeTypesJson {
+            total_edges: edge_types.iter().map(|(_, count)| count).sum(),
+            types: edge_types
+                .iter()
+                .map(|(edge_type, count)| EdgeTypeJson {
+                    edge_type: edge_type.as_str(),
+                    count: *count,
+                })
+                .collect(),
+        }),
         rows,
     };
-    serde_json::to_string_pretty(&payload).map_err(SnapshotError::Json)
+    style.render(&payload)
 }
 
-pub fn format_csv(result: &SummaryResult) -> String {
-    let mut output = String::new();
-    output.push_str("constructor,count,self_size_sum_bytes\n");
+pub fn format_csv(result: &SummaryResult, delimiter: char) -> String {
+    let weak_only = has_weak_only_counts(result);
+    let mut header = vec!["constructor", "count", "self_size_sum_bytes"];
+    if weak_only {
+        header.push("weak_only_count");
+    }
+    let mut writer = TableWriter::with_delimiter(&header, delimiter);
     for row in &result.rows {
-        output.push('"');
-        output.push_str(&row.name.replace('"', "\"\""));
-        output.push('"');
-        output.push(',');
-        output.push_str(&row.count.to_string());
-        output.push(',');
-        output.push_str(&row.self_size_sum.to_string());
-        output.push('\n');
+        let count = row.count.to_string();
+        let self_size_sum = row.self_size_sum.to_string();
+        let weak_only_count = row.weak_only_count.unwrap_or(0).to_string();
+        let mut fields = vec![row.name.as_str(), count.as_str(), self_size_sum.as_str()];
+        if weak_only {
+            fields.push(weak_only_count.as_str());
+        }
+        writer.row(&fields);
     }
-    output
+    writer.finish()
 }
 
 pub fn format_html(result: &SummaryResult, source_path: &Path) -> String {
@@ -97,13 +391,22 @@ pub fn format_html(result: &SummaryResult, source_path: &Path) -> String {
         "<p><strong>Total nodes:</strong> {}</p>",
         result.total_nodes
     );
+    if let Some(excluded) = &result.excluded {
+        let _ = writeln!(
+            output,
+            "<p><strong>Excluded {} nodes:</strong> {} ({} bytes)</p>",
+            SYNTHETIC_NODE_TYPES.join("/"),
+            excluded.count,
+            excluded.self_size_sum
+        );
+    }
     let _ = writeln!(
         output,
         "<table><thead><tr><th>Constructor</th><th>Count</th><th>Self Size Sum (bytes)</th></tr></thead><tbody>"
     );
     for row in &result.rows {
         let display_name = if row.name.is_empty() {
-            format_empty_name(&result.empty_name_types)
+            format_empty_name(&result.empty_name_types, Charset::Unicode)
         } else {
             row.name.clone()
         };
@@ -132,7 +435,7 @@ fn escape_table(value: &str) -> String {
     value.replace('|', "\\|")
 }
 
-fn escape_table_cell(value: &str) -> String {
+fn escape_table_cell(value: &str, charset: Charset) -> String {
     const MAX_LEN: usize = 120;
     let normalized = normalize_whitespace(value);
     if normalized.chars().count() <= MAX_LEN {
@@ -142,7 +445,8 @@ fn escape_table_cell(value: &str) -> String {
     let summary = truncate_chars(&normalized, MAX_LEN);
     let summary = escape_html_inline(&summary);
     let full = escape_html_inline(&normalized);
-    format!("<details><summary>{summary}…</summary><div>{full}</div></details>")
+    let ellipsis = charset.ellipsis();
+    format!("<details><summary>{summary}{ellipsis}</summary><div>{full}</div></details>")
 }
 
 fn escape_table_inline(value: &str) -> String {
@@ -234,7 +538,10 @@ fn base_styles() -> &'static str {
     "body{font-family:ui-sans-serif,system-ui,-apple-system,Segoe UI,Roboto,Helvetica,Arial,sans-serif;margin:24px;color:#111}table{border-collapse:collapse;width:100%;margin-top:12px}th,td{border:1px solid #ddd;padding:8px;vertical-align:top}th{text-align:left;background:#f6f6f6}tr:nth-child(even){background:#fafafa}.note{margin-top:16px;color:#444;font-size:0.9em}"
 }
 
-fn format_empty_name(types: &[crate::analysis::summary::EmptyTypeSummary]) -> String {
+fn format_empty_name(
+    types: &[crate::analysis::summary::EmptyTypeSummary],
+    charset: Charset,
+) -> String {
     if types.is_empty() {
         return "(empty)".to_string();
     }
@@ -242,6 +549,8 @@ fn format_empty_name(types: &[crate::analysis::summary::EmptyTypeSummary]) -> St
     for item in types.iter().take(3) {
         parts.push(format!("{}={}", item.node_type, item.count));
     }
-    let suffix = if types.len() > 3 { ", …" } else { "" };
-    format!("(empty; types: {}{suffix})", parts.join(", "))
+    if types.len() > 3 {
+        parts.push(charset.ellipsis().to_string());
+    }
+    format!("(empty; types: {})", parts.join(", "))
 }
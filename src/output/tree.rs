@@ -0,0 +1,103 @@
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::analysis::tree::{TreeNode, TreeResult};
+use crate::error::SnapshotError;
+use crate::output::dominator::retained_percent;
+use crate::output::header::JsonHeader;
+use crate::output::json::JsonStyle;
+use crate::output::retainers::{NodeJson, node_json};
+use crate::snapshot::SnapshotRaw;
+
+#[derive(Debug, Serialize)]
+struct TreeJson {
+    version: u32,
+    #[serde(flatten)]
+    header: JsonHeader,
+    total_size_bytes: i64,
+    root: TreeNodeJson,
+}
+
+#[derive(Debug, Serialize)]
+struct TreeNodeJson {
+    #[serde(flatten)]
+    node: NodeJson,
+    retained_size_bytes: i64,
+    retained_percent: f64,
+    children: Vec<TreeNodeJson>,
+    omitted_children: usize,
+    omitted_retained_size_bytes: i64,
+}
+
+pub fn format_markdown(snapshot: &SnapshotRaw, result: &TreeResult) -> String {
+    let mut output = String::new();
+    let (name, id) = node_label(snapshot, result.root.node_index);
+    let _ = writeln!(output, "- Dominator tree for {name} (id={id})");
+    write_node_markdown(&mut output, snapshot, &result.root, result.total_size, 1);
+    output
+}
+
+fn write_node_markdown(
+    output: &mut String,
+    snapshot: &SnapshotRaw,
+    node: &TreeNode,
+    total_size: i64,
+    depth: usize,
+) {
+    let indent = "  ".repeat(depth);
+    let (name, id) = node_label(snapshot, node.node_index);
+    let _ = writeln!(
+        output,
+        "{indent}- {name} (id={id}) retained={} bytes ({:.1}%)",
+        node.retained_size,
+        retained_percent(node.retained_size, total_size)
+    );
+    for child in &node.children {
+        write_node_markdown(output, snapshot, child, total_size, depth + 1);
+    }
+    if node.omitted_children > 0 {
+        let _ = writeln!(
+            output,
+            "{indent}  - ... {} more (retained={} bytes)",
+            node.omitted_children, node.omitted_retained_size
+        );
+    }
+}
+
+fn node_label(snapshot: &SnapshotRaw, node_index: usize) -> (&str, i64) {
+    let node = snapshot.node_view(node_index);
+    (
+        node.and_then(|value| value.name()).unwrap_or("<unknown>"),
+        node.and_then(|value| value.id()).unwrap_or(-1),
+    )
+}
+
+pub fn format_json(
+    snapshot: &SnapshotRaw,
+    result: &TreeResult,
+    style: JsonStyle,
+) -> Result<String, SnapshotError> {
+    let payload = TreeJson {
+        version: 1,
+        header: JsonHeader::from_snapshot(snapshot),
+        total_size_bytes: result.total_size,
+        root: node_tree_json(snapshot, &result.root, result.total_size),
+    };
+    style.render(&payload)
+}
+
+fn node_tree_json(snapshot: &SnapshotRaw, node: &TreeNode, total_size: i64) -> TreeNodeJson {
+    TreeNodeJson {
+        node: node_json(snapshot, node.node_index),
+        retained_size_bytes: node.retained_size,
+        retained_percent: retained_percent(node.retained_size, total_size),
+        children: node
+            .children
+            .iter()
+            .map(|child| node_tree_json(snapshot, child, total_size))
+            .collect(),
+        omitted_children: node.omitted_children,
+        omitted_retained_size_bytes: node.omitted_retained_size,
+    }
+}
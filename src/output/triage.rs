@@ -0,0 +1,87 @@
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::analysis::triage::TriageResult;
+use crate::error::SnapshotError;
+use crate::output::charset::Charset;
+use crate::output::header::JsonHeader;
+use crate::output::json::JsonStyle;
+use crate::output::retainers::{self, NodeJson, PathJson};
+use crate::snapshot::SnapshotRaw;
+
+#[derive(Debug, Serialize)]
+struct TriageJson {
+    version: u32,
+    #[serde(flatten)]
+    header: JsonHeader,
+    total_nodes: usize,
+    targets: Vec<TriageTargetJson>,
+}
+
+#[derive(Debug, Serialize)]
+struct TriageTargetJson {
+    rank: usize,
+    self_size_bytes: i64,
+    target: NodeJson,
+    paths: Vec<PathJson>,
+}
+
+pub fn format_markdown(snapshot: &SnapshotRaw, result: &TriageResult, charset: Charset) -> String {
+    let mut output = String::new();
+    let _ = writeln!(output, "# HeapSnapshot Triage");
+    let _ = writeln!(output);
+    let _ = writeln!(
+        output,
+        "- Biggest nodes: {} of {}",
+        result.entries.len(),
+        result.total_nodes
+    );
+
+    for (index, entry) in result.entries.iter().enumerate() {
+        let _ = writeln!(output);
+        let _ = writeln!(
+            output,
+            "## #{} self_size={} bytes",
+            index + 1,
+            entry.self_size
+        );
+        let _ = writeln!(output);
+        output.push_str(&retainers::format_markdown(
+            snapshot,
+            &entry.retainers,
+            charset,
+        ));
+        if entry.retainers.paths.is_empty() {
+            let _ = writeln!(output, "  - (no path to GC roots within --max-depth)");
+        }
+    }
+
+    output
+}
+
+pub fn format_json(
+    snapshot: &SnapshotRaw,
+    result: &TriageResult,
+    style: JsonStyle,
+) -> Result<String, SnapshotError> {
+    let targets = result
+        .entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| TriageTargetJson {
+            rank: index + 1,
+            self_size_bytes: entry.self_size,
+            target: retainers::node_json(snapshot, entry.retainers.target),
+            paths: retainers::paths_json(snapshot, &entry.retainers.paths),
+        })
+        .collect();
+
+    let payload = TriageJson {
+        version: 1,
+        header: JsonHeader::from_snapshot(snapshot),
+        total_nodes: result.total_nodes,
+        targets,
+    };
+    style.render(&payload)
+}
@@ -0,0 +1,98 @@
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::analysis::verify::VerifyReport;
+use crate::error::SnapshotError;
+use crate::output::json::JsonStyle;
+
+#[derive(Debug, Serialize)]
+struct VerifyJson<'a> {
+    version: u32,
+    ok: bool,
+    node_count: usize,
+    edge_count: usize,
+    string_count: usize,
+    declared_node_count: Option<u64>,
+    declared_edge_count: Option<u64>,
+    node_count_matches: bool,
+    edge_count_matches: bool,
+    edge_count_sum: u64,
+    edge_sum_matches: bool,
+    out_of_range_to_node_count: u64,
+    name_index_out_of_range_count: u64,
+    gc_root_count: u64,
+    has_gc_roots: bool,
+    checks: Vec<VerifyCheckJson<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct VerifyCheckJson<'a> {
+    name: &'a str,
+    status: &'a str,
+    details: &'a str,
+}
+
+pub fn format_markdown(report: &VerifyReport) -> String {
+    let mut output = String::new();
+    let _ = writeln!(output, "# HeapSnapshot Verify");
+    let _ = writeln!(output);
+    let _ = writeln!(
+        output,
+        "- Result: {}",
+        if report.passed() {
+            "PASS".to_string()
+        } else {
+            format!("FAIL ({} check(s) failed)", report.failed_count())
+        }
+    );
+    let _ = writeln!(
+        output,
+        "- Nodes: {} / Edges: {} / Strings: {}",
+        report.node_count, report.edge_count, report.string_count
+    );
+    let _ = writeln!(output);
+    let _ = writeln!(output, "| Check | Status | Details |");
+    let _ = writeln!(output, "| --- | --- | --- |");
+    for check in &report.checks {
+        let _ = writeln!(
+            output,
+            "| {} | {} | {} |",
+            check.name,
+            check.status.label(),
+            check.details.replace('|', "\\|")
+        );
+    }
+    output
+}
+
+pub fn format_json(report: &VerifyReport, style: JsonStyle) -> Result<String, SnapshotError> {
+    let checks = report
+        .checks
+        .iter()
+        .map(|check| VerifyCheckJson {
+            name: check.name,
+            status: check.status.label(),
+            details: check.details.as_str(),
+        })
+        .collect::<Vec<_>>();
+    let payload = VerifyJson {
+        version: 1,
+        ok: report.passed(),
+        node_count: report.node_count,
+        edge_count: report.edge_count,
+        string_count: report.string_count,
+        declared_node_count: report.declared_node_count,
+        declared_edge_count: report.declared_edge_count,
+        node_count_matches: report.node_count_matches(),
+        edge_count_matches: report.edge_count_matches(),
+        edge_count_sum: report.edge_count_sum,
+        edge_sum_matches: report.edge_sum_matches(),
+        out_of_range_to_node_count: report.out_of_range_to_node,
+        name_index_out_of_range_count: report.name_index_out_of_range,
+        gc_root_count: report.gc_root_count,
+        has_gc_roots: report.has_gc_roots(),
+        checks,
+    };
+    style.render(&payload)
+}
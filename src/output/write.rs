@@ -5,6 +5,10 @@ use std::path::{Path, PathBuf};
 use crate::error::SnapshotError;
 
 pub fn write_atomic(path: &Path, content: &str) -> Result<(), SnapshotError> {
+    write_atomic_bytes(path, content.as_bytes())
+}
+
+pub fn write_atomic_bytes(path: &Path, content: &[u8]) -> Result<(), SnapshotError> {
     let temp_path = temp_path(path);
     let result = write_file(&temp_path, content);
     if let Err(err) = result {
@@ -15,10 +19,9 @@ pub fn write_atomic(path: &Path, content: &str) -> Result<(), SnapshotError> {
     Ok(())
 }
 
-fn write_file(path: &Path, content: &str) -> Result<(), SnapshotError> {
+fn write_file(path: &Path, content: &[u8]) -> Result<(), SnapshotError> {
     let mut file = File::create(path).map_err(SnapshotError::Io)?;
-    file.write_all(content.as_bytes())
-        .map_err(SnapshotError::Io)?;
+    file.write_all(content).map_err(SnapshotError::Io)?;
     file.sync_all().map_err(SnapshotError::Io)?;
     Ok(())
 }
@@ -32,6 +35,45 @@ fn temp_path(path: &Path) -> PathBuf {
     temp
 }
 
+/// Streams what `write` produces to `path` (atomically, like `write_atomic`) or stdout,
+/// without first collecting it into a string.
+pub fn write_or_stdout_with<F>(path: Option<&Path>, write: F) -> Result<(), SnapshotError>
+where
+    F: FnOnce(&mut dyn Write) -> Result<(), SnapshotError>,
+{
+    match path {
+        Some(path) => {
+            let temp_path = temp_path(path);
+            let result = stream_file(&temp_path, write);
+            if let Err(err) = result {
+                let _ = fs::remove_file(&temp_path);
+                return Err(err);
+            }
+            fs::rename(&temp_path, path).map_err(SnapshotError::Io)?;
+            Ok(())
+        }
+        None => {
+            let mut stdout = io::BufWriter::new(io::stdout().lock());
+            write(&mut stdout)?;
+            stdout.flush().map_err(SnapshotError::Io)?;
+            Ok(())
+        }
+    }
+}
+
+fn stream_file<F>(path: &Path, write: F) -> Result<(), SnapshotError>
+where
+    F: FnOnce(&mut dyn Write) -> Result<(), SnapshotError>,
+{
+    let mut file = io::BufWriter::new(File::create(path).map_err(SnapshotError::Io)?);
+    write(&mut file)?;
+    let file = file
+        .into_inner()
+        .map_err(|err| SnapshotError::Io(err.into_error()))?;
+    file.sync_all().map_err(SnapshotError::Io)?;
+    Ok(())
+}
+
 pub fn write_or_stdout(path: Option<&Path>, content: &str) -> Result<(), SnapshotError> {
     match path {
         Some(path) => write_atomic(path, content),
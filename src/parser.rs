@@ -7,49 +7,215 @@ use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, Visitor};
 use crate::cancel::CancelToken;
 use crate::error::SnapshotError;
 use crate::lenient::LenientJsonReader;
-use crate::progress::ProgressReader;
-use crate::snapshot::{SnapshotMeta, SnapshotRaw, SnapshotRoot};
+use crate::progress::{ProgressMode, ProgressReader};
+use crate::snapshot::{
+    MetaIndex, NodeLocations, SnapshotLabels, SnapshotMeta, SnapshotRaw, SnapshotRoot,
+    StreamedSnapshot,
+};
+use crate::zip;
 
 pub struct ReadOptions {
-    pub progress: bool,
+    pub progress: ProgressMode,
     pub cancel: CancelToken,
 }
 
 impl ReadOptions {
-    pub fn new(progress: bool, cancel: CancelToken) -> Self {
-        Self { progress, cancel }
+    /// `progress` is a `ProgressMode`, or a `bool` for always / never.
+    pub fn new(progress: impl Into<ProgressMode>, cancel: CancelToken) -> Self {
+        Self {
+            progress: progress.into(),
+            cancel,
+        }
     }
 }
 
+/// Reads a snapshot file, or the member of a zip archive given as `archive.zip#member`
+/// (see `zip::split_member_path`); the member is decompressed while it is parsed.
+/// A snapshot without nodes is rejected (see `SnapshotRaw::require_nodes`).
 pub fn read_snapshot_file(path: &Path, options: ReadOptions) -> Result<SnapshotRaw, SnapshotError> {
+    if let Some((archive, member)) = zip::split_member_path(path) {
+        let reader = zip::open_member(&archive, &member)?;
+        let total = Some(reader.size);
+        return read_with_progress(reader, total, options);
+    }
     let file = File::open(path)?;
     let total = file.metadata().ok().map(|metadata| metadata.len());
-    let reader = BufReader::new(file);
+    read_with_progress(BufReader::new(file), total, options)
+}
+
+fn read_with_progress<R: Read>(
+    reader: R,
+    total: Option<u64>,
+    options: ReadOptions,
+) -> Result<SnapshotRaw, SnapshotError> {
     let mut progress_reader = ProgressReader::new(reader, options.progress, total, options.cancel);
     let snapshot = read_snapshot(&mut progress_reader)?;
     progress_reader.finish();
+    // Every command needs at least the root node; stop here instead of printing empty tables.
+    snapshot.require_nodes()?;
     Ok(snapshot)
 }
 
 pub fn read_snapshot<R: Read>(reader: &mut R) -> Result<SnapshotRaw, SnapshotError> {
     let mut lenient = LenientJsonReader::new(reader);
-    let mut deserializer = serde_json::Deserializer::from_reader(&mut lenient);
     let mut visitor = SnapshotVisitor::default();
-    match deserializer.deserialize_map(&mut visitor) {
+    let result = serde_json::Deserializer::from_reader(&mut lenient).deserialize_map(&mut visitor);
+    match result {
+        Ok(()) => visitor.into_snapshot(),
+        Err(err) => Err(visitor
+            .duplicate_key_error()
+            .unwrap_or_else(|| map_json_error(err, &lenient))),
+    }
+}
+
+/// Receives each node of a streamed snapshot as its `node_fields` values, in node order.
+pub type NodeSink<'a> = dyn FnMut(&MetaIndex, &[i64]) + 'a;
+
+/// `read_snapshot_file` for snapshots too large to hold: every node is handed to
+/// `on_node` as soon as its fields are read and `edges` is only counted, so memory stays
+/// at the string table. Needs `snapshot.meta` before `nodes`, as V8 writes it.
+pub fn stream_snapshot_file(
+    path: &Path,
+    options: ReadOptions,
+    on_node: &mut NodeSink<'_>,
+) -> Result<StreamedSnapshot, SnapshotError> {
+    if let Some((archive, member)) = zip::split_member_path(path) {
+        let reader = zip::open_member(&archive, &member)?;
+        let total = Some(reader.size);
+        return stream_with_progress(reader, total, options, on_node);
+    }
+    let file = File::open(path)?;
+    let total = file.metadata().ok().map(|metadata| metadata.len());
+    stream_with_progress(BufReader::new(file), total, options, on_node)
+}
+
+fn stream_with_progress<R: Read>(
+    reader: R,
+    total: Option<u64>,
+    options: ReadOptions,
+    on_node: &mut NodeSink<'_>,
+) -> Result<StreamedSnapshot, SnapshotError> {
+    let mut progress_reader = ProgressReader::new(reader, options.progress, total, options.cancel);
+    let snapshot = stream_snapshot(&mut progress_reader, on_node)?;
+    progress_reader.finish();
+    if snapshot.node_count == 0 {
+        return Err(SnapshotError::InvalidData {
+            details: "snapshot contains no nodes".to_string(),
+        });
+    }
+    Ok(snapshot)
+}
+
+/// Streaming counterpart of `read_snapshot` (see `stream_snapshot_file`).
+pub fn stream_snapshot<R: Read>(
+    reader: &mut R,
+    on_node: &mut NodeSink<'_>,
+) -> Result<StreamedSnapshot, SnapshotError> {
+    let mut lenient = LenientJsonReader::new(reader);
+    let mut visitor = StreamingVisitor {
+        on_node,
+        meta: None,
+        labels: SnapshotLabels::default(),
+        strings: Vec::new(),
+        node_values: 0,
+        edge_values: 0,
+        first_node_id: None,
+        last_node_id: None,
+        seen_keys: Vec::new(),
+        error: None,
+    };
+    let result = serde_json::Deserializer::from_reader(&mut lenient).deserialize_map(&mut visitor);
+    match result {
         Ok(()) => visitor.into_snapshot(),
-        Err(err) => Err(map_json_error(err)),
+        Err(err) => Err(visitor
+            .error
+            .take()
+            .unwrap_or_else(|| map_json_error(err, &lenient))),
     }
 }
 
+/// Reads only `snapshot.meta` from the first `head_bytes` bytes of the file.
+pub fn read_snapshot_meta_file(
+    path: &Path,
+    head_bytes: u64,
+) -> Result<SnapshotMeta, SnapshotError> {
+    let result = match zip::split_member_path(path) {
+        Some((archive, member)) => {
+            read_snapshot_meta(&mut zip::open_member(&archive, &member)?.take(head_bytes))
+        }
+        None => read_snapshot_meta(&mut BufReader::new(File::open(path)?).take(head_bytes)),
+    };
+    result.map_err(|err| match err {
+        SnapshotError::Json { error, .. } if error.is_eof() => SnapshotError::InvalidData {
+            details: format!(
+                "snapshot.meta not found within the first {head_bytes} bytes (raise --head-bytes)"
+            ),
+        },
+        other => other,
+    })
+}
+
+/// Parses `snapshot.meta` and stops before the `nodes`/`edges`/`strings` arrays are loaded.
+pub fn read_snapshot_meta<R: Read>(reader: &mut R) -> Result<SnapshotMeta, SnapshotError> {
+    let mut lenient = LenientJsonReader::new(reader);
+    let mut visitor = SnapshotVisitor::meta_only();
+    let result = serde_json::Deserializer::from_reader(&mut lenient).deserialize_map(&mut visitor);
+    match result {
+        Ok(()) => {}
+        // The early stop surfaces as a custom error; anything else is a real failure.
+        Err(_) if visitor.stopped_early => {}
+        Err(err) => {
+            return Err(visitor
+                .duplicate_key_error()
+                .unwrap_or_else(|| map_json_error(err, &lenient)));
+        }
+    }
+    visitor.meta.ok_or_else(|| SnapshotError::InvalidData {
+        details: "missing snapshot.meta (ensure the file is a Chrome DevTools heapsnapshot)"
+            .to_string(),
+    })
+}
+
+const META_ONLY_STOP: &str = "meta-only parse stopped after snapshot.meta";
+
+/// Top-level keys whose values are appended while streaming, so a second occurrence
+/// would silently concatenate two sections.
+const UNIQUE_KEYS: &[&str] = &["snapshot", "nodes", "edges", "strings", "locations"];
+
 #[derive(Default)]
 struct SnapshotVisitor {
     meta: Option<SnapshotMeta>,
+    declared_node_count: Option<u64>,
+    declared_edge_count: Option<u64>,
+    labels: SnapshotLabels,
     nodes: Vec<i64>,
     edges: Vec<i64>,
+    /// Lengths of the per-node sub-arrays when `edges` was nested; empty for the flat layout.
+    nested_edge_lengths: Vec<usize>,
     strings: Vec<String>,
+    /// Raw `locations` array; `None` when the section is absent.
+    locations: Option<Vec<i64>>,
+    meta_only: bool,
+    stopped_early: bool,
+    seen_keys: Vec<&'static str>,
+    /// Set when a key in `UNIQUE_KEYS` appeared twice; the parse is aborted at that point.
+    duplicate_key: Option<&'static str>,
 }
 
 impl SnapshotVisitor {
+    fn meta_only() -> Self {
+        Self {
+            meta_only: true,
+            ..Self::default()
+        }
+    }
+
+    fn duplicate_key_error(&self) -> Option<SnapshotError> {
+        self.duplicate_key.map(|key| SnapshotError::InvalidData {
+            details: format!("duplicate top-level key {key:?} in snapshot file"),
+        })
+    }
+
     fn into_snapshot(self) -> Result<SnapshotRaw, SnapshotError> {
         let meta = self.meta.ok_or_else(|| SnapshotError::InvalidData {
             details: "missing snapshot.meta (ensure the file is a Chrome DevTools heapsnapshot)"
@@ -66,6 +232,31 @@ impl SnapshotVisitor {
                 ),
             });
         }
+        if !self.nested_edge_lengths.is_empty() {
+            let node_count = self.nodes.len() / index.node_field_count;
+            if self.nested_edge_lengths.len() != node_count {
+                return Err(SnapshotError::InvalidData {
+                    details: format!(
+                        "nested edges has {} sub-arrays but there are {} nodes",
+                        self.nested_edge_lengths.len(),
+                        node_count
+                    ),
+                });
+            }
+            if let Some((node, len)) = self
+                .nested_edge_lengths
+                .iter()
+                .enumerate()
+                .find(|(_, len)| *len % index.edge_field_count != 0)
+            {
+                return Err(SnapshotError::InvalidData {
+                    details: format!(
+                        "nested edges for node {node} has length {len}, not a multiple of edge field count ({})",
+                        index.edge_field_count
+                    ),
+                });
+            }
+        }
         if self.edges.len() % index.edge_field_count != 0 {
             return Err(SnapshotError::InvalidData {
                 details: format!(
@@ -76,13 +267,21 @@ impl SnapshotVisitor {
             });
         }
 
-        Ok(SnapshotRaw {
-            nodes: self.nodes,
-            edges: self.edges,
-            strings: self.strings,
-            meta,
-            index,
-        })
+        let locations = match &self.locations {
+            Some(values) => Some(NodeLocations::from_raw(
+                values,
+                &meta.location_fields,
+                index.node_field_count,
+            )?),
+            None => None,
+        };
+
+        let mut snapshot = SnapshotRaw::new(self.nodes, self.edges, self.strings, meta, index);
+        snapshot.locations = locations;
+        snapshot.declared_node_count = self.declared_node_count;
+        snapshot.declared_edge_count = self.declared_edge_count;
+        snapshot.labels = self.labels;
+        Ok(snapshot)
     }
 }
 
@@ -98,22 +297,52 @@ impl<'de> Visitor<'de> for &mut SnapshotVisitor {
         M: MapAccess<'de>,
     {
         while let Some(key) = map.next_key::<String>()? {
+            if let Some(unique) = UNIQUE_KEYS.iter().copied().find(|unique| *unique == key) {
+                if self.seen_keys.contains(&unique) {
+                    self.duplicate_key = Some(unique);
+                    return Err(serde::de::Error::custom(format!(
+                        "duplicate top-level key: {unique}"
+                    )));
+                }
+                self.seen_keys.push(unique);
+            }
             match key.as_str() {
                 "snapshot" => {
                     let root = map.next_value::<SnapshotRoot>()?;
                     if let Some(meta) = root.meta {
                         self.meta = Some(meta);
                     }
+                    self.declared_node_count = root.node_count;
+                    self.declared_edge_count = root.edge_count;
+                    self.labels = SnapshotLabels {
+                        title: root.title,
+                        uid: root.uid,
+                        capture_time: root.timestamp,
+                    };
+                    if self.meta_only {
+                        self.stopped_early = true;
+                        return Err(serde::de::Error::custom(META_ONLY_STOP));
+                    }
+                }
+                _ if self.meta_only => {
+                    map.next_value::<IgnoredAny>()?;
                 }
                 "nodes" => {
                     map.next_value_seed(I64VecSeed(&mut self.nodes))?;
                 }
                 "edges" => {
-                    map.next_value_seed(I64VecSeed(&mut self.edges))?;
+                    map.next_value_seed(EdgesSeed {
+                        edges: &mut self.edges,
+                        nested_lengths: &mut self.nested_edge_lengths,
+                    })?;
                 }
                 "strings" => {
                     map.next_value_seed(StringVecSeed(&mut self.strings))?;
                 }
+                "locations" => {
+                    let locations = self.locations.get_or_insert_with(Vec::new);
+                    map.next_value_seed(I64VecSeed(locations))?;
+                }
                 _ => {
                     map.next_value::<IgnoredAny>()?;
                 }
@@ -156,6 +385,103 @@ impl<'de, 'a> Visitor<'de> for I64VecVisitor<'a> {
     }
 }
 
+/// `edges` is normally one flat integer array, but some dumps nest one array per node.
+/// Both are flattened into the same layout; mixing the two shapes is rejected.
+struct EdgesSeed<'a> {
+    edges: &'a mut Vec<i64>,
+    nested_lengths: &'a mut Vec<usize>,
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for EdgesSeed<'a> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for EdgesSeed<'a> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("array of integers or array of per-node integer arrays")
+    }
+
+    fn visit_seq<M>(self, mut seq: M) -> Result<Self::Value, M::Error>
+    where
+        M: serde::de::SeqAccess<'de>,
+    {
+        let mut flat = false;
+        while let Some(element) = seq.next_element_seed(EdgeElementSeed(self.edges))? {
+            match element {
+                EdgeElement::Value => flat = true,
+                EdgeElement::Nested(len) => self.nested_lengths.push(len),
+            }
+            if flat && !self.nested_lengths.is_empty() {
+                return Err(serde::de::Error::custom(
+                    "edges mixes integers and nested arrays",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+enum EdgeElement {
+    Value,
+    Nested(usize),
+}
+
+struct EdgeElementSeed<'a>(&'a mut Vec<i64>);
+
+impl<'de, 'a> DeserializeSeed<'de> for EdgeElementSeed<'a> {
+    type Value = EdgeElement;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for EdgeElementSeed<'a> {
+    type Value = EdgeElement;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("integer or array of integers")
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        self.0.push(value);
+        Ok(EdgeElement::Value)
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        let value = i64::try_from(value)
+            .map_err(|_| E::custom(format!("edge value out of range: {value}")))?;
+        self.visit_i64(value)
+    }
+
+    fn visit_seq<M>(self, seq: M) -> Result<Self::Value, M::Error>
+    where
+        M: serde::de::SeqAccess<'de>,
+    {
+        let before = self.0.len();
+        I64VecVisitor(self.0).visit_seq(seq)?;
+        Ok(EdgeElement::Nested(self.0.len() - before))
+    }
+}
+
 struct StringVecSeed<'a>(&'a mut Vec<String>);
 
 impl<'de, 'a> DeserializeSeed<'de> for StringVecSeed<'a> {
@@ -189,14 +515,243 @@ impl<'de, 'a> Visitor<'de> for StringVecVisitor<'a> {
     }
 }
 
-fn map_json_error(err: serde_json::Error) -> SnapshotError {
+/// Top-level visitor of `stream_snapshot`: keeps meta, labels and strings, and passes
+/// `nodes` through `on_node` one record at a time.
+struct StreamingVisitor<'s, 'f> {
+    on_node: &'s mut NodeSink<'f>,
+    meta: Option<(SnapshotMeta, MetaIndex)>,
+    labels: SnapshotLabels,
+    strings: Vec<String>,
+    /// Integers read from `nodes` and `edges`, checked against the field counts at the end.
+    node_values: usize,
+    edge_values: usize,
+    first_node_id: Option<i64>,
+    last_node_id: Option<i64>,
+    seen_keys: Vec<&'static str>,
+    /// Set when the parse was aborted for a reason that is not a JSON error.
+    error: Option<SnapshotError>,
+}
+
+impl StreamingVisitor<'_, '_> {
+    fn into_snapshot(self) -> Result<StreamedSnapshot, SnapshotError> {
+        let Some((meta, index)) = self.meta else {
+            return Err(SnapshotError::InvalidData {
+                details:
+                    "missing snapshot.meta (ensure the file is a Chrome DevTools heapsnapshot)"
+                        .to_string(),
+            });
+        };
+        if !self.node_values.is_multiple_of(index.node_field_count) {
+            return Err(SnapshotError::InvalidData {
+                details: format!(
+                    "nodes length ({}) is not divisible by node field count ({})",
+                    self.node_values, index.node_field_count
+                ),
+            });
+        }
+        if !self.edge_values.is_multiple_of(index.edge_field_count) {
+            return Err(SnapshotError::InvalidData {
+                details: format!(
+                    "edges length ({}) is not divisible by edge field count ({})",
+                    self.edge_values, index.edge_field_count
+                ),
+            });
+        }
+        Ok(StreamedSnapshot {
+            node_count: self.node_values / index.node_field_count,
+            edge_count: self.edge_values / index.edge_field_count,
+            first_node_id: self.first_node_id,
+            last_node_id: self.last_node_id,
+            strings: self.strings,
+            labels: self.labels,
+            meta,
+            index,
+        })
+    }
+
+    fn abort<E: serde::de::Error>(&mut self, error: SnapshotError) -> E {
+        let message = error.to_string();
+        self.error = Some(error);
+        E::custom(message)
+    }
+}
+
+impl<'de> Visitor<'de> for &mut StreamingVisitor<'_, '_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("heapsnapshot top-level object")
+    }
+
+    fn visit_map<M>(self, mut map: M) -> Result<Self::Value, M::Error>
+    where
+        M: MapAccess<'de>,
+    {
+        while let Some(key) = map.next_key::<String>()? {
+            if let Some(unique) = UNIQUE_KEYS.iter().copied().find(|unique| *unique == key) {
+                if self.seen_keys.contains(&unique) {
+                    return Err(self.abort(SnapshotError::InvalidData {
+                        details: format!("duplicate top-level key {unique:?} in snapshot file"),
+                    }));
+                }
+                self.seen_keys.push(unique);
+            }
+            match key.as_str() {
+                "snapshot" => {
+                    let root = map.next_value::<SnapshotRoot>()?;
+                    if let Some(meta) = root.meta {
+                        let index = meta.validate().map_err(|err| self.abort(err))?;
+                        self.meta = Some((meta, index));
+                    }
+                    self.labels = SnapshotLabels {
+                        title: root.title,
+                        uid: root.uid,
+                        capture_time: root.timestamp,
+                    };
+                }
+                "nodes" => {
+                    let Some((_, index)) = &self.meta else {
+                        return Err(self.abort(SnapshotError::InvalidData {
+                            details:
+                                "nodes appear before snapshot.meta, which streaming needs first"
+                                    .to_string(),
+                        }));
+                    };
+                    map.next_value_seed(NodeRecordsSeed {
+                        index,
+                        on_node: &mut *self.on_node,
+                        values: &mut self.node_values,
+                        first_id: &mut self.first_node_id,
+                        last_id: &mut self.last_node_id,
+                    })?;
+                }
+                "edges" => {
+                    map.next_value_seed(CountSeed(&mut self.edge_values))?;
+                }
+                "strings" => {
+                    map.next_value_seed(StringVecSeed(&mut self.strings))?;
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads `nodes` into a one-record buffer and hands each complete record to `on_node`.
+struct NodeRecordsSeed<'a, 'f> {
+    index: &'a MetaIndex,
+    on_node: &'a mut NodeSink<'f>,
+    values: &'a mut usize,
+    first_id: &'a mut Option<i64>,
+    last_id: &'a mut Option<i64>,
+}
+
+impl<'de> DeserializeSeed<'de> for NodeRecordsSeed<'_, '_> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for NodeRecordsSeed<'_, '_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("array of integers")
+    }
+
+    fn visit_seq<M>(self, mut seq: M) -> Result<Self::Value, M::Error>
+    where
+        M: serde::de::SeqAccess<'de>,
+    {
+        let field_count = self.index.node_field_count;
+        let id_field = self.index.node_field_index.id_idx;
+        let mut record = Vec::with_capacity(field_count);
+        while let Some(value) = seq.next_element::<i64>()? {
+            record.push(value);
+            *self.values += 1;
+            if record.len() == field_count {
+                if self.first_id.is_none() {
+                    *self.first_id = Some(record[id_field]);
+                }
+                *self.last_id = Some(record[id_field]);
+                (self.on_node)(self.index, &record);
+                record.clear();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Counts the integers of a flat or nested array without keeping them.
+struct CountSeed<'a>(&'a mut usize);
+
+impl<'de> DeserializeSeed<'de> for CountSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for CountSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("integer or array of integers")
+    }
+
+    fn visit_i64<E>(self, _value: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        *self.0 += 1;
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _value: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        *self.0 += 1;
+        Ok(())
+    }
+
+    fn visit_seq<M>(self, mut seq: M) -> Result<Self::Value, M::Error>
+    where
+        M: serde::de::SeqAccess<'de>,
+    {
+        while seq.next_element_seed(CountSeed(&mut *self.0))?.is_some() {}
+        Ok(())
+    }
+}
+
+/// Turns a failed parse into `Cancelled`, or into `Json` carrying where `lenient` had got
+/// to in the original file.
+fn map_json_error<R: Read>(
+    err: serde_json::Error,
+    lenient: &LenientJsonReader<'_, R>,
+) -> SnapshotError {
     if err.io_error_kind() == Some(std::io::ErrorKind::Interrupted) {
         return SnapshotError::Cancelled;
     }
     if err.is_io() && err.to_string().contains("cancelled") {
         return SnapshotError::Cancelled;
     }
-    SnapshotError::Json(err)
+    SnapshotError::Json {
+        error: err,
+        offset: lenient.last_read_offset(),
+    }
 }
 
 #[cfg(test)]
@@ -239,6 +794,45 @@ mod tests {
         assert_eq!(node.name(), Some("Root"));
         assert_eq!(node.id(), Some(1));
         assert_eq!(node.self_size(), Some(10));
+        assert!(snapshot.locations.is_none());
+    }
+
+    #[test]
+    fn parse_locations_section() {
+        let json = r#"
+        {
+          "snapshot": {
+            "meta": {
+              "node_fields": ["type","name","id","self_size","edge_count"],
+              "node_types": [["object"],"string","number","number","number"],
+              "edge_fields": ["type","name_or_index","to_node"],
+              "edge_types": [["property"],"string_or_number","node"],
+              "location_fields": ["object_index","script_id","line","column"]
+            }
+          },
+          "nodes": [0, 0, 1, 10, 0, 0, 0, 3, 20, 0],
+          "edges": [],
+          "locations": [5, 42, 9, 4],
+          "strings": ["Root"]
+        }
+        "#;
+
+        let mut reader = json.as_bytes();
+        let snapshot = read_snapshot(&mut reader).expect("parse ok");
+        assert_eq!(snapshot.locations.as_ref().map(|l| l.len()), Some(1));
+        assert_eq!(snapshot.location_of(0), None);
+        assert_eq!(
+            snapshot.location_of(1),
+            Some(crate::snapshot::SourceLocation {
+                script_id: 42,
+                line: 9,
+                column: 4,
+            })
+        );
+
+        let broken = json.replace("[5, 42, 9, 4]", "[3, 42, 9, 4]");
+        let err = read_snapshot(&mut broken.as_bytes()).expect_err("misaligned offset");
+        assert!(err.to_string().contains("object_index"), "{err}");
     }
 
     #[test]
@@ -273,4 +867,187 @@ mod tests {
         let snapshot = read_snapshot(&mut reader).expect("parse ok");
         assert_eq!(snapshot.strings[0], "\u{FFFD}");
     }
+
+    #[test]
+    fn parse_leading_utf8_bom() {
+        let json = "\u{FEFF}{\"snapshot\":{\"meta\":{\"node_fields\":[\"type\",\"name\",\"id\",\"self_size\",\"edge_count\"],\"node_types\":[[\"object\"],\"string\",\"number\",\"number\",\"number\"],\"edge_fields\":[\"type\",\"name_or_index\",\"to_node\"],\"edge_types\":[[\"property\"],\"string_or_number\",\"node\"]}},\"nodes\":[0,0,1,10,0],\"edges\":[],\"strings\":[\"Root\"]}";
+
+        let mut reader = json.as_bytes();
+        let snapshot = read_snapshot(&mut reader).expect("parse ok");
+        assert_eq!(snapshot.node_count(), 1);
+        assert_eq!(snapshot.strings[0], "Root");
+    }
+
+    #[test]
+    fn parse_snapshot_labels_leniently() {
+        let json = |labels: &str| {
+            format!(
+                r#"{{"snapshot":{{{labels}"meta":{{"node_fields":["type","name","id","self_size","edge_count"],"node_types":[["object"],"string","number","number","number"],"edge_fields":["type","name_or_index","to_node"],"edge_types":[["property"],"string_or_number","node"]}}}},"nodes":[0,0,1,10,0],"edges":[],"strings":["Root"]}}"#
+            )
+        };
+
+        let text = json(r#""title":"after login","uid":3,"timestamp":1700000000000,"#);
+        let snapshot = read_snapshot(&mut text.as_bytes()).expect("parse ok");
+        assert_eq!(snapshot.labels.title.as_deref(), Some("after login"));
+        assert_eq!(snapshot.labels.uid, Some(3));
+        assert_eq!(
+            snapshot.labels.capture_time.as_deref(),
+            Some("1700000000000")
+        );
+
+        // DevTools' empty title and values of other types leave the labels empty.
+        let text = json(r#""title":"","uid":"x","timestamp":null,"#);
+        let snapshot = read_snapshot(&mut text.as_bytes()).expect("parse ok");
+        assert!(snapshot.labels.is_empty());
+
+        let text = json("");
+        let snapshot = read_snapshot(&mut text.as_bytes()).expect("parse ok");
+        assert!(snapshot.labels.is_empty());
+    }
+
+    #[test]
+    fn parse_trailing_commas() {
+        let json = r#"
+        {
+          "snapshot": {
+            "meta": {
+              "node_fields": ["type","name","id","self_size","edge_count",],
+              "node_types": [["object"],"string","number","number","number"],
+              "edge_fields": ["type","name_or_index","to_node"],
+              "edge_types": [["property"],"string_or_number","node"],
+            },
+          },
+          "nodes": [0, 0, 1, 10, 0,
+          ],
+          "edges": [],
+          "strings": ["a,]", "b,}",],
+        }
+        "#;
+
+        let mut reader = json.as_bytes();
+        let snapshot = read_snapshot(&mut reader).expect("parse ok");
+        assert_eq!(snapshot.node_count(), 1);
+        assert_eq!(snapshot.strings, vec!["a,]", "b,}"]);
+    }
+
+    #[test]
+    fn parse_line_and_block_comments() {
+        let json = r#"
+        // exported by a tool that annotates its output
+        {
+          "snapshot": {
+            "meta": {
+              "node_fields": ["type","name","id","self_size","edge_count"], // V8 order
+              "node_types": [["object"],"string","number","number","number"],
+              "edge_fields": ["type","name_or_index","to_node"],
+              /* "edge_types": [], */
+              "edge_types": [["property"],"string_or_number","node"]
+            }
+          },
+          "nodes": [0, 0, 1, /* self_size */ 10, 0 /* , 1 */],
+          "edges": [],
+          "strings": ["http://example.com/*x*/"] // trailing
+        }
+        "#;
+
+        let mut reader = json.as_bytes();
+        let snapshot = read_snapshot(&mut reader).expect("parse ok");
+        assert_eq!(snapshot.nodes, vec![0, 0, 1, 10, 0]);
+        assert_eq!(snapshot.strings[0], "http://example.com/*x*/");
+    }
+
+    #[test]
+    fn trailing_comma_before_comment_is_dropped() {
+        let json = "{\"snapshot\":{\"meta\":{\"node_fields\":[\"type\",\"name\",\"id\",\"self_size\",\"edge_count\"],\"node_types\":[[\"object\"],\"string\",\"number\",\"number\",\"number\"],\"edge_fields\":[\"type\",\"name_or_index\",\"to_node\"],\"edge_types\":[[\"property\"],\"string_or_number\",\"node\"]}},\"nodes\":[0,0,1,10,0, // last node\n],\"edges\":[],\"strings\":[\"Root\"]}";
+
+        let mut reader = json.as_bytes();
+        let snapshot = read_snapshot(&mut reader).expect("parse ok");
+        assert_eq!(snapshot.node_count(), 1);
+
+        let mut reader = "{\"nodes\":[1,,2]}".as_bytes();
+        assert!(read_snapshot(&mut reader).is_err());
+    }
+
+    #[test]
+    fn meta_only_stops_before_arrays() {
+        let file = File::open("fixtures/small.heapsnapshot").expect("fixture");
+        let mut reader = BufReader::new(file);
+        let mut lenient = LenientJsonReader::new(&mut reader);
+        let mut deserializer = serde_json::Deserializer::from_reader(&mut lenient);
+        let mut visitor = SnapshotVisitor::meta_only();
+        let _ = deserializer.deserialize_map(&mut visitor);
+
+        assert!(visitor.stopped_early);
+        let meta = visitor.meta.expect("meta");
+        assert_eq!(meta.node_fields[0], "type");
+        assert!(visitor.nodes.is_empty());
+        assert!(visitor.edges.is_empty());
+        assert!(visitor.strings.is_empty());
+    }
+
+    #[test]
+    fn read_meta_with_small_head() {
+        let meta =
+            read_snapshot_meta_file(Path::new("fixtures/small.heapsnapshot"), 4096).expect("meta");
+        assert_eq!(meta.node_fields.len(), 5);
+
+        let result = read_snapshot_meta_file(Path::new("fixtures/small.heapsnapshot"), 16);
+        assert!(matches!(result, Err(SnapshotError::InvalidData { .. })));
+    }
+
+    fn edges_snapshot_json(edges: &str) -> String {
+        format!(
+            r#"{{
+              "snapshot": {{
+                "meta": {{
+                  "node_fields": ["type","name","id","self_size","edge_count"],
+                  "node_types": [["object"],"string","number","number","number"],
+                  "edge_fields": ["type","name_or_index","to_node"],
+                  "edge_types": [["property"],"string_or_number","node"]
+                }}
+              }},
+              "nodes": [0, 0, 1, 10, 2, 0, 0, 2, 20, 0],
+              "edges": {edges},
+              "strings": ["A"]
+            }}"#
+        )
+    }
+
+    #[test]
+    fn parse_nested_edges_matches_flat_layout() {
+        let flat = edges_snapshot_json("[0, 0, 5, 0, 0, 0]");
+        let nested = edges_snapshot_json("[[0, 0, 5, 0, 0, 0], []]");
+        let flat = read_snapshot(&mut flat.as_bytes()).expect("flat");
+        let nested = read_snapshot(&mut nested.as_bytes()).expect("nested");
+        assert_eq!(nested.edges, flat.edges);
+        assert_eq!(nested.edge_count(), 2);
+    }
+
+    #[test]
+    fn parse_nested_edges_rejects_bad_shapes() {
+        let bad_len = edges_snapshot_json("[[0, 0, 5, 0], [0, 0]]");
+        assert!(matches!(
+            read_snapshot(&mut bad_len.as_bytes()),
+            Err(SnapshotError::InvalidData { .. })
+        ));
+        let mixed = edges_snapshot_json("[[0, 0, 5], 0, 0, 0]");
+        assert!(read_snapshot(&mut mixed.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn parse_rejects_duplicate_top_level_keys() {
+        let json = edges_snapshot_json("[0, 0, 5, 0, 0, 0]");
+        let duplicated = json.replace(
+            r#""strings": ["A"]"#,
+            r#""strings": ["A"], "nodes": [0, 0, 3, 5, 0]"#,
+        );
+        match read_snapshot(&mut duplicated.as_bytes()) {
+            Err(SnapshotError::InvalidData { details }) => assert!(details.contains("\"nodes\"")),
+            other => panic!("expected duplicate key error, got {other:?}"),
+        }
+
+        // Unknown keys may still repeat.
+        let extra = json.replace(r#""strings""#, r#""x": 1, "x": 2, "strings""#);
+        assert!(read_snapshot(&mut extra.as_bytes()).is_ok());
+    }
 }
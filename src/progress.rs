@@ -1,8 +1,48 @@
-use std::io::{self, Read};
+use std::io::{self, IsTerminal, Read};
 use std::time::{Duration, Instant};
 
 use crate::cancel::CancelToken;
 
+/// When to print read progress to stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProgressMode {
+    Off,
+    /// Only when stderr is a terminal, so redirected logs stay free of progress lines.
+    #[default]
+    Auto,
+    /// Always, even when stderr is redirected (`--progress=true`).
+    On,
+}
+
+impl ProgressMode {
+    /// `--progress[=BOOL]`: unset means `Auto`.
+    pub fn from_flag(flag: Option<bool>) -> Self {
+        match flag {
+            None => ProgressMode::Auto,
+            Some(true) => ProgressMode::On,
+            Some(false) => ProgressMode::Off,
+        }
+    }
+
+    pub fn enabled(self) -> bool {
+        match self {
+            ProgressMode::Off => false,
+            ProgressMode::Auto => io::stderr().is_terminal(),
+            ProgressMode::On => true,
+        }
+    }
+}
+
+impl From<bool> for ProgressMode {
+    fn from(enabled: bool) -> Self {
+        if enabled {
+            ProgressMode::On
+        } else {
+            ProgressMode::Off
+        }
+    }
+}
+
 pub struct ProgressReader<R> {
     inner: R,
     enabled: bool,
@@ -13,10 +53,15 @@ pub struct ProgressReader<R> {
 }
 
 impl<R> ProgressReader<R> {
-    pub fn new(inner: R, enabled: bool, total_bytes: Option<u64>, cancel: CancelToken) -> Self {
+    pub fn new(
+        inner: R,
+        mode: impl Into<ProgressMode>,
+        total_bytes: Option<u64>,
+        cancel: CancelToken,
+    ) -> Self {
         Self {
             inner,
-            enabled,
+            enabled: mode.into().enabled(),
             total_bytes,
             read_bytes: 0,
             last_report: Instant::now(),
@@ -74,3 +119,17 @@ fn format_bytes(bytes: u64) -> String {
         format!("{bytes} B")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_flag_maps_to_mode() {
+        assert_eq!(ProgressMode::from_flag(None), ProgressMode::Auto);
+        assert_eq!(ProgressMode::from_flag(Some(true)), ProgressMode::On);
+        assert_eq!(ProgressMode::from_flag(Some(false)), ProgressMode::Off);
+        assert!(ProgressMode::On.enabled());
+        assert!(!ProgressMode::Off.enabled());
+    }
+}
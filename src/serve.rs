@@ -3,55 +3,85 @@ use std::fmt::Write as _;
 use std::fs;
 use std::hash::{Hash, Hasher};
 use std::io::{Read, Write};
-use std::net::TcpListener;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, ToSocketAddrs};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex, mpsc};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::sync::{Arc, Mutex, RwLock, mpsc};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use crate::analysis;
 use crate::cancel::CancelToken;
 use crate::error::SnapshotError;
+use crate::gzip;
+use crate::output::json::JsonStyle;
 use crate::parser::{self, ReadOptions};
+use crate::progress::ProgressMode;
 use crate::snapshot::SnapshotRaw;
 
 const HEADER_PREVIEW_MAX: usize = 50;
 const MAX_REQUEST_HEAD_BYTES: usize = 64 * 1024;
+const KEEP_ALIVE_IDLE_TIMEOUT: Duration = Duration::from_secs(5);
+const KEEP_ALIVE_MAX_REQUESTS: usize = 100;
+/// How often `--follow` checks the snapshot's mtime while the accept loop is idle.
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Bodies smaller than this are sent as-is even when the client accepts gzip.
+const GZIP_MIN_BYTES: usize = 1024;
+/// Default for `ServeOptions::max_body_bytes`.
+pub const DEFAULT_MAX_BODY_BYTES: usize = 8 * 1024 * 1024;
+const SHADE_ALPHA_MIN: f64 = 0.04;
+const SHADE_ALPHA_MAX: f64 = 0.30;
 const V8_HEAP_SNAPSHOT_STRING_LIMIT_DOC_URL: &str =
     "https://chromium.googlesource.com/v8/v8/+/refs/heads/main/src/flags/flag-definitions.h#3098";
 
 #[derive(Debug, Clone)]
 pub struct ServeOptions {
     pub file: PathBuf,
-    pub bind: String,
+    /// Loopback address to listen on (see `resolve_loopback_bind`).
+    pub bind: IpAddr,
     pub port: u16,
-    pub progress: bool,
+    pub progress: ProgressMode,
     pub cancel: CancelToken,
+    /// Re-parse `file` when its mtime changes, keeping the previous snapshot on failure.
+    pub follow: bool,
+    /// Tables stop gaining rows once a page's HTML reaches this many bytes.
+    pub max_body_bytes: usize,
+    /// Print one timing line per handled request to stderr.
+    pub log_requests: bool,
+    /// Leave out the startup banner and reload notices; errors still print.
+    pub quiet: bool,
 }
 
 pub fn run(options: ServeOptions) -> Result<(), SnapshotError> {
-    let snapshot = Arc::new(parser::read_snapshot_file(
+    let modified = file_modified(&options.file);
+    let snapshot = parser::read_snapshot_file(
         &options.file,
         ReadOptions::new(options.progress, options.cancel.clone()),
-    )?);
-    let id_index = build_id_index(&snapshot);
+    )?;
     let context = Arc::new(ServerContext {
-        snapshot,
+        current: RwLock::new(Arc::new(LoadedSnapshot::new(snapshot, modified))),
+        follow: options.follow,
+        max_body_bytes: options.max_body_bytes,
+        log_requests: options.log_requests,
+        quiet: options.quiet,
         before_path: options.file,
         cancel: options.cancel.clone(),
-        id_index,
         dominator_jobs: Arc::new(Mutex::new(HashMap::new())),
         dominator_session_active: Arc::new(Mutex::new(HashMap::new())),
-        dominator_index_cache: Arc::new(Mutex::new(None)),
         uploaded_temp_files: Arc::new(Mutex::new(Vec::new())),
         uploaded_display_names: Arc::new(Mutex::new(HashMap::new())),
         snapshot_cache: Arc::new(Mutex::new(HashMap::new())),
         diff_cache: Arc::new(Mutex::new(HashMap::new())),
     });
-    let (listener, selected_port) = bind_listener_with_retry(&options.bind, options.port)?;
-    let addr = format!("{}:{}", options.bind, selected_port);
+    let (listener, selected_port) =
+        bind_listener_with_retry(options.bind, options.port, options.quiet)?;
+    let addr = SocketAddr::new(options.bind, selected_port);
     listener.set_nonblocking(true).map_err(SnapshotError::Io)?;
-    eprintln!("serve listening on http://{addr}");
+    notice(
+        options.quiet,
+        format_args!("serve listening on http://{addr}"),
+    );
 
+    let mut last_seen_modified = modified;
+    let mut last_poll = Instant::now();
     while !options.cancel.is_cancelled() {
         match listener.accept() {
             Ok((mut stream, _)) => {
@@ -66,11 +96,17 @@ pub fn run(options: ServeOptions) -> Result<(), SnapshotError> {
                             500,
                             "text/plain; charset=utf-8",
                             format!("internal server error: {err}").as_bytes(),
+                            false,
+                            false,
                         );
                     }
                 });
             }
             Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                if options.follow && last_poll.elapsed() >= FOLLOW_POLL_INTERVAL {
+                    last_poll = Instant::now();
+                    reload_if_modified(&context, &mut last_seen_modified);
+                }
                 std::thread::sleep(Duration::from_millis(30));
             }
             Err(err) => return Err(SnapshotError::Io(err)),
@@ -80,6 +116,83 @@ pub fn run(options: ServeOptions) -> Result<(), SnapshotError> {
     Ok(())
 }
 
+/// For `archive.zip#member` this is the archive's mtime.
+fn file_modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(crate::zip::container_path(path))
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// `--follow`: re-parses the snapshot when its mtime moved past `last_seen` and swaps it in.
+/// A file that fails to parse (e.g. still being written) is logged and the previous
+/// snapshot keeps serving; the next mtime change triggers another attempt.
+fn reload_if_modified(context: &ServerContext, last_seen: &mut Option<SystemTime>) {
+    let modified = match file_modified(&context.before_path) {
+        Some(modified) => modified,
+        None => return,
+    };
+    if *last_seen == Some(modified) {
+        return;
+    }
+    *last_seen = Some(modified);
+
+    let snapshot = match parser::read_snapshot_file(
+        &context.before_path,
+        ReadOptions::new(false, context.cancel.clone()),
+    ) {
+        Ok(snapshot) => snapshot,
+        Err(SnapshotError::Cancelled) => return,
+        Err(err) => {
+            eprintln!(
+                "failed to reload {}: {err} (keeping previous snapshot)",
+                context.before_path.display()
+            );
+            return;
+        }
+    };
+    let loaded = Arc::new(LoadedSnapshot::new(snapshot, Some(modified)));
+    {
+        let mut guard = match context.current.write() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        *guard = loaded;
+    }
+    // Jobs and diffs computed against the old snapshot refer to stale node indexes.
+    {
+        let mut jobs = match context.dominator_jobs.lock() {
+            Ok(jobs) => jobs,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        for job in jobs.values() {
+            let guard = match job.lock() {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            guard.cancel.cancel();
+        }
+        jobs.clear();
+    }
+    {
+        let mut sessions = match context.dominator_session_active.lock() {
+            Ok(sessions) => sessions,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        sessions.clear();
+    }
+    {
+        let mut guard = match context.diff_cache.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        guard.clear();
+    }
+    notice(
+        context.quiet,
+        format_args!("reloaded {}", context.before_path.display()),
+    );
+}
+
 fn cleanup_uploaded_temp_files(context: &ServerContext) {
     let paths = {
         let mut guard = match context.uploaded_temp_files.lock() {
@@ -97,33 +210,59 @@ fn cleanup_uploaded_temp_files(context: &ServerContext) {
     }
 }
 
-fn build_id_index(snapshot: &SnapshotRaw) -> HashMap<u64, usize> {
-    let mut index = HashMap::new();
-    for node_index in 0..snapshot.node_count() {
-        let node = match snapshot.node_view(node_index) {
-            Some(node) => node,
-            None => continue,
-        };
-        let node_id = match node.id() {
-            Some(value) if value >= 0 => value as u64,
-            _ => continue,
-        };
-        index.insert(node_id, node_index);
+/// Parses `--bind` into a loopback address. `localhost` resolves to whichever loopback the
+/// OS lists first (IPv4 or IPv6); anything that is not loopback is rejected.
+pub fn resolve_loopback_bind(bind: &str) -> Result<IpAddr, SnapshotError> {
+    let trimmed = bind.trim_start_matches('[').trim_end_matches(']');
+    let ip = if let Ok(ip) = trimmed.parse::<IpAddr>() {
+        ip
+    } else if trimmed.eq_ignore_ascii_case("localhost") {
+        ("localhost", 0)
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addrs| addrs.find(|addr| addr.ip().is_loopback()))
+            .map(|addr| addr.ip())
+            .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+    } else {
+        return Err(SnapshotError::InvalidData {
+            details: format!(
+                "serve only supports loopback bind, got {bind} (use 127.0.0.1, ::1 or localhost)"
+            ),
+        });
+    };
+    if !ip.is_loopback() {
+        return Err(SnapshotError::InvalidData {
+            details: format!(
+                "serve only supports loopback bind, got {ip} (use 127.0.0.1, ::1 or localhost)"
+            ),
+        });
+    }
+    Ok(ip)
+}
+
+/// Informational stderr line (startup banner, reload and port notices) that `--quiet`
+/// leaves out. Errors are printed with `eprintln!` regardless.
+fn notice(quiet: bool, message: std::fmt::Arguments<'_>) {
+    if !quiet {
+        eprintln!("{message}");
     }
-    index
 }
 
 fn bind_listener_with_retry(
-    bind: &str,
+    bind: IpAddr,
     start_port: u16,
+    quiet: bool,
 ) -> Result<(TcpListener, u16), SnapshotError> {
-    bind_with_retry(start_port, |port| {
-        let addr = format!("{bind}:{port}");
-        TcpListener::bind(&addr)
+    bind_with_retry(start_port, quiet, |port| {
+        TcpListener::bind(SocketAddr::new(bind, port))
     })
 }
 
-fn bind_with_retry<T, F>(start_port: u16, mut bind_port: F) -> Result<(T, u16), SnapshotError>
+fn bind_with_retry<T, F>(
+    start_port: u16,
+    quiet: bool,
+    mut bind_port: F,
+) -> Result<(T, u16), SnapshotError>
 where
     F: FnMut(u16) -> Result<T, std::io::Error>,
 {
@@ -138,7 +277,10 @@ where
                     });
                 }
                 let next_port = port + 1;
-                eprintln!("port {port} is in use, retrying with {next_port}");
+                notice(
+                    quiet,
+                    format_args!("port {port} is in use, retrying with {next_port}"),
+                );
                 port = next_port;
             }
             Err(err) => return Err(SnapshotError::Io(err)),
@@ -147,19 +289,59 @@ where
 }
 
 struct ServerContext {
-    snapshot: Arc<SnapshotRaw>,
+    /// Snapshot of `before_path` currently being served; replaced as a whole by `--follow`.
+    current: RwLock<Arc<LoadedSnapshot>>,
+    follow: bool,
+    max_body_bytes: usize,
+    log_requests: bool,
+    /// `ServeOptions::quiet`.
+    quiet: bool,
     before_path: PathBuf,
     cancel: CancelToken,
-    id_index: HashMap<u64, usize>,
     dominator_jobs: Arc<Mutex<HashMap<DominatorJobKey, Arc<Mutex<DominatorJob>>>>>,
     dominator_session_active: Arc<Mutex<HashMap<String, DominatorJobKey>>>,
-    dominator_index_cache: Arc<Mutex<Option<analysis::dominator::DominatorIndex>>>,
     uploaded_temp_files: Arc<Mutex<Vec<PathBuf>>>,
     uploaded_display_names: Arc<Mutex<HashMap<PathBuf, String>>>,
     snapshot_cache: Arc<Mutex<HashMap<PathBuf, Arc<SnapshotRaw>>>>,
     diff_cache: Arc<Mutex<HashMap<DiffCacheKey, Arc<analysis::diff::DiffResult>>>>,
 }
 
+impl ServerContext {
+    fn current(&self) -> Arc<LoadedSnapshot> {
+        let guard = match self.current.read() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        Arc::clone(&guard)
+    }
+}
+
+/// One parsed generation of the served file with the indices derived from it.
+struct LoadedSnapshot {
+    snapshot: Arc<SnapshotRaw>,
+    /// mtime of the file when it was parsed.
+    modified: Option<SystemTime>,
+    dominator_index: Arc<Mutex<Option<analysis::dominator::DominatorIndex>>>,
+    /// Constructor name -> node indices, so name lookups do not rescan the nodes.
+    names: analysis::name_index::NameIndex,
+    /// `verify` checks that failed for this generation, shown as the nav health badge.
+    integrity_failures: usize,
+}
+
+impl LoadedSnapshot {
+    fn new(snapshot: SnapshotRaw, modified: Option<SystemTime>) -> Self {
+        let names = analysis::name_index::NameIndex::new(&snapshot);
+        let integrity_failures = analysis::verify::verify(&snapshot).failed_count();
+        Self {
+            snapshot: Arc::new(snapshot),
+            modified,
+            dominator_index: Arc::new(Mutex::new(None)),
+            names,
+            integrity_failures,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct DominatorJobKey {
     session: String,
@@ -255,37 +437,93 @@ fn handle_connection(
     stream
         .set_read_timeout(Some(Duration::from_millis(200)))
         .map_err(SnapshotError::Io)?;
-    let request = match read_http_request(stream, &context.cancel)? {
-        Some(request) => request,
-        None => return Ok(()),
-    };
-    if request.method != "GET" && request.method != "POST" {
-        return write_response(
-            stream,
-            405,
-            "text/plain; charset=utf-8",
-            b"method not allowed",
-        );
+    // Bytes read past the end of one request (pipelining) are kept for the next one.
+    let mut pending = Vec::new();
+    for served in 1..=KEEP_ALIVE_MAX_REQUESTS {
+        let request = match read_http_request(stream, &mut pending, &context.cancel) {
+            Ok(Some(request)) => request,
+            Ok(None) => return Ok(()),
+            // The stream cannot be framed past a malformed request, so answer and close.
+            Err(err @ SnapshotError::InvalidData { .. }) => {
+                write_response(
+                    stream,
+                    400,
+                    "text/plain; charset=utf-8",
+                    err.to_string().as_bytes(),
+                    false,
+                    false,
+                )?;
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        };
+        let started = Instant::now();
+        let log = |status: u16| {
+            if context.log_requests {
+                eprintln!(
+                    "{}",
+                    request_log_line(&request.method, &request.target, status, started.elapsed())
+                );
+            }
+        };
+        let keep_alive = served < KEEP_ALIVE_MAX_REQUESTS && request.wants_keep_alive();
+        if request.method != "GET" && request.method != "POST" {
+            write_response(
+                stream,
+                405,
+                "text/plain; charset=utf-8",
+                b"method not allowed",
+                keep_alive,
+                false,
+            )?;
+            log(405);
+        } else {
+            let (path, query_raw) = split_target(&request.target);
+            let query = parse_query(query_raw);
+            if request.method == "GET" && path == "/dominator/events" {
+                // Logged when the event stream ends, so the duration covers the whole job.
+                let result = write_dominator_events(stream, &query, context);
+                log(200);
+                return result;
+            }
+            let response = match route(
+                &request.method,
+                path,
+                &query,
+                &request.headers,
+                &request.body,
+                context,
+                request.wants_json(),
+            ) {
+                Ok(response) => response,
+                Err(SnapshotError::Cancelled) if context.cancel.is_cancelled() => {
+                    return Err(SnapshotError::Cancelled);
+                }
+                Err(err) => HttpResponse::error(&err),
+            };
+            write_response(
+                stream,
+                response.status,
+                response.content_type,
+                response.body.as_bytes(),
+                keep_alive,
+                request.accepts_gzip(),
+            )?;
+            log(response.status);
+        }
+        if !keep_alive {
+            break;
+        }
     }
+    Ok(())
+}
 
-    let (path, query_raw) = split_target(&request.target);
-    let query = parse_query(query_raw);
-    if request.method == "GET" && path == "/dominator/events" {
-        return write_dominator_events(stream, &query, context);
-    }
-    let response = route(
-        &request.method,
-        path,
-        &query,
-        &request.headers,
-        &request.body,
-        context,
-    )?;
-    write_response(
-        stream,
-        response.status,
-        "text/html; charset=utf-8",
-        response.body.as_bytes(),
+/// `--log` line for one request: `method target status duration_ms`. The target keeps its
+/// query so slow lookups can be traced to the node or name asked for.
+fn request_log_line(method: &str, target: &str, status: u16, elapsed: Duration) -> String {
+    format!(
+        "{method} {target} {status} {:.1}",
+        elapsed.as_secs_f64() * 1000.0
     )
 }
 
@@ -293,15 +531,66 @@ fn handle_connection(
 struct HttpRequest {
     method: String,
     target: String,
+    version: String,
     headers: HashMap<String, String>,
     body: Vec<u8>,
 }
 
+impl HttpRequest {
+    /// HTTP/1.1 defaults to keep-alive; HTTP/1.0 only when the client asks for it.
+    fn wants_keep_alive(&self) -> bool {
+        let connection = self
+            .headers
+            .get("connection")
+            .map(|value| value.to_ascii_lowercase())
+            .unwrap_or_default();
+        if connection.contains("close") {
+            return false;
+        }
+        self.version != "HTTP/1.0" || connection.contains("keep-alive")
+    }
+
+    /// True when `Accept-Encoding` lists `gzip` without `q=0`.
+    fn accepts_gzip(&self) -> bool {
+        self.header_quality("accept-encoding", "gzip") > 0.0
+    }
+
+    /// True when `Accept` ranks `application/json` above `text/html`. Browsers never list
+    /// JSON, so a missing header, `text/html` or `*/*` all keep the HTML pages.
+    fn wants_json(&self) -> bool {
+        let json = self.header_quality("accept", "application/json");
+        json > 0.0 && json > self.header_quality("accept", "text/html")
+    }
+
+    /// `q` of `token` in a comma-separated header such as `Accept`: 1.0 when listed without
+    /// a `q` parameter, 0.0 when the header or the token is missing.
+    fn header_quality(&self, header: &str, token: &str) -> f64 {
+        let Some(value) = self.headers.get(header) else {
+            return 0.0;
+        };
+        value
+            .split(',')
+            .filter_map(|item| {
+                let mut parts = item.split(';').map(str::trim);
+                if !parts.next()?.eq_ignore_ascii_case(token) {
+                    return None;
+                }
+                let q = parts
+                    .find_map(|param| param.strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.parse::<f64>().ok())?;
+                Some(q)
+            })
+            .fold(0.0, f64::max)
+    }
+}
+
 fn read_http_request(
     stream: &mut std::net::TcpStream,
+    pending: &mut Vec<u8>,
     cancel: &CancelToken,
 ) -> Result<Option<HttpRequest>, SnapshotError> {
-    let mut raw = Vec::with_capacity(8192);
+    let mut raw = std::mem::take(pending);
+    let idle_since = std::time::Instant::now();
     let header_end = loop {
         if let Some(idx) = find_subslice(&raw, b"\r\n\r\n") {
             break idx;
@@ -322,6 +611,9 @@ fn read_http_request(
                 if cancel.is_cancelled() {
                     return Err(SnapshotError::Cancelled);
                 }
+                if raw.is_empty() && idle_since.elapsed() >= KEEP_ALIVE_IDLE_TIMEOUT {
+                    return Ok(None);
+                }
                 continue;
             }
             Err(err) => return Err(SnapshotError::Io(err)),
@@ -346,6 +638,7 @@ fn read_http_request(
     let mut request_parts = request_line.split_whitespace();
     let method = request_parts.next().unwrap_or("").to_string();
     let target = request_parts.next().unwrap_or("/").to_string();
+    let version = request_parts.next().unwrap_or("HTTP/1.1").to_string();
 
     let mut headers = HashMap::new();
     for line in lines {
@@ -356,16 +649,93 @@ fn read_http_request(
             headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
         }
     }
-    let content_length = headers
-        .get("content-length")
-        .and_then(|value| value.parse::<usize>().ok())
-        .unwrap_or(0);
+    // Without a trustworthy length the next request cannot be found, so these end the
+    // connection instead of guessing.
+    if headers.contains_key("transfer-encoding") {
+        return Err(SnapshotError::InvalidData {
+            details: "Transfer-Encoding request bodies are not supported; send Content-Length"
+                .to_string(),
+        });
+    }
+    let content_length = match headers.get("content-length") {
+        Some(value) => value
+            .parse::<usize>()
+            .map_err(|_| SnapshotError::InvalidData {
+                details: format!("invalid Content-Length header: {value:?}"),
+            })?,
+        None => 0,
+    };
     let body_start = header_end + 4;
 
-    while raw.len().saturating_sub(body_start) < content_length {
+    // Only POST routes read a body; any other method's body is skipped unbuffered so a
+    // large one neither fills memory nor is mistaken for the next pipelined request.
+    if method != "POST" {
+        let buffered = raw.len() - body_start;
+        if buffered >= content_length {
+            *pending = raw.split_off(body_start + content_length);
+        } else {
+            *pending = discard_body(stream, content_length - buffered, cancel)?;
+        }
+        return Ok(Some(HttpRequest {
+            method,
+            target,
+            version,
+            headers,
+            body: Vec::new(),
+        }));
+    }
+
+    while raw.len() - body_start < content_length {
         let mut chunk = [0u8; 8192];
-        let read = match stream.read(&mut chunk) {
-            Ok(read) => read,
+        let read = read_body_chunk(stream, &mut chunk, cancel)?;
+        raw.extend_from_slice(&chunk[..read]);
+    }
+    let body_end = body_start + content_length;
+    let body = raw[body_start..body_end].to_vec();
+    *pending = raw.split_off(body_end);
+
+    Ok(Some(HttpRequest {
+        method,
+        target,
+        version,
+        headers,
+        body,
+    }))
+}
+
+/// Reads and drops `remaining` body bytes, returning whatever followed them in the last
+/// read (the start of a pipelined request).
+fn discard_body(
+    stream: &mut std::net::TcpStream,
+    mut remaining: usize,
+    cancel: &CancelToken,
+) -> Result<Vec<u8>, SnapshotError> {
+    let mut chunk = [0u8; 8192];
+    while remaining > 0 {
+        let read = read_body_chunk(stream, &mut chunk, cancel)?;
+        if read > remaining {
+            return Ok(chunk[remaining..read].to_vec());
+        }
+        remaining -= read;
+    }
+    Ok(Vec::new())
+}
+
+/// One read of a request body, waiting out read timeouts until the server is cancelled.
+/// EOF is an error since `Content-Length` promised more bytes.
+fn read_body_chunk(
+    stream: &mut std::net::TcpStream,
+    chunk: &mut [u8],
+    cancel: &CancelToken,
+) -> Result<usize, SnapshotError> {
+    loop {
+        match stream.read(chunk) {
+            Ok(0) => {
+                return Err(SnapshotError::InvalidData {
+                    details: "unexpected EOF while reading HTTP request body".to_string(),
+                });
+            }
+            Ok(read) => return Ok(read),
             Err(err)
                 if err.kind() == std::io::ErrorKind::WouldBlock
                     || err.kind() == std::io::ErrorKind::TimedOut =>
@@ -373,26 +743,10 @@ fn read_http_request(
                 if cancel.is_cancelled() {
                     return Err(SnapshotError::Cancelled);
                 }
-                continue;
             }
             Err(err) => return Err(SnapshotError::Io(err)),
-        };
-        if read == 0 {
-            return Err(SnapshotError::InvalidData {
-                details: "unexpected EOF while reading HTTP request body".to_string(),
-            });
         }
-        raw.extend_from_slice(&chunk[..read]);
     }
-    let body_end = body_start + content_length;
-    let body = raw.get(body_start..body_end).unwrap_or_default().to_vec();
-
-    Ok(Some(HttpRequest {
-        method,
-        target,
-        headers,
-        body,
-    }))
 }
 
 fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
@@ -404,6 +758,8 @@ fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
         .position(|window| window == needle)
 }
 
+/// `wants_json` (from the `Accept` header) switches the analysis pages to the same JSON
+/// as the CLI's `--format json`; the URLs and query parameters stay the same.
 fn route(
     method: &str,
     path: &str,
@@ -411,15 +767,20 @@ fn route(
     headers: &HashMap<String, String>,
     body: &[u8],
     context: &ServerContext,
+    wants_json: bool,
 ) -> Result<HttpResponse, SnapshotError> {
+    let page = |body: String| HttpResponse::page(body, wants_json);
     match (method, path) {
-        ("GET", "/") => Ok(HttpResponse::ok(render_index())),
-        ("GET", "/summary") => Ok(HttpResponse::ok(render_summary(query, context)?)),
-        ("GET", "/detail") => Ok(HttpResponse::ok(render_detail(query, context)?)),
-        ("GET", "/retainers") => Ok(HttpResponse::ok(render_retainers(query, context)?)),
-        ("GET", "/diff") => Ok(HttpResponse::ok(render_diff(query, context)?)),
-        ("POST", "/diff") => render_diff_post(headers, body, context),
-        ("GET", "/dominator") => Ok(HttpResponse::ok(render_dominator(query, context)?)),
+        ("GET", "/") => Ok(HttpResponse::ok(render_index(context))),
+        ("GET", "/summary") => Ok(page(render_summary(query, context, wants_json)?)),
+        ("GET", "/detail") => Ok(page(render_detail(query, context, wants_json)?)),
+        ("GET", "/retainers") => Ok(page(render_retainers(query, context, wants_json)?)),
+        ("GET", "/diff") => Ok(page(render_diff(query, context, wants_json)?)),
+        ("POST", "/diff") => render_diff_post(headers, body, context, wants_json),
+        ("GET", "/dominator") => Ok(page(render_dominator(query, context, wants_json)?)),
+        _ if wants_json => Ok(HttpResponse::error(&SnapshotError::NotFound {
+            details: format!("no such page: {path}"),
+        })),
         _ => Ok(HttpResponse::not_found(render_not_found(path))),
     }
 }
@@ -428,30 +789,31 @@ fn render_diff_post(
     headers: &HashMap<String, String>,
     body: &[u8],
     context: &ServerContext,
+    wants_json: bool,
 ) -> Result<HttpResponse, SnapshotError> {
+    let rejected = |err: SnapshotError| {
+        if wants_json {
+            HttpResponse::error(&err)
+        } else {
+            HttpResponse::bad_request(render_diff_upload(context, Some(&err.to_string())))
+        }
+    };
     let form = match parse_multipart_form(headers, body) {
         Ok(form) => form,
-        Err(err) => {
-            return Ok(HttpResponse::bad_request(render_diff_upload(
-                context,
-                Some(&err.to_string()),
-            )));
-        }
+        Err(err) => return Ok(rejected(err)),
     };
     let uploaded = match form.file {
         Some(file) => file,
         None => {
-            return Ok(HttpResponse::bad_request(render_diff_upload(
-                context,
-                Some("invalid data: missing `after` file field"),
-            )));
+            return Ok(rejected(SnapshotError::InvalidData {
+                details: "missing `after` file field".to_string(),
+            }));
         }
     };
     if uploaded.content.is_empty() {
-        return Ok(HttpResponse::bad_request(render_diff_upload(
-            context,
-            Some("invalid data: empty upload. Select a .heapsnapshot file and retry"),
-        )));
+        return Ok(rejected(SnapshotError::InvalidData {
+            details: "empty upload. Select a .heapsnapshot file and retry".to_string(),
+        }));
     }
 
     let temp_path = write_uploaded_after_snapshot(&uploaded)?;
@@ -504,29 +866,73 @@ fn render_diff_post(
     if let Some(value) = form.fields.get("size_unit") {
         query.insert("size_unit".to_string(), value.clone());
     }
-    Ok(HttpResponse::ok(render_diff(&query, context)?))
+    Ok(HttpResponse::page(
+        render_diff(&query, context, wants_json)?,
+        wants_json,
+    ))
 }
 
 struct HttpResponse {
     status: u16,
+    content_type: &'static str,
     body: String,
 }
 
+#[derive(Debug, serde::Serialize)]
+struct ErrorJson<'a> {
+    error: String,
+    kind: &'a str,
+}
+
 impl HttpResponse {
     fn ok(body: String) -> Self {
-        Self { status: 200, body }
+        Self::html(200, body)
+    }
+
+    /// 200 with a body rendered as JSON or HTML, as the request asked for.
+    fn page(body: String, json: bool) -> Self {
+        if json {
+            Self {
+                status: 200,
+                content_type: "application/json; charset=utf-8",
+                body,
+            }
+        } else {
+            Self::ok(body)
+        }
     }
 
     fn bad_request(body: String) -> Self {
-        Self { status: 400, body }
+        Self::html(400, body)
     }
 
     fn not_found(body: String) -> Self {
-        Self { status: 404, body }
+        Self::html(404, body)
+    }
+
+    fn html(status: u16, body: String) -> Self {
+        Self {
+            status,
+            content_type: "text/html; charset=utf-8",
+            body,
+        }
+    }
+
+    /// `{ "error": "...", "kind": "..." }` with the status from `SnapshotError::http_status`.
+    fn error(err: &SnapshotError) -> Self {
+        let payload = ErrorJson {
+            error: err.to_string(),
+            kind: err.kind(),
+        };
+        Self {
+            status: err.http_status(),
+            content_type: "application/json; charset=utf-8",
+            body: serde_json::to_string(&payload).unwrap_or_default(),
+        }
     }
 }
 
-fn render_index() -> String {
+fn render_index(context: &ServerContext) -> String {
     let mut out = String::new();
     let _ = writeln!(
         out,
@@ -534,6 +940,25 @@ fn render_index() -> String {
         base_styles()
     );
     let _ = writeln!(out, "<h1>heapsnap serve</h1>");
+    let labels = &context.current().snapshot.labels;
+    let _ = writeln!(
+        out,
+        "<p>File: {}</p>",
+        escape_html(&context.before_path.display().to_string())
+    );
+    if !labels.is_empty() {
+        let _ = writeln!(out, "<ul class=\"snapshot-labels\">");
+        if let Some(title) = &labels.title {
+            let _ = writeln!(out, "<li>Title: {}</li>", escape_html(title));
+        }
+        if let Some(uid) = labels.uid {
+            let _ = writeln!(out, "<li>UID: {uid}</li>");
+        }
+        if let Some(capture_time) = &labels.capture_time {
+            let _ = writeln!(out, "<li>Captured: {}</li>", escape_html(capture_time));
+        }
+        let _ = writeln!(out, "</ul>");
+    }
     let _ = writeln!(out, "<ul>");
     let _ = writeln!(out, "<li><a href=\"/summary\">Summary</a></li>");
     let _ = writeln!(
@@ -556,20 +981,27 @@ fn render_index() -> String {
 fn render_summary(
     query: &HashMap<String, String>,
     context: &ServerContext,
+    wants_json: bool,
 ) -> Result<String, SnapshotError> {
     let skip = query_usize(query, "skip", 0);
     let limit = query_usize(query, "limit", 50);
     let top = query_usize(query, "top", 50);
     let search = query.get("search").cloned();
     let size_unit = SizeUnit::from_query(query);
-    let scan_top = std::cmp::max(top, skip.saturating_add(limit));
+    let loaded = context.current();
     let result = analysis::summary::summarize(
-        &context.snapshot,
+        &loaded.snapshot,
         analysis::summary::SummaryOptions {
-            top: scan_top,
+            top,
             contains: search.clone(),
+            skip,
+            limit: Some(limit),
+            ..Default::default()
         },
     )?;
+    if wants_json {
+        return crate::output::summary::format_json(&result, JsonStyle::Compact);
+    }
 
     let mut out = String::new();
     let _ = writeln!(
@@ -577,38 +1009,55 @@ fn render_summary(
         "<!doctype html><html><head><meta charset=\"utf-8\"><title>Summary</title><style>{}</style></head><body>",
         base_styles()
     );
-    write_nav(&mut out);
+    write_nav(&mut out, context, &loaded);
     let _ = writeln!(
         out,
         "<h1>Summary</h1><p><strong>Total nodes:</strong> {}</p><p><strong>Rows:</strong> showing {}..{} (max {})</p>",
         result.total_nodes,
         skip,
-        skip + std::cmp::min(limit, result.rows.len().saturating_sub(skip)),
-        result.rows.len()
+        skip + result.rows.len(),
+        result.total_rows
     );
     write_summary_controls(&mut out, top, search.as_deref(), skip, limit, size_unit);
     let _ = writeln!(
         out,
-        "<table class=\"resizable-table\"><thead><tr><th>Constructor</th><th>Count</th><th>Self Size Sum ({})</th></tr></thead><tbody>",
+        "<table class=\"resizable-table\"><thead><tr><th>Constructor</th><th>Count</th><th>Self Size Sum ({})</th><th>Largest</th></tr></thead><tbody>",
         size_unit.label()
     );
-    for row in result.rows.iter().skip(skip).take(limit) {
+    let max_size = result
+        .rows
+        .iter()
+        .map(|row| row.self_size_sum)
+        .max()
+        .unwrap_or(0);
+    let mut cap = RowCap::new(context.max_body_bytes);
+    for row in &result.rows {
+        if !cap.admit(&out) {
+            break;
+        }
         let name = if row.name.is_empty() {
             "(empty)".to_string()
         } else {
             row.name.clone()
         };
         let link = format!("/detail?name={}", url_encode(&name));
+        let sample = row
+            .sample_id
+            .map(|id| format!("<a href=\"/detail?id={id}\">@{id}</a>"))
+            .unwrap_or_default();
         let _ = writeln!(
             out,
-            "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td></tr>",
+            "<tr{}><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            size_shade_style(row.self_size_sum, max_size),
             link,
             escape_html(&name),
             row.count,
-            size_unit.format_i64(row.self_size_sum)
+            size_unit.format_i64(row.self_size_sum),
+            sample
         );
     }
     let _ = writeln!(out, "</tbody></table>");
+    cap.write_notice(&mut out);
     let _ = writeln!(out, "<script>{}</script>", table_column_resize_script());
     let _ = writeln!(out, "</body></html>");
     Ok(out)
@@ -617,23 +1066,36 @@ fn render_summary(
 fn render_detail(
     query: &HashMap<String, String>,
     context: &ServerContext,
+    wants_json: bool,
 ) -> Result<String, SnapshotError> {
     let id = query_u64_opt(query, "id");
     let name = query.get("name").cloned();
+    let name_index = query
+        .get("name_index")
+        .and_then(|v| v.parse::<usize>().ok());
     let skip = query_usize(query, "skip", 0);
     let limit = query_usize(query, "limit", 200);
     let size_unit = SizeUnit::from_query(query);
-    let detail = analysis::detail::detail(
-        &context.snapshot,
+    let loaded = context.current();
+    let detail = analysis::detail::detail_with_names(
+        &loaded.snapshot,
+        &loaded.names,
         analysis::detail::DetailOptions {
             id,
             name,
+            name_index,
             skip,
             limit,
             top_retainers: query_usize(query, "top_retainers", 10),
             top_edges: query_usize(query, "top_edges", 10),
+            edge_name_filter: None,
+            raw: false,
+            max_edges_per_node: None,
         },
     )?;
+    if wants_json {
+        return crate::output::detail::format_json(&detail, JsonStyle::Compact);
+    }
 
     let mut out = String::new();
     let _ = writeln!(
@@ -641,7 +1103,7 @@ fn render_detail(
         "<!doctype html><html><head><meta charset=\"utf-8\"><title>Detail</title><style>{}</style></head><body>",
         base_styles()
     );
-    write_nav(&mut out);
+    write_nav(&mut out, context, &loaded);
     match detail {
         analysis::detail::DetailResult::ByName(ref data) => {
             write_detail_header(&mut out, &data.name, None);
@@ -655,24 +1117,57 @@ fn render_detail(
             );
             let _ = writeln!(
                 out,
-                "<p>Count={} SelfSizeSum({})={} Avg({})={:.2}</p>",
+                "<p>Count={} SelfSizeSum({})={} Avg({})={:.2} Median({})={} P95({})={}</p>",
                 data.total_count,
                 size_unit.label(),
                 size_unit.format_i64(data.self_size_sum),
                 size_unit.label(),
-                data.avg_self_size / size_unit.factor()
+                data.avg_self_size / size_unit.factor(),
+                size_unit.label(),
+                size_unit.format_i64(data.median_self_size),
+                size_unit.label(),
+                size_unit.format_i64(data.p95_self_size)
             );
+            // Only worth a table when one constructor name spans several node types.
+            if data.node_type_breakdown.len() > 1 {
+                let _ = writeln!(
+                    out,
+                    "<h2>Node Types</h2><table class=\"resizable-table\"><thead><tr><th>Type</th><th>Count</th><th>Self Size Sum ({})</th></tr></thead><tbody>",
+                    size_unit.label()
+                );
+                for (node_type, count, self_size_sum) in &data.node_type_breakdown {
+                    let _ = writeln!(
+                        out,
+                        "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                        escape_html(node_type),
+                        count,
+                        size_unit.format_i64(*self_size_sum)
+                    );
+                }
+                let _ = writeln!(out, "</tbody></table>");
+            }
             let _ = writeln!(
                 out,
                 "<table class=\"resizable-table\"><thead><tr><th>Index</th><th>ID</th><th>Type</th><th>Self Size ({})</th></tr></thead><tbody>",
                 size_unit.label()
             );
+            let max_size = data
+                .ids
+                .iter()
+                .map(|item| item.self_size)
+                .max()
+                .unwrap_or(0);
+            let mut cap = RowCap::new(context.max_body_bytes);
             for item in &data.ids {
+                if !cap.admit(&out) {
+                    break;
+                }
                 let id_value = item.id.unwrap_or(-1);
                 let link = format!("/detail?id={id_value}");
                 let _ = writeln!(
                     out,
-                    "<tr><td>{}</td><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td></tr>",
+                    "<tr{}><td>{}</td><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td></tr>",
+                    size_shade_style(item.self_size, max_size),
                     item.index,
                     link,
                     id_value,
@@ -681,13 +1176,14 @@ fn render_detail(
                 );
             }
             let _ = writeln!(out, "</tbody></table>");
+            cap.write_notice(&mut out);
         }
         analysis::detail::DetailResult::ById(ref data) => {
             write_detail_header(&mut out, &data.name, Some(data.id));
             write_detail_controls(&mut out, None, Some(data.id), skip, limit, size_unit);
             let _ = writeln!(
                 out,
-                "<p>Type={} SelfSize({})={} Count={} SelfSizeSum({})={} Avg({})={:.2}</p>",
+                "<p>Type={} SelfSize({})={} Count={} SelfSizeSum({})={} Avg({})={:.2} Median({})={} P95({})={}</p>",
                 escape_html(data.node_type.as_deref().unwrap_or("")),
                 size_unit.label(),
                 size_unit.format_i64(data.self_size),
@@ -695,14 +1191,38 @@ fn render_detail(
                 size_unit.label(),
                 size_unit.format_i64(data.self_size_sum),
                 size_unit.label(),
-                data.avg_self_size / size_unit.factor()
+                data.avg_self_size / size_unit.factor(),
+                size_unit.label(),
+                size_unit.format_i64(data.median_self_size),
+                size_unit.label(),
+                size_unit.format_i64(data.p95_self_size)
             );
+            let _ = writeln!(out, "<p>Reachability: {}</p>", data.reachability.label());
+            if let Some(location) = data.location {
+                let _ = writeln!(
+                    out,
+                    "<p>Allocated at: {}</p>",
+                    escape_html(&location.to_string())
+                );
+            }
             let _ = writeln!(
                 out,
-                "<h2>Top Retainers</h2><table class=\"resizable-table\"><thead><tr><th>From Name</th><th>From ID</th><th>From Size ({})</th><th>Edge</th></tr></thead><tbody>",
+                "<h2>Top Retainers</h2><p>Showing top {} of {} retainers</p><table class=\"resizable-table\"><thead><tr><th>From Name</th><th>From ID</th><th>From Size ({})</th><th>Edge</th></tr></thead><tbody>",
+                data.retainers.len(),
+                data.total_retainers,
                 size_unit.label()
             );
+            let max_size = data
+                .retainers
+                .iter()
+                .map(|item| item.from_self_size)
+                .max()
+                .unwrap_or(0);
+            let mut cap = RowCap::new(context.max_body_bytes);
             for item in &data.retainers {
+                if !cap.admit(&out) {
+                    break;
+                }
                 let detail_link = item
                     .from_id
                     .map(|idv| format!("<a href=\"/detail?id={idv}\">{idv}</a>"))
@@ -720,7 +1240,8 @@ fn render_detail(
                     .unwrap_or_else(|| "-".to_string());
                 let _ = writeln!(
                     out,
-                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}:{}</td></tr>",
+                    "<tr{}><td>{}</td><td>{}</td><td>{}</td><td>{}:{}</td></tr>",
+                    size_shade_style(item.from_self_size, max_size),
                     name_link,
                     detail_link,
                     size_unit.format_i64(item.from_self_size),
@@ -729,12 +1250,23 @@ fn render_detail(
                 );
             }
             let _ = writeln!(out, "</tbody></table>");
+            cap.write_notice(&mut out);
             let _ = writeln!(
                 out,
                 "<h2>Top Outgoing Edges</h2><table class=\"resizable-table\"><thead><tr><th>To Name</th><th>To ID</th><th>To Size ({})</th><th>Edge</th></tr></thead><tbody>",
                 size_unit.label()
             );
+            let max_size = data
+                .outgoing_edges
+                .iter()
+                .map(|item| item.to_self_size)
+                .max()
+                .unwrap_or(0);
+            let mut cap = RowCap::new(context.max_body_bytes);
             for item in &data.outgoing_edges {
+                if !cap.admit(&out) {
+                    break;
+                }
                 let detail_link = item
                     .to_id
                     .map(|idv| format!("<a href=\"/detail?id={idv}\">{idv}</a>"))
@@ -752,7 +1284,8 @@ fn render_detail(
                     .unwrap_or_else(|| "-".to_string());
                 let _ = writeln!(
                     out,
-                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}:{}</td></tr>",
+                    "<tr{}><td>{}</td><td>{}</td><td>{}</td><td>{}:{}</td></tr>",
+                    size_shade_style(item.to_self_size, max_size),
                     name_link,
                     detail_link,
                     size_unit.format_i64(item.to_self_size),
@@ -761,6 +1294,7 @@ fn render_detail(
                 );
             }
             let _ = writeln!(out, "</tbody></table>");
+            cap.write_notice(&mut out);
         }
     }
     let _ = writeln!(out, "<script>{}</script>", table_column_resize_script());
@@ -813,36 +1347,82 @@ fn write_constructor_limit_note(out: &mut String, constructor_chars: usize) {
 fn render_retainers(
     query: &HashMap<String, String>,
     context: &ServerContext,
+    wants_json: bool,
 ) -> Result<String, SnapshotError> {
-    let id = query_u64(query, "id")?;
     let skip = query_usize(query, "skip", 0);
     let limit = query_usize(query, "limit", 5);
     let paths = query_usize(query, "paths", 5);
     let max_depth = query_usize(query, "max_depth", 10);
-    let target = analysis::retainers::find_target_by_id(&context.snapshot, id)?;
-    let result = analysis::retainers::find_retaining_paths(
-        &context.snapshot,
+    let loaded = context.current();
+    // `name` picks the largest instance of the best-matching constructor, as the CLI does.
+    let (id, target) = match query.get("name").filter(|name| !name.is_empty()) {
+        Some(name) => {
+            let target = analysis::retainers::match_target_with_names(
+                &loaded.snapshot,
+                &loaded.names,
+                name,
+                analysis::retainers::PickStrategy::Largest,
+                false,
+            )?
+            .node_index;
+            let id = loaded
+                .snapshot
+                .node_view(target)
+                .and_then(|node| node.id())
+                .and_then(|id| u64::try_from(id).ok())
+                .unwrap_or_default();
+            (id, target)
+        }
+        None => {
+            let id = query_u64(query, "id")?;
+            (
+                id,
+                analysis::retainers::find_target_by_id(&loaded.snapshot, id)?,
+            )
+        }
+    };
+    // The snapshot keeps its inbound index, so only the first click pays for the edge scan.
+    let result = analysis::retainers::find_retaining_paths_indexed(
+        &loaded.snapshot,
         target,
         analysis::retainers::RetainersOptions {
             max_paths: std::cmp::max(paths, skip.saturating_add(limit)),
             max_depth,
+            distinct_roots: false,
+            rank: analysis::retainers::PathRank::Shortest,
+            depth_histogram: false,
             cancel: context.cancel.clone(),
         },
     )?;
+    if wants_json {
+        return crate::output::retainers::format_json(
+            &loaded.snapshot,
+            &result,
+            JsonStyle::Compact,
+        );
+    }
     let mut out = String::new();
     let _ = writeln!(
         out,
         "<!doctype html><html><head><meta charset=\"utf-8\"><title>Retainers</title><style>{}</style></head><body>",
         base_styles()
     );
-    write_nav(&mut out);
+    write_nav(&mut out, context, &loaded);
     let _ = writeln!(out, "<h1>Retainers (id={id})</h1>");
     write_retainers_controls(&mut out, id, paths, max_depth, skip, limit);
+    let _ = writeln!(
+        out,
+        "<p>{}</p>",
+        crate::output::retainers::stats_line(&result.stats)
+    );
+    if let Some(warning) = crate::output::retainers::truncation_warning(&result.stats) {
+        let _ = writeln!(out, "<p><strong>{warning}</strong></p>");
+    }
     for (index, path) in result.paths.iter().skip(skip).take(limit).enumerate() {
         let _ = writeln!(out, "<h2>Path #{}</h2><ol>", skip + index + 1);
         for step in path {
-            let from = context.snapshot.node_view(step.from_node);
-            let to = context.snapshot.node_view(step.to_node);
+            let from = loaded.snapshot.node_view(step.from_node);
+            let to = loaded.snapshot.node_view(step.to_node);
             let from_name = from.and_then(|n| n.name()).unwrap_or("<unknown>");
             let to_name = to.and_then(|n| n.name()).unwrap_or("<unknown>");
             let line = format!(
@@ -863,8 +1443,9 @@ fn render_retainers(
 fn render_diff(
     query: &HashMap<String, String>,
     context: &ServerContext,
+    wants_json: bool,
 ) -> Result<String, SnapshotError> {
-    if !query.contains_key("before") && !query.contains_key("after") {
+    if !query.contains_key("before") && !query.contains_key("after") && !wants_json {
         return Ok(render_diff_upload(context, None));
     }
 
@@ -910,6 +1491,10 @@ fn render_diff(
                 analysis::diff::DiffOptions {
                     top: scan_top,
                     contains: search.clone(),
+                    min_self_size: None,
+                    min_count: None,
+                    sort: analysis::diff::DiffSort::SizeDelta,
+                    only_in: None,
                 },
             )?);
             {
@@ -922,13 +1507,16 @@ fn render_diff(
             computed
         }
     };
+    if wants_json {
+        return crate::output::diff::format_json(&result, JsonStyle::Compact);
+    }
     let mut out = String::new();
     let _ = writeln!(
         out,
         "<!doctype html><html><head><meta charset=\"utf-8\"><title>Diff</title><style>{}</style></head><body>",
         base_styles()
     );
-    write_nav(&mut out);
+    write_nav(&mut out, context, &context.current());
     let before_label = display_name_for_path(context, &before_path);
     let after_label = display_name_for_path(context, &after_path);
     let _ = writeln!(
@@ -955,7 +1543,11 @@ fn render_diff(
         "<h1>Diff</h1><table class=\"resizable-table\"><thead><tr><th>Constructor</th><th>Count Δ</th><th>Self Size Δ ({})</th></tr></thead><tbody>",
         size_unit.label()
     );
+    let mut cap = RowCap::new(context.max_body_bytes);
     for row in result.rows.iter().skip(skip).take(limit) {
+        if !cap.admit(&out) {
+            break;
+        }
         let _ = writeln!(
             out,
             "<tr><td><a href=\"/detail?name={}\">{}</a></td><td>{}</td><td>{}</td></tr>",
@@ -966,6 +1558,7 @@ fn render_diff(
         );
     }
     let _ = writeln!(out, "</tbody></table>");
+    cap.write_notice(&mut out);
     let _ = writeln!(out, "<script>{}</script>", table_column_resize_script());
     let _ = writeln!(out, "</body></html>");
     Ok(out)
@@ -992,7 +1585,7 @@ fn load_snapshot_cached(
     path: &Path,
 ) -> Result<Arc<SnapshotRaw>, SnapshotError> {
     if path == context.before_path {
-        return Ok(Arc::clone(&context.snapshot));
+        return Ok(Arc::clone(&context.current().snapshot));
     }
     let path_buf = path.to_path_buf();
     {
@@ -1025,7 +1618,7 @@ fn render_diff_upload(context: &ServerContext, error: Option<&str>) -> String {
         "<!doctype html><html><head><meta charset=\"utf-8\"><title>Diff upload</title><style>{}</style></head><body>",
         base_styles()
     );
-    write_nav(&mut out);
+    write_nav(&mut out, context, &context.current());
     let _ = writeln!(out, "<h1>Diff</h1>");
     let _ = writeln!(
         out,
@@ -1224,11 +1817,13 @@ fn content_fingerprint(content: &[u8]) -> u64 {
 fn render_dominator(
     query: &HashMap<String, String>,
     context: &ServerContext,
+    wants_json: bool,
 ) -> Result<String, SnapshotError> {
-    let (key, id, skip, limit) = dominator_job_from_query(query, context)?;
+    let loaded = context.current();
+    let (key, id, skip, limit) = dominator_job_from_query(query, &loaded)?;
     let max_depth = key.max_depth;
     let session = key.session.clone();
-    let job = get_or_start_dominator_job(context, key);
+    let job = get_or_start_dominator_job(context, &loaded, key);
     let (progress, result, error) = {
         let guard = match job.lock() {
             Ok(guard) => guard,
@@ -1241,16 +1836,30 @@ fn render_dominator(
         )
     };
 
+    if wants_json {
+        return match (error, result) {
+            (Some(reason), _) => Err(SnapshotError::InvalidData {
+                details: format!("dominator calculation failed: {reason}"),
+            }),
+            (None, Some(result)) => {
+                crate::output::dominator::format_json(&loaded.snapshot, &result, JsonStyle::Compact)
+            }
+            (None, None) => dominator_running_json(&session, &progress),
+        };
+    }
+    // Built from the same generation as the job, even if `--follow` swaps it meanwhile.
+    let mut nav = String::new();
+    write_nav(&mut nav, context, &loaded);
     if let Some(reason) = error {
         return Ok(render_dominator_failed(
-            id, max_depth, skip, limit, &session, &reason,
+            &nav, id, max_depth, skip, limit, &session, &reason,
         ));
     }
     let result = match result {
         Some(result) => result,
         None => {
             return Ok(render_dominator_loading(
-                id, max_depth, skip, limit, &session, &progress,
+                &nav, id, max_depth, skip, limit, &session, &progress,
             ));
         }
     };
@@ -1261,7 +1870,7 @@ fn render_dominator(
         "<!doctype html><html><head><meta charset=\"utf-8\"><title>Dominator</title><style>{}</style></head><body>",
         base_styles()
     );
-    write_nav(&mut out);
+    out.push_str(&nav);
     let _ = writeln!(
         out,
         "<script>if (window.location.search.indexOf('session=') === -1) {{ history.replaceState(null, '', '/dominator?id={}&max_depth={}&skip={}&limit={}&session={}'); }}</script>",
@@ -1273,24 +1882,40 @@ fn render_dominator(
     );
     let _ = writeln!(out, "<h1>Dominator (id={id})</h1><ol>");
     write_dominator_controls(&mut out, id, max_depth, skip, limit, &session);
-    for node_index in result.chain.iter().skip(skip).take(limit) {
-        if let Some(node) = context.snapshot.node_view(*node_index) {
+    for (node_index, retained) in result
+        .chain
+        .iter()
+        .zip(&result.retained_sizes)
+        .skip(skip)
+        .take(limit)
+    {
+        if let Some(node) = loaded.snapshot.node_view(*node_index) {
             let name = node.name().unwrap_or("<unknown>");
             let _ = writeln!(
                 out,
-                "<li><a href=\"/detail?name={}\">{}</a> (id={})</li>",
+                "<li><a href=\"/detail?name={}\">{}</a> (id={}) retained {} bytes ({:.1}%)</li>",
                 url_encode(name),
                 escape_html(name),
-                node.id().unwrap_or(-1)
+                node.id().unwrap_or(-1),
+                retained,
+                percent_of(*retained, result.total_size)
             );
         }
     }
-    let _ = writeln!(out, "</ol></body></html>");
+    let _ = writeln!(out, "</ol>");
+    if let Some(unreachable) = &result.unreachable {
+        out.push_str(&crate::output::dominator::unreachable_html(
+            &loaded.snapshot,
+            unreachable,
+        ));
+    }
+    let _ = writeln!(out, "</body></html>");
     Ok(out)
 }
 
 fn get_or_start_dominator_job(
     context: &ServerContext,
+    loaded: &LoadedSnapshot,
     key: DominatorJobKey,
 ) -> Arc<Mutex<DominatorJob>> {
     {
@@ -1347,9 +1972,9 @@ fn get_or_start_dominator_job(
         jobs.insert(key.clone(), Arc::clone(&job));
     }
 
-    let snapshot = Arc::clone(&context.snapshot);
+    let snapshot = Arc::clone(&loaded.snapshot);
     let context_cancel = context.cancel.clone();
-    let index_cache = Arc::clone(&context.dominator_index_cache);
+    let index_cache = Arc::clone(&loaded.dominator_index);
     let job_ref = Arc::clone(&job);
     std::thread::spawn(move || {
         let (progress_tx, progress_rx) = mpsc::channel();
@@ -1367,6 +1992,7 @@ fn get_or_start_dominator_job(
             };
             let result = if let Some(index) = maybe_cached {
                 analysis::dominator::dominator_chain_from_index(
+                    &worker_snapshot,
                     &index,
                     key.target,
                     key.max_depth,
@@ -1375,6 +2001,7 @@ fn get_or_start_dominator_job(
             } else {
                 match analysis::dominator::compute_dominator_index(
                     &worker_snapshot,
+                    &analysis::dominator::default_ignored_edge_types(),
                     worker_cancel.clone(),
                     Some(progress_tx),
                 ) {
@@ -1387,6 +2014,7 @@ fn get_or_start_dominator_job(
                             *guard = Some(index.clone());
                         }
                         analysis::dominator::dominator_chain_from_index(
+                            &worker_snapshot,
                             &index,
                             key.target,
                             key.max_depth,
@@ -1510,7 +2138,34 @@ fn progress_to_view(progress: &analysis::dominator::DominatorProgress) -> Domina
     }
 }
 
+#[derive(Debug, serde::Serialize)]
+struct DominatorRunningJson<'a> {
+    status: &'static str,
+    session: &'a str,
+    phase: &'a str,
+    percent: u8,
+    completed: u64,
+    total: u64,
+}
+
+/// JSON counterpart of the loading page: poll again with the same `session` until the
+/// chain is returned.
+fn dominator_running_json(
+    session: &str,
+    progress: &DominatorProgressView,
+) -> Result<String, SnapshotError> {
+    JsonStyle::Compact.render(&DominatorRunningJson {
+        status: "running",
+        session,
+        phase: &progress.phase,
+        percent: progress.percent,
+        completed: progress.completed,
+        total: progress.total,
+    })
+}
+
 fn render_dominator_loading(
+    nav: &str,
     id: u64,
     max_depth: usize,
     skip: usize,
@@ -1524,7 +2179,7 @@ fn render_dominator_loading(
         "<!doctype html><html><head><meta charset=\"utf-8\"><title>Dominator</title><style>{}</style></head><body>",
         base_styles()
     );
-    write_nav(&mut out);
+    out.push_str(nav);
     let _ = writeln!(
         out,
         "<script>if (window.location.search.indexOf('session=') === -1) {{ history.replaceState(null, '', '/dominator?id={}&max_depth={}&skip={}&limit={}&session={}'); }}</script>",
@@ -1556,6 +2211,7 @@ fn render_dominator_loading(
 }
 
 fn render_dominator_failed(
+    nav: &str,
     id: u64,
     max_depth: usize,
     skip: usize,
@@ -1569,7 +2225,7 @@ fn render_dominator_failed(
         "<!doctype html><html><head><meta charset=\"utf-8\"><title>Dominator</title><style>{}</style></head><body>",
         base_styles()
     );
-    write_nav(&mut out);
+    out.push_str(nav);
     let _ = writeln!(
         out,
         "<script>if (window.location.search.indexOf('session=') === -1) {{ history.replaceState(null, '', '/dominator?id={}&max_depth={}&skip={}&limit={}&session={}'); }}</script>",
@@ -1639,8 +2295,9 @@ fn write_dominator_events(
     query: &HashMap<String, String>,
     context: &ServerContext,
 ) -> Result<(), SnapshotError> {
-    let (key, _, _, _) = dominator_job_from_query(query, context)?;
-    let job = get_or_start_dominator_job(context, key);
+    let loaded = context.current();
+    let (key, _, _, _) = dominator_job_from_query(query, &loaded)?;
+    let job = get_or_start_dominator_job(context, &loaded, key);
     let header = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream; charset=utf-8\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n";
     stream
         .write_all(header.as_bytes())
@@ -1692,7 +2349,7 @@ fn write_dominator_events(
 
 fn dominator_job_from_query(
     query: &HashMap<String, String>,
-    context: &ServerContext,
+    loaded: &LoadedSnapshot,
 ) -> Result<(DominatorJobKey, u64, usize, usize), SnapshotError> {
     let id = query_u64(query, "id")?;
     let skip = query_usize(query, "skip", 0);
@@ -1702,11 +2359,10 @@ fn dominator_job_from_query(
         .get("session")
         .cloned()
         .unwrap_or_else(generate_session_token);
-    let target = context
-        .id_index
-        .get(&id)
-        .copied()
-        .ok_or_else(|| SnapshotError::InvalidData {
+    let target = i64::try_from(id)
+        .ok()
+        .and_then(|id| Some(loaded.snapshot.node_by_id(id)?.node_index()))
+        .ok_or_else(|| SnapshotError::NotFound {
             details: format!("node id not found: {id}"),
         })?;
     Ok((
@@ -1729,12 +2385,57 @@ fn generate_session_token() -> String {
     format!("s{ts}-{}", std::process::id())
 }
 
-fn write_nav(out: &mut String) {
+fn write_nav(out: &mut String, context: &ServerContext, loaded: &LoadedSnapshot) {
+    let failures = loaded.integrity_failures;
+    let badge = if failures == 0 {
+        "<span class=\"badge ok\">integrity: ok</span>".to_string()
+    } else {
+        format!(
+            "<span class=\"badge fail\" title=\"run heapsnap verify for details\">integrity: {failures} check(s) failed</span>"
+        )
+    };
     let _ = writeln!(
         out,
-        "<p><a href=\"/\">Home</a> | <a href=\"/summary\">Summary</a></p>"
+        "<p><a href=\"/\">Home</a> | <a href=\"/summary\">Summary</a> {badge}</p>"
     );
-}
+    if context.follow {
+        let modified = loaded
+            .modified
+            .map(format_utc)
+            .unwrap_or_else(|| "unknown".to_string());
+        let _ = writeln!(
+            out,
+            "<p class=\"note\">Following {} (loaded snapshot modified {})</p>",
+            escape_html(&context.before_path.display().to_string()),
+            modified
+        );
+    }
+}
+
+/// `YYYY-MM-DD HH:MM:SS UTC`, without pulling in a date crate.
+fn format_utc(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil-from-days (Howard Hinnant), valid for any date after 1970.
+    let z = days as i64 + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
 
 fn write_summary_controls(
     out: &mut String,
@@ -2167,27 +2868,29 @@ fn parse_query(query_raw: &str) -> HashMap<String, String> {
     map
 }
 
+/// Percent-decodes into bytes first so multi-byte UTF-8 (`%E5%90%8D`) survives; invalid
+/// sequences become U+FFFD instead of failing the request.
 fn url_decode(value: &str) -> String {
     let bytes = value.as_bytes();
-    let mut out = String::new();
+    let mut out = Vec::with_capacity(bytes.len());
     let mut i = 0usize;
     while i < bytes.len() {
         if bytes[i] == b'%' && i + 2 < bytes.len() {
-            let hex = &value[i + 1..i + 3];
-            if let Ok(v) = u8::from_str_radix(hex, 16) {
-                out.push(v as char);
+            let hex = (hex_value(bytes[i + 1]), hex_value(bytes[i + 2]));
+            if let (Some(high), Some(low)) = hex {
+                out.push(high << 4 | low);
                 i += 3;
                 continue;
             }
         }
-        if bytes[i] == b'+' {
-            out.push(' ');
-        } else {
-            out.push(bytes[i] as char);
-        }
+        out.push(if bytes[i] == b'+' { b' ' } else { bytes[i] });
         i += 1;
     }
-    out
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|digit| digit as u8)
 }
 
 fn url_encode(value: &str) -> String {
@@ -2234,17 +2937,29 @@ fn write_response(
     status: u16,
     content_type: &str,
     body: &[u8],
+    keep_alive: bool,
+    accepts_gzip: bool,
 ) -> Result<(), SnapshotError> {
+    let connection = if keep_alive { "keep-alive" } else { "close" };
+    let compressed = (accepts_gzip && body.len() >= GZIP_MIN_BYTES).then(|| gzip::compress(body));
+    let (body, encoding) = match &compressed {
+        Some(gz) => (
+            gz.as_slice(),
+            "Content-Encoding: gzip\r\nVary: Accept-Encoding\r\n",
+        ),
+        None => (body, ""),
+    };
     let status_text = match status {
         200 => "OK",
         400 => "Bad Request",
         404 => "Not Found",
         405 => "Method Not Allowed",
         500 => "Internal Server Error",
+        503 => "Service Unavailable",
         _ => "OK",
     };
     let header = format!(
-        "HTTP/1.1 {status} {status_text}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        "HTTP/1.1 {status} {status_text}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n{encoding}Connection: {connection}\r\n\r\n",
         body.len()
     );
     stream
@@ -2255,6 +2970,24 @@ fn write_response(
     Ok(())
 }
 
+fn percent_of(value: i64, total: i64) -> f64 {
+    if total <= 0 {
+        return 0.0;
+    }
+    value as f64 * 100.0 / total as f64
+}
+
+/// Inline row background scaled by `value / max`, so the heaviest rows stand out.
+/// The alpha stays low enough that link/text contrast is preserved.
+fn size_shade_style(value: i64, max: i64) -> String {
+    if value <= 0 || max <= 0 {
+        return String::new();
+    }
+    let fraction = (value as f64 / max as f64).min(1.0);
+    let alpha = SHADE_ALPHA_MIN + (SHADE_ALPHA_MAX - SHADE_ALPHA_MIN) * fraction;
+    format!(" style=\"background:rgba(255,140,0,{alpha:.2})\"")
+}
+
 fn escape_html(value: &str) -> String {
     value
         .replace('&', "&amp;")
@@ -2264,8 +2997,47 @@ fn escape_html(value: &str) -> String {
         .replace('\'', "&#39;")
 }
 
+/// Stops a table from growing once the page reaches `max_bytes`, so that a huge `top` /
+/// `limit` query cannot produce a page that hangs the browser.
+struct RowCap {
+    max_bytes: usize,
+    shown: usize,
+    truncated: bool,
+}
+
+impl RowCap {
+    fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            shown: 0,
+            truncated: false,
+        }
+    }
+
+    /// Whether another row may be appended to `out`; call before writing each row.
+    fn admit(&mut self, out: &str) -> bool {
+        if out.len() >= self.max_bytes {
+            self.truncated = true;
+            return false;
+        }
+        self.shown += 1;
+        true
+    }
+
+    /// Writes the truncation notice after the table, if any row was dropped.
+    fn write_notice(&self, out: &mut String) {
+        if self.truncated {
+            let _ = writeln!(
+                out,
+                "<p class=\"truncated\"><strong>Output truncated at {} rows</strong> (page limit {} bytes); lower ?limit and page with ?skip, or use the CLI.</p>",
+                self.shown, self.max_bytes
+            );
+        }
+    }
+}
+
 fn base_styles() -> &'static str {
-    "body{font-family:ui-sans-serif,system-ui,-apple-system,Segoe UI,Roboto,Helvetica,Arial,sans-serif;margin:24px;color:#111}table{border-collapse:collapse;width:100%;margin-top:12px}.resizable-table{table-layout:fixed}th,td{border:1px solid #ddd;padding:8px;vertical-align:top;overflow-wrap:anywhere;word-break:break-word}th{text-align:left;background:#f6f6f6}.col-resizer{position:absolute;top:0;right:-3px;width:6px;height:100%;cursor:col-resize;user-select:none}.resizing,.resizing *{cursor:col-resize!important;user-select:none!important}tr:nth-child(even){background:#fafafa}a{color:#0b5fff;text-decoration:none}a:hover{text-decoration:underline}.controls{display:flex;gap:12px;align-items:end;margin:12px 0;flex-wrap:wrap}.controls label{display:flex;gap:6px;align-items:center}.progress-status{margin:8px 0}progress{display:block;width:min(520px,100%);height:14px;margin:6px 0 10px}"
+    "body{font-family:ui-sans-serif,system-ui,-apple-system,Segoe UI,Roboto,Helvetica,Arial,sans-serif;margin:24px;color:#111}table{border-collapse:collapse;width:100%;margin-top:12px}.resizable-table{table-layout:fixed}th,td{border:1px solid #ddd;padding:8px;vertical-align:top;overflow-wrap:anywhere;word-break:break-word}th{text-align:left;background:#f6f6f6}.col-resizer{position:absolute;top:0;right:-3px;width:6px;height:100%;cursor:col-resize;user-select:none}.resizing,.resizing *{cursor:col-resize!important;user-select:none!important}tr:nth-child(even){background:#fafafa}a{color:#0b5fff;text-decoration:none}a:hover{text-decoration:underline}.controls{display:flex;gap:12px;align-items:end;margin:12px 0;flex-wrap:wrap}.controls label{display:flex;gap:6px;align-items:center}.progress-status{margin:8px 0}.truncated{margin:8px 0;padding:8px;border:1px solid #e0b000;background:#fff8e0}progress{display:block;width:min(520px,100%);height:14px;margin:6px 0 10px}.badge{margin-left:8px;padding:2px 6px;border-radius:4px;font-size:0.85em}.badge.ok{background:#e6f4ea;color:#1e6b34}.badge.fail{background:#fde8e8;color:#a11}"
 }
 
 #[cfg(test)]
@@ -2277,15 +3049,16 @@ mod tests {
     use std::sync::atomic::{AtomicUsize, Ordering};
 
     fn test_context(snapshot: SnapshotRaw) -> ServerContext {
-        let id_index = build_id_index(&snapshot);
         ServerContext {
-            snapshot: Arc::new(snapshot),
+            current: RwLock::new(Arc::new(LoadedSnapshot::new(snapshot, None))),
+            follow: false,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            log_requests: false,
+            quiet: true,
             before_path: PathBuf::from("fixtures/small.heapsnapshot"),
             cancel: CancelToken::new(),
-            id_index,
             dominator_jobs: Arc::new(Mutex::new(HashMap::new())),
             dominator_session_active: Arc::new(Mutex::new(HashMap::new())),
-            dominator_index_cache: Arc::new(Mutex::new(None)),
             uploaded_temp_files: Arc::new(Mutex::new(Vec::new())),
             uploaded_display_names: Arc::new(Mutex::new(HashMap::new())),
             snapshot_cache: Arc::new(Mutex::new(HashMap::new())),
@@ -2330,6 +3103,28 @@ mod tests {
         (headers, body)
     }
 
+    #[test]
+    fn resolve_loopback_bind_accepts_only_loopback() {
+        assert_eq!(
+            resolve_loopback_bind("127.0.0.1").expect("v4"),
+            IpAddr::V4(Ipv4Addr::LOCALHOST)
+        );
+        assert_eq!(
+            resolve_loopback_bind("::1").expect("v6"),
+            IpAddr::V6(std::net::Ipv6Addr::LOCALHOST)
+        );
+        assert!(resolve_loopback_bind("[::1]").is_ok());
+        assert!(
+            resolve_loopback_bind("localhost")
+                .expect("localhost")
+                .is_loopback()
+        );
+        assert!(resolve_loopback_bind("0.0.0.0").is_err());
+        assert!(resolve_loopback_bind("192.168.1.10").is_err());
+        assert!(resolve_loopback_bind("::").is_err());
+        assert!(resolve_loopback_bind("example.com").is_err());
+    }
+
     #[test]
     fn parse_query_decodes_values() {
         let q = parse_query("name=Foo%20Bar&id=123");
@@ -2337,6 +3132,16 @@ mod tests {
         assert_eq!(q.get("id").map(String::as_str), Some("123"));
     }
 
+    #[test]
+    fn url_decode_keeps_multibyte_utf8() {
+        assert_eq!(url_decode(&url_encode("名前")), "名前");
+        assert_eq!(url_decode("%E2%9C%93+ok"), "✓ ok");
+        assert_eq!(url_decode("名前%2F"), "名前/");
+        // Broken escapes stay literal; invalid UTF-8 becomes U+FFFD.
+        assert_eq!(url_decode("100%"), "100%");
+        assert_eq!(url_decode("%zz%E5"), "%zz\u{fffd}");
+    }
+
     #[test]
     fn split_target_handles_query() {
         let (path, query) = split_target("/detail?id=1");
@@ -2362,6 +3167,7 @@ mod tests {
             &headers,
             &body,
             &context,
+            false,
         )
         .expect("summary");
         assert_eq!(res.status, 200);
@@ -2370,21 +3176,45 @@ mod tests {
 
         let mut detail_query = HashMap::new();
         detail_query.insert("name".to_string(), "Node1".to_string());
-        let res =
-            route("GET", "/detail", &detail_query, &headers, &body, &context).expect("detail");
+        let res = route(
+            "GET",
+            "/detail",
+            &detail_query,
+            &headers,
+            &body,
+            &context,
+            false,
+        )
+        .expect("detail");
         assert_eq!(res.status, 200);
         assert!(res.body.contains("class=\"resizable-table\""));
 
         let mut ret_query = HashMap::new();
         ret_query.insert("id".to_string(), "3".to_string());
-        let res =
-            route("GET", "/retainers", &ret_query, &headers, &body, &context).expect("retainers");
+        let res = route(
+            "GET",
+            "/retainers",
+            &ret_query,
+            &headers,
+            &body,
+            &context,
+            false,
+        )
+        .expect("retainers");
         assert_eq!(res.status, 200);
 
         let mut dom_query = HashMap::new();
         dom_query.insert("id".to_string(), "3".to_string());
-        let res =
-            route("GET", "/dominator", &dom_query, &headers, &body, &context).expect("dominator");
+        let res = route(
+            "GET",
+            "/dominator",
+            &dom_query,
+            &headers,
+            &body,
+            &context,
+            false,
+        )
+        .expect("dominator");
         assert_eq!(res.status, 200);
 
         let mut diff_query = HashMap::new();
@@ -2396,11 +3226,330 @@ mod tests {
             "after".to_string(),
             "fixtures/small.heapsnapshot".to_string(),
         );
-        let res = route("GET", "/diff", &diff_query, &headers, &body, &context).expect("diff");
+        let res = route(
+            "GET",
+            "/diff",
+            &diff_query,
+            &headers,
+            &body,
+            &context,
+            false,
+        )
+        .expect("diff");
         assert_eq!(res.status, 200);
         assert!(res.body.contains("class=\"resizable-table\""));
     }
 
+    #[test]
+    fn summary_rows_are_shaded_by_size_fraction() {
+        let snapshot = parser::read_snapshot_file(
+            Path::new("fixtures/small.heapsnapshot"),
+            ReadOptions::new(false, CancelToken::new()),
+        )
+        .expect("snapshot");
+        let context = test_context(snapshot);
+        let res = route(
+            "GET",
+            "/summary",
+            &HashMap::new(),
+            &HashMap::new(),
+            &Vec::new(),
+            &context,
+            false,
+        )
+        .expect("summary");
+        assert_eq!(res.status, 200);
+
+        let alphas: Vec<f64> = res
+            .body
+            .split("<tr style=\"background:rgba(255,140,0,")
+            .skip(1)
+            .map(|rest| rest.split(')').next().unwrap().parse().unwrap())
+            .collect();
+        assert!(alphas.len() > 1);
+        // Rows are sorted by self size, so the first row is the largest one.
+        assert_eq!(alphas[0], SHADE_ALPHA_MAX);
+        assert!(alphas[1..].iter().all(|alpha| *alpha < alphas[0]));
+    }
+
+    fn read_keep_alive_response(stream: &mut std::net::TcpStream) -> (String, String) {
+        let mut raw = Vec::new();
+        let mut byte = [0u8; 1];
+        while find_subslice(&raw, b"\r\n\r\n").is_none() {
+            stream.read_exact(&mut byte).expect("read header");
+            raw.push(byte[0]);
+        }
+        let head = String::from_utf8_lossy(&raw).to_string();
+        let length: usize = head
+            .lines()
+            .find_map(|line| line.strip_prefix("Content-Length: "))
+            .and_then(|value| value.trim().parse().ok())
+            .expect("content length");
+        let mut body = vec![0u8; length];
+        stream.read_exact(&mut body).expect("read body");
+        (head, String::from_utf8_lossy(&body).to_string())
+    }
+
+    #[test]
+    fn get_request_bodies_are_skipped_without_desyncing_the_connection() {
+        let snapshot = parser::read_snapshot_file(
+            Path::new("fixtures/small.heapsnapshot"),
+            ReadOptions::new(false, CancelToken::new()),
+        )
+        .expect("snapshot");
+        let context = Arc::new(test_context(snapshot));
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            handle_connection(&mut stream, &context)
+        });
+
+        // A body that looks like a request, larger than one read, sent after the head.
+        let mut body = b"GET /retainers?id=999 HTTP/1.1\r\n\r\n".to_vec();
+        body.resize(100_000, b'x');
+        let mut client = std::net::TcpStream::connect(addr).expect("connect");
+        client
+            .write_all(
+                format!(
+                    "GET /summary HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+                    body.len()
+                )
+                .as_bytes(),
+            )
+            .expect("write head");
+        std::thread::sleep(Duration::from_millis(50));
+        client.write_all(&body).expect("write body");
+        client
+            .write_all(b"GET /detail?name=Node1 HTTP/1.1\r\nConnection: close\r\n\r\n")
+            .expect("write next");
+        let (head, body) = read_keep_alive_response(&mut client);
+        assert!(head.starts_with("HTTP/1.1 200 OK"));
+        assert!(body.contains("Summary"));
+        let (head, body) = read_keep_alive_response(&mut client);
+        assert!(head.starts_with("HTTP/1.1 200 OK"));
+        assert!(body.contains("Node1"));
+        server.join().expect("join").expect("handle");
+    }
+
+    #[test]
+    fn unframeable_request_bodies_are_rejected_and_close_the_connection() {
+        let snapshot = parser::read_snapshot_file(
+            Path::new("fixtures/small.heapsnapshot"),
+            ReadOptions::new(false, CancelToken::new()),
+        )
+        .expect("snapshot");
+        let context = Arc::new(test_context(snapshot));
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        let server = std::thread::spawn(move || {
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().expect("accept");
+                handle_connection(&mut stream, &context).expect("handle");
+            }
+        });
+
+        for (request, message) in [
+            (
+                &b"GET /summary HTTP/1.1\r\nContent-Length: -1\r\n\r\n"[..],
+                "invalid Content-Length",
+            ),
+            (
+                &b"GET /summary HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n"[..],
+                "Transfer-Encoding",
+            ),
+        ] {
+            let mut client = std::net::TcpStream::connect(addr).expect("connect");
+            client.write_all(request).expect("write");
+            let (head, body) = read_keep_alive_response(&mut client);
+            assert!(head.starts_with("HTTP/1.1 400 Bad Request"), "{head}");
+            assert!(head.contains("Connection: close"));
+            assert!(body.contains(message), "{body}");
+        }
+        server.join().expect("join");
+    }
+
+    #[test]
+    fn connection_serves_pipelined_requests_until_close() {
+        let snapshot = parser::read_snapshot_file(
+            Path::new("fixtures/small.heapsnapshot"),
+            ReadOptions::new(false, CancelToken::new()),
+        )
+        .expect("snapshot");
+        let context = Arc::new(test_context(snapshot));
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            handle_connection(&mut stream, &context)
+        });
+
+        let mut client = std::net::TcpStream::connect(addr).expect("connect");
+        client
+            .write_all(
+                b"GET /summary HTTP/1.1\r\nHost: x\r\n\r\nGET /detail?name=Node1 HTTP/1.1\r\nHost: x\r\n\r\n",
+            )
+            .expect("write");
+        let (head, body) = read_keep_alive_response(&mut client);
+        assert!(head.contains("Connection: keep-alive"));
+        assert!(body.contains("Summary"));
+        let (head, body) = read_keep_alive_response(&mut client);
+        assert!(head.contains("Connection: keep-alive"));
+        assert!(body.contains("Node1"));
+
+        client
+            .write_all(b"GET /summary HTTP/1.1\r\nConnection: close\r\n\r\n")
+            .expect("write");
+        let (head, _) = read_keep_alive_response(&mut client);
+        assert!(head.contains("Connection: close"));
+        server.join().expect("join").expect("handle");
+    }
+
+    #[test]
+    fn route_errors_are_returned_as_json_with_mapped_status() {
+        let snapshot = parser::read_snapshot_file(
+            Path::new("fixtures/small.heapsnapshot"),
+            ReadOptions::new(false, CancelToken::new()),
+        )
+        .expect("snapshot");
+        let context = Arc::new(test_context(snapshot));
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            handle_connection(&mut stream, &context)
+        });
+
+        let mut client = std::net::TcpStream::connect(addr).expect("connect");
+        client
+            .write_all(
+                b"GET /retainers?id=999 HTTP/1.1\r\n\r\nGET /retainers HTTP/1.1\r\nConnection: close\r\n\r\n",
+            )
+            .expect("write");
+        let (head, body) = read_keep_alive_response(&mut client);
+        assert!(head.starts_with("HTTP/1.1 404 Not Found"));
+        assert!(head.contains("Content-Type: application/json"));
+        let value: serde_json::Value = serde_json::from_str(&body).expect("json");
+        assert_eq!(value["kind"], "not_found");
+        assert!(value["error"].as_str().unwrap_or_default().contains("999"));
+
+        let (head, body) = read_keep_alive_response(&mut client);
+        assert!(head.starts_with("HTTP/1.1 400 Bad Request"));
+        let value: serde_json::Value = serde_json::from_str(&body).expect("json");
+        assert_eq!(value["kind"], "invalid_data");
+        server.join().expect("join").expect("handle");
+    }
+
+    #[test]
+    fn html_responses_are_gzipped_only_when_accepted() {
+        let snapshot = parser::read_snapshot_file(
+            Path::new("fixtures/small.heapsnapshot"),
+            ReadOptions::new(false, CancelToken::new()),
+        )
+        .expect("snapshot");
+        let context = Arc::new(test_context(snapshot));
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            handle_connection(&mut stream, &context)
+        });
+
+        let mut client = std::net::TcpStream::connect(addr).expect("connect");
+        client
+            .write_all(
+                b"GET /summary HTTP/1.1\r\nAccept-Encoding: br, gzip;q=0.8\r\n\r\nGET /summary HTTP/1.1\r\nAccept-Encoding: gzip;q=0, identity\r\nConnection: close\r\n\r\n",
+            )
+            .expect("write");
+        let (head, body) = read_keep_alive_response(&mut client);
+        assert!(head.contains("Content-Encoding: gzip"));
+        assert!(head.contains("Vary: Accept-Encoding"));
+        assert!(!body.contains("Summary"));
+        let (head, body) = read_keep_alive_response(&mut client);
+        assert!(!head.contains("Content-Encoding"));
+        assert!(body.contains("Summary"));
+        server.join().expect("join").expect("handle");
+    }
+
+    #[test]
+    fn accept_header_prefers_json_only_when_ranked_above_html() {
+        let request = |accept: Option<&str>| HttpRequest {
+            method: "GET".to_string(),
+            target: "/summary".to_string(),
+            version: "HTTP/1.1".to_string(),
+            headers: accept
+                .map(|value| HashMap::from([("accept".to_string(), value.to_string())]))
+                .unwrap_or_default(),
+            body: Vec::new(),
+        };
+        assert!(request(Some("application/json")).wants_json());
+        assert!(request(Some("application/json, text/plain, */*")).wants_json());
+        assert!(request(Some("text/html;q=0.5, application/json")).wants_json());
+        assert!(!request(None).wants_json());
+        assert!(!request(Some("*/*")).wants_json());
+        assert!(!request(Some("text/html,application/xhtml+xml,*/*;q=0.8")).wants_json());
+        assert!(!request(Some("text/html, application/json;q=0.9")).wants_json());
+        assert!(!request(Some("application/json;q=0")).wants_json());
+    }
+
+    #[test]
+    fn pages_return_cli_json_when_requested() {
+        let snapshot = parser::read_snapshot_file(
+            Path::new("fixtures/small.heapsnapshot"),
+            ReadOptions::new(false, CancelToken::new()),
+        )
+        .expect("snapshot");
+        let context = test_context(snapshot);
+        let headers = HashMap::new();
+        let body = Vec::new();
+
+        let res = route(
+            "GET",
+            "/summary",
+            &HashMap::new(),
+            &headers,
+            &body,
+            &context,
+            true,
+        )
+        .expect("summary");
+        assert_eq!(res.status, 200);
+        assert!(res.content_type.starts_with("application/json"));
+        let value: serde_json::Value = serde_json::from_str(&res.body).expect("json");
+        assert_eq!(value["total_nodes"], 3);
+
+        let query = HashMap::from([("id".to_string(), "3".to_string())]);
+        let res =
+            route("GET", "/retainers", &query, &headers, &body, &context, true).expect("retainers");
+        let value: serde_json::Value = serde_json::from_str(&res.body).expect("json");
+        assert!(value["paths"].is_array());
+
+        let res = route(
+            "GET",
+            "/missing",
+            &HashMap::new(),
+            &headers,
+            &body,
+            &context,
+            true,
+        )
+        .expect("missing");
+        assert_eq!(res.status, 404);
+        assert!(res.body.contains("\"kind\":\"not_found\""));
+
+        let res = route(
+            "GET",
+            "/summary",
+            &HashMap::new(),
+            &headers,
+            &body,
+            &context,
+            false,
+        )
+        .expect("summary");
+        assert!(res.content_type.starts_with("text/html"));
+    }
+
     #[test]
     fn detail_controls_reflect_query_values() {
         let snapshot = parser::read_snapshot_file(
@@ -2416,7 +3565,8 @@ mod tests {
         query.insert("name".to_string(), "Node1".to_string());
         query.insert("skip".to_string(), "1".to_string());
         query.insert("limit".to_string(), "50".to_string());
-        let res = route("GET", "/detail", &query, &headers, &body, &context).expect("detail");
+        let res =
+            route("GET", "/detail", &query, &headers, &body, &context, false).expect("detail");
         assert_eq!(res.status, 200);
         assert!(res.body.contains("name=\"skip\" value=\"1\""));
         assert!(
@@ -2425,6 +3575,78 @@ mod tests {
         );
     }
 
+    #[test]
+    fn retainers_accepts_constructor_name() {
+        let snapshot = parser::read_snapshot_file(
+            Path::new("fixtures/small.heapsnapshot"),
+            ReadOptions::new(false, CancelToken::new()),
+        )
+        .expect("snapshot");
+        let context = test_context(snapshot);
+        let headers = HashMap::new();
+        let body = Vec::new();
+
+        let mut query = HashMap::new();
+        query.insert("name".to_string(), "Node2".to_string());
+        let res = route(
+            "GET",
+            "/retainers",
+            &query,
+            &headers,
+            &body,
+            &context,
+            false,
+        )
+        .expect("retainers");
+        assert_eq!(res.status, 200);
+        assert!(res.body.contains("<h1>Retainers (id=3)</h1>"));
+        assert!(res.body.contains("name=\"id\" value=\"3\""));
+    }
+
+    #[test]
+    fn summary_stops_adding_rows_at_the_body_cap() {
+        let snapshot = parser::read_snapshot_file(
+            Path::new("fixtures/medium.heapsnapshot"),
+            ReadOptions::new(false, CancelToken::new()),
+        )
+        .expect("snapshot");
+        let full = route(
+            "GET",
+            "/summary",
+            &HashMap::new(),
+            &HashMap::new(),
+            &Vec::new(),
+            &test_context(snapshot),
+            false,
+        )
+        .expect("summary");
+        assert!(!full.body.contains("Output truncated"));
+
+        let snapshot = parser::read_snapshot_file(
+            Path::new("fixtures/medium.heapsnapshot"),
+            ReadOptions::new(false, CancelToken::new()),
+        )
+        .expect("snapshot");
+        let mut context = test_context(snapshot);
+        // Room for the page head and a few rows only.
+        context.max_body_bytes = full.body.find("<tbody>").expect("table") + 600;
+        let res = route(
+            "GET",
+            "/summary",
+            &HashMap::new(),
+            &HashMap::new(),
+            &Vec::new(),
+            &context,
+            false,
+        )
+        .expect("summary");
+        assert_eq!(res.status, 200);
+        let rows = |body: &str| body.matches("<tr").count();
+        assert!(rows(&res.body) > 1 && rows(&res.body) < rows(&full.body));
+        assert!(res.body.contains("Output truncated at"));
+        assert!(res.body.contains("</body></html>"));
+    }
+
     #[test]
     fn summary_controls_reflect_query_values() {
         let snapshot = parser::read_snapshot_file(
@@ -2441,7 +3663,8 @@ mod tests {
         query.insert("search".to_string(), "Node".to_string());
         query.insert("skip".to_string(), "2".to_string());
         query.insert("limit".to_string(), "25".to_string());
-        let res = route("GET", "/summary", &query, &headers, &body, &context).expect("summary");
+        let res =
+            route("GET", "/summary", &query, &headers, &body, &context, false).expect("summary");
         assert_eq!(res.status, 200);
         assert!(res.body.contains("name=\"top\" value=\"99\""));
         assert!(res.body.contains("name=\"search\" value=\"Node\""));
@@ -2469,7 +3692,8 @@ mod tests {
 
         let mut query = HashMap::new();
         query.insert("size_unit".to_string(), "mib".to_string());
-        let res = route("GET", "/summary", &query, &headers, &body, &context).expect("summary");
+        let res =
+            route("GET", "/summary", &query, &headers, &body, &context, false).expect("summary");
         assert_eq!(res.status, 200);
         assert!(res.body.contains("Self Size Sum (MiB)"));
         assert!(
@@ -2478,10 +3702,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn request_log_line_has_method_target_status_and_millis() {
+        assert_eq!(
+            request_log_line("GET", "/retainers?id=3", 200, Duration::from_micros(12_345)),
+            "GET /retainers?id=3 200 12.3"
+        );
+    }
+
     #[test]
     fn index_has_diff_link() {
-        let html = render_index();
+        let load = || {
+            parser::read_snapshot_file(
+                Path::new("fixtures/small.heapsnapshot"),
+                ReadOptions::new(false, CancelToken::new()),
+            )
+            .expect("snapshot")
+        };
+        let html = render_index(&test_context(load()));
         assert!(html.contains("<a href=\"/diff\">Diff (upload file)</a>"));
+        assert!(!html.contains("snapshot-labels"));
+
+        let mut snapshot = load();
+        snapshot.labels = crate::snapshot::SnapshotLabels {
+            title: Some("after <login>".to_string()),
+            uid: Some(7),
+            capture_time: None,
+        };
+        let html = render_index(&test_context(snapshot));
+        assert!(html.contains("<li>Title: after &lt;login&gt;</li>"));
+        assert!(html.contains("<li>UID: 7</li>"));
+        assert!(!html.contains("Captured"));
+    }
+
+    #[test]
+    fn nav_shows_integrity_badge_from_verify() {
+        let snapshot = parser::read_snapshot_file(
+            Path::new("fixtures/small.heapsnapshot"),
+            ReadOptions::new(false, CancelToken::new()),
+        )
+        .expect("snapshot");
+        let mut nav = String::new();
+        let context = test_context(snapshot);
+        write_nav(&mut nav, &context, &context.current());
+        assert!(nav.contains("integrity: ok"));
+
+        // Declares 3 nodes but holds 2, and one edge points past the nodes.
+        let json = r#"
+        {
+          "snapshot": {
+            "meta": {
+              "node_fields": ["type","name","id","self_size","edge_count"],
+              "node_types": [["object"],"string","number","number","number"],
+              "edge_fields": ["type","name_or_index","to_node"],
+              "edge_types": [["property"],"string_or_number","node"]
+            },
+            "node_count": 3
+          },
+          "nodes": [0,0,1,10,1, 0,0,2,20,0],
+          "edges": [0,0,99],
+          "strings": ["GC roots"]
+        }
+        "#;
+        let snapshot = parser::read_snapshot(&mut json.as_bytes()).expect("snapshot");
+        let mut nav = String::new();
+        let context = test_context(snapshot);
+        write_nav(&mut nav, &context, &context.current());
+        assert!(nav.contains("integrity: 2 check(s) failed"));
     }
 
     #[test]
@@ -2495,7 +3782,16 @@ mod tests {
         let headers = HashMap::new();
         let body = Vec::new();
 
-        let res = route("GET", "/diff", &HashMap::new(), &headers, &body, &context).expect("diff");
+        let res = route(
+            "GET",
+            "/diff",
+            &HashMap::new(),
+            &headers,
+            &body,
+            &context,
+            false,
+        )
+        .expect("diff");
         assert_eq!(res.status, 200);
         assert!(res.body.contains("type=\"file\""));
         assert!(res.body.contains("name=\"after\""));
@@ -2567,7 +3863,7 @@ mod tests {
     fn bind_with_retry_advances_port_on_addr_in_use() {
         let attempts = Arc::new(AtomicUsize::new(0));
         let attempts_ref = Arc::clone(&attempts);
-        let result = bind_with_retry(9000, move |_port| {
+        let result = bind_with_retry(9000, true, move |_port| {
             let n = attempts_ref.fetch_add(1, Ordering::SeqCst);
             if n < 2 {
                 Err(io::Error::from(io::ErrorKind::AddrInUse))
@@ -2582,7 +3878,7 @@ mod tests {
 
     #[test]
     fn bind_with_retry_returns_error_on_non_addr_in_use() {
-        let err = bind_with_retry(9100, |_port| -> Result<(), io::Error> {
+        let err = bind_with_retry(9100, true, |_port| -> Result<(), io::Error> {
             Err(io::Error::from(io::ErrorKind::PermissionDenied))
         })
         .expect_err("expected error");
@@ -2591,7 +3887,7 @@ mod tests {
 
     #[test]
     fn bind_with_retry_reports_exhaustion_at_u16_max() {
-        let err = bind_with_retry(u16::MAX, |_port| -> Result<(), io::Error> {
+        let err = bind_with_retry(u16::MAX, true, |_port| -> Result<(), io::Error> {
             Err(io::Error::from(io::ErrorKind::AddrInUse))
         })
         .expect_err("expected exhaustion");
@@ -2624,7 +3920,7 @@ mod tests {
         .expect("snapshot");
         let context = test_context(snapshot);
         let (headers, body) = multipart_request(&[("top", None, b"10")]);
-        let res = render_diff_post(&headers, &body, &context).expect("response");
+        let res = render_diff_post(&headers, &body, &context, false).expect("response");
         assert_eq!(res.status, 400);
         assert!(res.body.contains("missing `after` file field"));
     }
@@ -2639,7 +3935,7 @@ mod tests {
         let context = test_context(snapshot);
         let bytes = fs::read("fixtures/small.heapsnapshot").expect("fixture bytes");
         let (headers, body) = multipart_request(&[("after", Some("small.heapsnapshot"), &bytes)]);
-        let res = render_diff_post(&headers, &body, &context).expect("response");
+        let res = render_diff_post(&headers, &body, &context, false).expect("response");
         assert_eq!(res.status, 200);
         let guard = context.uploaded_temp_files.lock().expect("lock");
         assert_eq!(guard.len(), 1);
@@ -2660,7 +3956,7 @@ mod tests {
             ("after", Some("small.heapsnapshot"), &bytes),
             ("size_unit", None, b"gib"),
         ]);
-        let res = render_diff_post(&headers, &body, &context).expect("response");
+        let res = render_diff_post(&headers, &body, &context, false).expect("response");
         assert_eq!(res.status, 200);
         assert!(res.body.contains("Self Size Δ (GiB)"));
         assert!(
@@ -2695,7 +3991,7 @@ mod tests {
         let mut query = HashMap::new();
         query.insert("before".to_string(), before_path.display().to_string());
         query.insert("after".to_string(), after_path.display().to_string());
-        let html = render_diff(&query, &context).expect("render");
+        let html = render_diff(&query, &context, false).expect("render");
         assert!(html.contains("Before:</strong> small.heapsnapshot (fixtures/small.heapsnapshot)"));
         assert!(html.contains("After:</strong> from-browser.heapsnapshot ("));
         let _ = fs::remove_file(after_path);
@@ -2711,7 +4007,16 @@ mod tests {
 
     #[test]
     fn render_dominator_loading_contains_session_replace_script() {
+        let snapshot = parser::read_snapshot_file(
+            Path::new("fixtures/small.heapsnapshot"),
+            ReadOptions::new(false, CancelToken::new()),
+        )
+        .expect("snapshot");
+        let context = test_context(snapshot);
+        let mut nav = String::new();
+        write_nav(&mut nav, &context, &context.current());
         let html = render_dominator_loading(
+            &nav,
             3,
             50,
             0,
@@ -2736,10 +4041,17 @@ mod tests {
         )
         .expect("snapshot");
         let context = test_context(snapshot);
-        let target = *context.id_index.values().next().expect("any target");
+        let loaded = context.current();
+        let target = loaded
+            .snapshot
+            .nodes()
+            .next()
+            .expect("any target")
+            .node_index();
 
         let first = get_or_start_dominator_job(
             &context,
+            &loaded,
             DominatorJobKey {
                 session: "s1".to_string(),
                 target,
@@ -2748,6 +4060,7 @@ mod tests {
         );
         let second = get_or_start_dominator_job(
             &context,
+            &loaded,
             DominatorJobKey {
                 session: "s1".to_string(),
                 target,
@@ -2794,7 +4107,55 @@ mod tests {
         let context = test_context(snapshot);
         let before = context.before_path.clone();
         let loaded = load_snapshot_cached(&context, &before).expect("loaded");
-        assert!(Arc::ptr_eq(&loaded, &context.snapshot));
+        assert!(Arc::ptr_eq(&loaded, &context.current().snapshot));
+    }
+
+    #[test]
+    fn follow_reload_swaps_snapshot_and_keeps_previous_on_parse_error() {
+        let dir = std::env::temp_dir().join(format!("heapsnap-follow-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("mkdir");
+        let path = dir.join("app.heapsnapshot");
+        fs::copy("fixtures/small.heapsnapshot", &path).expect("copy");
+        let snapshot =
+            parser::read_snapshot_file(&path, ReadOptions::new(false, CancelToken::new()))
+                .expect("snapshot");
+        let mut context = test_context(snapshot);
+        context.before_path = path.clone();
+        context.follow = true;
+        let mut last_seen = file_modified(&path);
+
+        let set_mtime = |secs: u64| {
+            fs::File::options()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_modified(UNIX_EPOCH + Duration::from_secs(secs)))
+                .expect("set mtime");
+        };
+
+        fs::copy("fixtures/medium.heapsnapshot", &path).expect("copy");
+        set_mtime(1_700_000_000);
+        reload_if_modified(&context, &mut last_seen);
+        let reloaded = context.current();
+        assert_eq!(
+            reloaded.modified,
+            Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+        );
+        let medium = parser::read_snapshot_file(
+            Path::new("fixtures/medium.heapsnapshot"),
+            ReadOptions::new(false, CancelToken::new()),
+        )
+        .expect("medium");
+        assert_eq!(reloaded.snapshot.node_count(), medium.node_count());
+
+        fs::write(&path, b"{ not a snapshot").expect("write");
+        set_mtime(1_700_000_100);
+        reload_if_modified(&context, &mut last_seen);
+        assert!(Arc::ptr_eq(&context.current(), &reloaded));
+
+        let mut nav = String::new();
+        write_nav(&mut nav, &context, &context.current());
+        assert!(nav.contains("2023-11-14 22:13:20 UTC"));
+        let _ = fs::remove_dir_all(&dir);
     }
 
     #[test]
@@ -2815,9 +4176,9 @@ mod tests {
             "fixtures/small.heapsnapshot".to_string(),
         );
         query.insert("top".to_string(), "50".to_string());
-        let _ = render_diff(&query, &context).expect("first");
+        let _ = render_diff(&query, &context, false).expect("first");
         let cache_len_after_first = context.diff_cache.lock().expect("lock").len();
-        let _ = render_diff(&query, &context).expect("second");
+        let _ = render_diff(&query, &context, false).expect("second");
         let cache_len_after_second = context.diff_cache.lock().expect("lock").len();
         assert_eq!(cache_len_after_first, 1);
         assert_eq!(cache_len_after_second, 1);
@@ -1,3 +1,7 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::OnceLock;
+
 use serde::Deserialize;
 
 use crate::error::SnapshotError;
@@ -5,6 +9,46 @@ use crate::error::SnapshotError;
 #[derive(Debug, Deserialize)]
 pub struct SnapshotRoot {
     pub meta: Option<SnapshotMeta>,
+    pub node_count: Option<u64>,
+    pub edge_count: Option<u64>,
+    /// Labels some producers add next to `meta`. Read leniently: a missing key or a value
+    /// of an unexpected type leaves the field empty instead of failing the parse.
+    #[serde(default, deserialize_with = "lenient_text")]
+    pub title: Option<String>,
+    #[serde(default, deserialize_with = "lenient_u64")]
+    pub uid: Option<u64>,
+    #[serde(default, deserialize_with = "lenient_text")]
+    pub timestamp: Option<String>,
+}
+
+/// Identifying labels of a snapshot, for telling a folder of captures apart.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotLabels {
+    /// `snapshot.title`; DevTools writes an empty title for most captures, kept as `None`.
+    pub title: Option<String>,
+    pub uid: Option<u64>,
+    /// `snapshot.timestamp` as written (a number is kept as its decimal text).
+    pub capture_time: Option<String>,
+}
+
+impl SnapshotLabels {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.uid.is_none() && self.capture_time.is_none()
+    }
+}
+
+fn lenient_text<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(text) if !text.is_empty() => Some(text),
+        serde_json::Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    })
+}
+
+fn lenient_u64<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    Ok(serde_json::Value::deserialize(deserializer)?.as_u64())
 }
 
 #[derive(Debug, Deserialize)]
@@ -13,6 +57,9 @@ pub struct SnapshotMeta {
     pub node_types: Vec<MetaType>,
     pub edge_fields: Vec<String>,
     pub edge_types: Vec<MetaType>,
+    /// Layout of the optional top-level `locations` array (absent in most snapshots).
+    #[serde(default)]
+    pub location_fields: Vec<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -32,13 +79,53 @@ pub struct MetaIndex {
     pub edge_field_count: usize,
 }
 
+/// `detachedness` values as written by V8: 0 = unknown, 1 = attached, 2 = detached DOM node.
+pub const DETACHEDNESS_DETACHED: i64 = 2;
+
 #[derive(Debug)]
 pub struct NodeFieldIndex {
     pub type_idx: usize,
     pub name_idx: usize,
     pub id_idx: usize,
     pub self_size_idx: usize,
-    pub edge_count_idx: usize,
+    pub edge_layout: EdgeLayout,
+    /// `detachedness` is only written by newer V8 versions.
+    pub detachedness_idx: Option<usize>,
+}
+
+/// How a node's outgoing edges are located in `edges`. Both layouts store the edges in
+/// node order; they differ in the node field that delimits them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeLayout {
+    /// `edge_count` node field: each node owns the next N edges (V8 since 2012).
+    EdgeCount(usize),
+    /// `edges_index` node field: array offset of the node's first edge, its edges ending
+    /// where the next node's begin (older V8).
+    EdgesIndex(usize),
+}
+
+impl EdgeLayout {
+    /// Name of the node field the layout was detected from.
+    pub fn field_name(self) -> &'static str {
+        match self {
+            EdgeLayout::EdgeCount(_) => "edge_count",
+            EdgeLayout::EdgesIndex(_) => "edges_index",
+        }
+    }
+
+    fn detect(node_fields: &[String]) -> Result<Self, SnapshotError> {
+        let position = |name: &str| node_fields.iter().position(|field| field == name);
+        if let Some(index) = position("edge_count") {
+            return Ok(EdgeLayout::EdgeCount(index));
+        }
+        if let Some(index) = position("edges_index") {
+            return Ok(EdgeLayout::EdgesIndex(index));
+        }
+        Err(SnapshotError::MetaMismatch {
+            details: "missing required field: edge_count (or edges_index in older snapshots)"
+                .to_string(),
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -77,7 +164,11 @@ impl SnapshotMeta {
             name_idx: find_field(&self.node_fields, "name")?,
             id_idx: find_field(&self.node_fields, "id")?,
             self_size_idx: find_field(&self.node_fields, "self_size")?,
-            edge_count_idx: find_field(&self.node_fields, "edge_count")?,
+            edge_layout: EdgeLayout::detect(&self.node_fields)?,
+            detachedness_idx: self
+                .node_fields
+                .iter()
+                .position(|field| field == "detachedness"),
         };
 
         let edge_field_index = EdgeFieldIndex {
@@ -130,6 +221,95 @@ fn find_field(fields: &[String], name: &str) -> Result<usize, SnapshotError> {
         })
 }
 
+/// Where a node was allocated, as recorded by V8. `line` and `column` are 0-based.
+/// Snapshots carry no script URLs, so the script is identified by its id only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub script_id: i64,
+    pub line: i64,
+    pub column: i64,
+}
+
+/// Formats as `script 42, line 10, column 5` with 1-based line/column, matching DevTools.
+impl std::fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "script {}, line {}, column {}",
+            self.script_id,
+            self.line + 1,
+            self.column + 1
+        )
+    }
+}
+
+/// Allocation sites from the top-level `locations` array, sorted by node index.
+#[derive(Debug, Default)]
+pub struct NodeLocations {
+    entries: Vec<(usize, SourceLocation)>,
+}
+
+impl NodeLocations {
+    /// Decodes the flat `locations` array using `meta.location_fields`.
+    /// `object_index` is an offset into `nodes` (like `to_node`), not a node index.
+    pub fn from_raw(
+        values: &[i64],
+        location_fields: &[String],
+        node_field_count: usize,
+    ) -> Result<Self, SnapshotError> {
+        let field_count = location_fields.len();
+        let object_idx = find_field(location_fields, "object_index")?;
+        let script_id_idx = find_field(location_fields, "script_id")?;
+        let line_idx = find_field(location_fields, "line")?;
+        let column_idx = find_field(location_fields, "column")?;
+        if !values.len().is_multiple_of(field_count) {
+            return Err(SnapshotError::InvalidData {
+                details: format!(
+                    "locations length ({}) is not divisible by location field count ({field_count})",
+                    values.len()
+                ),
+            });
+        }
+
+        let mut entries = Vec::with_capacity(values.len() / field_count);
+        for record in values.chunks_exact(field_count) {
+            let offset = record[object_idx];
+            let node_index = usize::try_from(offset)
+                .ok()
+                .filter(|offset| offset.is_multiple_of(node_field_count))
+                .map(|offset| offset / node_field_count)
+                .ok_or_else(|| SnapshotError::InvalidData {
+                    details: format!("locations object_index is not a node offset: {offset}"),
+                })?;
+            entries.push((
+                node_index,
+                SourceLocation {
+                    script_id: record[script_id_idx],
+                    line: record[line_idx],
+                    column: record[column_idx],
+                },
+            ));
+        }
+        entries.sort_by_key(|(node_index, _)| *node_index);
+        Ok(Self { entries })
+    }
+
+    pub fn get(&self, node_index: usize) -> Option<SourceLocation> {
+        self.entries
+            .binary_search_by_key(&node_index, |(index, _)| *index)
+            .ok()
+            .map(|position| self.entries[position].1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
 #[derive(Debug)]
 pub struct SnapshotRaw {
     pub nodes: Vec<i64>,
@@ -137,13 +317,64 @@ pub struct SnapshotRaw {
     pub strings: Vec<String>,
     pub meta: SnapshotMeta,
     pub index: MetaIndex,
+    /// `snapshot.node_count` / `snapshot.edge_count` as written by the producer, if present.
+    pub declared_node_count: Option<u64>,
+    pub declared_edge_count: Option<u64>,
+    /// `snapshot.title` / `uid` / `timestamp`, when the producer wrote them.
+    pub labels: SnapshotLabels,
+    /// Present only when the snapshot has a `locations` section.
+    pub locations: Option<NodeLocations>,
+    edge_offsets: OnceLock<Vec<usize>>,
+    id_index: OnceLock<HashMap<u64, usize>>,
+    retainer_index: OnceLock<RetainerIndex>,
+}
+
+/// Inbound edges grouped by target: `links[offsets[n]..offsets[n + 1]]` point at node `n`.
+#[derive(Debug)]
+struct RetainerIndex {
+    offsets: Vec<usize>,
+    /// (from node index, edge index)
+    links: Vec<(usize, usize)>,
 }
 
 impl SnapshotRaw {
+    pub fn new(
+        nodes: Vec<i64>,
+        edges: Vec<i64>,
+        strings: Vec<String>,
+        meta: SnapshotMeta,
+        index: MetaIndex,
+    ) -> Self {
+        Self {
+            nodes,
+            edges,
+            strings,
+            meta,
+            index,
+            declared_node_count: None,
+            declared_edge_count: None,
+            labels: SnapshotLabels::default(),
+            locations: None,
+            edge_offsets: OnceLock::new(),
+            id_index: OnceLock::new(),
+            retainer_index: OnceLock::new(),
+        }
+    }
+
     pub fn node_count(&self) -> usize {
         self.nodes.len() / self.index.node_field_count
     }
 
+    /// Fails for a snapshot with an empty `nodes` array, which has no root to analyse from.
+    pub fn require_nodes(&self) -> Result<(), SnapshotError> {
+        if self.node_count() == 0 {
+            return Err(SnapshotError::InvalidData {
+                details: "snapshot contains no nodes".to_string(),
+            });
+        }
+        Ok(())
+    }
+
     pub fn edge_count(&self) -> usize {
         self.edges.len() / self.index.edge_field_count
     }
@@ -168,6 +399,219 @@ impl SnapshotRaw {
         })
     }
 
+    pub fn nodes(&self) -> NodeIter<'_> {
+        NodeIter {
+            snapshot: self,
+            range: 0..self.node_count(),
+        }
+    }
+
+    pub fn edges(&self) -> EdgeIter<'_> {
+        EdgeIter {
+            snapshot: self,
+            range: 0..self.edge_count(),
+        }
+    }
+
+    /// Iterates the outgoing edges of a node.
+    /// The first call computes and caches the per-node edge offsets.
+    pub fn edges_of(&self, node_index: usize) -> Result<EdgeIter<'_>, SnapshotError> {
+        let offsets = self.edge_offsets()?;
+        let (start, end) = match (offsets.get(node_index), offsets.get(node_index + 1)) {
+            (Some(start), Some(end)) => (*start, *end),
+            _ => {
+                return Err(SnapshotError::InvalidData {
+                    details: format!("node index out of range: {node_index}"),
+                });
+            }
+        };
+        Ok(EdgeIter {
+            snapshot: self,
+            range: start..end,
+        })
+    }
+
+    /// Returns the first edge index of every node, followed by the total edge count.
+    pub fn edge_offsets(&self) -> Result<&[usize], SnapshotError> {
+        if let Some(offsets) = self.edge_offsets.get() {
+            return Ok(offsets);
+        }
+        let offsets = self.compute_edge_offsets()?;
+        Ok(self.edge_offsets.get_or_init(|| offsets))
+    }
+
+    fn compute_edge_offsets(&self) -> Result<Vec<usize>, SnapshotError> {
+        let layout = self.index.node_field_index.edge_layout;
+        if let EdgeLayout::EdgesIndex(field) = layout
+            && let Some(first) = self
+                .node_view(0)
+                .and_then(|node| node.field_value(field))
+                .filter(|first| *first != 0)
+        {
+            return Err(SnapshotError::InvalidData {
+                details: format!("edges_index of the first node is {first}, expected 0"),
+            });
+        }
+
+        let mut offsets = Vec::with_capacity(self.node_count() + 1);
+        let mut cursor = 0usize;
+
+        for node in self.nodes() {
+            offsets.push(cursor);
+            let edge_count = node
+                .edge_count()
+                .ok_or_else(|| SnapshotError::InvalidData {
+                    details: format!(
+                        "{} at node {} does not fall on an edge boundary",
+                        layout.field_name(),
+                        node.node_index()
+                    ),
+                })?;
+            let edge_count =
+                usize::try_from(edge_count).map_err(|_| SnapshotError::InvalidData {
+                    details: match layout {
+                        EdgeLayout::EdgeCount(_) => {
+                            format!("edge_count negative at node {}", node.node_index())
+                        }
+                        EdgeLayout::EdgesIndex(_) => {
+                            format!("edges_index decreases after node {}", node.node_index())
+                        }
+                    },
+                })?;
+            cursor = cursor.saturating_add(edge_count);
+        }
+
+        if cursor != self.edge_count() {
+            return Err(SnapshotError::InvalidData {
+                details: format!(
+                    "edge_count sum ({}) does not match edges length ({})",
+                    cursor,
+                    self.edge_count()
+                ),
+            });
+        }
+        offsets.push(cursor);
+
+        Ok(offsets)
+    }
+
+    /// Node with the given heap object id, via a map built on first call.
+    /// When ids repeat (malformed snapshots), the last node wins.
+    pub fn node_by_id(&self, id: i64) -> Option<NodeView<'_>> {
+        let id = u64::try_from(id).ok()?;
+        let index = self.id_index.get_or_init(|| {
+            self.nodes()
+                .filter_map(|node| Some((u64::try_from(node.id()?).ok()?, node.node_index())))
+                .collect()
+        });
+        self.node_view(*index.get(&id)?)
+    }
+
+    /// Iterates the edges pointing at a node, with the node holding each edge.
+    /// The first call builds an inbound index over every edge and keeps it.
+    pub fn retainers_of(&self, node_index: usize) -> Result<RetainerIter<'_>, SnapshotError> {
+        let index = match self.retainer_index.get() {
+            Some(index) => index,
+            None => {
+                let index = self.compute_retainer_index()?;
+                self.retainer_index.get_or_init(|| index)
+            }
+        };
+        let (start, end) = match (
+            index.offsets.get(node_index),
+            index.offsets.get(node_index + 1),
+        ) {
+            (Some(start), Some(end)) => (*start, *end),
+            _ => {
+                return Err(SnapshotError::InvalidData {
+                    details: format!("node index out of range: {node_index}"),
+                });
+            }
+        };
+        Ok(RetainerIter {
+            snapshot: self,
+            links: index.links[start..end].iter(),
+        })
+    }
+
+    fn compute_retainer_index(&self) -> Result<RetainerIndex, SnapshotError> {
+        let node_count = self.node_count();
+        let target_of = |edge: &EdgeView<'_>| edge.to_node_index().filter(|to| *to < node_count);
+
+        let mut offsets = vec![0usize; node_count + 1];
+        for edge in self.edges() {
+            if let Some(to_node) = target_of(&edge) {
+                offsets[to_node + 1] += 1;
+            }
+        }
+        for node_index in 0..node_count {
+            offsets[node_index + 1] += offsets[node_index];
+        }
+
+        let mut cursor = offsets[..node_count].to_vec();
+        let mut links = vec![(0, 0); offsets[node_count]];
+        for node in self.nodes() {
+            for edge in node.outgoing_edges()? {
+                if let Some(to_node) = target_of(&edge) {
+                    links[cursor[to_node]] = (node.node_index(), edge.edge_index());
+                    cursor[to_node] += 1;
+                }
+            }
+        }
+        Ok(RetainerIndex { offsets, links })
+    }
+
+    /// Allocation site of a node, if the snapshot recorded one.
+    pub fn location_of(&self, node_index: usize) -> Option<SourceLocation> {
+        self.locations.as_ref()?.get(node_index)
+    }
+
+    /// String-table indices whose text equals `text`. The table is not guaranteed to be
+    /// deduplicated, so one constructor name can live at several indices.
+    pub fn string_indices_of(&self, text: &str) -> Vec<usize> {
+        self.strings
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| value.as_str() == text)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Content hash (FNV-1a) over the node, edge and string tables.
+    /// Stable across runs and platforms, so it can key on-disk caches.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash = Fnv1a::new();
+        hash.write_u64(self.nodes.len() as u64);
+        hash.write_u64(self.edges.len() as u64);
+        hash.write_u64(self.strings.len() as u64);
+        for value in self.nodes.iter().chain(self.edges.iter()) {
+            hash.write_u64(*value as u64);
+        }
+        for value in &self.strings {
+            hash.write_u64(value.len() as u64);
+            hash.write_bytes(value.as_bytes());
+        }
+        hash.finish()
+    }
+
+    /// Cheap identity hash (FNV-1a) over the table lengths and the first and last node ids.
+    /// Unlike `fingerprint` it does not walk the tables, so every JSON output can carry it
+    /// to tell which snapshot a result was computed from.
+    pub fn quick_fingerprint(&self) -> u64 {
+        let node_id = |index: Option<usize>| {
+            index
+                .and_then(|index| self.node_view(index))
+                .and_then(|node| node.id())
+        };
+        quick_fingerprint(
+            self.node_count(),
+            self.edge_count(),
+            node_id(Some(0)),
+            node_id(self.node_count().checked_sub(1)),
+            self.strings.len(),
+        )
+    }
+
     pub fn memory_estimate_bytes(&self) -> u64 {
         let nodes_bytes = self.nodes.len() * std::mem::size_of::<i64>();
         let edges_bytes = self.edges.len() * std::mem::size_of::<i64>();
@@ -176,6 +620,154 @@ impl SnapshotRaw {
     }
 }
 
+/// A snapshot read by `parser::stream_snapshot`: its nodes went to a callback and its
+/// edges were only counted, so neither array is kept.
+#[derive(Debug)]
+pub struct StreamedSnapshot {
+    pub strings: Vec<String>,
+    pub meta: SnapshotMeta,
+    pub index: MetaIndex,
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub first_node_id: Option<i64>,
+    pub last_node_id: Option<i64>,
+    pub labels: SnapshotLabels,
+}
+
+impl StreamedSnapshot {
+    /// Same value as `SnapshotRaw::quick_fingerprint` for the same file.
+    pub fn quick_fingerprint(&self) -> u64 {
+        quick_fingerprint(
+            self.node_count,
+            self.edge_count,
+            self.first_node_id,
+            self.last_node_id,
+            self.strings.len(),
+        )
+    }
+}
+
+fn quick_fingerprint(
+    node_count: usize,
+    edge_count: usize,
+    first_node_id: Option<i64>,
+    last_node_id: Option<i64>,
+    string_count: usize,
+) -> u64 {
+    let mut hash = Fnv1a::new();
+    hash.write_u64(node_count as u64);
+    hash.write_u64(edge_count as u64);
+    hash.write_u64(first_node_id.unwrap_or(-1) as u64);
+    hash.write_u64(last_node_id.unwrap_or(-1) as u64);
+    hash.write_u64(string_count as u64);
+    hash.finish()
+}
+
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NodeIter<'a> {
+    snapshot: &'a SnapshotRaw,
+    range: Range<usize>,
+}
+
+impl<'a> Iterator for NodeIter<'a> {
+    type Item = NodeView<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node_index = self.range.next()?;
+        Some(NodeView {
+            snapshot: self.snapshot,
+            node_index,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl ExactSizeIterator for NodeIter<'_> {}
+
+#[derive(Debug, Clone)]
+pub struct EdgeIter<'a> {
+    snapshot: &'a SnapshotRaw,
+    range: Range<usize>,
+}
+
+impl<'a> Iterator for EdgeIter<'a> {
+    type Item = EdgeView<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let edge_index = self.range.next()?;
+        Some(EdgeView {
+            snapshot: self.snapshot,
+            edge_index,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl ExactSizeIterator for EdgeIter<'_> {}
+
+/// Yields `(retaining node, edge)` pairs; see `SnapshotRaw::retainers_of`.
+#[derive(Debug, Clone)]
+pub struct RetainerIter<'a> {
+    snapshot: &'a SnapshotRaw,
+    links: std::slice::Iter<'a, (usize, usize)>,
+}
+
+impl<'a> Iterator for RetainerIter<'a> {
+    type Item = (NodeView<'a>, EdgeView<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node_index, edge_index) = *self.links.next()?;
+        Some((
+            NodeView {
+                snapshot: self.snapshot,
+                node_index,
+            },
+            EdgeView {
+                snapshot: self.snapshot,
+                edge_index,
+            },
+        ))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.links.size_hint()
+    }
+}
+
+impl ExactSizeIterator for RetainerIter<'_> {}
+
 #[derive(Debug, Clone, Copy)]
 pub struct NodeView<'a> {
     snapshot: &'a SnapshotRaw,
@@ -214,8 +806,49 @@ impl<'a> NodeView<'a> {
         self.field_value(self.snapshot.index.node_field_index.self_size_idx)
     }
 
+    /// Number of outgoing edges; for `EdgeLayout::EdgesIndex` the distance to the next
+    /// node's first edge, negative when the offsets go backwards and `None` when they do
+    /// not fall on an edge boundary.
     pub fn edge_count(&self) -> Option<i64> {
-        self.field_value(self.snapshot.index.node_field_index.edge_count_idx)
+        match self.snapshot.index.node_field_index.edge_layout {
+            EdgeLayout::EdgeCount(field) => self.field_value(field),
+            EdgeLayout::EdgesIndex(field) => {
+                let start = self.field_value(field)?;
+                let end = match self.snapshot.node_view(self.node_index + 1) {
+                    Some(next) => next.field_value(field)?,
+                    None => i64::try_from(self.snapshot.edges.len()).ok()?,
+                };
+                let field_count = i64::try_from(self.snapshot.index.edge_field_count).ok()?;
+                let slots = end - start;
+                (slots % field_count == 0).then_some(slots / field_count)
+            }
+        }
+    }
+
+    /// `None` when the snapshot has no `detachedness` field; see `DETACHEDNESS_DETACHED`.
+    pub fn detachedness(&self) -> Option<i64> {
+        self.field_value(self.snapshot.index.node_field_index.detachedness_idx?)
+    }
+
+    pub fn is_detached(&self) -> bool {
+        self.detachedness() == Some(DETACHEDNESS_DETACHED)
+    }
+
+    /// Outgoing edges of this node (see `SnapshotRaw::edges_of`).
+    pub fn outgoing_edges(&self) -> Result<EdgeIter<'a>, SnapshotError> {
+        self.snapshot.edges_of(self.node_index)
+    }
+
+    /// Edges pointing at this node with their holders (see `SnapshotRaw::retainers_of`).
+    pub fn retainers(&self) -> Result<RetainerIter<'a>, SnapshotError> {
+        self.snapshot.retainers_of(self.node_index)
+    }
+
+    /// Every field of this node in `meta.node_fields` order, including ones the tool does not interpret.
+    pub fn raw_fields(&self) -> &'a [i64] {
+        let count = self.snapshot.index.node_field_count;
+        let base = self.node_index * count;
+        self.snapshot.nodes.get(base..base + count).unwrap_or(&[])
     }
 
     fn field_value(&self, field_index: usize) -> Option<i64> {
@@ -264,6 +897,13 @@ impl<'a> EdgeView<'a> {
         Some(to_node / self.snapshot.index.node_field_count)
     }
 
+    /// Every field of this edge in `meta.edge_fields` order.
+    pub fn raw_fields(&self) -> &'a [i64] {
+        let count = self.snapshot.index.edge_field_count;
+        let base = self.edge_index * count;
+        self.snapshot.edges.get(base..base + count).unwrap_or(&[])
+    }
+
     fn field_value(&self, field_index: usize) -> Option<i64> {
         let base = self.edge_index * self.snapshot.index.edge_field_count;
         self.snapshot.edges.get(base + field_index).copied()
@@ -274,9 +914,8 @@ impl<'a> EdgeView<'a> {
 mod tests {
     use super::*;
 
-    #[test]
-    fn validate_meta_indexes() {
-        let meta = SnapshotMeta {
+    fn sample_meta() -> SnapshotMeta {
+        SnapshotMeta {
             node_fields: vec![
                 "type".to_string(),
                 "name".to_string(),
@@ -301,12 +940,157 @@ mod tests {
                 MetaType::String("string_or_number".to_string()),
                 MetaType::String("node".to_string()),
             ],
-        };
+            location_fields: Vec::new(),
+        }
+    }
 
+    #[test]
+    fn validate_meta_indexes() {
+        let meta = sample_meta();
         let index = meta.validate().expect("meta valid");
         assert_eq!(index.node_field_count, 5);
         assert_eq!(index.edge_field_count, 3);
         assert_eq!(index.node_type_names.len(), 2);
         assert_eq!(index.edge_type_names.len(), 2);
     }
+
+    #[test]
+    fn iterate_nodes_and_edges_of() {
+        let meta = sample_meta();
+        let index = meta.validate().expect("meta valid");
+        let snapshot = SnapshotRaw::new(
+            vec![
+                0, 0, 1, 10, 2, // node 0: two edges
+                0, 1, 2, 20, 0, // node 1: no edges
+                1, 1, 3, 30, 1, // node 2: one edge
+            ],
+            vec![
+                0, 0, 5, // edge 0: node 0 -> node 1
+                1, 0, 10, // edge 1: node 0 -> node 2
+                0, 0, 0, // edge 2: node 2 -> node 0
+            ],
+            vec!["Foo".to_string(), "Bar".to_string()],
+            meta,
+            index,
+        );
+
+        let ids: Vec<Option<i64>> = snapshot.nodes().map(|node| node.id()).collect();
+        assert_eq!(ids, vec![Some(1), Some(2), Some(3)]);
+        assert_eq!(snapshot.edges().len(), 3);
+
+        let targets: Vec<Option<usize>> = snapshot
+            .edges_of(0)
+            .expect("edges")
+            .map(|edge| edge.to_node_index())
+            .collect();
+        assert_eq!(targets, vec![Some(1), Some(2)]);
+        assert_eq!(snapshot.edges_of(1).expect("edges").len(), 0);
+        let edge = snapshot.edges_of(2).expect("edges").next().expect("edge");
+        assert_eq!(edge.edge_index(), 2);
+        assert!(snapshot.edges_of(3).is_err());
+    }
+
+    #[test]
+    fn node_by_id_walks_neighbours() {
+        let meta = sample_meta();
+        let index = meta.validate().expect("meta valid");
+        let snapshot = SnapshotRaw::new(
+            vec![
+                0, 0, 1, 10, 2, // node 0: two edges
+                0, 1, 2, 20, 0, // node 1: no edges
+                1, 1, 3, 30, 1, // node 2: one edge
+            ],
+            vec![
+                0, 0, 5, // edge 0: node 0 -> node 1
+                1, 0, 10, // edge 1: node 0 -> node 2
+                0, 0, 10, // edge 2: node 2 -> node 2
+            ],
+            vec!["Foo".to_string(), "Bar".to_string()],
+            meta,
+            index,
+        );
+
+        let node = snapshot.node_by_id(3).expect("node");
+        assert_eq!(node.node_index(), 2);
+        assert!(snapshot.node_by_id(4).is_none());
+        assert!(snapshot.node_by_id(-1).is_none());
+
+        let targets: Vec<Option<i64>> = node
+            .outgoing_edges()
+            .expect("edges")
+            .map(|edge| edge.to_node())
+            .collect();
+        assert_eq!(targets, vec![Some(10)]);
+
+        let retainers: Vec<(usize, usize)> = node
+            .retainers()
+            .expect("retainers")
+            .map(|(from, edge)| (from.node_index(), edge.edge_index()))
+            .collect();
+        assert_eq!(retainers, vec![(0, 1), (2, 2)]);
+        let root = snapshot.node_by_id(1).expect("node");
+        assert_eq!(root.retainers().expect("retainers").len(), 0);
+    }
+
+    #[test]
+    fn edges_index_layout_delimits_edges_by_offset() {
+        let mut meta = sample_meta();
+        meta.node_fields[4] = "edges_index".to_string();
+        let index = meta.validate().expect("meta valid");
+        assert_eq!(
+            index.node_field_index.edge_layout,
+            EdgeLayout::EdgesIndex(4)
+        );
+        let edges = vec![
+            0, 0, 5, // edge 0: node 0 -> node 1
+            1, 0, 10, // edge 1: node 0 -> node 2
+            0, 0, 0, // edge 2: node 2 -> node 0
+        ];
+        let snapshot = SnapshotRaw::new(
+            vec![
+                0, 0, 1, 10, 0, // node 0: edges from slot 0
+                0, 1, 2, 20, 6, // node 1: none, node 2 starts at slot 6
+                1, 1, 3, 30, 6, // node 2: edges from slot 6 to the end
+            ],
+            edges.clone(),
+            vec!["Foo".to_string(), "Bar".to_string()],
+            meta,
+            index,
+        );
+
+        let counts: Vec<Option<i64>> = snapshot.nodes().map(|node| node.edge_count()).collect();
+        assert_eq!(counts, vec![Some(2), Some(0), Some(1)]);
+        assert_eq!(snapshot.edge_offsets().expect("offsets"), &[0, 2, 2, 3]);
+        let targets: Vec<Option<usize>> = snapshot
+            .edges_of(2)
+            .expect("edges")
+            .map(|edge| edge.to_node_index())
+            .collect();
+        assert_eq!(targets, vec![Some(0)]);
+
+        let broken = |nodes: Vec<i64>| {
+            let mut meta = sample_meta();
+            meta.node_fields[4] = "edges_index".to_string();
+            let index = meta.validate().expect("meta valid");
+            let snapshot = SnapshotRaw::new(nodes, edges.clone(), Vec::new(), meta, index);
+            snapshot
+                .edge_offsets()
+                .expect_err("broken offsets")
+                .to_string()
+        };
+        assert!(broken(vec![0, 0, 1, 0, 3, 0, 0, 2, 0, 3]).contains("expected 0"));
+        assert!(
+            broken(vec![0, 0, 1, 0, 0, 0, 0, 2, 0, 6, 0, 0, 3, 0, 3])
+                .contains("decreases after node 1")
+        );
+        assert!(broken(vec![0, 0, 1, 0, 0, 0, 0, 2, 0, 4]).contains("edge boundary"));
+    }
+
+    #[test]
+    fn meta_without_an_edge_layout_field_is_rejected() {
+        let mut meta = sample_meta();
+        meta.node_fields[4] = "children".to_string();
+        let err = meta.validate().expect_err("no edge layout");
+        assert!(err.to_string().contains("edge_count (or edges_index"));
+    }
 }
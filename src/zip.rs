@@ -0,0 +1,246 @@
+//! Reading one member of a `.zip` archive, addressed as `archive.zip#member`.
+//!
+//! Only what CI artifacts need: the central directory is read to find the member, and
+//! its stored or deflated bytes are streamed through `inflate::Inflate`. Zip64, split
+//! archives and encrypted members are rejected with an explanation.
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Take};
+use std::path::{Path, PathBuf};
+
+use crate::error::SnapshotError;
+use crate::gzip::crc32_update;
+use crate::inflate::Inflate;
+
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+const CENTRAL_DIRECTORY_ENTRY: u32 = 0x0201_4b50;
+const LOCAL_FILE_HEADER: u32 = 0x0403_4b50;
+const END_RECORD_LEN: u64 = 22;
+const MAX_COMMENT_LEN: u64 = 0xffff;
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+/// Members listed in the "not found" error at most.
+const LISTED_MEMBERS: usize = 10;
+
+/// Splits `archive.zip#member` into the archive path and the member name. A path that
+/// exists as written is never split, so file names containing `#` keep working.
+pub fn split_member_path(path: &Path) -> Option<(PathBuf, String)> {
+    if path.exists() {
+        return None;
+    }
+    let text = path.to_str()?;
+    let (archive, member) = text.rsplit_once('#')?;
+    if archive.is_empty() || member.is_empty() {
+        return None;
+    }
+    Some((PathBuf::from(archive), member.to_string()))
+}
+
+/// The file whose modification time stands for `path`: the archive for
+/// `archive.zip#member`, `path` itself otherwise.
+pub fn container_path(path: &Path) -> PathBuf {
+    split_member_path(path)
+        .map(|(archive, _)| archive)
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Decompressed contents of one archive member; fails with `InvalidData` at the end when
+/// the CRC-32 or the length differs from the central directory.
+pub struct ZipMember {
+    reader: MemberReader,
+    /// Uncompressed size recorded in the central directory.
+    pub size: u64,
+    expected_crc: u32,
+    crc: u32,
+    read_bytes: u64,
+}
+
+enum MemberReader {
+    Stored(Take<BufReader<File>>),
+    Deflated(Box<Inflate<Take<BufReader<File>>>>),
+}
+
+impl Read for ZipMember {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let bytes = match &mut self.reader {
+            MemberReader::Stored(reader) => reader.read(buf)?,
+            MemberReader::Deflated(reader) => reader.read(buf)?,
+        };
+        self.crc = crc32_update(self.crc, &buf[..bytes]);
+        self.read_bytes += bytes as u64;
+        if bytes == 0
+            && !buf.is_empty()
+            && (self.crc != self.expected_crc || self.read_bytes != self.size)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "zip member is corrupt: {} bytes with CRC-32 {:08x}, expected {} bytes with {:08x}",
+                    self.read_bytes, self.crc, self.size, self.expected_crc
+                ),
+            ));
+        }
+        Ok(bytes)
+    }
+}
+
+/// A central directory record.
+struct Entry {
+    name: String,
+    flags: u16,
+    method: u16,
+    crc: u32,
+    compressed_size: u32,
+    size: u32,
+    local_header_offset: u32,
+}
+
+/// Opens `member` of the zip file at `archive` for streaming.
+pub fn open_member(archive: &Path, member: &str) -> Result<ZipMember, SnapshotError> {
+    let mut file = File::open(archive)?;
+    let entries = read_central_directory(&mut file, archive)?;
+    let Some(entry) = entries.iter().find(|entry| entry.name == member) else {
+        let names: Vec<&str> = entries
+            .iter()
+            .filter(|entry| !entry.name.ends_with('/'))
+            .map(|entry| entry.name.as_str())
+            .take(LISTED_MEMBERS)
+            .collect();
+        let more = entries.len().saturating_sub(names.len());
+        return Err(SnapshotError::NotFound {
+            details: format!(
+                "no member {member:?} in {} (members: {}{})",
+                archive.display(),
+                names.join(", "),
+                if more > 0 {
+                    format!(", ... {more} more")
+                } else {
+                    String::new()
+                }
+            ),
+        });
+    };
+    if entry.flags & 1 != 0 {
+        return Err(invalid(archive, format!("member {member:?} is encrypted")));
+    }
+    if entry.compressed_size == u32::MAX || entry.size == u32::MAX {
+        return Err(invalid(
+            archive,
+            format!(
+                "member {member:?} is a zip64 entry (over 4 GiB), which is not supported; unzip it first"
+            ),
+        ));
+    }
+
+    file.seek(SeekFrom::Start(u64::from(entry.local_header_offset)))?;
+    let mut header = [0u8; 30];
+    file.read_exact(&mut header)?;
+    if le_u32(&header, 0) != LOCAL_FILE_HEADER {
+        return Err(invalid(
+            archive,
+            format!("no local header for member {member:?}"),
+        ));
+    }
+    let skip = i64::from(le_u16(&header, 26)) + i64::from(le_u16(&header, 28));
+    file.seek(SeekFrom::Current(skip))?;
+
+    let data = BufReader::new(file).take(u64::from(entry.compressed_size));
+    let reader = match entry.method {
+        METHOD_STORED => MemberReader::Stored(data),
+        METHOD_DEFLATED => MemberReader::Deflated(Box::new(Inflate::new(data))),
+        method => {
+            return Err(invalid(
+                archive,
+                format!(
+                    "member {member:?} uses compression method {method}; only stored and deflate are supported"
+                ),
+            ));
+        }
+    };
+    Ok(ZipMember {
+        reader,
+        size: u64::from(entry.size),
+        expected_crc: entry.crc,
+        crc: 0,
+        read_bytes: 0,
+    })
+}
+
+/// Finds the end of central directory record in the file's tail and reads every entry.
+fn read_central_directory(file: &mut File, archive: &Path) -> Result<Vec<Entry>, SnapshotError> {
+    let len = file.metadata()?.len();
+    let tail_len = len.min(END_RECORD_LEN + MAX_COMMENT_LEN);
+    file.seek(SeekFrom::Start(len - tail_len))?;
+    let mut tail = vec![0u8; tail_len as usize];
+    file.read_exact(&mut tail)?;
+
+    let end = (0..tail.len().saturating_sub(END_RECORD_LEN as usize - 1))
+        .rev()
+        .find(|&pos| le_u32(&tail, pos) == END_OF_CENTRAL_DIRECTORY)
+        .ok_or_else(|| {
+            invalid(
+                archive,
+                "no end of central directory record; the `file#member` syntax only reads .zip archives"
+                    .to_string(),
+            )
+        })?;
+    let record = &tail[end..];
+    let entry_count = le_u16(record, 10);
+    let directory_size = le_u32(record, 12);
+    let directory_offset = le_u32(record, 16);
+    if le_u16(record, 4) != 0 || le_u16(record, 6) != 0 {
+        return Err(invalid(
+            archive,
+            "split archives are not supported".to_string(),
+        ));
+    }
+    if entry_count == u16::MAX || directory_offset == u32::MAX {
+        return Err(invalid(
+            archive,
+            "zip64 archives are not supported; unzip the member first".to_string(),
+        ));
+    }
+
+    file.seek(SeekFrom::Start(u64::from(directory_offset)))?;
+    let mut directory = vec![0u8; directory_size as usize];
+    file.read_exact(&mut directory)?;
+
+    let mut entries = Vec::with_capacity(usize::from(entry_count));
+    let mut pos = 0;
+    for _ in 0..entry_count {
+        if pos + 46 > directory.len() || le_u32(&directory, pos) != CENTRAL_DIRECTORY_ENTRY {
+            return Err(invalid(archive, "central directory is corrupt".to_string()));
+        }
+        let name_len = usize::from(le_u16(&directory, pos + 28));
+        let extra_len = usize::from(le_u16(&directory, pos + 30));
+        let comment_len = usize::from(le_u16(&directory, pos + 32));
+        let name = directory
+            .get(pos + 46..pos + 46 + name_len)
+            .ok_or_else(|| invalid(archive, "central directory is corrupt".to_string()))?;
+        entries.push(Entry {
+            name: String::from_utf8_lossy(name).into_owned(),
+            flags: le_u16(&directory, pos + 8),
+            method: le_u16(&directory, pos + 10),
+            crc: le_u32(&directory, pos + 16),
+            compressed_size: le_u32(&directory, pos + 20),
+            size: le_u32(&directory, pos + 24),
+            local_header_offset: le_u32(&directory, pos + 42),
+        });
+        pos += 46 + name_len + extra_len + comment_len;
+    }
+    Ok(entries)
+}
+
+fn le_u16(bytes: &[u8], pos: usize) -> u16 {
+    u16::from_le_bytes([bytes[pos], bytes[pos + 1]])
+}
+
+fn le_u32(bytes: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]])
+}
+
+fn invalid(archive: &Path, details: String) -> SnapshotError {
+    SnapshotError::InvalidData {
+        details: format!("{}: {details}", archive.display()),
+    }
+}
@@ -0,0 +1,77 @@
+use std::path::Path;
+
+use heapsnap::Analyzer;
+use heapsnap::analysis::dominator::{
+    DominatorOptions, default_ignored_edge_types, dominator_chain,
+};
+use heapsnap::analysis::retainers::{PathRank, RetainersOptions, find_retaining_paths};
+use heapsnap::analysis::summary::{SummaryOptions, summarize};
+use heapsnap::cancel::CancelToken;
+use heapsnap::parser::{ReadOptions, read_snapshot_file};
+
+fn dominator_options() -> DominatorOptions {
+    DominatorOptions {
+        max_depth: 20,
+        cancel: CancelToken::new(),
+        progress: None,
+        ignore_edge_types: default_ignored_edge_types(),
+    }
+}
+
+fn retainers_options() -> RetainersOptions<'static> {
+    RetainersOptions {
+        max_paths: 5,
+        max_depth: 10,
+        distinct_roots: false,
+        rank: PathRank::Shortest,
+        depth_histogram: false,
+        cancel: CancelToken::new(),
+    }
+}
+
+#[test]
+fn analyzer_answers_repeated_queries_like_free_functions() {
+    let path = Path::new("fixtures/large.heapsnapshot");
+    let analyzer =
+        Analyzer::open(path, ReadOptions::new(false, CancelToken::new())).expect("analyzer");
+    let snapshot =
+        read_snapshot_file(path, ReadOptions::new(false, CancelToken::new())).expect("snapshot");
+
+    let summary_options = || SummaryOptions {
+        top: 10,
+        ..Default::default()
+    };
+    let expected = summarize(&snapshot, summary_options()).expect("summary");
+    let actual = analyzer.summary(summary_options()).expect("summary");
+    assert_eq!(actual.total_rows, expected.total_rows);
+    assert_eq!(actual.rows.len(), expected.rows.len());
+
+    let target = analyzer.snapshot().node_count() - 1;
+    let node_id = analyzer
+        .snapshot()
+        .node_view(target)
+        .and_then(|node| node.id())
+        .expect("id") as u64;
+    assert_eq!(analyzer.node_index_by_id(node_id), Some(target));
+
+    let expected = find_retaining_paths(&snapshot, target, retainers_options()).expect("paths");
+    for _ in 0..2 {
+        let actual = analyzer
+            .retainers(target, retainers_options())
+            .expect("paths");
+        assert_eq!(actual.paths.len(), expected.paths.len());
+    }
+
+    let expected = dominator_chain(&snapshot, target, dominator_options()).expect("dominator");
+    for _ in 0..2 {
+        let actual = analyzer
+            .dominator(target, dominator_options())
+            .expect("dominator");
+        assert_eq!(actual.chain, expected.chain);
+        assert_eq!(actual.retained_sizes, expected.retained_sizes);
+    }
+    let retained = analyzer
+        .retained_sizes(&default_ignored_edge_types(), CancelToken::new())
+        .expect("retained");
+    assert_eq!(retained.len(), snapshot.node_count());
+}
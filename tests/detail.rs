@@ -1,8 +1,12 @@
 use std::path::Path;
 
-use heapsnap::analysis::detail::{DetailOptions, DetailResult, detail};
+use heapsnap::analysis::detail::{DetailOptions, DetailResult, detail, detail_with_names};
+use heapsnap::analysis::name_index::NameIndex;
 use heapsnap::cancel::CancelToken;
+use heapsnap::output::charset::Charset;
+use heapsnap::output::color::Color;
 use heapsnap::output::detail as detail_output;
+use heapsnap::output::json::JsonStyle;
 use heapsnap::parser::{ReadOptions, read_snapshot_file};
 
 #[test]
@@ -16,21 +20,29 @@ fn detail_name_json_fixture_small() {
         DetailOptions {
             id: None,
             name: Some("Node1".to_string()),
+            name_index: None,
             skip: 0,
             limit: 10,
             top_retainers: 5,
             top_edges: 5,
+            edge_name_filter: None,
+            raw: false,
+            max_edges_per_node: None,
         },
     )
     .expect("detail");
 
-    let json = detail_output::format_json(&result).expect("json");
+    let json = detail_output::format_json(&result, JsonStyle::Pretty).expect("json");
     let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
 
     assert_eq!(value["version"], 1);
     assert_eq!(value["mode"], "name");
     assert_eq!(value["name"], "Node1");
     assert!(value["constructor_summary"]["total_count"].is_number());
+    assert_eq!(
+        value["snapshot_fingerprint"],
+        heapsnap::output::header::fingerprint_hex(snapshot.quick_fingerprint())
+    );
 
     let html = detail_output::format_html(&result, path);
     assert!(html.contains("static report"));
@@ -47,15 +59,19 @@ fn detail_id_json_fixture_small() {
         DetailOptions {
             id: Some(2),
             name: None,
+            name_index: None,
             skip: 0,
             limit: 10,
             top_retainers: 5,
             top_edges: 5,
+            edge_name_filter: None,
+            raw: false,
+            max_edges_per_node: None,
         },
     )
     .expect("detail");
 
-    let json = detail_output::format_json(&result).expect("json");
+    let json = detail_output::format_json(&result, JsonStyle::Pretty).expect("json");
     let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
 
     assert_eq!(value["version"], 1);
@@ -67,3 +83,739 @@ fn detail_id_json_fixture_small() {
     assert!(value["shallow_size_distribution"].is_array());
     assert!(matches!(result, DetailResult::ById(_)));
 }
+
+#[test]
+fn detail_name_groups_duplicate_string_entries() {
+    // "Foo" is stored twice in the string table; both indices are the same constructor.
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0, 0, 1, 10, 0, 0, 1, 2, 20, 0, 0, 2, 3, 5, 0],
+      "edges": [],
+      "strings": ["Foo", "Bar", "Foo"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = heapsnap::parser::read_snapshot(&mut reader).expect("snapshot");
+    assert_eq!(snapshot.string_indices_of("Foo"), vec![0, 2]);
+
+    let result = detail(
+        &snapshot,
+        DetailOptions {
+            id: None,
+            name: Some("Foo".to_string()),
+            name_index: None,
+            skip: 0,
+            limit: 10,
+            top_retainers: 5,
+            top_edges: 5,
+            edge_name_filter: None,
+            raw: false,
+            max_edges_per_node: None,
+        },
+    )
+    .expect("detail");
+    match result {
+        DetailResult::ByName(data) => {
+            assert_eq!(data.total_count, 2);
+            assert_eq!(data.self_size_sum, 15);
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    let target = heapsnap::analysis::retainers::find_target_by_name(
+        &snapshot,
+        "Fo",
+        heapsnap::analysis::retainers::PickStrategy::Largest,
+    )
+    .expect("target");
+    assert_eq!(target, 0);
+}
+
+#[test]
+fn detail_name_index_selects_one_string_table_entry() {
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0, 0, 1, 10, 0, 0, 1, 2, 20, 0, 0, 2, 3, 5, 0],
+      "edges": [],
+      "strings": ["Foo", "Bar", "Foo"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = heapsnap::parser::read_snapshot(&mut reader).expect("snapshot");
+    let options = |name_index| DetailOptions {
+        id: None,
+        name: None,
+        name_index: Some(name_index),
+        skip: 0,
+        limit: 10,
+        top_retainers: 5,
+        top_edges: 5,
+        edge_name_filter: None,
+        raw: false,
+        max_edges_per_node: None,
+    };
+
+    let result = detail(&snapshot, options(2)).expect("detail");
+    match &result {
+        DetailResult::ByName(data) => {
+            assert_eq!(data.name, "Foo");
+            assert_eq!(data.name_index, Some(2));
+            assert_eq!(data.total_count, 1);
+            assert_eq!(data.self_size_sum, 5);
+            assert_eq!(data.ids[0].id, Some(3));
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+    let json = detail_output::format_json(&result, JsonStyle::Compact).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["name_index"], 2);
+    assert!(detail_output::format_markdown(&result).contains("- String index: 2"));
+
+    let summary = heapsnap::analysis::summary::summarize(
+        &snapshot,
+        heapsnap::analysis::summary::SummaryOptions {
+            top: 10,
+            ..Default::default()
+        },
+    )
+    .expect("summary");
+    let mut foo_indices: Vec<_> = summary
+        .rows
+        .iter()
+        .filter(|row| row.name == "Foo")
+        .map(|row| row.name_index)
+        .collect();
+    foo_indices.sort();
+    assert_eq!(foo_indices, vec![Some(0), Some(2)]);
+
+    let err = detail(&snapshot, options(3)).expect_err("out of range");
+    assert!(err.to_string().contains("out of range"), "{err}");
+    let mut both = options(0);
+    both.name = Some("Foo".to_string());
+    assert!(detail(&snapshot, both).is_err());
+}
+
+#[test]
+fn detail_name_breaks_down_node_types() {
+    // "Foo" is used by an object node and a hidden node.
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object","hidden"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0, 0, 1, 10, 0, 1, 0, 2, 40, 0, 0, 0, 3, 5, 0],
+      "edges": [],
+      "strings": ["Foo"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = heapsnap::parser::read_snapshot(&mut reader).expect("snapshot");
+
+    let result = detail(
+        &snapshot,
+        DetailOptions {
+            id: None,
+            name: Some("Foo".to_string()),
+            name_index: None,
+            skip: 0,
+            limit: 10,
+            top_retainers: 5,
+            top_edges: 5,
+            edge_name_filter: None,
+            raw: false,
+            max_edges_per_node: None,
+        },
+    )
+    .expect("detail");
+    match &result {
+        DetailResult::ByName(data) => assert_eq!(
+            data.node_type_breakdown,
+            vec![("hidden".to_string(), 1, 40), ("object".to_string(), 2, 15)]
+        ),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    let json = detail_output::format_json(&result, JsonStyle::Pretty).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["node_type_breakdown"][0]["node_type"], "hidden");
+    assert_eq!(value["node_type_breakdown"][1]["count"], 2);
+    assert!(detail_output::format_markdown(&result).contains("| hidden | 1 | 40 |"));
+}
+
+#[test]
+fn detail_edge_name_filter_applies_before_top_truncation() {
+    // Node 0 (id=1) points at nodes 1..3; node 3 also points back to node 0 via "owner".
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0, 0, 1, 10, 3, 0, 0, 2, 50, 0, 0, 0, 3, 500, 0, 0, 0, 4, 20, 1],
+      "edges": [0, 1, 5, 0, 2, 10, 0, 3, 15, 0, 4, 0],
+      "strings": ["Foo", "_parent", "child", "_parent_alt", "owner"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = heapsnap::parser::read_snapshot(&mut reader).expect("snapshot");
+
+    let run = |filter: Option<&str>| {
+        let result = detail(
+            &snapshot,
+            DetailOptions {
+                id: Some(1),
+                name: None,
+                name_index: None,
+                skip: 0,
+                limit: 10,
+                top_retainers: 5,
+                top_edges: 1,
+                edge_name_filter: filter.map(str::to_string),
+                raw: false,
+                max_edges_per_node: None,
+            },
+        )
+        .expect("detail");
+        match result {
+            DetailResult::ById(data) => data,
+            other => panic!("unexpected result: {other:?}"),
+        }
+    };
+
+    let unfiltered = run(None);
+    assert_eq!(
+        unfiltered.outgoing_edges[0].edge_name.as_deref(),
+        Some("child")
+    );
+    assert_eq!(unfiltered.retainers.len(), 1);
+
+    let filtered = run(Some("_parent"));
+    assert_eq!(filtered.outgoing_edges.len(), 1);
+    assert_eq!(
+        filtered.outgoing_edges[0].edge_name.as_deref(),
+        Some("_parent")
+    );
+    assert!(filtered.retainers.is_empty());
+}
+
+#[test]
+fn detail_id_counts_all_retainers_beyond_top() {
+    // Nodes 0..2 each hold a "ref" edge to node 3 (id=4).
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0, 0, 1, 10, 1, 0, 0, 2, 30, 1, 0, 0, 3, 20, 1, 0, 1, 4, 5, 0],
+      "edges": [0, 2, 15, 0, 2, 15, 0, 2, 15],
+      "strings": ["Holder", "Pinned", "ref"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = heapsnap::parser::read_snapshot(&mut reader).expect("snapshot");
+
+    let result = detail(
+        &snapshot,
+        DetailOptions {
+            id: Some(4),
+            name: None,
+            name_index: None,
+            skip: 0,
+            limit: 10,
+            top_retainers: 2,
+            top_edges: 5,
+            edge_name_filter: None,
+            raw: false,
+            max_edges_per_node: None,
+        },
+    )
+    .expect("detail");
+    let DetailResult::ById(data) = &result else {
+        panic!("unexpected result: {result:?}");
+    };
+    assert_eq!(data.retainers.len(), 2);
+    assert_eq!(data.total_retainers, 3);
+    assert!(detail_output::format_markdown(&result).contains("Showing top 2 of 3 retainers"));
+
+    let json = detail_output::format_json(&result, JsonStyle::Pretty).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["total_retainers"], 3);
+}
+
+#[test]
+fn detail_id_keeps_largest_retainers_in_node_then_edge_order() {
+    // Holders of sizes 10, 30 (two "ref" edges), 30 and 20 all point at node 4 (id=5).
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0,0,1,10,1, 0,0,2,30,2, 0,0,3,30,1, 0,0,4,20,1, 0,1,5,5,0],
+      "edges": [0,2,20, 0,2,20, 0,2,20, 0,2,20, 0,2,20],
+      "strings": ["Holder", "Pinned", "ref"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = heapsnap::parser::read_snapshot(&mut reader).expect("snapshot");
+    let retainers = |top_retainers| {
+        let result = detail(
+            &snapshot,
+            DetailOptions {
+                id: Some(5),
+                name: None,
+                name_index: None,
+                skip: 0,
+                limit: 10,
+                top_retainers,
+                top_edges: 5,
+                edge_name_filter: None,
+                raw: false,
+                max_edges_per_node: None,
+            },
+        )
+        .expect("detail");
+        let DetailResult::ById(data) = result else {
+            panic!("unexpected result: {result:?}");
+        };
+        let rows: Vec<(usize, usize, i64)> = data
+            .retainers
+            .iter()
+            .map(|row| (row.from_index, row.edge_index, row.from_self_size))
+            .collect();
+        (rows, data.total_retainers)
+    };
+
+    assert_eq!(retainers(3), (vec![(1, 1, 30), (1, 2, 30), (2, 3, 30)], 5));
+    assert_eq!(retainers(0), (vec![], 5));
+    let (all, total) = retainers(10);
+    assert_eq!(total, 5);
+    assert_eq!(
+        all.iter().map(|row| row.0).collect::<Vec<_>>(),
+        vec![1, 1, 2, 3, 0]
+    );
+}
+
+#[test]
+fn detail_id_shows_allocation_location_when_recorded() {
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"],
+          "location_fields": ["object_index","script_id","line","column"]
+        }
+      },
+      "nodes": [0, 0, 1, 10, 0, 0, 0, 3, 20, 0],
+      "edges": [],
+      "locations": [5, 42, 9, 4],
+      "strings": ["Foo"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = heapsnap::parser::read_snapshot(&mut reader).expect("snapshot");
+
+    let options = |id| DetailOptions {
+        id: Some(id),
+        name: None,
+        name_index: None,
+        skip: 0,
+        limit: 10,
+        top_retainers: 5,
+        top_edges: 5,
+        edge_name_filter: None,
+        raw: false,
+        max_edges_per_node: None,
+    };
+    let result = detail(&snapshot, options(3)).expect("detail");
+    let json = detail_output::format_json(&result, JsonStyle::Pretty).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["location"]["script_id"], 42);
+    assert_eq!(value["location"]["line"], 9);
+    assert_eq!(value["location"]["column"], 4);
+    assert!(
+        detail_output::format_markdown(&result)
+            .contains("- Allocated at: script 42, line 10, column 5")
+    );
+
+    let result = detail(&snapshot, options(1)).expect("detail");
+    let json = detail_output::format_json(&result, JsonStyle::Pretty).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert!(value.get("location").is_none());
+    assert!(!detail_output::format_markdown(&result).contains("Allocated at"));
+}
+
+#[test]
+fn detail_raw_dumps_fields_the_tool_does_not_interpret() {
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count","trace_node_id","detachedness"],
+          "node_types": [["object"],"string","number","number","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0, 0, 1, 10, 1, 0, 0, 0, 1, 3, 20, 0, 7, 2],
+      "edges": [0, 1, 7],
+      "strings": ["Root", "Foo"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = heapsnap::parser::read_snapshot(&mut reader).expect("snapshot");
+
+    let options = |raw| DetailOptions {
+        id: Some(1),
+        name: None,
+        name_index: None,
+        skip: 0,
+        limit: 10,
+        top_retainers: 5,
+        top_edges: 5,
+        edge_name_filter: None,
+        raw,
+        max_edges_per_node: None,
+    };
+    let result = detail(&snapshot, options(true)).expect("detail");
+    let json = detail_output::format_json(&result, JsonStyle::Pretty).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["raw"]["node"][6]["field"], "detachedness");
+    assert_eq!(value["raw"]["node"][6]["value"], 0);
+    assert_eq!(value["raw"]["edges"][0]["edge_index"], 0);
+    assert_eq!(value["raw"]["edges"][0]["fields"][2]["field"], "to_node");
+    assert_eq!(value["raw"]["edges"][0]["fields"][2]["value"], 7);
+
+    let markdown = detail_output::format_markdown(&result);
+    assert!(markdown.contains("| trace_node_id | 0 |"));
+    assert!(markdown.contains("| Edge | type | name_or_index | to_node |"));
+
+    let result = detail(&snapshot, options(false)).expect("detail");
+    let json = detail_output::format_json(&result, JsonStyle::Pretty).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert!(value.get("raw").is_none());
+}
+
+#[test]
+fn detail_with_name_index_matches_scan() {
+    let path = Path::new("fixtures/medium.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+    let names = NameIndex::new(&snapshot);
+    let options = |id: Option<u64>, name: Option<&str>| DetailOptions {
+        id,
+        name: name.map(str::to_string),
+        name_index: None,
+        skip: 0,
+        limit: 10,
+        top_retainers: 5,
+        top_edges: 5,
+        edge_name_filter: None,
+        raw: false,
+        max_edges_per_node: None,
+    };
+
+    for (id, name) in [(None, Some("Node7")), (Some(3), None)] {
+        let scanned = detail(&snapshot, options(id, name)).expect("scan");
+        let indexed = detail_with_names(&snapshot, &names, options(id, name)).expect("index");
+        assert_eq!(
+            detail_output::format_json(&indexed, JsonStyle::Compact).expect("json"),
+            detail_output::format_json(&scanned, JsonStyle::Compact).expect("json")
+        );
+    }
+    assert!(detail_with_names(&snapshot, &names, options(None, Some("Missing"))).is_err());
+    for err in [
+        detail(&snapshot, options(None, Some("node7"))).expect_err("scan"),
+        detail_with_names(&snapshot, &names, options(None, Some("node7"))).expect_err("index"),
+    ] {
+        assert!(err.to_string().contains("did you mean 'Node7'"), "{err}");
+    }
+}
+
+#[test]
+fn detail_json_reports_next_page() {
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0, 0, 1, 10, 0, 0, 0, 2, 20, 0, 0, 0, 3, 30, 0],
+      "edges": [],
+      "strings": ["Foo"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = heapsnap::parser::read_snapshot(&mut reader).expect("snapshot");
+    let page = |skip| {
+        let result = detail(
+            &snapshot,
+            DetailOptions {
+                id: None,
+                name: Some("Foo".to_string()),
+                name_index: None,
+                skip,
+                limit: 2,
+                top_retainers: 5,
+                top_edges: 5,
+                edge_name_filter: None,
+                raw: false,
+                max_edges_per_node: None,
+            },
+        )
+        .expect("detail");
+        let json = detail_output::format_json(&result, JsonStyle::Compact).expect("json");
+        let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+        value["constructor_summary"].clone()
+    };
+
+    let first = page(0);
+    assert_eq!(first["has_more"], true);
+    assert_eq!(first["next_skip"], 2);
+    let last = page(2);
+    assert_eq!(last["has_more"], false);
+    assert!(last["next_skip"].is_null());
+}
+
+#[test]
+fn detail_name_reports_median_and_p95() {
+    // 19 one-byte "Foo" nodes and one 1000-byte giant that dominates the average.
+    let nodes: Vec<String> = (0..20)
+        .map(|i| format!("0,0,{},{},0", i + 1, if i == 7 { 1000 } else { 1 }))
+        .collect();
+    let json = format!(
+        r#"{{
+          "snapshot": {{
+            "meta": {{
+              "node_fields": ["type","name","id","self_size","edge_count"],
+              "node_types": [["object"],"string","number","number","number"],
+              "edge_fields": ["type","name_or_index","to_node"],
+              "edge_types": [["property"],"string_or_number","node"]
+            }}
+          }},
+          "nodes": [{}],
+          "edges": [],
+          "strings": ["Foo"]
+        }}"#,
+        nodes.join(",")
+    );
+    let mut reader = json.as_bytes();
+    let snapshot = heapsnap::parser::read_snapshot(&mut reader).expect("snapshot");
+
+    let result = detail(
+        &snapshot,
+        DetailOptions {
+            id: None,
+            name: Some("Foo".to_string()),
+            name_index: None,
+            skip: 0,
+            limit: 10,
+            top_retainers: 5,
+            top_edges: 5,
+            edge_name_filter: None,
+            raw: false,
+            max_edges_per_node: None,
+        },
+    )
+    .expect("detail");
+    match &result {
+        DetailResult::ByName(data) => {
+            assert_eq!(data.median_self_size, 1);
+            assert_eq!(data.p95_self_size, 1);
+            assert_eq!(data.max_self_size, 1000);
+            assert!(data.avg_self_size > 50.0);
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    let json = detail_output::format_json(&result, JsonStyle::Compact).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["constructor_summary"]["median_self_size_bytes"], 1);
+    assert_eq!(value["constructor_summary"]["p95_self_size_bytes"], 1);
+    assert!(detail_output::format_markdown(&result).contains("- P95 self size: 1"));
+    assert!(detail_output::format_csv(&result, ',').contains("summary,median_self_size_bytes,1"));
+}
+
+#[test]
+fn detail_many_ids_combine_single_id_reports() {
+    let path = Path::new("fixtures/small.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+    let by_id = |id: u64| {
+        detail(
+            &snapshot,
+            DetailOptions {
+                id: Some(id),
+                name: None,
+                name_index: None,
+                skip: 0,
+                limit: 10,
+                top_retainers: 5,
+                top_edges: 5,
+                edge_name_filter: None,
+                raw: false,
+                max_edges_per_node: None,
+            },
+        )
+        .expect("detail")
+    };
+    let results = vec![by_id(2), by_id(3)];
+
+    let json = detail_output::format_json_many(&results, JsonStyle::Compact).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    let single: serde_json::Value = serde_json::from_str(
+        &detail_output::format_json(&results[1], JsonStyle::Compact).expect("json"),
+    )
+    .expect("parse json");
+    assert_eq!(value.as_array().map(Vec::len), Some(2));
+    assert_eq!(value[0]["id"], 2);
+    assert_eq!(value[1], single);
+
+    let markdown = detail_output::format_markdown_many(&results, Color::OFF, Charset::Unicode);
+    let first = detail_output::format_markdown(&results[0]);
+    assert!(markdown.starts_with(&first));
+    assert!(markdown.contains("\n---\n\n# Detail: Node2 (id=3)"));
+
+    let csv = detail_output::format_csv_many(&results, ',');
+    assert_eq!(
+        csv.lines()
+            .filter(|line| line.starts_with("section,"))
+            .count(),
+        1
+    );
+    assert!(csv.lines().any(|line| line == "id,,2"));
+    assert!(csv.lines().any(|line| line == "id,,3"));
+}
+
+#[test]
+fn detail_id_reports_reachability() {
+    let path = Path::new("fixtures/small.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+    let result = detail(
+        &snapshot,
+        DetailOptions {
+            id: Some(3),
+            name: None,
+            name_index: None,
+            skip: 0,
+            limit: 10,
+            top_retainers: 5,
+            top_edges: 5,
+            edge_name_filter: None,
+            raw: false,
+            max_edges_per_node: None,
+        },
+    )
+    .expect("detail");
+
+    assert!(detail_output::format_markdown(&result).contains("- Reachability: strong"));
+    let json = detail_output::format_json(&result, JsonStyle::Compact).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["reachability"], "strong");
+    assert!(
+        detail_output::format_csv(&result, ',')
+            .lines()
+            .any(|line| line == "reachability,,strong")
+    );
+}
+
+#[test]
+fn detail_max_edges_per_node_reports_truncated_edge_scan() {
+    // Root has three outgoing edges; only the first two are read.
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0,0,1,0,3, 0,1,3,10,0, 0,1,5,20,0, 0,1,7,30,0],
+      "edges": [0,2,5, 0,2,10, 0,2,15],
+      "strings": ["Root","Foo","p"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = heapsnap::parser::read_snapshot(&mut reader).expect("snapshot");
+    let options = |max_edges_per_node| DetailOptions {
+        id: Some(1),
+        name: None,
+        name_index: None,
+        skip: 0,
+        limit: 10,
+        top_retainers: 5,
+        top_edges: 5,
+        edge_name_filter: None,
+        raw: false,
+        max_edges_per_node,
+    };
+
+    let result = detail(&snapshot, options(Some(2))).expect("detail");
+    let DetailResult::ById(by_id) = &result else {
+        panic!("expected id result");
+    };
+    let sizes: Vec<i64> = by_id
+        .outgoing_edges
+        .iter()
+        .map(|edge| edge.to_self_size)
+        .collect();
+    assert_eq!(sizes, vec![20, 10]);
+    let markdown = detail_output::format_markdown(&result);
+    assert!(
+        markdown.contains("**Edges truncated: examined the first 2 of 3 outgoing edges"),
+        "{markdown}"
+    );
+    let json = detail_output::format_json(&result, JsonStyle::Compact).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["edges_truncated"]["examined"], 2);
+    assert_eq!(value["edges_truncated"]["total"], 3);
+
+    let result = detail(&snapshot, options(Some(3))).expect("detail");
+    let json = detail_output::format_json(&result, JsonStyle::Compact).expect("json");
+    assert!(!json.contains("edges_truncated"));
+    assert!(!detail_output::format_markdown(&result).contains("Edges truncated"));
+}
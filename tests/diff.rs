@@ -1,9 +1,18 @@
 use std::path::Path;
 
-use heapsnap::analysis::diff::{DiffOptions, diff_summaries};
+use heapsnap::analysis::diff::{
+    DiffOptions, DiffResult, DiffRow, DiffSide, DiffSort, Trend, diff_series_results,
+    diff_summaries, diff_summary_results, summarize_all,
+};
+use heapsnap::analysis::summary::{SummaryOptions, summarize};
 use heapsnap::cancel::CancelToken;
+use heapsnap::output::charset::Charset;
+use heapsnap::output::color::Color;
 use heapsnap::output::diff as diff_output;
+use heapsnap::output::json::JsonStyle;
+use heapsnap::output::summary as summary_output;
 use heapsnap::parser::{ReadOptions, read_snapshot_file};
+use heapsnap::snapshot::SnapshotRaw;
 
 #[test]
 fn diff_json_units_fixture_small() {
@@ -20,11 +29,15 @@ fn diff_json_units_fixture_small() {
         DiffOptions {
             top: 10,
             contains: None,
+            min_self_size: None,
+            min_count: None,
+            sort: DiffSort::SizeDelta,
+            only_in: None,
         },
     )
     .expect("diff");
 
-    let json = diff_output::format_json(&result).expect("json");
+    let json = diff_output::format_json(&result, JsonStyle::Pretty).expect("json");
     let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
 
     assert_eq!(value["version"], 1);
@@ -51,11 +64,15 @@ fn diff_csv_units_header() {
         DiffOptions {
             top: 10,
             contains: None,
+            min_self_size: None,
+            min_count: None,
+            sort: DiffSort::SizeDelta,
+            only_in: None,
         },
     )
     .expect("diff");
 
-    let csv = diff_output::format_csv(&result);
+    let csv = diff_output::format_csv(&result, ',');
     let header = csv.lines().next().expect("csv header");
     assert_eq!(
         header,
@@ -78,6 +95,10 @@ fn diff_html_includes_table() {
         DiffOptions {
             top: 10,
             contains: None,
+            min_self_size: None,
+            min_count: None,
+            sort: DiffSort::SizeDelta,
+            only_in: None,
         },
     )
     .expect("diff");
@@ -85,3 +106,350 @@ fn diff_html_includes_table() {
     let html = diff_output::format_html(&result);
     assert!(html.contains("<table>"));
 }
+
+#[test]
+fn diff_unified_marks_rows_by_delta_sign() {
+    let row = |name: &str, count_a: u64, count_b: u64, size_a: i64, size_b: i64| DiffRow {
+        name: name.to_string(),
+        count_a,
+        count_b,
+        count_delta: count_b as i64 - count_a as i64,
+        self_size_sum_a: size_a,
+        self_size_sum_b: size_b,
+        self_size_sum_delta: size_b - size_a,
+    };
+    let result = DiffResult {
+        total_nodes_a: 20,
+        total_nodes_b: 30,
+        snapshot_fingerprint_a: 0,
+        snapshot_fingerprint_b: 0,
+        rows: vec![
+            row("Foo", 10, 15, 1229, 1843),
+            row("Added", 0, 4, 0, 64),
+            row("Removed", 3, 0, 48, 0),
+            row("Shrunk", 8, 6, 128, 96),
+            row("MoreOfSameSize", 2, 3, 32, 32),
+            row("Same", 2, 2, 32, 32),
+        ],
+    };
+
+    let unified = diff_output::format_unified(&result, Charset::Unicode);
+    let lines: Vec<&str> = unified.lines().collect();
+    assert_eq!(lines[0], "--- A (nodes=20)");
+    assert_eq!(lines[1], "+++ B (nodes=30)");
+    assert!(lines.contains(&"+ Foo  count 10→15  size 1.2KiB→1.8KiB"));
+    assert!(lines.contains(&"+ Added  count 0→4  size 0B→64B"));
+    assert!(lines.contains(&"- Removed  count 3→0  size 48B→0B"));
+    assert!(lines.contains(&"- Shrunk  count 8→6  size 128B→96B"));
+    // Equal sizes fall back to the count delta; a row without any change keeps `~`.
+    assert!(lines.contains(&"+ MoreOfSameSize  count 2→3  size 32B→32B"));
+    assert!(lines.contains(&"~ Same  count 2→2  size 32B→32B"));
+    assert_eq!(lines.len(), 2 + result.rows.len());
+
+    let ascii = diff_output::format_unified(&result, Charset::Ascii);
+    assert!(ascii.contains("+ Foo  count 10->15  size 1.2KiB->1.8KiB"));
+    assert!(ascii.is_ascii());
+}
+
+#[test]
+fn diff_thresholds_compare_absolute_deltas() {
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot_a =
+        read_snapshot_file(Path::new("fixtures/medium.heapsnapshot"), options).expect("a");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot_b =
+        read_snapshot_file(Path::new("fixtures/small.heapsnapshot"), options).expect("b");
+
+    // Going from medium to small removes Node3..Node19, so every delta is negative.
+    let result = diff_summaries(
+        &snapshot_a,
+        &snapshot_b,
+        DiffOptions {
+            top: 100,
+            contains: None,
+            min_self_size: Some(50),
+            min_count: None,
+            sort: DiffSort::SizeDelta,
+            only_in: None,
+        },
+    )
+    .expect("diff");
+    let names: Vec<&str> = result.rows.iter().map(|row| row.name.as_str()).collect();
+    assert_eq!(names, vec!["Node19", "Node18", "Node17"]);
+
+    let result = diff_summaries(
+        &snapshot_a,
+        &snapshot_b,
+        DiffOptions {
+            top: 100,
+            contains: None,
+            min_self_size: None,
+            min_count: Some(1),
+            sort: DiffSort::SizeDelta,
+            only_in: None,
+        },
+    )
+    .expect("diff");
+    assert_eq!(result.rows.len(), 17);
+    assert!(result.rows.iter().all(|row| row.count_delta == -1));
+}
+
+#[test]
+fn diff_series_columns_and_trend() {
+    let read = |path: &str| {
+        read_snapshot_file(Path::new(path), ReadOptions::new(false, CancelToken::new()))
+            .expect("snapshot")
+    };
+    let small = read("fixtures/small.heapsnapshot");
+    let medium = read("fixtures/medium.heapsnapshot");
+    let options = || DiffOptions {
+        top: 50,
+        contains: None,
+        min_self_size: None,
+        min_count: None,
+        sort: DiffSort::SizeDelta,
+        only_in: None,
+    };
+    let diff_series = |snapshots: &[&SnapshotRaw], options| {
+        let summaries: Vec<_> = snapshots
+            .iter()
+            .map(|snapshot| summarize_all(snapshot).expect("summary"))
+            .collect();
+        diff_series_results(&summaries, options)
+    };
+
+    let result = diff_series(&[&small, &medium, &medium], options()).expect("series");
+    assert_eq!(result.total_nodes, vec![3, 20, 20]);
+    let row = |name: &str| {
+        result
+            .rows
+            .iter()
+            .find(|row| row.name == name)
+            .expect("row")
+            .clone()
+    };
+    // Node5 appears at T1 and then stays put.
+    let node5 = row("Node5");
+    assert_eq!(node5.self_size_sums, vec![0, 15, 15]);
+    assert_eq!(node5.self_size_sum_delta, 15);
+    assert_eq!(node5.trend, Trend::Noisy);
+    assert_eq!(row("Node1").trend, Trend::Flat);
+
+    let markdown = diff_output::format_series_markdown(&result, Charset::Unicode);
+    assert!(markdown.contains("| Count T2 | Self Size T2 (bytes) |"));
+    assert!(markdown.contains("| Node5 | ∿ | 0 | 0 | 1 | 15 | 1 | 15 | 1 | 15 |"));
+    let ascii = diff_output::format_series_markdown(&result, Charset::Ascii);
+    assert!(ascii.contains("| Node5 | noisy | 0 | 0 | 1 | 15 | 1 | 15 | 1 | 15 |"));
+    assert!(ascii.contains("| delta Count | delta Self Size (bytes) |"));
+    assert!(ascii.is_ascii());
+
+    let grown = diff_series(&[&small, &medium], options()).expect("series");
+    let node5 = grown
+        .rows
+        .iter()
+        .find(|row| row.name == "Node5")
+        .expect("row");
+    assert_eq!(node5.trend, Trend::Growing);
+
+    let json = diff_output::format_series_json(&grown, JsonStyle::Pretty).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(
+        value["snapshot_fingerprints"].as_array().map(Vec::len),
+        Some(2)
+    );
+    assert!(
+        value["rows"]
+            .as_array()
+            .expect("rows")
+            .iter()
+            .any(|row| row["name"] == "Node5" && row["trend"] == "growing")
+    );
+
+    assert!(diff_series(&[&small], options()).is_err());
+}
+
+#[test]
+fn diff_markdown_colours_deltas_only_when_enabled() {
+    let result = DiffResult {
+        total_nodes_a: 2,
+        total_nodes_b: 2,
+        snapshot_fingerprint_a: 0,
+        snapshot_fingerprint_b: 0,
+        rows: vec![
+            DiffRow {
+                name: "Grew".to_string(),
+                count_a: 1,
+                count_b: 3,
+                count_delta: 2,
+                self_size_sum_a: 16,
+                self_size_sum_b: 48,
+                self_size_sum_delta: 32,
+            },
+            DiffRow {
+                name: "Shrank".to_string(),
+                count_a: 3,
+                count_b: 1,
+                count_delta: -2,
+                self_size_sum_a: 48,
+                self_size_sum_b: 16,
+                self_size_sum_delta: -32,
+            },
+        ],
+    };
+
+    let colored = diff_output::format_markdown_colored(&result, Color::ON, Charset::Unicode);
+    assert!(colored.contains("\x1b[31m32\x1b[0m"));
+    assert!(colored.contains("\x1b[32m-32\x1b[0m"));
+    let plain = diff_output::format_markdown(&result);
+    assert!(!plain.contains('\x1b'));
+    assert_eq!(
+        plain,
+        diff_output::format_markdown_colored(&result, Color::OFF, Charset::Unicode)
+    );
+    let ascii = diff_output::format_markdown_colored(&result, Color::OFF, Charset::Ascii);
+    assert!(ascii.contains("| delta Count |"));
+    assert!(ascii.is_ascii());
+}
+
+#[test]
+fn diff_of_saved_summaries_matches_diff_of_snapshots() {
+    let read = |path: &str| {
+        read_snapshot_file(Path::new(path), ReadOptions::new(false, CancelToken::new()))
+            .expect("snapshot")
+    };
+    let snapshot_a = read("fixtures/small.heapsnapshot");
+    let snapshot_b = read("fixtures/medium.heapsnapshot");
+    let options = || DiffOptions {
+        top: 10,
+        contains: None,
+        min_self_size: None,
+        min_count: None,
+        sort: DiffSort::SizeDelta,
+        only_in: None,
+    };
+    let saved = |snapshot| {
+        let summary = summarize(
+            snapshot,
+            SummaryOptions {
+                top: usize::MAX,
+                ..Default::default()
+            },
+        )
+        .expect("summary");
+        let json = summary_output::format_json(&summary, JsonStyle::Compact).expect("json");
+        summary_output::parse_json(&json).expect("parse summary")
+    };
+
+    let summary_a = saved(&snapshot_a);
+    assert_eq!(
+        summary_a.snapshot_fingerprint,
+        snapshot_a.quick_fingerprint()
+    );
+    let from_snapshots = diff_summaries(&snapshot_a, &snapshot_b, options()).expect("diff");
+    let from_summaries = diff_summary_results(&summary_a, &saved(&snapshot_b), options());
+    let from_snapshots = diff_output::format_json(&from_snapshots, JsonStyle::Compact).unwrap();
+    assert_eq!(
+        diff_output::format_json(&from_summaries, JsonStyle::Compact).unwrap(),
+        from_snapshots
+    );
+
+    // A saved diff reads back to the same output.
+    let reparsed = diff_output::parse_json(&from_snapshots).expect("parse diff");
+    assert_eq!(
+        diff_output::format_json(&reparsed, JsonStyle::Compact).unwrap(),
+        from_snapshots
+    );
+
+    let err = summary_output::parse_json(&from_snapshots).expect_err("diff is not a summary");
+    assert!(
+        err.to_string().contains("not a heapsnap summary json"),
+        "{err}"
+    );
+    let err =
+        summary_output::parse_json(r#"{"version":2,"total_nodes":0,"total_rows":0,"rows":[]}"#)
+            .expect_err("future version");
+    assert!(err.to_string().contains("version 2"), "{err}");
+}
+
+#[test]
+fn diff_series_of_saved_summaries_matches_series_of_snapshots() {
+    let snapshots: Vec<_> = ["small", "medium", "large"]
+        .iter()
+        .map(|name| {
+            let path = format!("fixtures/{name}.heapsnapshot");
+            read_snapshot_file(
+                Path::new(&path),
+                ReadOptions::new(false, CancelToken::new()),
+            )
+            .expect("snapshot")
+        })
+        .collect();
+    let summaries: Vec<_> = snapshots
+        .iter()
+        .map(|snapshot| summarize_all(snapshot).expect("summary"))
+        .collect();
+    // The same summaries written with `summary --format json` and read back.
+    let saved: Vec<_> = summaries
+        .iter()
+        .map(|summary| {
+            let json = summary_output::format_json(summary, JsonStyle::Compact).expect("json");
+            summary_output::parse_json(&json).expect("read back")
+        })
+        .collect();
+    let options = || DiffOptions {
+        top: 5,
+        contains: Some("Node1".to_string()),
+        min_self_size: None,
+        min_count: None,
+        sort: DiffSort::SizeB,
+        only_in: None,
+    };
+
+    let from_snapshots = diff_series_results(&summaries, options()).expect("series");
+    let from_summaries = diff_series_results(&saved, options()).expect("series");
+    assert_eq!(
+        diff_output::format_series_json(&from_summaries, JsonStyle::Compact).unwrap(),
+        diff_output::format_series_json(&from_snapshots, JsonStyle::Compact).unwrap()
+    );
+    assert!(diff_series_results(&summaries[..1], options()).is_err());
+}
+
+#[test]
+fn diff_only_in_keeps_constructors_missing_from_the_other_side() {
+    let options = ReadOptions::new(false, CancelToken::new());
+    let medium =
+        read_snapshot_file(Path::new("fixtures/medium.heapsnapshot"), options).expect("medium");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let small =
+        read_snapshot_file(Path::new("fixtures/small.heapsnapshot"), options).expect("small");
+    let diff = |a, b, only_in, top, contains: Option<&str>| {
+        diff_summaries(
+            a,
+            b,
+            DiffOptions {
+                top,
+                contains: contains.map(str::to_string),
+                min_self_size: None,
+                min_count: None,
+                sort: DiffSort::SizeDelta,
+                only_in,
+            },
+        )
+        .expect("diff")
+    };
+
+    // Node3..Node19 exist only in medium; Node1 and Node2 are on both sides.
+    let gone = diff(&medium, &small, Some(DiffSide::A), 100, None);
+    assert_eq!(gone.rows.len(), 17);
+    assert!(gone.rows.iter().all(|row| row.count_b == 0));
+    assert!(
+        diff(&medium, &small, Some(DiffSide::B), 100, None)
+            .rows
+            .is_empty()
+    );
+
+    let appeared = diff(&small, &medium, Some(DiffSide::B), 2, Some("Node1"));
+    let names: Vec<&str> = appeared.rows.iter().map(|row| row.name.as_str()).collect();
+    assert_eq!(names, vec!["Node19", "Node18"]);
+    assert!(appeared.rows.iter().all(|row| row.count_a == 0));
+}
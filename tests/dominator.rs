@@ -0,0 +1,145 @@
+use heapsnap::analysis::dominator::{
+    compute_dominator_index, default_ignored_edge_types, dominated_children, retained_sizes,
+};
+use heapsnap::cancel::CancelToken;
+use heapsnap::output::dominator::{
+    format_dominated_json, format_dominated_markdown, format_tree_json,
+};
+use heapsnap::output::json::JsonStyle;
+use heapsnap::parser::read_snapshot;
+
+#[test]
+fn full_tree_json_nests_from_roots_and_keeps_largest_under_caps() {
+    // GC roots -> A1 -> {B1, C}, GC roots -> A2 -> B2 -> D.
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0,0,1,0,2, 0,1,3,10,2, 0,2,5,5,0, 0,3,7,7,0, 0,1,9,10,1, 0,2,11,5,1, 0,4,13,4,0],
+      "edges": [0,5,5, 0,5,20, 0,5,10, 0,5,15, 0,5,25, 0,5,30],
+      "strings": ["GC roots","A","B","C","D","p"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = read_snapshot(&mut reader).expect("snapshot");
+    let index = compute_dominator_index(
+        &snapshot,
+        &default_ignored_edge_types(),
+        CancelToken::new(),
+        None,
+    )
+    .expect("index");
+    assert_eq!(
+        index.idom.iter().filter(|parent| parent.is_some()).count(),
+        7
+    );
+    let tree = |max_nodes, max_depth| -> serde_json::Value {
+        let json = format_tree_json(&snapshot, &index, max_nodes, max_depth, JsonStyle::Compact)
+            .expect("json");
+        serde_json::from_str(&json).expect("parse json")
+    };
+
+    let full = tree(100, 100);
+    assert_eq!(full["truncated"], false);
+    assert_eq!(full["emitted_nodes"], 7);
+    let root = &full["roots"][0];
+    assert_eq!(root["name"], "GC roots");
+    assert_eq!(root["retained_size"], 41);
+    assert_eq!(root["children"][0]["id"], 3);
+    assert_eq!(root["children"][0]["self_size"], 10);
+    assert_eq!(root["children"][0]["retained_size"], 22);
+    assert_eq!(root["children"][0]["children"][0]["name"], "C");
+    assert_eq!(
+        root["children"][1]["children"][0]["children"][0]["name"],
+        "D"
+    );
+
+    let capped = tree(3, 100);
+    assert_eq!(capped["truncated"], true);
+    assert_eq!(capped["reachable_nodes"], 7);
+    assert_eq!(capped["emitted_nodes"], 3);
+    let first = &capped["roots"][0]["children"][0];
+    assert_eq!(first["children"].as_array().map(Vec::len), Some(0));
+    assert_eq!(first["omitted_children"], 2);
+    assert_eq!(first["omitted_retained_size"], 12);
+
+    let shallow = tree(100, 2);
+    assert_eq!(shallow["emitted_nodes"], 6);
+    let b2 = &shallow["roots"][0]["children"][1]["children"][0];
+    assert_eq!(b2["name"], "B");
+    assert_eq!(b2["omitted_children"], 1);
+    assert_eq!(b2["omitted_retained_size"], 4);
+}
+
+#[test]
+fn dominated_children_lists_what_a_node_holds_largest_first() {
+    // GC roots -> A1 -> {B1, C}, GC roots -> A2 -> B2 -> D.
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0,0,1,0,2, 0,1,3,10,2, 0,2,5,5,0, 0,3,7,7,0, 0,1,9,10,1, 0,2,11,5,1, 0,4,13,4,0],
+      "edges": [0,5,5, 0,5,20, 0,5,10, 0,5,15, 0,5,25, 0,5,30],
+      "strings": ["GC roots","A","B","C","D","p"]
+    }
+    "#;
+    let snapshot = read_snapshot(&mut json.as_bytes()).expect("snapshot");
+    let index = compute_dominator_index(
+        &snapshot,
+        &default_ignored_edge_types(),
+        CancelToken::new(),
+        None,
+    )
+    .expect("index");
+    let retained = retained_sizes(&snapshot, &index);
+    let dominated = |target, top| {
+        dominated_children(
+            &snapshot,
+            &index,
+            &retained,
+            target,
+            top,
+            CancelToken::new(),
+        )
+        .expect("dominated")
+    };
+
+    let a1 = dominated(1, 10);
+    assert_eq!(a1.target_retained_size, 22);
+    assert_eq!(a1.children, vec![(3, 7), (2, 5)]);
+    assert_eq!(a1.total_children, 2);
+    assert!(a1.unreachable.is_none());
+
+    let root = dominated(0, 1);
+    assert_eq!(root.children, vec![(1, 22)]);
+    assert_eq!(root.total_children, 2);
+    assert_eq!(root.omitted_retained_size, 19);
+    let markdown = format_dominated_markdown(&snapshot, &root);
+    assert!(markdown.starts_with("- Dominated by GC roots (id=1) retained=41 bytes (100.0%)\n"));
+    assert!(markdown.contains("  - #1 A (id=3) retained=22 bytes (53.7%)\n"));
+    assert!(markdown.contains("  - 1 more (--top) retained=19 bytes (46.3%)\n"));
+    let value: serde_json::Value = serde_json::from_str(
+        &format_dominated_json(&snapshot, &root, JsonStyle::Compact).expect("json"),
+    )
+    .expect("parse json");
+    assert_eq!(value["children"][0]["id"], 3);
+    assert_eq!(value["children"][0]["retained_size_bytes"], 22);
+    assert_eq!(value["omitted_children"], 1);
+    assert_eq!(value["omitted_retained_size_bytes"], 19);
+
+    let leaf = dominated(6, 10);
+    assert!(leaf.children.is_empty());
+    assert!(format_dominated_markdown(&snapshot, &leaf).contains("(dominates no other node)"));
+}
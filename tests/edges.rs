@@ -0,0 +1,87 @@
+use heapsnap::analysis::edges::{EdgesOptions, edges};
+use heapsnap::output::edges as edges_output;
+use heapsnap::output::json::JsonStyle;
+use heapsnap::snapshot::SnapshotRaw;
+
+// Two "Timer" nodes, each holding a "Fn" node through `callback` and `other`.
+fn timers_snapshot() -> SnapshotRaw {
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0, 0, 1, 10, 2, 0, 0, 3, 10, 2, 0, 1, 5, 4, 0],
+      "edges": [0, 2, 10, 0, 3, 10, 0, 2, 10, 0, 3, 10],
+      "strings": ["Timer", "Fn", "callback", "other"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    heapsnap::parser::read_snapshot(&mut reader).expect("snapshot")
+}
+
+fn options(name: &str) -> EdgesOptions {
+    EdgesOptions {
+        name: name.to_string(),
+        incoming: false,
+        edge_name_filter: None,
+        skip: 0,
+        limit: 200,
+    }
+}
+
+#[test]
+fn edges_lists_named_edges_across_every_instance() {
+    let snapshot = timers_snapshot();
+    let result = edges(
+        &snapshot,
+        EdgesOptions {
+            edge_name_filter: Some("callback".to_string()),
+            ..options("Timer")
+        },
+    )
+    .expect("edges");
+
+    assert_eq!(result.matched_nodes, 2);
+    assert_eq!(result.total_edges, 2);
+    let from: Vec<Option<i64>> = result.rows.iter().map(|row| row.from_id).collect();
+    assert_eq!(from, vec![Some(1), Some(3)]);
+    assert!(result.rows.iter().all(|row| row.to_id == Some(5)));
+
+    let csv = edges_output::format_csv(&result, ',');
+    assert_eq!(
+        csv,
+        "from_id,edge_type,edge_name,to_id,to_name\n1,property,callback,5,Fn\n3,property,callback,5,Fn\n"
+    );
+}
+
+#[test]
+fn edges_incoming_lists_retainers_and_pages() {
+    let snapshot = timers_snapshot();
+    let result = edges(
+        &snapshot,
+        EdgesOptions {
+            incoming: true,
+            skip: 1,
+            limit: 2,
+            ..options("Fn")
+        },
+    )
+    .expect("edges");
+
+    assert_eq!(result.total_edges, 4);
+    assert_eq!(result.rows.len(), 2);
+    assert_eq!(result.rows[0].edge_name.as_deref(), Some("other"));
+    assert_eq!(result.next_skip(), Some(3));
+
+    let json = edges_output::format_json(&result, JsonStyle::Compact).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["direction"], "incoming");
+    assert_eq!(value["has_more"], true);
+    assert_eq!(value["next_skip"], 3);
+    assert_eq!(value["edges"][0]["from_name"], "Timer");
+}
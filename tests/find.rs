@@ -0,0 +1,95 @@
+use heapsnap::analysis::find::{FindOptions, find};
+use heapsnap::output::find as find_output;
+use heapsnap::output::json::JsonStyle;
+use heapsnap::snapshot::SnapshotRaw;
+
+// Two "Request" objects: id 1 holds "https://a" through both `url` and `other`,
+// id 5 holds "https://b" through `url`.
+fn requests_snapshot() -> SnapshotRaw {
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object","string"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property","element"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0, 0, 1, 10, 3, 1, 1, 3, 20, 0, 0, 0, 5, 10, 1, 1, 4, 7, 20, 0],
+      "edges": [0, 2, 5, 0, 3, 5, 1, 2, 5, 0, 2, 15],
+      "strings": ["Request", "https://a", "url", "other", "https://b"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    heapsnap::parser::read_snapshot(&mut reader).expect("snapshot")
+}
+
+fn options(edge_name: &str, value: &str) -> FindOptions {
+    FindOptions {
+        edge_name: edge_name.to_string(),
+        value: value.to_string(),
+        limit: 200,
+    }
+}
+
+#[test]
+fn find_reports_owner_of_matching_property() {
+    let snapshot = requests_snapshot();
+    let result = find(&snapshot, options("url", "https://a")).expect("find");
+
+    // `other` has the right value but the wrong name; the element edge [2] is not named.
+    assert_eq!(result.total_matches, 1);
+    let found = &result.matches[0];
+    assert_eq!(found.owner_id, Some(1));
+    assert_eq!(found.owner_name.as_deref(), Some("Request"));
+    assert_eq!(found.edge_type.as_deref(), Some("property"));
+    assert_eq!(found.target_id, Some(3));
+    assert_eq!(found.target_type.as_deref(), Some("string"));
+
+    let result = find(&snapshot, options("url", "https://b")).expect("find");
+    assert_eq!(
+        result
+            .matches
+            .iter()
+            .map(|m| m.owner_id)
+            .collect::<Vec<_>>(),
+        vec![Some(5)]
+    );
+}
+
+#[test]
+fn find_counts_matches_past_the_limit() {
+    let snapshot = requests_snapshot();
+    let mut options = options("url", "https://a");
+    options.limit = 0;
+    let result = find(&snapshot, options).expect("find");
+    assert_eq!(result.total_matches, 1);
+    assert!(result.matches.is_empty());
+
+    let missing = find(&snapshot, self::options("url", "https://c")).expect("find");
+    assert_eq!(missing.total_matches, 0);
+    assert!(find_output::format_markdown(&missing).contains("No matching edges."));
+}
+
+#[test]
+fn find_json_and_csv_list_owner_ids() {
+    let snapshot = requests_snapshot();
+    let result = find(&snapshot, options("url", "https://a")).expect("find");
+
+    let json = find_output::format_json(&result, JsonStyle::Pretty).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse");
+    assert_eq!(value["edge_name"], "url");
+    assert_eq!(value["total_matches"], 1);
+    assert_eq!(value["matches"][0]["owner_id"], 1);
+
+    let csv = find_output::format_csv(&result, ',');
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "owner_id,owner_name,edge_type,target_id,target_type",
+            "1,Request,property,3,string"
+        ]
+    );
+}
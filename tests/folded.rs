@@ -0,0 +1,57 @@
+use heapsnap::analysis::dominator::{compute_dominator_index, retained_sizes};
+use heapsnap::analysis::tree::{TreeOptions, dominator_subtree};
+use heapsnap::cancel::CancelToken;
+use heapsnap::output::folded;
+use heapsnap::parser::read_snapshot;
+
+#[test]
+fn folded_stacks_sum_equal_paths_and_keep_cut_children_in_parent() {
+    // GC roots -> A1 -> {B1, C}, GC roots -> A2 -> B2 -> D; A1 and A2 share the name "A".
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0,0,1,0,2, 0,1,3,10,2, 0,2,5,5,0, 0,3,7,7,0, 0,1,9,10,1, 0,2,11,5,1, 0,4,13,4,0],
+      "edges": [0,5,5, 0,5,20, 0,5,10, 0,5,15, 0,5,25, 0,5,30],
+      "strings": ["GC roots","A","B;x","C","D","p"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = read_snapshot(&mut reader).expect("snapshot");
+    let index = compute_dominator_index(&snapshot, &[], CancelToken::new(), None).expect("index");
+    let retained = retained_sizes(&snapshot, &index);
+
+    let tree = |max_depth| {
+        dominator_subtree(
+            &snapshot,
+            &index,
+            &retained,
+            0,
+            TreeOptions {
+                max_depth,
+                max_children: 10,
+                cancel: CancelToken::new(),
+            },
+        )
+        .expect("tree")
+    };
+
+    let full = folded::format(&snapshot, &tree(3));
+    assert_eq!(
+        full,
+        "GC roots;A 20\nGC roots;A;B,x 10\nGC roots;A;B,x;D 4\nGC roots;A;C 7\n"
+    );
+
+    // D is cut at depth 2 and stays in B2's weight; the total is unchanged.
+    let shallow = folded::format(&snapshot, &tree(2));
+    assert_eq!(
+        shallow,
+        "GC roots;A 20\nGC roots;A;B,x 14\nGC roots;A;C 7\n"
+    );
+}
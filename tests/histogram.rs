@@ -0,0 +1,58 @@
+use std::path::Path;
+
+use heapsnap::analysis::histogram::{DEFAULT_BUCKET_BOUNDS, HistogramOptions, histogram};
+use heapsnap::cancel::CancelToken;
+use heapsnap::output::histogram as histogram_output;
+use heapsnap::output::json::JsonStyle;
+use heapsnap::parser::{ReadOptions, read_snapshot_file};
+
+#[test]
+fn histogram_bins_every_node_and_honours_filters() {
+    let path = Path::new("fixtures/medium.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+
+    let all = histogram(
+        &snapshot,
+        HistogramOptions {
+            name: None,
+            node_type: None,
+            bounds: DEFAULT_BUCKET_BOUNDS.to_vec(),
+        },
+    )
+    .expect("histogram");
+    assert_eq!(all.matched_nodes, 20);
+    let counts: Vec<u64> = all.buckets.iter().map(|bucket| bucket.count).collect();
+    assert_eq!(counts, vec![1, 10, 9, 0, 0, 0, 0, 0]);
+    assert_eq!(
+        all.buckets
+            .iter()
+            .map(|bucket| bucket.self_size_sum)
+            .sum::<i64>(),
+        all.self_size_sum
+    );
+
+    // Node1 and Node10..Node19 (self size 3 and 30..57).
+    let filtered = histogram(
+        &snapshot,
+        HistogramOptions {
+            name: Some("Node1".to_string()),
+            node_type: None,
+            bounds: vec![10, 30],
+        },
+    )
+    .expect("histogram");
+    assert_eq!(filtered.matched_nodes, 11);
+    let counts: Vec<u64> = filtered.buckets.iter().map(|bucket| bucket.count).collect();
+    assert_eq!(counts, vec![1, 0, 10]);
+
+    let markdown = histogram_output::format_markdown(&filtered);
+    assert!(markdown.contains("| 30+ | 10 | 435 |"));
+    assert!(markdown.contains("```text"));
+
+    let json = histogram_output::format_json(&filtered, JsonStyle::Pretty).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["name"], "Node1");
+    assert_eq!(value["buckets"][2]["min"], 30);
+    assert!(value["buckets"][2]["max"].is_null());
+}
@@ -0,0 +1,27 @@
+use std::path::Path;
+
+use heapsnap::cancel::CancelToken;
+use heapsnap::output::info as info_output;
+use heapsnap::output::json::JsonStyle;
+use heapsnap::parser::{ReadOptions, read_snapshot_file};
+
+#[test]
+fn info_reports_counts_of_fixture_medium() {
+    let path = Path::new("fixtures/medium.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+
+    let json = info_output::format_json(&snapshot, JsonStyle::Pretty).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["node_count"], 20);
+    assert_eq!(value["edge_count"], 19);
+    assert_eq!(value["string_count"], snapshot.strings.len());
+    assert_eq!(
+        value["memory_estimate_bytes"],
+        snapshot.memory_estimate_bytes()
+    );
+
+    let markdown = info_output::format_markdown(&snapshot);
+    assert!(markdown.contains("- Nodes: 20"));
+    assert!(markdown.contains("- Edges: 19"));
+}
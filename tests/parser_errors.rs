@@ -8,10 +8,32 @@ fn parse_invalid_json() {
     let result = read_snapshot(&mut reader);
     assert!(matches!(
         result,
-        Err(heapsnap::error::SnapshotError::Json(_))
+        Err(heapsnap::error::SnapshotError::Json { .. })
     ));
 }
 
+#[test]
+fn parse_error_reports_offset_in_original_file() {
+    let offset = |data: &[u8]| match read_snapshot(&mut &data[..]) {
+        Err(heapsnap::error::SnapshotError::Json { offset, .. }) => offset,
+        other => panic!("expected a JSON error, got {other:?}"),
+    };
+    // The `x` is at byte 11 in the first input.
+    assert_eq!(offset(br#"{"nodes": [x]}"#), Some(11));
+    // A BOM and a comment are dropped before serde_json sees the input; the offset still
+    // points into the file as written.
+    assert_eq!(
+        offset("\u{feff}/* note */ {\"nodes\": [x]}".as_bytes()),
+        Some(3 + 11 + 11)
+    );
+
+    let err = read_snapshot(&mut &br#"{"nodes": [x]}"#[..]).expect_err("invalid json");
+    assert!(
+        err.to_string().ends_with("(near byte 11 of the file)"),
+        "{err}"
+    );
+}
+
 #[test]
 fn parse_missing_meta() {
     let json = r#"{ "snapshot": {}, "nodes": [], "edges": [], "strings": [] }"#;
@@ -38,3 +60,41 @@ fn parse_cancelled() {
         Err(heapsnap::error::SnapshotError::Cancelled)
     ));
 }
+
+fn assert_no_nodes<T: std::fmt::Debug>(result: Result<T, heapsnap::error::SnapshotError>) {
+    match result {
+        Err(heapsnap::error::SnapshotError::InvalidData { details }) => {
+            assert_eq!(details, "snapshot contains no nodes");
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn empty_snapshot_file_is_rejected_before_any_command() {
+    let path = std::path::Path::new("fixtures/empty.heapsnapshot");
+    let options = || heapsnap::parser::ReadOptions::new(false, CancelToken::new());
+    assert_no_nodes(heapsnap::parser::read_snapshot_file(path, options()).map(|_| ()));
+    assert_no_nodes(heapsnap::Analyzer::open(path, options()).map(|_| ()));
+}
+
+#[test]
+fn root_based_analyses_reject_an_empty_snapshot() {
+    let text = std::fs::read_to_string("fixtures/empty.heapsnapshot").expect("fixture");
+    let snapshot = read_snapshot(&mut text.as_bytes()).expect("parse ok");
+    assert_eq!(snapshot.node_count(), 0);
+
+    assert_no_nodes(heapsnap::analysis::retainers::find_roots(
+        &snapshot,
+        &heapsnap::analysis::retainers::RootOptions::default(),
+    ));
+    assert_no_nodes(heapsnap::analysis::dominator::compute_dominator_index(
+        &snapshot,
+        &heapsnap::analysis::dominator::default_ignored_edge_types(),
+        CancelToken::new(),
+        None,
+    ));
+    assert_no_nodes(heapsnap::analysis::reachability::classify_reachability(
+        &snapshot,
+    ));
+}
@@ -1,8 +1,17 @@
 use std::path::Path;
 
-use heapsnap::analysis::retainers::{RetainersOptions, find_retaining_paths, find_target_by_id};
-use heapsnap::cancel::CancelToken;
-use heapsnap::parser::{ReadOptions, read_snapshot_file};
+use heapsnap::analysis::dominator::{
+    compute_dominator_index, default_ignored_edge_types, retained_sizes,
+};
+use heapsnap::analysis::name_index::NameIndex;
+use heapsnap::analysis::retainers::{
+    PathRank, PickStrategy, RetainersOptions, dedupe_by_constructor, find_retaining_paths,
+    find_target_by_id, find_target_by_name, match_target_by_name, match_target_with_names,
+};
+use heapsnap::cancel::{CancelToken, spawn_timeout};
+use heapsnap::output::charset::Charset;
+use heapsnap::output::json::JsonStyle;
+use heapsnap::parser::{ReadOptions, read_snapshot, read_snapshot_file};
 
 #[test]
 fn retainers_paths_fixture_small() {
@@ -17,6 +26,9 @@ fn retainers_paths_fixture_small() {
         RetainersOptions {
             max_paths: 5,
             max_depth: 10,
+            distinct_roots: false,
+            rank: PathRank::Shortest,
+            depth_histogram: false,
             cancel: CancelToken::new(),
         },
     )
@@ -24,6 +36,118 @@ fn retainers_paths_fixture_small() {
 
     assert_eq!(result.paths.len(), 1);
     assert_eq!(result.paths[0].len(), 2);
+    assert_eq!(result.stats.min_len, Some(2));
+    assert_eq!(result.stats.max_len, Some(2));
+    assert_eq!(result.stats.distinct_roots_reached, 1);
+    assert_eq!(result.stats.total_paths_found, 1);
+    assert!(!result.stats.truncated());
+}
+
+#[test]
+fn retainers_ids_only_prints_one_id_chain_per_path() {
+    let path = Path::new("fixtures/small.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+
+    let target = find_target_by_id(&snapshot, 3).expect("target");
+    let result = find_retaining_paths(
+        &snapshot,
+        target,
+        RetainersOptions {
+            max_paths: 5,
+            max_depth: 10,
+            distinct_roots: false,
+            rank: PathRank::Shortest,
+            depth_histogram: false,
+            cancel: CancelToken::new(),
+        },
+    )
+    .expect("paths");
+
+    assert_eq!(
+        heapsnap::output::retainers::format_ids(&snapshot, &result),
+        "1 -> 2 -> 3\n"
+    );
+}
+
+#[test]
+fn retainers_stats_flag_depth_limit() {
+    let path = Path::new("fixtures/small.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+
+    let target = find_target_by_id(&snapshot, 3).expect("target");
+    let result = find_retaining_paths(
+        &snapshot,
+        target,
+        RetainersOptions {
+            max_paths: 5,
+            max_depth: 1,
+            distinct_roots: false,
+            rank: PathRank::Shortest,
+            depth_histogram: false,
+            cancel: CancelToken::new(),
+        },
+    )
+    .expect("paths");
+
+    assert!(result.paths.is_empty());
+    assert_eq!(result.stats.min_len, None);
+    assert!(result.stats.hit_max_depth);
+    assert!(!result.stats.hit_max_paths);
+    let markdown =
+        heapsnap::output::retainers::format_markdown(&snapshot, &result, Charset::Unicode);
+    assert!(markdown.contains("Truncated: --max-depth reached"));
+}
+
+#[test]
+fn retainers_depth_histogram_counts_open_paths_per_depth() {
+    let path = Path::new("fixtures/small.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+    let target = find_target_by_id(&snapshot, 3).expect("target");
+    let search = |max_depth, depth_histogram| {
+        find_retaining_paths(
+            &snapshot,
+            target,
+            RetainersOptions {
+                max_paths: 5,
+                max_depth,
+                distinct_roots: false,
+                rank: PathRank::Shortest,
+                depth_histogram,
+                cancel: CancelToken::new(),
+            },
+        )
+        .expect("paths")
+    };
+
+    // Node2 <- Node1 <- GC roots: one open path at depth 0 and 1.
+    let cut = search(1, true);
+    assert_eq!(cut.depth_histogram, vec![1, 1]);
+    let markdown = heapsnap::output::retainers::format_markdown(&snapshot, &cut, Charset::Unicode);
+    assert!(
+        markdown.contains("    - 1: 1 (cut off by --max-depth)"),
+        "{markdown}"
+    );
+    let json = heapsnap::output::retainers::format_json(&snapshot, &cut, JsonStyle::Compact)
+        .expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["depth_histogram"], serde_json::json!([1, 1]));
+
+    let reached = search(5, true);
+    assert_eq!(reached.paths.len(), 1);
+    assert_eq!(reached.depth_histogram, vec![1, 1]);
+    let markdown =
+        heapsnap::output::retainers::format_markdown(&snapshot, &reached, Charset::Unicode);
+    assert!(markdown.contains("Open paths by depth"));
+    assert!(!markdown.contains("cut off"));
+
+    let plain = search(1, false);
+    assert!(plain.depth_histogram.is_empty());
+    let json = heapsnap::output::retainers::format_json(&snapshot, &plain, JsonStyle::Compact)
+        .expect("json");
+    assert!(!json.contains("depth_histogram"));
 }
 
 #[test]
@@ -42,6 +166,67 @@ fn retainers_cancelled() {
         RetainersOptions {
             max_paths: 5,
             max_depth: 10,
+            distinct_roots: false,
+            rank: PathRank::Shortest,
+            depth_histogram: false,
+            cancel: token,
+        },
+    );
+
+    assert!(matches!(
+        result,
+        Err(heapsnap::error::SnapshotError::Cancelled)
+    ));
+}
+
+#[test]
+fn retainers_respects_timeout() {
+    // GC roots, then a chain of CHAIN nodes each retained by the next and none by the
+    // roots: the search walks the whole chain one layer at a time, which takes minutes.
+    const CHAIN: usize = 100_000;
+    let mut nodes = vec!["0,0,1,0,0".to_string()];
+    let mut edges = Vec::new();
+    for node in 1..=CHAIN {
+        let edge_count = usize::from(node > 1);
+        nodes.push(format!("0,1,{},8,{edge_count}", node * 2 + 1));
+        if node > 1 {
+            edges.push(format!("0,2,{}", (node - 1) * 5));
+        }
+    }
+    let json = format!(
+        r#"{{
+      "snapshot": {{
+        "meta": {{
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        }}
+      }},
+      "nodes": [{}],
+      "edges": [{}],
+      "strings": ["GC roots","Link","next"]
+    }}"#,
+        nodes.join(","),
+        edges.join(",")
+    );
+    let snapshot = read_snapshot(&mut json.as_bytes()).expect("snapshot");
+
+    let token = CancelToken::new();
+    let deadline = std::time::Duration::from_millis(100);
+    let started = std::time::Instant::now();
+    let _watcher = spawn_timeout(token.clone(), deadline);
+    assert!(!token.is_cancelled());
+
+    let result = find_retaining_paths(
+        &snapshot,
+        1,
+        RetainersOptions {
+            max_paths: 1000,
+            max_depth: CHAIN + 1,
+            distinct_roots: false,
+            rank: PathRank::Shortest,
+            depth_histogram: false,
             cancel: token,
         },
     );
@@ -50,4 +235,273 @@ fn retainers_cancelled() {
         result,
         Err(heapsnap::error::SnapshotError::Cancelled)
     ));
+    // Aborted partway through: after the deadline, long before the chain's end.
+    let elapsed = started.elapsed();
+    assert!(elapsed >= deadline, "{elapsed:?}");
+    assert!(elapsed < std::time::Duration::from_secs(30), "{elapsed:?}");
+}
+
+#[test]
+fn pick_retained_prefers_dominating_node_over_largest_self_size() {
+    let path = Path::new("fixtures/small.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+
+    // Node1 (self 3) dominates Node2 (self 6), so it retains 9 bytes.
+    let largest = find_target_by_name(&snapshot, "Node", PickStrategy::Largest).expect("target");
+    assert_eq!(largest, find_target_by_id(&snapshot, 3).expect("Node2"));
+
+    let index = compute_dominator_index(
+        &snapshot,
+        &default_ignored_edge_types(),
+        CancelToken::new(),
+        None,
+    )
+    .expect("dominator index");
+    let retained = retained_sizes(&snapshot, &index);
+    let picked = find_target_by_name(&snapshot, "Node", PickStrategy::RetainedSize(&retained))
+        .expect("target");
+    assert_eq!(picked, find_target_by_id(&snapshot, 2).expect("Node1"));
+    assert_eq!(retained[picked], 9);
+}
+
+#[test]
+fn match_target_reports_candidates_and_exact_requires_full_name() {
+    let path = Path::new("fixtures/medium.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+
+    // "Node1" is a substring of Node1 and Node10..Node19.
+    let loose =
+        match_target_by_name(&snapshot, "Node1", PickStrategy::Largest, false).expect("target");
+    assert_eq!(loose.candidates.len(), 11);
+    assert_eq!(loose.candidates[0].name, "Node19");
+
+    let exact =
+        match_target_by_name(&snapshot, "Node1", PickStrategy::Largest, true).expect("target");
+    assert_eq!(exact.candidates.len(), 1);
+    assert_eq!(
+        exact.node_index,
+        find_target_by_id(&snapshot, 2).expect("Node1")
+    );
+
+    let err = match_target_by_name(&snapshot, "Node", PickStrategy::Largest, true)
+        .expect_err("no exact match");
+    assert!(err.to_string().contains("--exact"));
+
+    // A typo is answered with the closest names, from both lookups.
+    let err = match_target_by_name(&snapshot, "Nde12", PickStrategy::Largest, false)
+        .expect_err("no match");
+    assert!(
+        err.to_string().ends_with("; did you mean 'Node12'?"),
+        "{err}"
+    );
+    let names = NameIndex::new(&snapshot);
+    let err = match_target_with_names(&snapshot, &names, "Nde12", PickStrategy::Largest, false)
+        .expect_err("no match");
+    assert!(
+        err.to_string().ends_with("; did you mean 'Node12'?"),
+        "{err}"
+    );
+}
+
+#[test]
+fn retainers_json_streams_paths_with_steps() {
+    let path = Path::new("fixtures/small.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+
+    let target = find_target_by_id(&snapshot, 3).expect("target");
+    let result = find_retaining_paths(
+        &snapshot,
+        target,
+        RetainersOptions {
+            max_paths: 5,
+            max_depth: 10,
+            distinct_roots: false,
+            rank: PathRank::Shortest,
+            depth_histogram: false,
+            cancel: CancelToken::new(),
+        },
+    )
+    .expect("paths");
+
+    let mut streamed = Vec::new();
+    heapsnap::output::retainers::write_json(&snapshot, &result, &mut streamed, JsonStyle::Pretty)
+        .expect("json");
+    let value: serde_json::Value = serde_json::from_slice(&streamed).expect("parse json");
+
+    assert_eq!(value["version"], 1);
+    assert_eq!(value["target"]["id"], 3);
+    assert_eq!(value["stats"]["min_len"], 2);
+    assert_eq!(value["stats"]["truncated"], false);
+    let steps = value["paths"][0]["steps"].as_array().expect("steps");
+    assert_eq!(steps.len(), 2);
+    assert_eq!(steps[0]["from"]["name"], "GC roots");
+    assert_eq!(steps[1]["to"]["name"], "Node2");
+    assert_eq!(steps[1]["edge"]["edge_type"], "property");
+    assert_eq!(
+        String::from_utf8(streamed).expect("utf-8"),
+        heapsnap::output::retainers::format_json(&snapshot, &result, JsonStyle::Pretty)
+            .expect("json")
+    );
+}
+
+#[test]
+fn name_index_lookup_matches_node_scan() {
+    let path = Path::new("fixtures/medium.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+    let names = NameIndex::new(&snapshot);
+    assert_eq!(names.nodes("Node2").len(), 1);
+
+    for (filter, exact) in [("Node1", false), ("Node1", true), ("Node", false)] {
+        let scanned =
+            match_target_by_name(&snapshot, filter, PickStrategy::Largest, exact).expect("scan");
+        let indexed =
+            match_target_with_names(&snapshot, &names, filter, PickStrategy::Largest, exact)
+                .expect("index");
+        assert_eq!(indexed.node_index, scanned.node_index, "{filter}");
+        assert_eq!(indexed.candidates, scanned.candidates, "{filter}");
+    }
+    assert!(
+        match_target_with_names(&snapshot, &names, "Missing", PickStrategy::Largest, false)
+            .is_err()
+    );
+}
+
+#[test]
+fn retainers_indexed_matches_per_layer_scan() {
+    let path = Path::new("fixtures/medium.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+    let options = || RetainersOptions {
+        max_paths: 5,
+        max_depth: 25,
+        distinct_roots: false,
+        rank: PathRank::Shortest,
+        depth_histogram: false,
+        cancel: CancelToken::new(),
+    };
+
+    for target in 0..snapshot.node_count() {
+        let scanned = find_retaining_paths(&snapshot, target, options()).expect("scanned");
+        let indexed = heapsnap::analysis::retainers::find_retaining_paths_indexed(
+            &snapshot,
+            target,
+            options(),
+        )
+        .expect("indexed");
+        assert_eq!(scanned.paths, indexed.paths, "target {target}");
+        assert_eq!(scanned.stats, indexed.stats, "target {target}");
+    }
+}
+
+#[test]
+fn rank_reorders_paths_before_truncating() {
+    // GC roots -[element 0]-> E -[t]-> T and GC roots -[p]-> P -[q]-> Q -[t]-> T.
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["element","property"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0,0,1,0,2, 0,1,3,10,1, 0,2,5,10,1, 0,3,7,10,1, 0,4,9,10,0],
+      "edges": [0,0,5, 1,5,10, 1,7,20, 1,6,15, 1,7,20],
+      "strings": ["GC roots","E","P","Q","T","p","q","t"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = read_snapshot(&mut reader).expect("snapshot");
+    let target = find_target_by_id(&snapshot, 9).expect("T");
+    let first_hop = |rank: PathRank<'_>, max_paths: usize| {
+        let result = find_retaining_paths(
+            &snapshot,
+            target,
+            RetainersOptions {
+                max_paths,
+                max_depth: 10,
+                distinct_roots: false,
+                rank,
+                depth_histogram: false,
+                cancel: CancelToken::new(),
+            },
+        )
+        .expect("paths");
+        let hops: Vec<usize> = result.paths.iter().map(|path| path[0].to_node).collect();
+        (hops, result.stats.hit_max_paths)
+    };
+
+    assert_eq!(first_hop(PathRank::Shortest, 5), (vec![1, 2], false));
+    assert_eq!(first_hop(PathRank::Shortest, 1), (vec![1], true));
+    assert_eq!(first_hop(PathRank::FewestElementEdges, 1), (vec![2], true));
+    let retained = [50, 20, 30, 20, 10];
+    assert_eq!(
+        first_hop(PathRank::LargestRetainer(&retained), 5),
+        (vec![2, 1], false)
+    );
+}
+
+#[test]
+fn dedupe_by_constructor_collapses_paths_through_same_named_nodes() {
+    // GC roots -[element 0]-> A -[x]-> T, GC roots -[element 1]-> A -[y]-> T
+    // and GC roots -[p]-> B -[z]-> T.
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["element","property"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0,0,1,0,3, 0,1,3,10,1, 0,1,5,10,1, 0,2,7,10,1, 0,3,9,10,0],
+      "edges": [0,0,5, 0,1,10, 1,6,15, 1,4,20, 1,5,20, 1,7,20],
+      "strings": ["GC roots","A","B","T","x","y","p","z"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = read_snapshot(&mut reader).expect("snapshot");
+    let target = find_target_by_id(&snapshot, 9).expect("T");
+    let mut result = find_retaining_paths(
+        &snapshot,
+        target,
+        RetainersOptions {
+            max_paths: 5,
+            max_depth: 10,
+            distinct_roots: false,
+            rank: PathRank::Shortest,
+            depth_histogram: false,
+            cancel: CancelToken::new(),
+        },
+    )
+    .expect("paths");
+    assert_eq!(result.paths.len(), 3);
+
+    dedupe_by_constructor(&snapshot, &mut result);
+    let hops: Vec<usize> = result.paths.iter().map(|path| path[0].to_node).collect();
+    assert_eq!(hops, vec![1, 3]);
+    assert_eq!(result.similar, vec![2, 1]);
+    assert_eq!(result.stats.total_paths_found, 3);
+
+    let markdown =
+        heapsnap::output::retainers::format_markdown(&snapshot, &result, Charset::Unicode);
+    assert!(
+        markdown.contains("  - Path #1 (×2 similar)\n"),
+        "{markdown}"
+    );
+    assert!(markdown.contains("  - Path #2\n"), "{markdown}");
+    let ascii = heapsnap::output::retainers::format_markdown(&snapshot, &result, Charset::Ascii);
+    assert!(ascii.contains("  - Path #1 (x2 similar)\n"), "{ascii}");
+
+    let json = heapsnap::output::retainers::format_json(&snapshot, &result, JsonStyle::Compact)
+        .expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["paths"][0]["similar"], 2);
+    assert_eq!(value["paths"][1]["similar"], 1);
 }
@@ -0,0 +1,84 @@
+use heapsnap::analysis::strings::{PREVIEW_CHARS, StringsOptions, strings};
+use heapsnap::output::json::JsonStyle;
+use heapsnap::output::strings as strings_output;
+use heapsnap::parser::read_snapshot;
+use heapsnap::snapshot::SnapshotRaw;
+
+fn string_snapshot(long: &str) -> SnapshotRaw {
+    let json = format!(
+        r#"
+    {{
+      "snapshot": {{
+        "meta": {{
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        }}
+      }},
+      "nodes": [0,0,1,0,0],
+      "edges": [],
+      "strings": ["GC roots","dup|x","{long}","dup|x","ab","dup|x","ab"]
+    }}
+    "#
+    );
+    let mut reader = json.as_bytes();
+    read_snapshot(&mut reader).expect("snapshot")
+}
+
+#[test]
+fn strings_reports_sizes_and_duplicated_values() {
+    let long = "d".repeat(PREVIEW_CHARS + 20);
+    let snapshot = string_snapshot(&long);
+    let result = strings(
+        &snapshot,
+        StringsOptions {
+            top: 2,
+            previews: true,
+        },
+    );
+
+    assert_eq!(result.string_count, 7);
+    assert_eq!(result.total_bytes, 8 + 5 * 3 + 100 + 2 * 2);
+    assert_eq!(result.largest[0].index, 2);
+    assert_eq!(result.largest[0].bytes, 100);
+    let preview = result.largest[0].preview.as_deref().expect("preview");
+    assert_eq!(preview.chars().count(), PREVIEW_CHARS + 1);
+    assert!(preview.ends_with('…'));
+    assert_eq!(result.largest[1].index, 0);
+
+    assert_eq!(result.duplicate_values, 2);
+    assert_eq!(result.duplicate_strings, 3);
+    assert_eq!(result.duplicate_bytes, 2 * 5 + 2);
+    assert_eq!(result.duplicates[0].first_index, 1);
+    assert_eq!(result.duplicates[0].count, 3);
+    assert_eq!(result.duplicates[0].wasted_bytes(), 10);
+
+    let markdown = strings_output::format_markdown(&result);
+    assert!(markdown.contains("| 1 | 3 | 5 | 10 | dup\\|x |"));
+}
+
+#[test]
+fn strings_hides_contents_without_previews() {
+    let snapshot = string_snapshot("secret-token");
+    let result = strings(
+        &snapshot,
+        StringsOptions {
+            top: 5,
+            previews: false,
+        },
+    );
+
+    let markdown = strings_output::format_markdown(&result);
+    assert!(!markdown.contains("secret-token"));
+    assert!(markdown.contains("pass --verbose"));
+
+    let json = strings_output::format_json(&result, JsonStyle::Pretty).expect("json");
+    assert!(!json.contains("secret-token"));
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["version"], 1);
+    assert_eq!(value["duplicate_values"], 2);
+    assert_eq!(value["largest"][0]["index"], 2);
+    assert!(value["largest"][0].get("preview").is_none());
+    assert_eq!(value["duplicates"][0]["wasted_bytes"], 10);
+}
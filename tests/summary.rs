@@ -1,9 +1,14 @@
 use std::path::Path;
 
-use heapsnap::analysis::summary::{SummaryOptions, summarize};
+use heapsnap::analysis::budget::{SizeBudget, check_budgets};
+use heapsnap::analysis::dominator::{
+    compute_dominator_index, default_ignored_edge_types, retained_sizes,
+};
+use heapsnap::analysis::summary::{StreamingSummary, SummaryOptions, summarize};
 use heapsnap::cancel::CancelToken;
+use heapsnap::output::json::JsonStyle;
 use heapsnap::output::summary as summary_output;
-use heapsnap::parser::{ReadOptions, read_snapshot_file};
+use heapsnap::parser::{ReadOptions, read_snapshot_file, stream_snapshot, stream_snapshot_file};
 
 #[test]
 fn summary_json_fixture_small() {
@@ -15,12 +20,12 @@ fn summary_json_fixture_small() {
         &snapshot,
         SummaryOptions {
             top: 10,
-            contains: None,
+            ..Default::default()
         },
     )
     .expect("summary");
 
-    let json = summary_output::format_json(&result).expect("json");
+    let json = summary_output::format_json(&result, JsonStyle::Pretty).expect("json");
     let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
 
     assert_eq!(value["version"], 1);
@@ -29,6 +34,9 @@ fn summary_json_fixture_small() {
     assert_eq!(value["rows"][0]["name"], "Node2");
     assert_eq!(value["rows"][1]["name"], "Node1");
     assert_eq!(value["rows"][2]["name"], "GC roots");
+    // One instance each, so the sample is that node.
+    assert_eq!(value["rows"][0]["sample_id"], 3);
+    assert_eq!(value["rows"][1]["sample_id"], 2);
 }
 
 #[test]
@@ -41,7 +49,7 @@ fn summary_markdown_units_header() {
         &snapshot,
         SummaryOptions {
             top: 10,
-            contains: None,
+            ..Default::default()
         },
     )
     .expect("summary");
@@ -60,14 +68,19 @@ fn summary_csv_units_header() {
         &snapshot,
         SummaryOptions {
             top: 10,
-            contains: None,
+            ..Default::default()
         },
     )
     .expect("summary");
 
-    let csv = summary_output::format_csv(&result);
+    let csv = summary_output::format_csv(&result, ',');
     let header = csv.lines().next().expect("csv header");
     assert_eq!(header, "constructor,count,self_size_sum_bytes");
+
+    let tsv = summary_output::format_csv(&result, '\t');
+    let header = tsv.lines().next().expect("tsv header");
+    assert_eq!(header, "constructor\tcount\tself_size_sum_bytes");
+    assert!(tsv.contains("GC roots\t1\t0\n"));
 }
 
 #[test]
@@ -80,7 +93,7 @@ fn summary_html_includes_table_and_links() {
         &snapshot,
         SummaryOptions {
             top: 10,
-            contains: None,
+            ..Default::default()
         },
     )
     .expect("summary");
@@ -89,3 +102,480 @@ fn summary_html_includes_table_and_links() {
     assert!(html.contains("<table>"));
     assert!(html.contains("static report"));
 }
+
+#[test]
+fn summary_json_excludes_synthetic_nodes() {
+    let path = Path::new("fixtures/small.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+
+    let result = summarize(
+        &snapshot,
+        SummaryOptions {
+            top: 10,
+            exclude_synthetic: true,
+            ..Default::default()
+        },
+    )
+    .expect("summary");
+
+    let json = summary_output::format_json(&result, JsonStyle::Pretty).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+
+    assert_eq!(value["total_nodes"], 3);
+    assert_eq!(value["rows"].as_array().map(Vec::len), Some(2));
+    assert_eq!(value["excluded"]["count"], 1);
+    assert_eq!(value["excluded"]["node_types"][0], "synthetic");
+}
+
+#[test]
+fn summary_thresholds_apply_before_top() {
+    let path = Path::new("fixtures/medium.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+
+    let result = summarize(
+        &snapshot,
+        SummaryOptions {
+            top: 2,
+            min_self_size: Some(50),
+            min_count: Some(1),
+            ..Default::default()
+        },
+    )
+    .expect("summary");
+
+    // Node17..Node19 (51, 54, 57 bytes) pass the threshold; --top keeps the first two.
+    assert_eq!(result.total_rows, 3);
+    let names: Vec<&str> = result.rows.iter().map(|row| row.name.as_str()).collect();
+    assert_eq!(names, vec!["Node19", "Node18"]);
+}
+
+#[test]
+fn summary_json_carries_tool_version_and_fingerprint() {
+    let options = || SummaryOptions {
+        top: 10,
+        ..Default::default()
+    };
+    let fingerprint = |path: &str| {
+        let snapshot =
+            read_snapshot_file(Path::new(path), ReadOptions::new(false, CancelToken::new()))
+                .expect("snapshot");
+        let result = summarize(&snapshot, options()).expect("summary");
+        let json = summary_output::format_json(&result, JsonStyle::Pretty).expect("json");
+        let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+        assert_eq!(value["tool_version"], env!("CARGO_PKG_VERSION"));
+        value["snapshot_fingerprint"]
+            .as_str()
+            .expect("fingerprint")
+            .to_string()
+    };
+
+    let small = fingerprint("fixtures/small.heapsnapshot");
+    assert_eq!(small.len(), 16);
+    assert_eq!(small, fingerprint("fixtures/small.heapsnapshot"));
+    assert_ne!(small, fingerprint("fixtures/medium.heapsnapshot"));
+}
+
+#[test]
+fn summary_jobs_match_single_threaded_result() {
+    let path = Path::new("fixtures/medium.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+
+    let run = |jobs: usize| {
+        let result = summarize(
+            &snapshot,
+            SummaryOptions {
+                exclude_synthetic: true,
+                jobs,
+                ..Default::default()
+            },
+        )
+        .expect("summary");
+        summary_output::format_json(&result, JsonStyle::Pretty).expect("json")
+    };
+
+    let sequential = run(1);
+    // Far more jobs than cores (or nodes) is capped instead of spawning a thread each.
+    for jobs in [0, 3, 4, 64, 100_000] {
+        assert_eq!(run(jobs), sequential, "jobs={jobs}");
+    }
+}
+
+#[test]
+fn summary_detached_only_keeps_detached_dom_nodes() {
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count","detachedness"],
+          "node_types": [["object","native"],"string","number","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        }
+      },
+      "nodes": [
+        0, 0, 1, 0, 0, 0,
+        1, 1, 3, 40, 0, 2,
+        1, 1, 5, 60, 0, 1,
+        1, 2, 7, 30, 0, 2
+      ],
+      "edges": [],
+      "strings": ["Window", "HTMLDivElement", "HTMLSpanElement"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = heapsnap::parser::read_snapshot(&mut reader).expect("snapshot");
+    assert_eq!(snapshot.node_view(1).expect("node").detachedness(), Some(2));
+    assert!(!snapshot.node_view(2).expect("node").is_detached());
+
+    let options = || SummaryOptions {
+        top: 10,
+        detached_only: true,
+        ..Default::default()
+    };
+    let result = summarize(&snapshot, options()).expect("summary");
+    let rows: Vec<(&str, u64, i64)> = result
+        .rows
+        .iter()
+        .map(|row| (row.name.as_str(), row.count, row.self_size_sum))
+        .collect();
+    assert_eq!(
+        rows,
+        vec![("HTMLDivElement", 1, 40), ("HTMLSpanElement", 1, 30)]
+    );
+    let json = summary_output::format_json(&result, JsonStyle::Pretty).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["detached_only"], true);
+
+    // Snapshots without the field report why instead of returning an empty summary.
+    let options_small = ReadOptions::new(false, CancelToken::new());
+    let small = read_snapshot_file(Path::new("fixtures/small.heapsnapshot"), options_small)
+        .expect("snapshot");
+    let err = summarize(&small, options()).expect_err("no detachedness field");
+    assert!(err.to_string().contains("detachedness"), "{err}");
+}
+
+#[test]
+fn summary_budgets_report_only_exceeded_constructors() {
+    let path = Path::new("fixtures/medium.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+    let budgets: Vec<SizeBudget> = ["Node19<=56", "Node18<=1KB", "Node2:retained<=100"]
+        .iter()
+        .map(|spec| SizeBudget::parse(spec).expect("budget"))
+        .collect();
+
+    let missing = check_budgets(&snapshot, &budgets, None);
+    assert!(missing.is_err(), "retained budgets need the dominator tree");
+
+    let index = compute_dominator_index(
+        &snapshot,
+        &default_ignored_edge_types(),
+        CancelToken::new(),
+        None,
+    )
+    .expect("dominators");
+    let retained = retained_sizes(&snapshot, &index);
+    let violations =
+        check_budgets(&snapshot, &budgets, Some((&index, retained.as_slice()))).expect("check");
+    let exceeded: Vec<(&str, i64)> = violations
+        .iter()
+        .map(|violation| (violation.budget.name.as_str(), violation.actual_bytes))
+        .collect();
+    assert_eq!(exceeded[0], ("Node19", 57));
+    assert_eq!(exceeded[1].0, "Node2");
+    assert_eq!(exceeded.len(), 2);
+
+    // A constructor missing from the snapshot fails the check instead of passing as 0 bytes.
+    let typo = [SizeBudget::parse("Node199<=10MB").expect("budget")];
+    let err = check_budgets(&snapshot, &typo, None).expect_err("unknown constructor");
+    assert!(matches!(
+        err,
+        heapsnap::error::SnapshotError::NotFound { .. }
+    ));
+    assert!(err.to_string().contains("'Node19'"), "{err}");
+}
+
+#[test]
+fn summary_edge_type_summary_counts_every_edge() {
+    // Root holds two properties and an element; the element target holds a weak edge.
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property","element","weak"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0, 0, 1, 10, 3, 0, 1, 3, 20, 1, 0, 2, 5, 30, 0],
+      "edges": [0, 3, 5, 0, 4, 10, 1, 0, 10, 2, 0, 0],
+      "strings": ["Root", "Leaf", "Other", "a", "b"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = heapsnap::parser::read_snapshot(&mut reader).expect("snapshot");
+    let histogram = heapsnap::analysis::edges::edge_type_histogram(&snapshot);
+    assert_eq!(
+        histogram,
+        vec![
+            ("property".to_string(), 2),
+            ("element".to_string(), 1),
+            ("weak".to_string(), 1),
+        ]
+    );
+
+    let mut result = summarize(
+        &snapshot,
+        SummaryOptions {
+            top: 1,
+            ..Default::default()
+        },
+    )
+    .expect("summary");
+    let plain = summary_output::format_json(&result, JsonStyle::Pretty).expect("json");
+    assert!(!plain.contains("edge_types"));
+
+    result.edge_types = Some(histogram);
+    let markdown = summary_output::format_markdown(&result);
+    assert!(markdown.contains("- Total edges: 4"));
+    assert!(markdown.contains("| property | 2 | 50.0% |"));
+    let json = summary_output::format_json(&result, JsonStyle::Pretty).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["edge_types"]["total_edges"], 4);
+    assert_eq!(value["edge_types"]["types"][2]["edge_type"], "weak");
+}
+
+#[test]
+fn summary_reachability_counts_weak_only_nodes() {
+    // GC roots holds one Cache strongly and a second one only through a weak edge,
+    // which in turn holds an Entry.
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property","weak"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0, 0, 1, 0, 2, 0, 1, 3, 10, 0, 0, 1, 5, 10, 1, 0, 2, 7, 5, 0],
+      "edges": [0, 3, 5, 1, 3, 10, 0, 3, 15],
+      "strings": ["GC roots", "Cache", "Entry", "x"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = heapsnap::parser::read_snapshot(&mut reader).expect("snapshot");
+    let options = |reachability| SummaryOptions {
+        top: 10,
+        jobs: 2,
+        reachability,
+        ..Default::default()
+    };
+
+    let result = summarize(&snapshot, options(true)).expect("summary");
+    let rows: Vec<(&str, u64, Option<u64>)> = result
+        .rows
+        .iter()
+        .map(|row| (row.name.as_str(), row.count, row.weak_only_count))
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            ("Cache", 2, Some(1)),
+            ("Entry", 1, Some(1)),
+            ("GC roots", 1, Some(0))
+        ]
+    );
+    let markdown = summary_output::format_markdown(&result);
+    assert!(markdown.contains("| Weak-only |"));
+    assert!(markdown.contains("| Cache | 2 | 20 | 1 |"));
+    let csv = summary_output::format_csv(&result, ',');
+    assert!(csv.starts_with("constructor,count,self_size_sum_bytes,weak_only_count\n"));
+
+    let plain = summarize(&snapshot, options(false)).expect("summary");
+    assert!(!summary_output::format_markdown(&plain).contains("Weak-only"));
+    let json = summary_output::format_json(&plain, JsonStyle::Pretty).expect("json");
+    assert!(!json.contains("weak_only_count"));
+}
+
+#[test]
+fn summary_unreachable_only_aggregates_nodes_without_a_root_path() {
+    // GC roots holds Cache strongly and Entry through a weak edge; the second Cache and
+    // the Orphan it holds are not referenced from any reachable node.
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property","weak"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0, 0, 1, 0, 2, 0, 1, 3, 10, 0, 0, 2, 5, 5, 0, 0, 1, 7, 30, 1, 0, 3, 9, 7, 0],
+      "edges": [0, 4, 5, 1, 4, 10, 0, 4, 20],
+      "strings": ["GC roots", "Cache", "Entry", "Orphan", "x"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = heapsnap::parser::read_snapshot(&mut reader).expect("snapshot");
+    let result = summarize(
+        &snapshot,
+        SummaryOptions {
+            top: 10,
+            jobs: 2,
+            unreachable_only: true,
+            ..Default::default()
+        },
+    )
+    .expect("summary");
+
+    let rows: Vec<(&str, u64, i64)> = result
+        .rows
+        .iter()
+        .map(|row| (row.name.as_str(), row.count, row.self_size_sum))
+        .collect();
+    assert_eq!(rows, vec![("Cache", 1, 30), ("Orphan", 1, 7)]);
+    assert_eq!(result.total_nodes, 5);
+    let markdown = summary_output::format_markdown(&result);
+    assert!(markdown.contains("- Unreachable nodes only"));
+    let json = summary_output::format_json(&result, JsonStyle::Pretty).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["unreachable_only"], true);
+}
+
+#[test]
+fn summary_samples_list_first_instances_per_constructor() {
+    // GC roots, five Items and one Other; none of them has edges.
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        }
+      },
+      "nodes": [0,0,1,0,0, 0,1,3,4,0, 0,1,5,8,0, 0,2,7,2,0, 0,1,9,4,0, 0,1,11,4,0, 0,1,13,4,0],
+      "edges": [],
+      "strings": ["GC roots", "Item", "Other"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = heapsnap::parser::read_snapshot(&mut reader).expect("snapshot");
+    let options = |samples, jobs| SummaryOptions {
+        top: 10,
+        jobs,
+        samples,
+        ..Default::default()
+    };
+
+    let result = summarize(&snapshot, options(4, 1)).expect("summary");
+    assert_eq!(result.rows[0].name, "Item");
+    assert_eq!(result.rows[0].examples, vec![3, 5, 9, 11]);
+    assert_eq!(result.rows[1].examples, vec![7]);
+    // Chunks are merged in node order, so threads keep the same first instances.
+    for jobs in [2, 3, 7] {
+        let threaded = summarize(&snapshot, options(4, jobs)).expect("summary");
+        assert_eq!(
+            threaded.rows[0].examples, result.rows[0].examples,
+            "jobs={jobs}"
+        );
+    }
+
+    let markdown = summary_output::format_markdown(&result);
+    assert!(markdown.contains("| Constructor | Count | Self Size Sum (bytes) | Examples |"));
+    assert!(markdown.contains("| Item | 5 | 24 | 3, 5, 9, … |"));
+    assert!(markdown.contains("| Other | 1 | 2 | 7 |"));
+    let json = summary_output::format_json(&result, JsonStyle::Compact).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(
+        value["rows"][0]["examples"],
+        serde_json::json!([3, 5, 9, 11])
+    );
+    let parsed = summary_output::parse_json(&json).expect("read back");
+    assert_eq!(parsed.rows[0].examples, vec![3, 5, 9, 11]);
+
+    let plain = summarize(&snapshot, options(0, 1)).expect("summary");
+    assert!(!summary_output::format_markdown(&plain).contains("Examples"));
+    let json = summary_output::format_json(&plain, JsonStyle::Compact).expect("json");
+    assert!(!json.contains("examples"));
+}
+
+#[test]
+fn summary_streaming_matches_loaded_summary() {
+    let path = Path::new("fixtures/medium.heapsnapshot");
+    let snapshot =
+        read_snapshot_file(path, ReadOptions::new(false, CancelToken::new())).expect("snapshot");
+    let options = |contains: Option<&str>, exclude_synthetic, split_empty| SummaryOptions {
+        top: 10,
+        contains: contains.map(str::to_string),
+        skip: 1,
+        exclude_synthetic,
+        split_empty,
+        samples: 3,
+        ..Default::default()
+    };
+
+    for (contains, exclude_synthetic, split_empty) in [
+        (None, false, false),
+        (None, true, true),
+        (Some("Node1"), true, false),
+    ] {
+        let loaded = summarize(&snapshot, options(contains, exclude_synthetic, split_empty))
+            .expect("summary");
+        let mut streaming =
+            StreamingSummary::new(options(contains, exclude_synthetic, split_empty))
+                .expect("options");
+        let streamed = stream_snapshot_file(
+            path,
+            ReadOptions::new(false, CancelToken::new()),
+            &mut |index, fields| streaming.add_node(index, fields),
+        )
+        .expect("stream");
+        assert_eq!(streamed.node_count, snapshot.node_count());
+        assert_eq!(streamed.edge_count, snapshot.edge_count());
+        let streamed = streaming.finish(&streamed).expect("summary");
+        assert_eq!(
+            summary_output::format_json(&streamed, JsonStyle::Compact).expect("json"),
+            summary_output::format_json(&loaded, JsonStyle::Compact).expect("json"),
+            "contains={contains:?} exclude_synthetic={exclude_synthetic}"
+        );
+    }
+
+    let reachability = SummaryOptions {
+        reachability: true,
+        ..options(None, false, false)
+    };
+    assert!(StreamingSummary::new(reachability).is_err());
+}
+
+#[test]
+fn summary_streaming_needs_meta_before_nodes() {
+    let json = r#"
+    {
+      "nodes": [0,0,1,0,0],
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        }
+      },
+      "edges": [],
+      "strings": ["GC roots"]
+    }
+    "#;
+    let mut nodes = 0;
+    let err = stream_snapshot(&mut json.as_bytes(), &mut |_, _| nodes += 1)
+        .expect_err("nodes before meta");
+    assert!(err.to_string().contains("before snapshot.meta"), "{err}");
+    assert_eq!(nodes, 0);
+}
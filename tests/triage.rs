@@ -0,0 +1,38 @@
+use std::path::Path;
+
+use heapsnap::analysis::triage::{TriageOptions, find_biggest, triage};
+use heapsnap::cancel::CancelToken;
+use heapsnap::output::json::JsonStyle;
+use heapsnap::output::triage as triage_output;
+use heapsnap::parser::{ReadOptions, read_snapshot_file};
+
+#[test]
+fn triage_reports_biggest_node_with_retaining_path() {
+    let path = Path::new("fixtures/small.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+
+    let biggest = find_biggest(&snapshot, 1);
+    let result = triage(
+        &snapshot,
+        TriageOptions {
+            top: 2,
+            max_paths: 5,
+            max_depth: 10,
+            cancel: CancelToken::new(),
+        },
+    )
+    .expect("triage");
+
+    assert_eq!(result.entries.len(), 2);
+    let first = &result.entries[0];
+    assert_eq!(first.retainers.target, biggest[0].0);
+    assert_eq!(first.self_size, biggest[0].1);
+    assert!(first.self_size >= result.entries[1].self_size);
+    assert!(!first.retainers.paths.is_empty());
+
+    let json = triage_output::format_json(&snapshot, &result, JsonStyle::Pretty).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["targets"][0]["rank"], 1);
+    assert!(!value["targets"][0]["paths"].as_array().unwrap().is_empty());
+}
@@ -0,0 +1,117 @@
+use std::path::Path;
+
+use heapsnap::analysis::verify::{CheckStatus, verify};
+use heapsnap::cancel::CancelToken;
+use heapsnap::output::json::JsonStyle;
+use heapsnap::output::verify as verify_output;
+use heapsnap::parser::{ReadOptions, read_snapshot, read_snapshot_file};
+
+#[test]
+fn verify_fixture_small_passes() {
+    let path = Path::new("fixtures/small.heapsnapshot");
+    let options = ReadOptions::new(false, CancelToken::new());
+    let snapshot = read_snapshot_file(path, options).expect("snapshot");
+
+    let report = verify(&snapshot);
+    assert!(report.passed());
+    assert_eq!(report.gc_root_count, 1);
+
+    let json = verify_output::format_json(&report, JsonStyle::Pretty).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["version"], 1);
+    assert_eq!(value["ok"], true);
+    assert_eq!(value["node_count_matches"], true);
+    assert_eq!(value["edge_sum_matches"], true);
+    assert_eq!(value["out_of_range_to_node_count"], 0);
+    assert_eq!(value["has_gc_roots"], true);
+    assert_eq!(value["name_index_out_of_range_count"], 0);
+}
+
+#[test]
+fn verify_reports_every_problem() {
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edge_count"],
+          "node_types": [["object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        },
+        "node_count": 3,
+        "edge_count": 2
+      },
+      "nodes": [0, 0, 1, 10, 2, 0, 9, 2, 20, 0],
+      "edges": [0, 0, 5, 0, 0, 99],
+      "strings": ["Root"]
+    }
+    "#;
+    let mut reader = json.as_bytes();
+    let snapshot = read_snapshot(&mut reader).expect("parse ok");
+
+    let report = verify(&snapshot);
+    assert!(!report.passed());
+    assert_eq!(report.out_of_range_to_node, 1);
+    assert_eq!(report.name_index_out_of_range, 1);
+
+    let status = |name: &str| {
+        report
+            .checks
+            .iter()
+            .find(|check| check.name == name)
+            .map(|check| check.status)
+    };
+    assert_eq!(status("node_count"), Some(CheckStatus::Fail));
+    assert_eq!(status("edge_count"), Some(CheckStatus::Pass));
+    assert_eq!(status("edge_count_sum"), Some(CheckStatus::Pass));
+    assert_eq!(status("gc_roots"), Some(CheckStatus::Warn));
+
+    let markdown = verify_output::format_markdown(&report);
+    assert!(markdown.contains("FAIL (3 check(s) failed)"));
+
+    let json = verify_output::format_json(&report, JsonStyle::Compact).expect("json");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("parse json");
+    assert_eq!(value["ok"], false);
+    assert_eq!(value["node_count_matches"], false);
+    assert_eq!(value["edge_count_matches"], true);
+    assert_eq!(value["edge_sum_matches"], true);
+    assert_eq!(value["out_of_range_to_node_count"], 1);
+    assert_eq!(value["name_index_out_of_range_count"], 1);
+    assert_eq!(value["has_gc_roots"], false);
+}
+
+#[test]
+fn verify_accepts_the_older_edges_index_layout() {
+    // Older V8 writes each node's first edge offset instead of its edge count.
+    let json = r#"
+    {
+      "snapshot": {
+        "meta": {
+          "node_fields": ["type","name","id","self_size","edges_index"],
+          "node_types": [["synthetic","object"],"string","number","number","number"],
+          "edge_fields": ["type","name_or_index","to_node"],
+          "edge_types": [["property"],"string_or_number","node"]
+        },
+        "node_count": 3,
+        "edge_count": 2
+      },
+      "nodes": [0,0,1,0,0, 1,1,3,16,3, 1,2,5,8,6],
+      "edges": [0,3,5, 0,3,10],
+      "strings": ["GC roots","Parent","Child","child"]
+    }
+    "#;
+    let snapshot = read_snapshot(&mut json.as_bytes()).expect("snapshot");
+
+    let report = verify(&snapshot);
+    assert!(report.passed(), "{:?}", report.checks);
+    assert_eq!(report.edge_count_sum, 2);
+    let children: Vec<Option<usize>> = snapshot
+        .edges_of(1)
+        .expect("edges")
+        .map(|edge| edge.to_node_index())
+        .collect();
+    assert_eq!(children, vec![Some(2)]);
+
+    let peek = heapsnap::output::peek::format_markdown(&snapshot.meta);
+    assert!(peek.contains("- Edge layout: edges_index"));
+}
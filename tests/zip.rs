@@ -0,0 +1,51 @@
+use std::path::Path;
+
+use heapsnap::cancel::CancelToken;
+use heapsnap::error::SnapshotError;
+use heapsnap::parser::{ReadOptions, read_snapshot_file, read_snapshot_meta_file};
+
+fn read(path: &str) -> Result<heapsnap::snapshot::SnapshotRaw, SnapshotError> {
+    read_snapshot_file(Path::new(path), ReadOptions::new(false, CancelToken::new()))
+}
+
+#[test]
+fn zip_members_match_the_plain_files() {
+    // `heap/medium.heapsnapshot` is deflated with dynamic Huffman blocks,
+    // `small.heapsnapshot` is stored.
+    for (member, plain) in [
+        (
+            "fixtures/snapshots.zip#heap/medium.heapsnapshot",
+            "fixtures/medium.heapsnapshot",
+        ),
+        (
+            "fixtures/snapshots.zip#small.heapsnapshot",
+            "fixtures/small.heapsnapshot",
+        ),
+    ] {
+        let zipped = read(member).expect("zip member");
+        let expected = read(plain).expect("plain file");
+        assert_eq!(zipped.nodes, expected.nodes);
+        assert_eq!(zipped.edges, expected.edges);
+        assert_eq!(zipped.strings, expected.strings);
+    }
+
+    let meta = read_snapshot_meta_file(
+        Path::new("fixtures/snapshots.zip#heap/medium.heapsnapshot"),
+        4096,
+    )
+    .expect("meta");
+    assert!(meta.node_fields.contains(&"edge_count".to_string()));
+}
+
+#[test]
+fn zip_member_errors_name_the_problem() {
+    let err = read("fixtures/snapshots.zip#missing.heapsnapshot").expect_err("missing");
+    let message = err.to_string();
+    assert!(matches!(err, SnapshotError::NotFound { .. }));
+    assert!(message.contains("\"missing.heapsnapshot\""));
+    assert!(message.contains("heap/medium.heapsnapshot, small.heapsnapshot"));
+
+    let err = read("fixtures/small.heapsnapshot#member").expect_err("not a zip");
+    assert!(matches!(err, SnapshotError::InvalidData { .. }));
+    assert!(err.to_string().contains("only reads .zip archives"));
+}